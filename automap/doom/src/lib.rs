@@ -0,0 +1,674 @@
+//! The automap, an overhead view of the level drawn in place of the player
+//! view. Lines are coloured by what they are (walls, steps, doors, secrets)
+//! and only lines the player has seen are shown unless the map cheat or the
+//! computer area map power is in effect.
+//!
+//! The map is drawn in floating point screen coords, vanilla Doom used
+//! fixed-point `frame buffer` coords so there may be some differences in how
+//! lines are clipped at the screen edges.
+
+use gamestate_traits::english::{
+    AMSTR_FOLLOWOFF, AMSTR_FOLLOWON, AMSTR_GRIDOFF, AMSTR_GRIDON, AMSTR_MARKEDSPOT,
+    AMSTR_MARKSCLEARED,
+};
+use gamestate_traits::sdl2::keyboard::Keycode;
+use gamestate_traits::{
+    Action, GameTraits, LineDefFlags, PixelBuffer, PlayerState, PowerType, Scancode,
+    SubsystemTrait, deh_cheat,
+};
+use wad::WadData;
+use wad::types::{WadPalette, WadPatch};
+
+const REDS: usize = 256 - 5 * 16;
+const GRAYS: usize = 6 * 16;
+const BROWNS: usize = 4 * 16;
+const YELLOWS: usize = 256 - 32 + 7;
+const GREENS: usize = 7 * 16;
+const WHITE: usize = 256 - 47;
+const BLUES: usize = 256 - 4 * 16 + 8;
+const MAGENTAS: usize = 250;
+const BACKGROUND: usize = 0;
+
+const WALL_COLOUR: usize = REDS;
+const TELEPORT_COLOUR: usize = REDS + 8;
+/// Secret lines are only shown apart from walls under the map cheat
+const SECRET_COLOUR: usize = MAGENTAS + 2;
+const DOOR_COLOUR: usize = BLUES;
+const FLOOR_CHANGE_COLOUR: usize = BROWNS;
+const CEILING_CHANGE_COLOUR: usize = YELLOWS;
+const TWO_SIDED_COLOUR: usize = GRAYS;
+const ALLMAP_COLOUR: usize = GRAYS + 3;
+const GRID_COLOUR: usize = GRAYS + 8;
+const THING_COLOUR: usize = GREENS;
+const PLAYER_COLOUR: usize = WHITE;
+
+/// Size of the grid squares in map units, same as a blockmap block
+const GRID_SIZE: f32 = 128.0;
+/// Zoom multiplier for each press of the zoom keys
+const ZOOM_STEP: f32 = 1.25;
+/// How far in screen pixels to pan for each press of a direction key
+const PAN_STEP: f32 = 32.0;
+/// The actions the automap responds to while it is open
+const MAP_ACTIONS: [Action; 12] = [
+    Action::Automap,
+    Action::MapZoomIn,
+    Action::MapZoomOut,
+    Action::MapBigMap,
+    Action::MapPanLeft,
+    Action::MapPanRight,
+    Action::MapPanUp,
+    Action::MapPanDown,
+    Action::MapFollow,
+    Action::MapGrid,
+    Action::MapMark,
+    Action::MapClearMarks,
+];
+/// Size things are drawn at when the map cheat shows them
+const THING_RADIUS: f32 = 16.0;
+const PLAYER_RADIUS: f32 = 16.0;
+const MAX_MARKS: usize = 10;
+
+/// Doom special for the common walk-over teleporter
+const TELEPORT_SPECIAL: i16 = 39;
+/// Vanilla door specials, manual, triggered and locked
+const DOOR_SPECIALS: [i16; 43] = [
+    1, 2, 3, 4, 16, 26, 27, 28, 29, 31, 32, 33, 34, 42, 46, 50, 61, 63, 75, 76, 86, 90, 99, 103,
+    105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 133, 134, 135, 136, 137,
+];
+/// Range of the Boom generalized locked and normal door specials
+const GEN_DOOR_SPECIALS: std::ops::Range<i16> = 0x3800..0x4000;
+
+fn is_door(special: i16) -> bool {
+    DOOR_SPECIALS.contains(&special) || GEN_DOOR_SPECIALS.contains(&special)
+}
+
+const R: f32 = 8.0 * PLAYER_RADIUS / 7.0;
+/// The vanilla player arrow, pointing along the X axis
+const PLAYER_ARROW: [[(f32, f32); 2]; 7] = [
+    [(-R + R / 8.0, 0.0), (R, 0.0)],
+    [(R, 0.0), (R - R / 2.0, R / 4.0)],
+    [(R, 0.0), (R - R / 2.0, -R / 4.0)],
+    [(-R + R / 8.0, 0.0), (-R - R / 8.0, R / 4.0)],
+    [(-R + R / 8.0, 0.0), (-R - R / 8.0, -R / 4.0)],
+    [(-R + 3.0 * R / 8.0, 0.0), (-R + R / 8.0, R / 4.0)],
+    [(-R + 3.0 * R / 8.0, 0.0), (-R + R / 8.0, -R / 4.0)],
+];
+
+/// The triangle used for things when the map cheat is fully on
+const THING_TRIANGLE: [[(f32, f32); 2]; 3] = [
+    [(-0.867, -0.5), (0.867, -0.5)],
+    [(0.867, -0.5), (0.0, 1.0)],
+    [(0.0, 1.0), (-0.867, -0.5)],
+];
+
+/// A line snapshotted from the level for drawing
+struct AmLine {
+    a: (f32, f32),
+    b: (f32, f32),
+    colour: usize,
+}
+
+/// A thing snapshotted from the level for drawing
+struct AmThing {
+    xy: (f32, f32),
+    angle: f32,
+}
+
+pub struct AutoMap {
+    palette: WadPalette,
+    /// Numbers drawn on the marks
+    mark_nums: Vec<WadPatch>,
+    active: bool,
+    /// Centre the map on the player
+    follow: bool,
+    grid: bool,
+    /// 0 = off, 1 = all lines, 2 = all lines and things
+    cheating: u8,
+    /// The cheat to toggle the full map, and then the things
+    cheat: &'static [u8],
+    cheat_pos: usize,
+    /// Pixels per map unit. Zero means it is recalculated on next draw
+    scale: f32,
+    min_scale: f32,
+    max_scale: f32,
+    /// Map coords in the centre of the screen
+    centre: (f32, f32),
+    /// Saved `scale` and `centre` while the whole map is shown
+    bigmap: Option<(f32, (f32, f32))>,
+    marks: [Option<(f32, f32)>; MAX_MARKS],
+    next_mark: usize,
+    min_map: (f32, f32),
+    max_map: (f32, f32),
+    lines: Vec<AmLine>,
+    things: Vec<AmThing>,
+    player: AmThing,
+    screen_width: f32,
+    screen_height: f32,
+}
+
+impl AutoMap {
+    pub fn new(wad: &WadData) -> Self {
+        let palette = wad.playpal_iter().next().unwrap();
+        let mark_nums = (0..MAX_MARKS)
+            .map(|n| WadPatch::from_lump(wad.get_lump(&format!("AMMNUM{n}")).unwrap()))
+            .collect();
+
+        Self {
+            palette,
+            mark_nums,
+            active: false,
+            follow: true,
+            grid: false,
+            cheating: 0,
+            cheat: deh_cheat("iddt").as_bytes(),
+            cheat_pos: 0,
+            scale: 0.0,
+            min_scale: 0.0,
+            max_scale: 0.0,
+            centre: (0.0, 0.0),
+            bigmap: None,
+            marks: [None; MAX_MARKS],
+            next_mark: 0,
+            min_map: (0.0, 0.0),
+            max_map: (0.0, 0.0),
+            lines: Vec::new(),
+            things: Vec::new(),
+            player: AmThing {
+                xy: (0.0, 0.0),
+                angle: 0.0,
+            },
+            screen_width: 0.0,
+            screen_height: 0.0,
+        }
+    }
+
+    /// Set the scale limits for the screen size, and the starting scale if
+    /// one hasn't been set yet. Doom function name `AM_findMinMaxBoundaries`
+    fn set_scales(&mut self, width: f32, height: f32) {
+        let map_w = (self.max_map.0 - self.min_map.0).max(1.0);
+        let map_h = (self.max_map.1 - self.min_map.1).max(1.0);
+        self.min_scale = (width / map_w).min(height / map_h);
+        self.max_scale = height / (2.0 * PLAYER_RADIUS);
+        if self.scale == 0.0 {
+            self.scale = self.min_scale / 0.7;
+        }
+        self.scale = self.scale.clamp(self.min_scale, self.max_scale);
+    }
+
+    /// Returns false if the map isn't set up yet
+    fn zoom(&mut self, factor: f32) -> bool {
+        if self.scale == 0.0 {
+            return false;
+        }
+        self.bigmap = None;
+        self.scale = (self.scale * factor).clamp(self.min_scale, self.max_scale);
+        true
+    }
+
+    /// Show the whole map, or go back to the view from before
+    fn toggle_bigmap(&mut self) {
+        if let Some((scale, centre)) = self.bigmap.take() {
+            self.scale = scale;
+            self.centre = centre;
+        } else {
+            self.bigmap = Some((self.scale, self.centre));
+            self.scale = self.min_scale;
+            self.centre = (
+                (self.min_map.0 + self.max_map.0) / 2.0,
+                (self.min_map.1 + self.max_map.1) / 2.0,
+            );
+        }
+    }
+
+    /// Returns false if the map can't be panned, the keys are then left for
+    /// moving the player
+    fn pan(&mut self, dx: f32, dy: f32) -> bool {
+        if self.follow || self.scale == 0.0 {
+            return false;
+        }
+        self.centre.0 =
+            (self.centre.0 + dx * PAN_STEP / self.scale).clamp(self.min_map.0, self.max_map.0);
+        self.centre.1 =
+            (self.centre.1 + dy * PAN_STEP / self.scale).clamp(self.min_map.1, self.max_map.1);
+        true
+    }
+
+    fn add_mark(&mut self) {
+        self.marks[self.next_mark] = Some(self.centre);
+        self.next_mark = (self.next_mark + 1) % MAX_MARKS;
+    }
+
+    fn clear_marks(&mut self) {
+        self.marks = [None; MAX_MARKS];
+        self.next_mark = 0;
+    }
+
+    /// Returns true when the full cheat sequence has been typed
+    fn check_cheat(&mut self, sc: Scancode) -> bool {
+        let Some(key) = Keycode::from_scancode(sc) else {
+            return false;
+        };
+        let key = key.into_i32() as u8;
+        if self.cheat[self.cheat_pos] == key {
+            self.cheat_pos += 1;
+            if self.cheat_pos == self.cheat.len() {
+                self.cheat_pos = 0;
+                return true;
+            }
+        } else {
+            self.cheat_pos = (self.cheat[0] == key) as usize;
+        }
+        false
+    }
+
+    /// Copy out what is to be drawn so that `draw()` doesn't need the level
+    fn snapshot(&mut self, game: &impl GameTraits) {
+        let Some(level) = game.level() else {
+            return;
+        };
+        let player = game.player();
+        let allmap = player.status.powers[PowerType::Allmap as usize] != 0;
+
+        let extents = level.map_data.get_map_extents();
+        self.min_map = (
+            extents.min_vertex.x.to_float(),
+            extents.min_vertex.y.to_float(),
+        );
+        self.max_map = (
+            extents.max_vertex.x.to_float(),
+            extents.max_vertex.y.to_float(),
+        );
+
+        self.lines.clear();
+        for line in level.map_data.linedefs() {
+            let mapped = line.flags & LineDefFlags::Mapped as u32 != 0;
+            let never_see = line.flags & LineDefFlags::UnMapped as u32 != 0;
+            let colour = if self.cheating != 0 || mapped {
+                if never_see && self.cheating == 0 {
+                    continue;
+                }
+                if let Some(back) = line.backsector.as_ref() {
+                    let front = &line.frontsector;
+                    if line.special == TELEPORT_SPECIAL {
+                        TELEPORT_COLOUR
+                    } else if line.flags & LineDefFlags::Secret as u32 != 0 {
+                        if self.cheating != 0 {
+                            SECRET_COLOUR
+                        } else {
+                            WALL_COLOUR
+                        }
+                    } else if is_door(line.special) {
+                        DOOR_COLOUR
+                    } else if back.floorheight != front.floorheight {
+                        FLOOR_CHANGE_COLOUR
+                    } else if back.ceilingheight != front.ceilingheight {
+                        CEILING_CHANGE_COLOUR
+                    } else if self.cheating != 0 {
+                        TWO_SIDED_COLOUR
+                    } else {
+                        continue;
+                    }
+                } else {
+                    WALL_COLOUR
+                }
+            } else if allmap && !never_see {
+                ALLMAP_COLOUR
+            } else {
+                continue;
+            };
+            self.lines.push(AmLine {
+                a: (line.v1.x.to_float(), line.v1.y.to_float()),
+                b: (line.v2.x.to_float(), line.v2.y.to_float()),
+                colour,
+            });
+        }
+
+        self.things.clear();
+        if self.cheating == 2 {
+            level.thinkers.for_each_thing(|thinker| {
+                if thinker.is_mobj() {
+                    let mobj = thinker.mobj();
+                    self.things.push(AmThing {
+                        xy: (mobj.xy.x.to_float(), mobj.xy.y.to_float()),
+                        angle: mobj.angle.to_float_angle().rad(),
+                    });
+                }
+                true
+            });
+        }
+
+        if let Some(mobj) = player.mobj() {
+            self.player = AmThing {
+                xy: (mobj.xy.x.to_float(), mobj.xy.y.to_float()),
+                angle: mobj.angle.to_float_angle().rad(),
+            };
+            if self.follow {
+                self.centre = self.player.xy;
+            }
+        }
+    }
+
+    fn to_screen(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (
+            (x - self.centre.0) * self.scale + self.screen_width / 2.0,
+            self.screen_height / 2.0 - (y - self.centre.1) * self.scale,
+        )
+    }
+
+    fn draw_map_line(
+        &self,
+        a: (f32, f32),
+        b: (f32, f32),
+        colour: usize,
+        buffer: &mut impl PixelBuffer,
+    ) {
+        let Some((a, b)) = clip_line(
+            self.to_screen(a),
+            self.to_screen(b),
+            self.screen_width - 1.0,
+            self.screen_height - 1.0,
+        ) else {
+            return;
+        };
+        let colour = &self.palette.0[colour];
+        draw_line(a, b, |x, y| buffer.set_pixel(x, y, colour));
+    }
+
+    /// Draw a line shape rotated by `angle` and moved to `xy`
+    fn draw_shape(
+        &self,
+        shape: &[[(f32, f32); 2]],
+        scale: f32,
+        thing: &AmThing,
+        colour: usize,
+        buffer: &mut impl PixelBuffer,
+    ) {
+        let (sin, cos) = thing.angle.sin_cos();
+        let place = |(x, y): (f32, f32)| {
+            let (x, y) = (x * scale, y * scale);
+            (
+                thing.xy.0 + x * cos - y * sin,
+                thing.xy.1 + x * sin + y * cos,
+            )
+        };
+        for [a, b] in shape {
+            self.draw_map_line(place(*a), place(*b), colour, buffer);
+        }
+    }
+
+    fn draw_grid(&self, buffer: &mut impl PixelBuffer) {
+        let half_w = self.screen_width / 2.0 / self.scale;
+        let half_h = self.screen_height / 2.0 / self.scale;
+        let (left, right) = (self.centre.0 - half_w, self.centre.0 + half_w);
+        let (bottom, top) = (self.centre.1 - half_h, self.centre.1 + half_h);
+
+        let mut x = (left / GRID_SIZE).floor() * GRID_SIZE;
+        while x <= right {
+            self.draw_map_line((x, bottom), (x, top), GRID_COLOUR, buffer);
+            x += GRID_SIZE;
+        }
+        let mut y = (bottom / GRID_SIZE).floor() * GRID_SIZE;
+        while y <= top {
+            self.draw_map_line((left, y), (right, y), GRID_COLOUR, buffer);
+            y += GRID_SIZE;
+        }
+    }
+
+    fn draw_marks(&self, buffer: &mut impl PixelBuffer) {
+        // Patches are drawn in 320x200 space, and clipped to the buffer
+        let scale = self.screen_scale(buffer.size());
+        for (i, mark) in self.marks.iter().enumerate() {
+            let Some(mark) = mark else {
+                continue;
+            };
+            let (x, y) = self.to_screen(*mark);
+            let (x, y) = scale.from_buffer(x, y);
+            let patch = &self.mark_nums[i];
+            let x = x as i32 - patch.width as i32 / 2;
+            let y = y as i32 - patch.height as i32 / 2;
+            self.draw_patch_pixels(patch, x, y, buffer);
+        }
+    }
+}
+
+impl SubsystemTrait for AutoMap {
+    /// Doom function name `AM_LevelInit`
+    fn init(&mut self, _game: &impl GameTraits) {
+        self.clear_marks();
+        self.scale = 0.0;
+        self.bigmap = None;
+        self.cheat_pos = 0;
+        self.lines.clear();
+        self.things.clear();
+    }
+
+    fn responder(&mut self, sc: Scancode, game: &mut impl GameTraits) -> bool {
+        if !self.active {
+            if game.key_bindings().is_key(Action::Automap, sc) {
+                game.set_automap_active(true);
+                self.active = true;
+                return true;
+            }
+            return false;
+        }
+
+        if self.check_cheat(sc) {
+            self.cheating = (self.cheating + 1) % 3;
+            return false;
+        }
+
+        let Some(action) = MAP_ACTIONS
+            .into_iter()
+            .find(|action| game.key_bindings().is_key(*action, sc))
+        else {
+            return false;
+        };
+        match action {
+            Action::Automap => {
+                game.set_automap_active(false);
+                self.active = false;
+                self.bigmap = None;
+            }
+            // Keys that do nothing in the current mode aren't eaten, so they
+            // still reach the game, as the pan keys do in follow mode in vanilla
+            Action::MapZoomIn => return self.zoom(ZOOM_STEP),
+            Action::MapZoomOut => return self.zoom(1.0 / ZOOM_STEP),
+            Action::MapBigMap => {
+                if self.scale == 0.0 {
+                    return false;
+                }
+                self.toggle_bigmap()
+            }
+            Action::MapPanLeft => return self.pan(-1.0, 0.0),
+            Action::MapPanRight => return self.pan(1.0, 0.0),
+            Action::MapPanUp => return self.pan(0.0, 1.0),
+            Action::MapPanDown => return self.pan(0.0, -1.0),
+            Action::MapFollow => {
+                self.follow = !self.follow;
+                game.player_msg_set(if self.follow {
+                    AMSTR_FOLLOWON
+                } else {
+                    AMSTR_FOLLOWOFF
+                });
+            }
+            Action::MapGrid => {
+                self.grid = !self.grid;
+                game.player_msg_set(if self.grid {
+                    AMSTR_GRIDON
+                } else {
+                    AMSTR_GRIDOFF
+                });
+            }
+            Action::MapMark => {
+                self.add_mark();
+                game.player_msg_set(AMSTR_MARKEDSPOT);
+            }
+            Action::MapClearMarks => {
+                self.clear_marks();
+                game.player_msg_set(AMSTR_MARKSCLEARED);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn ticker(&mut self, game: &mut impl GameTraits) -> bool {
+        self.active = game.automap_active();
+        if !self.active {
+            return false;
+        }
+        // The automap is closed when the player dies
+        if game.player().player_state == PlayerState::Dead {
+            game.set_automap_active(false);
+            self.active = false;
+            return false;
+        }
+        self.snapshot(game);
+        false
+    }
+
+    fn get_palette(&self) -> &WadPalette {
+        &self.palette
+    }
+
+    fn draw(&mut self, buffer: &mut impl PixelBuffer) {
+        self.screen_width = buffer.size().width() as f32;
+        self.screen_height = buffer.size().height() as f32;
+        self.set_scales(self.screen_width, self.screen_height);
+
+        buffer.clear_with_colour(&self.palette.0[BACKGROUND]);
+        if self.grid {
+            self.draw_grid(buffer);
+        }
+        for line in self.lines.iter() {
+            self.draw_map_line(line.a, line.b, line.colour, buffer);
+        }
+        for thing in self.things.iter() {
+            self.draw_shape(&THING_TRIANGLE, THING_RADIUS, thing, THING_COLOUR, buffer);
+        }
+        // The arrow points along X, angle zero
+        self.draw_shape(&PLAYER_ARROW, 1.0, &self.player, PLAYER_COLOUR, buffer);
+        self.draw_marks(buffer);
+    }
+}
+
+/// Cohen-Sutherland clip of a line to the rectangle `0..=max_x`, `0..=max_y`.
+/// Returns `None` if the line is entirely outside
+fn clip_line(
+    mut a: (f32, f32),
+    mut b: (f32, f32),
+    max_x: f32,
+    max_y: f32,
+) -> Option<((f32, f32), (f32, f32))> {
+    const LEFT: u8 = 1;
+    const RIGHT: u8 = 2;
+    const TOP: u8 = 4;
+    const BOTTOM: u8 = 8;
+
+    let outcode = |(x, y): (f32, f32)| {
+        let mut code = 0;
+        if x < 0.0 {
+            code |= LEFT;
+        } else if x > max_x {
+            code |= RIGHT;
+        }
+        if y < 0.0 {
+            code |= TOP;
+        } else if y > max_y {
+            code |= BOTTOM;
+        }
+        code
+    };
+
+    let mut code_a = outcode(a);
+    let mut code_b = outcode(b);
+    loop {
+        if code_a | code_b == 0 {
+            return Some((a, b));
+        }
+        if code_a & code_b != 0 {
+            return None;
+        }
+        let code = if code_a != 0 { code_a } else { code_b };
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let point = if code & TOP != 0 {
+            (a.0 + dx * (0.0 - a.1) / dy, 0.0)
+        } else if code & BOTTOM != 0 {
+            (a.0 + dx * (max_y - a.1) / dy, max_y)
+        } else if code & RIGHT != 0 {
+            (max_x, a.1 + dy * (max_x - a.0) / dx)
+        } else {
+            (0.0, a.1 + dy * (0.0 - a.0) / dx)
+        };
+        if code == code_a {
+            a = point;
+            code_a = outcode(a);
+        } else {
+            b = point;
+            code_b = outcode(b);
+        }
+    }
+}
+
+/// Bresenham line between two on-screen points
+fn draw_line(a: (f32, f32), b: (f32, f32), mut plot: impl FnMut(usize, usize)) {
+    let (mut x, mut y) = (a.0.round() as i32, a.1.round() as i32);
+    let (x1, y1) = (b.0.round() as i32, b.1.round() as i32);
+    let dx = (x1 - x).abs();
+    let dy = -(y1 - y).abs();
+    let sx = if x < x1 { 1 } else { -1 };
+    let sy = if y < y1 { 1 } else { -1 };
+    let mut err = dx + dy;
+    loop {
+        plot(x as usize, y as usize);
+        if x == x1 && y == y1 {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{clip_line, draw_line, is_door};
+
+    #[test]
+    fn clip_inside_and_outside() {
+        let inside = clip_line((1.0, 1.0), (10.0, 10.0), 100.0, 100.0);
+        assert_eq!(inside, Some(((1.0, 1.0), (10.0, 10.0))));
+
+        let outside = clip_line((-10.0, -10.0), (-1.0, 50.0), 100.0, 100.0);
+        assert_eq!(outside, None);
+
+        let (a, b) = clip_line((-50.0, 50.0), (150.0, 50.0), 100.0, 100.0).unwrap();
+        assert_eq!(a, (0.0, 50.0));
+        assert_eq!(b, (100.0, 50.0));
+    }
+
+    #[test]
+    fn line_hits_both_ends() {
+        let mut points = Vec::new();
+        draw_line((0.0, 0.0), (4.0, 2.0), |x, y| points.push((x, y)));
+        assert_eq!(points.first(), Some(&(0, 0)));
+        assert_eq!(points.last(), Some(&(4, 2)));
+        assert_eq!(points.len(), 5);
+    }
+
+    #[test]
+    fn door_specials() {
+        assert!(is_door(1));
+        assert!(is_door(137));
+        assert!(is_door(0x3c00 + 3));
+        assert!(!is_door(39));
+        assert!(!is_door(0));
+        assert!(!is_door(0x4000));
+    }
+}
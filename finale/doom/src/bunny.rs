@@ -0,0 +1,91 @@
+//! The end of episode 3. The demon rabbit scrolls in from the right, then
+//! "THE END" is shot on to the screen.
+
+use gamestate_traits::scale::draw_pixel;
+use gamestate_traits::{GameTraits, PixelBuffer, SfxName, SubsystemTrait};
+use wad::WadData;
+use wad::types::WadPatch;
+
+/// Tics before the scroll starts
+const SCROLL_START: i32 = 230;
+/// Tics before "THE END" is shown
+const END_START: i32 = 1130;
+/// Tics before "THE END" is shot
+const SHOT_START: i32 = 1180;
+
+pub(crate) struct Bunny {
+    /// The left (PFUB2) and right (PFUB1) pictures
+    left: WadPatch,
+    right: WadPatch,
+    /// END0 to END6, each with one more bullet hole
+    end: Vec<WadPatch>,
+    count: i32,
+    stage: usize,
+}
+
+impl Bunny {
+    /// Returns `None` if the WAD doesn't have the pictures, such as Doom II
+    pub(crate) fn new(wad: &WadData) -> Option<Self> {
+        let patch = |name: &str| wad.get_lump(name).map(WadPatch::from_lump);
+        Some(Self {
+            left: patch("PFUB2")?,
+            right: patch("PFUB1")?,
+            end: (0..7)
+                .map(|i| patch(&format!("END{i}")))
+                .collect::<Option<_>>()?,
+            count: 0,
+            stage: 0,
+        })
+    }
+
+    pub(crate) fn ticker(&mut self, game: &mut impl GameTraits) {
+        self.count += 1;
+        if self.count < SHOT_START {
+            return;
+        }
+        let stage = (((self.count - SHOT_START) / 5) as usize).min(self.end.len() - 1);
+        if stage > self.stage {
+            game.start_sound(SfxName::Pistol);
+            self.stage = stage;
+        }
+    }
+
+    /// Draw the columns of `patch` that are on screen when its left edge is
+    /// at `x`
+    fn draw_scrolled(
+        patch: &WadPatch,
+        x: i32,
+        machination: &impl SubsystemTrait,
+        pixels: &mut impl PixelBuffer,
+    ) {
+        let scale = machination.screen_scale(pixels.size());
+        let palette = &machination.get_palette().0;
+        let mut sx = x;
+        for column in patch.columns.iter() {
+            if column.y_offset == 255 {
+                sx += 1;
+                continue;
+            }
+            if !(0..320).contains(&sx) {
+                continue;
+            }
+            for (y, p) in column.pixels.iter().enumerate() {
+                let sy = column.y_offset + y as i32;
+                draw_pixel(sx, sy, &scale, &palette[*p], pixels);
+            }
+        }
+    }
+
+    /// Doom function name `F_BunnyScroll`
+    pub(crate) fn draw(&self, machination: &impl SubsystemTrait, pixels: &mut impl PixelBuffer) {
+        let scrolled = (320 - (self.count - SCROLL_START) / 2).clamp(0, 320);
+        Self::draw_scrolled(&self.left, -scrolled, machination, pixels);
+        Self::draw_scrolled(&self.right, 320 - scrolled, machination, pixels);
+
+        if self.count < END_START {
+            return;
+        }
+        let end = &self.end[self.stage];
+        machination.draw_patch_pixels(end, (320 - 13 * 8) / 2, (200 - 8 * 8) / 2, pixels);
+    }
+}
@@ -0,0 +1,245 @@
+//! The Doom II cast call shown after MAP30. Each monster walks, attacks, and
+//! dies on request, while its name is shown beneath it.
+
+use crate::text::*;
+use gamestate_traits::scale::draw_pixel;
+use gamestate_traits::{
+    GameTraits, MOBJINFO, MapObjInfo, MapObjKind, MusTrack, PixelBuffer, STATES, SfxName,
+    SpritePic, StateNum, SubsystemTrait, deh_bex_string,
+};
+use hud_util::{HUD_STRING, HUDString};
+use wad::types::WadPatch;
+
+const FF_FRAMEMASK: u32 = 0x7FFF;
+
+/// The name mnemonic used by BEX, the default name, and the monster
+const CAST_ORDER: [(&str, &str, MapObjKind); 17] = [
+    ("CC_ZOMBIE", CC_ZOMBIE, MapObjKind::MT_POSSESSED),
+    ("CC_SHOTGUN", CC_SHOTGUN, MapObjKind::MT_SHOTGUY),
+    ("CC_HEAVY", CC_HEAVY, MapObjKind::MT_CHAINGUY),
+    ("CC_IMP", CC_IMP, MapObjKind::MT_TROOP),
+    ("CC_DEMON", CC_DEMON, MapObjKind::MT_SERGEANT),
+    ("CC_LOST", CC_LOST, MapObjKind::MT_SKULL),
+    ("CC_CACO", CC_CACO, MapObjKind::MT_HEAD),
+    ("CC_HELL", CC_HELL, MapObjKind::MT_KNIGHT),
+    ("CC_BARON", CC_BARON, MapObjKind::MT_BRUISER),
+    ("CC_ARACH", CC_ARACH, MapObjKind::MT_BABY),
+    ("CC_PAIN", CC_PAIN, MapObjKind::MT_PAIN),
+    ("CC_REVEN", CC_REVEN, MapObjKind::MT_UNDEAD),
+    ("CC_MANCU", CC_MANCU, MapObjKind::MT_FATSO),
+    ("CC_ARCH", CC_ARCH, MapObjKind::MT_VILE),
+    ("CC_SPIDER", CC_SPIDER, MapObjKind::MT_SPIDER),
+    ("CC_CYBER", CC_CYBER, MapObjKind::MT_CYBORG),
+    ("CC_HERO", CC_HERO, MapObjKind::MT_PLAYER),
+];
+
+/// The attack sounds that would otherwise be played by the state actions
+fn attack_sound(state: StateNum) -> SfxName {
+    match state {
+        StateNum::PLAY_ATK1 => SfxName::Dshtgn,
+        StateNum::POSS_ATK2 => SfxName::Pistol,
+        StateNum::SPOS_ATK2 => SfxName::Shotgn,
+        StateNum::VILE_ATK2 => SfxName::Vilatk,
+        StateNum::SKEL_FIST2 => SfxName::Skeswg,
+        StateNum::SKEL_FIST4 => SfxName::Skepch,
+        StateNum::SKEL_MISS2 => SfxName::Skeatk,
+        StateNum::FATT_ATK2 | StateNum::FATT_ATK5 | StateNum::FATT_ATK8 => SfxName::Firsht,
+        StateNum::CPOS_ATK2 | StateNum::CPOS_ATK3 | StateNum::CPOS_ATK4 => SfxName::Shotgn,
+        StateNum::TROO_ATK3 => SfxName::Claw,
+        StateNum::SARG_ATK2 => SfxName::Sgtatk,
+        StateNum::BOSS_ATK2 | StateNum::BOS2_ATK2 | StateNum::HEAD_ATK2 => SfxName::Firsht,
+        StateNum::SKULL_ATK2 => SfxName::Sklatk,
+        StateNum::SPID_ATK2 | StateNum::SPID_ATK3 => SfxName::Shotgn,
+        StateNum::BSPI_ATK2 => SfxName::Plasma,
+        StateNum::CYBER_ATK2 | StateNum::CYBER_ATK4 | StateNum::CYBER_ATK6 => SfxName::Rlaunc,
+        StateNum::PAIN_ATK3 => SfxName::Sklatk,
+        _ => SfxName::None,
+    }
+}
+
+pub(crate) struct Cast {
+    background: Option<WadPatch>,
+    /// Index in to `CAST_ORDER`
+    num: usize,
+    state: StateNum,
+    tics: i32,
+    /// Frames shown since the monster started walking or attacking
+    frames: i32,
+    death: bool,
+    on_melee: bool,
+    attacking: bool,
+    name: HUDString,
+    /// The sprite of the current state, and if it is flipped
+    sprite: Option<(SpritePic, bool)>,
+}
+
+impl Cast {
+    /// Doom function name `F_StartCast`
+    pub(crate) fn new(game: &impl GameTraits) -> Self {
+        game.change_music(MusTrack::Evil);
+        let background = game
+            .get_wad_data()
+            .get_lump(deh_bex_string("BGCASTCALL", "BOSSBACK"))
+            .map(WadPatch::from_lump);
+        let mut cast = Self {
+            background,
+            num: 0,
+            state: StateNum::None,
+            tics: 0,
+            frames: 0,
+            death: false,
+            on_melee: false,
+            attacking: false,
+            name: HUD_STRING,
+            sprite: None,
+        };
+        cast.start_member(game);
+        cast
+    }
+
+    fn info(&self) -> MapObjInfo {
+        unsafe { MOBJINFO[CAST_ORDER[self.num].2 as usize] }
+    }
+
+    /// Set up the monster at `num` walking
+    fn start_member(&mut self, game: &impl GameTraits) {
+        let (key, name, _) = CAST_ORDER[self.num];
+        self.name
+            .replace(deh_bex_string(key, name).to_ascii_uppercase());
+        self.name.set_draw_all();
+        self.death = false;
+        self.frames = 0;
+        self.set_state(self.info().seestate, game);
+    }
+
+    fn set_state(&mut self, state: StateNum, game: &impl GameTraits) {
+        self.state = state;
+        let (sprite, frame) =
+            unsafe { (STATES[state as usize].sprite, STATES[state as usize].frame) };
+        let pic_data = game.pic_data();
+        self.sprite = pic_data
+            .sprite_def(sprite as usize)
+            .frames
+            .get((frame & FF_FRAMEMASK) as usize)
+            .filter(|frame| frame.lump[0] >= 0)
+            .map(|frame| {
+                let patch = pic_data.sprite_patch(frame.lump[0] as usize).clone();
+                (patch, frame.flip[0] != 0)
+            });
+    }
+
+    /// Kill the monster being shown, if it isn't already dying.
+    ///
+    /// Doom function name `F_CastResponder`
+    pub(crate) fn responder(&mut self, game: &mut impl GameTraits) {
+        if self.death {
+            return;
+        }
+        let info = self.info();
+        self.death = true;
+        self.set_state(info.deathstate, game);
+        self.tics = unsafe { STATES[self.state as usize].tics };
+        self.frames = 0;
+        self.attacking = false;
+        if info.deathsound != SfxName::None {
+            game.start_sound(info.deathsound);
+        }
+    }
+
+    /// Doom function name `F_CastTicker`
+    pub(crate) fn ticker(&mut self, game: &mut impl GameTraits) {
+        self.tics -= 1;
+        if self.tics > 0 {
+            return;
+        }
+
+        let (tics, next) = unsafe {
+            (
+                STATES[self.state as usize].tics,
+                STATES[self.state as usize].next_state,
+            )
+        };
+        let mut stop_attack = false;
+        if tics == -1 || next == StateNum::None {
+            // Move on to the next monster
+            self.num = (self.num + 1) % CAST_ORDER.len();
+            let info = self.info();
+            if info.seesound != SfxName::None {
+                game.start_sound(info.seesound);
+            }
+            self.start_member(game);
+        } else if self.state == StateNum::PLAY_ATK1 {
+            stop_attack = true;
+        } else {
+            // Just advance to the next state in the animation
+            self.set_state(next, game);
+            self.frames += 1;
+            let sfx = attack_sound(next);
+            if sfx != SfxName::None {
+                game.start_sound(sfx);
+            }
+        }
+
+        let info = self.info();
+        if !stop_attack && self.frames == 12 {
+            // Go in to an attack, alternating melee and missile if it has both
+            self.attacking = true;
+            let (first, second) = if self.on_melee {
+                (info.meleestate, info.missilestate)
+            } else {
+                (info.missilestate, info.meleestate)
+            };
+            self.on_melee = !self.on_melee;
+            self.set_state(
+                if first == StateNum::None {
+                    second
+                } else {
+                    first
+                },
+                game,
+            );
+        }
+
+        if stop_attack || (self.attacking && (self.frames == 24 || self.state == info.seestate)) {
+            self.attacking = false;
+            self.frames = 0;
+            self.set_state(info.seestate, game);
+        }
+
+        self.tics = unsafe { STATES[self.state as usize].tics };
+        if self.tics == -1 {
+            self.tics = 15;
+        }
+    }
+
+    /// Doom function name `F_CastDrawer`
+    pub(crate) fn draw(&self, machination: &impl SubsystemTrait, pixels: &mut impl PixelBuffer) {
+        if let Some(background) = &self.background {
+            machination.draw_patch_pixels(background, 0, 0, pixels);
+        }
+        self.name
+            .draw(160 - self.name.width() / 2, 180, machination, pixels);
+
+        let Some((sprite, flip)) = &self.sprite else {
+            return;
+        };
+        let scale = machination.screen_scale(pixels.size());
+        let palette = &machination.get_palette().0;
+        let width = sprite.data.len();
+        for (x, column) in sprite.data.iter().enumerate() {
+            let x = if *flip { width - 1 - x } else { x };
+            for (y, p) in column.iter().enumerate() {
+                if *p == usize::MAX {
+                    continue;
+                }
+                draw_pixel(
+                    160 - sprite.left_offset + x as i32,
+                    170 - sprite.top_offset + y as i32,
+                    &scale,
+                    &palette[*p],
+                    pixels,
+                );
+            }
+        }
+    }
+}
@@ -1,12 +1,16 @@
+mod bunny;
+mod cast;
 mod text;
 
+use crate::bunny::Bunny;
+use crate::cast::Cast;
 use crate::text::*;
 use gamestate_traits::{
-    GameMode, GameTraits, MusTrack, PixelBuffer, Scancode, SubsystemTrait, TICRATE
+    GameMode, GameTraits, MusTrack, PixelBuffer, Scancode, SubsystemTrait, TICRATE, deh_bex_string,
 };
 use hud_util::{HUD_STRING, HUDString, load_char_patches};
 use wad::WadData;
-use wad::types::{WadFlat, WadPalette};
+use wad::types::{WadFlat, WadPalette, WadPatch};
 
 pub struct Finale {
     palette: WadPalette,
@@ -14,6 +18,19 @@ pub struct Finale {
     screen_height: i32,
     text: HUDString,
     bg_flat: WadFlat,
+    /// A UMAPINFO backdrop that is a patch instead of a flat
+    bg_patch: Option<WadPatch>,
+    /// The picture shown after the text if the game ends, either from
+    /// UMAPINFO or the one for the episode
+    end_pic: Option<WadPatch>,
+    /// Show the bunny scroller once the text is done
+    end_bunny: bool,
+    /// Show the cast call once the text is done
+    end_cast: bool,
+    bunny: Option<Bunny>,
+    cast: Option<Cast>,
+    /// The text has been shown for long enough or was skipped
+    text_done: bool,
     count: i32,
 }
 
@@ -35,27 +52,58 @@ impl Finale {
             screen_height: 0,
             text: HUD_STRING,
             bg_flat,
+            bg_patch: None,
+            end_pic: None,
+            end_bunny: false,
+            end_cast: false,
+            bunny: None,
+            cast: None,
+            text_done: false,
             count: 0,
         }
     }
 
     fn draw_pixels(&mut self, pixels: &mut impl PixelBuffer) {
-        let f = pixels.size().height() / 200;
         self.screen_width = pixels.size().width();
         self.screen_height = pixels.size().height();
 
+        // The end screens clear the pillarbox or letterbox bars
+        if self.text_done {
+            if let Some(cast) = &self.cast {
+                pixels.clear();
+                cast.draw(self, pixels);
+                return;
+            }
+            if let Some(bunny) = &self.bunny {
+                pixels.clear();
+                bunny.draw(self, pixels);
+                return;
+            }
+            if let Some(patch) = &self.end_pic {
+                pixels.clear();
+                self.draw_patch_pixels(patch, 0, 0, pixels);
+                return;
+            }
+        }
+        if let Some(patch) = &self.bg_patch {
+            self.draw_patch_pixels(patch, 0, 0, pixels);
+            self.text.draw_pixels(6, 6, self, pixels);
+            return;
+        }
+
+        // Tile the flat at the same scale as the text, filling the whole buffer
         let pal = &self.palette;
-        for sx in (0..self.screen_width).step_by(64) {
-            for sy in (0..self.screen_height).step_by(64) {
-                for (y, col) in self.bg_flat.data.chunks(64).enumerate() {
-                    for (x, c) in col.iter().enumerate() {
-                        let c = &pal.0[*c as usize];
-                        pixels.set_pixel(sx as usize + x, sy as usize + y, &c);
-                    }
-                }
+        let scale = self.screen_scale(pixels.size());
+        for py in 0..self.screen_height {
+            for px in 0..self.screen_width {
+                let (x, y) = scale.from_buffer(px as f32 + 0.5, py as f32 + 0.5);
+                let x = (x.floor() as i32).rem_euclid(64) as usize;
+                let y = (y.floor() as i32).rem_euclid(64) as usize;
+                let c = &pal.0[self.bg_flat.data[y * 64 + x] as usize];
+                pixels.set_pixel(px as usize, py as usize, c);
             }
         }
-        self.text.draw_pixels(6 * f, 6 * f, self, pixels);
+        self.text.draw_pixels(6, 6, self, pixels);
     }
 }
 
@@ -63,25 +111,35 @@ impl SubsystemTrait for Finale {
     fn init(&mut self, game: &impl GameTraits) {
         let mut name = "FLOOR4_8";
         self.count = 20 * TICRATE;
+        self.text.clear();
+        self.bg_patch = None;
+        self.end_pic = None;
+        self.bunny = None;
+        self.cast = None;
+        self.text_done = false;
 
         if game.get_mode() != GameMode::Commercial {
             game.change_music(MusTrack::Victor);
             match game.level_end_info().episode + 1 {
                 1 => {
-                    name = "FLOOR4_8";
-                    self.text.replace(E1TEXT.to_ascii_uppercase());
+                    name = deh_bex_string("BGFLATE1", "FLOOR4_8");
+                    self.text
+                        .replace(deh_bex_string("E1TEXT", E1TEXT).to_ascii_uppercase());
                 }
                 2 => {
-                    name = "SFLR6_1";
-                    self.text.replace(E2TEXT.to_ascii_uppercase());
+                    name = deh_bex_string("BGFLATE2", "SFLR6_1");
+                    self.text
+                        .replace(deh_bex_string("E2TEXT", E2TEXT).to_ascii_uppercase());
                 }
                 3 => {
-                    name = "MFLR8_4";
-                    self.text.replace(E3TEXT.to_ascii_uppercase());
+                    name = deh_bex_string("BGFLATE3", "MFLR8_4");
+                    self.text
+                        .replace(deh_bex_string("E3TEXT", E3TEXT).to_ascii_uppercase());
                 }
                 4 => {
-                    name = "MFLR8_3";
-                    self.text.replace(E4TEXT.to_ascii_uppercase());
+                    name = deh_bex_string("BGFLATE4", "MFLR8_3");
+                    self.text
+                        .replace(deh_bex_string("E4TEXT", E4TEXT).to_ascii_uppercase());
                 }
                 _ => {}
             }
@@ -89,41 +147,123 @@ impl SubsystemTrait for Finale {
             game.change_music(MusTrack::Read_M);
             match game.level_end_info().last {
                 6 => {
-                    name = "SLIME16";
-                    self.text.replace(C1TEXT.to_ascii_uppercase());
+                    name = deh_bex_string("BGFLAT06", "SLIME16");
+                    self.text
+                        .replace(deh_bex_string("C1TEXT", C1TEXT).to_ascii_uppercase());
                 }
                 11 => {
-                    name = "RROCK14";
-                    self.text.replace(C2TEXT.to_ascii_uppercase());
+                    name = deh_bex_string("BGFLAT11", "RROCK14");
+                    self.text
+                        .replace(deh_bex_string("C2TEXT", C2TEXT).to_ascii_uppercase());
                 }
                 20 => {
-                    name = "RROCK07";
-                    self.text.replace(C3TEXT.to_ascii_uppercase());
+                    name = deh_bex_string("BGFLAT20", "RROCK07");
+                    self.text
+                        .replace(deh_bex_string("C3TEXT", C3TEXT).to_ascii_uppercase());
                 }
                 30 => {
-                    name = "RROCK17";
-                    self.text.replace(C4TEXT.to_ascii_uppercase());
+                    name = deh_bex_string("BGFLAT30", "RROCK17");
+                    self.text
+                        .replace(deh_bex_string("C4TEXT", C4TEXT).to_ascii_uppercase());
                 }
                 15 => {
-                    name = "RROCK13";
-                    self.text.replace(C5TEXT.to_ascii_uppercase());
+                    name = deh_bex_string("BGFLAT15", "RROCK13");
+                    self.text
+                        .replace(deh_bex_string("C5TEXT", C5TEXT).to_ascii_uppercase());
                 }
                 31 => {
-                    name = "RROCK19";
-                    self.text.replace(C6TEXT.to_ascii_uppercase());
+                    name = deh_bex_string("BGFLAT31", "RROCK19");
+                    self.text
+                        .replace(deh_bex_string("C6TEXT", C6TEXT).to_ascii_uppercase());
                 }
                 _ => {}
             }
         };
 
-        let lump = game.get_wad_data().get_lump(name).unwrap();
+        let wad = game.get_wad_data();
+        let info = game.level_end_info();
+        if let Some(map_info) = &info.last_info {
+            let text = if info.secret_exit {
+                &map_info.inter_text_secret
+            } else {
+                &map_info.inter_text
+            };
+            if let Some(text) = text {
+                self.text.replace(
+                    text.to_ascii_uppercase()
+                        .chars()
+                        .filter(|c| *c == '\n' || (' '..='_').contains(c))
+                        .collect(),
+                );
+            }
+            if let Some(backdrop) = &map_info.inter_backdrop
+                && let Some(lump) = wad.get_lump(backdrop)
+            {
+                // Flats are a raw 64x64 block, anything else must be a patch
+                if lump.data.len() == 64 * 64 {
+                    name = backdrop;
+                } else {
+                    self.bg_patch = Some(WadPatch::from_lump(lump));
+                }
+            }
+            if let Some(music) = &map_info.inter_music {
+                game.change_music_lump(music);
+            }
+            if info.end_game {
+                self.end_pic = map_info
+                    .end_pic
+                    .as_ref()
+                    .and_then(|pic| wad.get_lump(pic))
+                    .map(WadPatch::from_lump);
+            }
+        }
+        let mode = game.get_mode();
+        self.end_cast = info.end_game
+            && match &info.last_info {
+                Some(map_info) if map_info.end_cast => true,
+                Some(map_info) if map_info.end_pic.is_some() || map_info.end_bunny => false,
+                _ => mode == GameMode::Commercial,
+            };
+        self.end_bunny = info.end_game
+            && match &info.last_info {
+                Some(map_info) if map_info.end_bunny => true,
+                Some(map_info) if map_info.end_pic.is_some() || map_info.end_cast => false,
+                _ => mode != GameMode::Commercial && info.episode == 2,
+            };
+        if info.end_game && self.end_pic.is_none() && !self.end_cast && !self.end_bunny {
+            let name = match info.episode + 1 {
+                1 if mode == GameMode::Retail => "CREDIT",
+                1 => "HELP2",
+                2 => "VICTORY2",
+                _ => "ENDPIC",
+            };
+            self.end_pic = wad.get_lump(name).map(WadPatch::from_lump);
+        }
+        if self.text.line().is_empty() {
+            self.count = 0;
+        }
+
+        let lump = wad.get_lump(name).unwrap();
         self.bg_flat = WadFlat {
             name: name.to_string(),
             data: lump.data.clone(),
         };
     }
 
-    fn responder(&mut self, sc: Scancode, _game: &mut impl GameTraits) -> bool {
+    fn responder(&mut self, sc: Scancode, game: &mut impl GameTraits) -> bool {
+        if self.text_done && self.cast.is_none() {
+            return false;
+        }
+        if let Some(cast) = &mut self.cast {
+            if matches!(
+                sc,
+                Scancode::Return | Scancode::Space | Scancode::LCtrl | Scancode::RCtrl
+            ) {
+                cast.responder(game);
+                return true;
+            }
+            return false;
+        }
         if sc == Scancode::Return || sc == Scancode::Space {
             if !self.text.is_at_end() {
                 self.text.set_draw_all();
@@ -136,13 +276,27 @@ impl SubsystemTrait for Finale {
     }
 
     fn ticker(&mut self, game: &mut impl GameTraits) -> bool {
+        if let Some(cast) = &mut self.cast {
+            cast.ticker(game);
+            return false;
+        }
+        if let Some(bunny) = &mut self.bunny {
+            bunny.ticker(game);
+            return false;
+        }
         self.text.inc_current_char();
         self.count -= 1;
-        if self.count <= 0
-            && game.get_mode() == GameMode::Commercial
-            && game.level_end_info().last != 30
-        {
+        if self.count <= 0 && !self.text_done {
+            self.text_done = true;
             game.finale_done();
+            if self.end_cast {
+                self.cast = Some(Cast::new(game));
+            } else if self.end_bunny {
+                self.bunny = Bunny::new(game.get_wad_data());
+                if self.bunny.is_some() {
+                    game.change_music(MusTrack::Bunny);
+                }
+            }
         }
         false
     }
@@ -271,3 +271,21 @@ BLAZE THROUGH THIS ONE!";
 
 // As you step off the transport, you hear
 // the stomp of a cyberdemon's iron shoe.";
+
+pub(crate) const CC_ZOMBIE: &str = "ZOMBIEMAN";
+pub(crate) const CC_SHOTGUN: &str = "SHOTGUN GUY";
+pub(crate) const CC_HEAVY: &str = "HEAVY WEAPON DUDE";
+pub(crate) const CC_IMP: &str = "IMP";
+pub(crate) const CC_DEMON: &str = "DEMON";
+pub(crate) const CC_LOST: &str = "LOST SOUL";
+pub(crate) const CC_CACO: &str = "CACODEMON";
+pub(crate) const CC_HELL: &str = "HELL KNIGHT";
+pub(crate) const CC_BARON: &str = "BARON OF HELL";
+pub(crate) const CC_ARACH: &str = "ARACHNOTRON";
+pub(crate) const CC_PAIN: &str = "PAIN ELEMENTAL";
+pub(crate) const CC_REVEN: &str = "REVENANT";
+pub(crate) const CC_MANCU: &str = "MANCUBUS";
+pub(crate) const CC_ARCH: &str = "ARCH-VILE";
+pub(crate) const CC_SPIDER: &str = "THE SPIDER MASTERMIND";
+pub(crate) const CC_CYBER: &str = "THE CYBERDEMON";
+pub(crate) const CC_HERO: &str = "OUR HERO";
@@ -1,7 +1,7 @@
 //! Game cheats. These are what players type in, e.g, `iddqd`
 
 use gameplay::log::debug;
-use gameplay::{GameMission, PlayerCheat, PowerType, Skill, WeaponType, english};
+use gameplay::{GameMission, PlayerCheat, PowerType, Skill, WeaponType, deh_cheat, english};
 use gamestate::Game;
 use gamestate_traits::GameTraits;
 use gamestate_traits::sdl2::keyboard::{Keycode, Scancode};
@@ -37,25 +37,25 @@ pub struct Cheats {
 }
 
 impl Cheats {
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
         Self {
-            god: Cheat::new("iddqd", 0),
-            mus: Cheat::new("idmus", 2),
-            ammo: Cheat::new("idkfa", 0),
-            ammonokey: Cheat::new("idfa", 0),
-            noclip: Cheat::new("idspispopd", 0),
-            commercial_noclip: Cheat::new("idclip", 0),
+            god: Cheat::new(deh_cheat("iddqd"), 0),
+            mus: Cheat::new(deh_cheat("idmus"), 2),
+            ammo: Cheat::new(deh_cheat("idkfa"), 0),
+            ammonokey: Cheat::new(deh_cheat("idfa"), 0),
+            noclip: Cheat::new(deh_cheat("idspispopd"), 0),
+            commercial_noclip: Cheat::new(deh_cheat("idclip"), 0),
             powerup: [
-                Cheat::new("idbeholdv", 0),
-                Cheat::new("idbeholds", 0),
-                Cheat::new("idbeholdi", 0),
-                Cheat::new("idbeholdr", 0),
-                Cheat::new("idbeholda", 0),
-                Cheat::new("idbeholdl", 0),
+                Cheat::new(deh_cheat("idbeholdv"), 0),
+                Cheat::new(deh_cheat("idbeholds"), 0),
+                Cheat::new(deh_cheat("idbeholdi"), 0),
+                Cheat::new(deh_cheat("idbeholdr"), 0),
+                Cheat::new(deh_cheat("idbeholda"), 0),
+                Cheat::new(deh_cheat("idbeholdl"), 0),
             ],
-            choppers: Cheat::new("idchoppers", 0),
-            _clev: Cheat::new("idclev", 2),
-            mypos: Cheat::new("idmypos", 0),
+            choppers: Cheat::new(deh_cheat("idchoppers"), 0),
+            _clev: Cheat::new(deh_cheat("idclev"), 2),
+            mypos: Cheat::new(deh_cheat("idmypos"), 0),
         }
     }
 
@@ -16,6 +16,9 @@ pub struct CLIOptions {
     /// path to patch WAD
     #[argh(option, short = 'p')]
     pub pwad: Vec<String>,
+    /// path to dehacked or BEX patch file
+    #[argh(option)]
+    pub deh: Vec<String>,
     /// resolution width in pixels
     #[argh(option, default = "0", short = 'w')]
     pub width: u32,
@@ -35,16 +38,31 @@ pub struct CLIOptions {
     /// disable monsters
     #[argh(switch, short = 'n')]
     pub no_monsters: bool,
-    // /// Monsters respawn after being killed
-    // pub respawn_parm: bool,
-    // /// Monsters move faster
-    // pub fast_parm: bool,
+    /// monsters respawn after being killed, as in nightmare
+    #[argh(switch)]
+    pub respawn: bool,
+    /// monsters attack faster and some projectiles are faster, as in
+    /// nightmare
+    #[argh(switch)]
+    pub fast: bool,
     /// developer mode. Screen is cleared with green colour for seg/flat drawing
     /// leak checks
     #[argh(switch)]
     pub dev_parm: bool,
-    //     help = "Start a deathmatch game-exe: 1 = classic, 2 = Start a deathmatch 2.0 game-exe.
-    // Weapons do not stay in place and all items respawn after 30 seconds" pub deathmatch: u8,
+    /// start a deathmatch game. Weapons stay in place and items do not
+    /// respawn
+    #[argh(switch)]
+    pub deathmatch: bool,
+    /// start a deathmatch 2.0 game. Weapons do not stay in place and all
+    /// items respawn after 30 seconds. Takes precedence over `--deathmatch`
+    #[argh(switch)]
+    pub altdeath: bool,
+    /// end each deathmatch level after this many minutes
+    #[argh(option)]
+    pub time_limit: Option<i32>,
+    /// end each deathmatch level when a player reaches this many frags
+    #[argh(option)]
+    pub frag_limit: Option<i32>,
     // pub autostart: bool,
     /// set the game-exe skill, 0-4 (0: easiest, 4: hardest)
     #[argh(option, short = 's')]
@@ -65,9 +83,43 @@ pub struct CLIOptions {
     /// music type <fluidsynth, timidity(default)>. Unfinished
     #[argh(option, short = 'M')]
     pub music_type: Option<MusicType>,
-    /// enable demo playback (currently bad due to f32 used in movements)
+    /// draw fireballs, fog and some powerups translucent, as BOOM does
+    #[argh(option)]
+    pub translucency: Option<bool>,
+    /// record a demo to this file, `.lmp` is added if there is no extension.
+    /// The game starts at the chosen episode, map, and skill
+    #[argh(option)]
+    pub record: Option<String>,
+    /// play a demo lump or file then quit
+    #[argh(option)]
+    pub playdemo: Option<String>,
+    /// run this many tics with no window or sound, then print the level stats
+    /// and player position
+    #[argh(option)]
+    pub headless: Option<u32>,
+    /// tic commands for a headless run, one per line as `forward side turn
+    /// buttons [repeat]`
+    #[argh(option)]
+    pub tic_script: Option<String>,
+    /// play a netgame as this player, 1-4. Player 1 chooses the skill,
+    /// episode, and map for everyone
+    #[argh(option)]
+    pub net: Option<usize>,
+    /// `address:port` of a netgame node. Given once for each player, in player
+    /// order, and the same on every node
+    #[argh(option)]
+    pub node: Vec<String>,
+    /// enable demo playback, demos always use the vanilla physics
     #[argh(switch, short = 'E')]
     pub enable_demos: bool,
+    /// use the vanilla collision and movement so the game plays exactly as the
+    /// original does
+    #[argh(switch)]
+    pub vanilla_physics: bool,
+    /// let pain elementals spit out any number of lost souls, instead of
+    /// stopping at 20 on the map
+    #[argh(switch)]
+    pub no_skull_limit: bool,
 }
 
 impl From<CLIOptions> for GameOptions {
@@ -80,16 +132,30 @@ impl From<CLIOptions> for GameOptions {
             skill: g.skill.unwrap_or_default(),
             episode: g.episode.unwrap_or_default(),
             map: g.map.unwrap_or_default(),
-            warp: g.map.is_some() || g.episode.is_some(),
+            // A headless run has no title screen, so goes straight to the game
+            warp: g.map.is_some() || g.episode.is_some() || g.headless.is_some(),
             hi_res: g.hi_res && !g.lo_res,
             verbose: g.verbose.unwrap_or(log::LevelFilter::Warn),
-            respawn_parm: false,
+            respawn_parm: g.respawn,
             respawn_monsters: false,
-            fast_parm: false,
-            deathmatch: 0,
+            fast_parm: g.fast,
+            deathmatch: if g.altdeath {
+                2
+            } else if g.deathmatch {
+                1
+            } else {
+                0
+            },
+            time_limit: g.time_limit,
+            frag_limit: g.frag_limit,
             autostart: false,
             enable_demos: g.enable_demos,
             netgame: false,
+            save_dir: config::get_save_dir(),
+            deh: g.deh,
+            translucency: g.translucency.unwrap_or_default(),
+            vanilla_physics: g.vanilla_physics,
+            no_skull_limit: g.no_skull_limit,
         }
     }
 }
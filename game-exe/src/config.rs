@@ -3,6 +3,7 @@
 use crate::{BASE_DIR, CLIOptions};
 use dirs::config_dir;
 use gameplay::log::{error, info, warn};
+use gamestate_traits::Settings;
 use input::config::InputConfig;
 use nanoserde::{DeRon, SerRon};
 use render_target::shaders::Shaders;
@@ -14,7 +15,7 @@ use std::str::FromStr;
 
 const LOG_TAG: &str = "UserConfig";
 
-fn get_cfg_file() -> PathBuf {
+fn get_cfg_dir() -> PathBuf {
     let mut dir =
         config_dir().unwrap_or_else(|| panic!("{}: Couldn't open user config dir", LOG_TAG));
     dir.push(BASE_DIR);
@@ -22,10 +23,37 @@ fn get_cfg_file() -> PathBuf {
         create_dir(&dir)
             .unwrap_or_else(|e| panic!("{}: Couldn't create {:?}: {}", LOG_TAG, dir, e));
     }
+    dir
+}
+
+fn get_cfg_file() -> PathBuf {
+    let mut dir = get_cfg_dir();
     dir.push("user.toml");
     dir
 }
 
+/// Savegames are kept in a `saves` directory beside the user config
+pub fn get_save_dir() -> PathBuf {
+    let mut dir = get_cfg_dir();
+    dir.push("saves");
+    if !dir.exists() {
+        create_dir(&dir)
+            .unwrap_or_else(|e| panic!("{}: Couldn't create {:?}: {}", LOG_TAG, dir, e));
+    }
+    dir
+}
+
+/// Screenshots are kept in a `screenshots` directory beside the user config
+pub fn get_screenshot_dir() -> PathBuf {
+    let mut dir = get_cfg_dir();
+    dir.push("screenshots");
+    if !dir.exists() {
+        create_dir(&dir)
+            .unwrap_or_else(|e| panic!("{}: Couldn't create {:?}: {}", LOG_TAG, dir, e));
+    }
+    dir
+}
+
 #[derive(Debug, Default, PartialEq, PartialOrd, Clone, Copy, DeRon, SerRon)]
 pub enum RenderType {
     /// Purely software. Typically used with blitting a framebuffer maintained
@@ -69,6 +97,17 @@ impl Into<render_target::RenderApiType> for RenderType {
     }
 }
 
+impl From<render_target::RenderApiType> for RenderType {
+    fn from(api: render_target::RenderApiType) -> Self {
+        match api {
+            render_target::RenderApiType::Software => RenderType::Software,
+            render_target::RenderApiType::SoftOpenGL => RenderType::SoftOpenGL,
+            render_target::RenderApiType::OpenGL => RenderType::OpenGL,
+            render_target::RenderApiType::Vulkan => RenderType::Vulkan,
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, DeRon, SerRon)]
 pub enum MusicType {
     FluidSynth,
@@ -105,6 +144,25 @@ pub struct UserConfig {
     pub music_type: MusicType,
     pub gus_mem_size: GusMemSize,
     pub input: InputConfig,
+    /// Draw fireballs, fog and some powerups translucent, as BOOM does
+    #[nserde(default)]
+    pub translucency: bool,
+    /// Mouse sensitivity as set in the options menu, 0-9
+    #[nserde(default = 5)]
+    pub mouse_sensitivity: i32,
+    /// Screen size as set in the options menu, 10 with the statusbar or 11
+    /// without
+    #[nserde(default = 10)]
+    pub screen_size: i32,
+    /// Show player messages such as item pickups
+    #[nserde(default = "true")]
+    pub show_messages: bool,
+    /// Statusbar and message size as a percentage, 50-100
+    #[nserde(default = 100)]
+    pub hud_scale: i32,
+    /// Stretch menus, HUD and other screens to 4:3
+    #[nserde(default = "true")]
+    pub aspect_correct: bool,
 }
 
 impl UserConfig {
@@ -143,6 +201,11 @@ impl UserConfig {
             fullscreen: true,
             sfx_vol: 80,
             mus_vol: 70,
+            mouse_sensitivity: 5,
+            screen_size: 10,
+            show_messages: true,
+            hud_scale: 100,
+            aspect_correct: true,
             ..UserConfig::default()
         };
         info!("Created default user config file");
@@ -161,6 +224,41 @@ impl UserConfig {
             .unwrap_or_else(|err| error!("Could not write config: {}", err));
     }
 
+    /// The settings that can be changed from the menus
+    pub fn settings(&self) -> Settings {
+        Settings {
+            sfx_volume: self.sfx_vol,
+            mus_volume: self.mus_vol,
+            mouse_sensitivity: self.mouse_sensitivity,
+            // Older configs may have a smaller vanilla size
+            screen_size: self.screen_size.clamp(10, 11),
+            show_messages: self.show_messages,
+            hi_res: self.hi_res,
+            width: self.width,
+            height: self.height,
+            fullscreen: self.fullscreen,
+            renderer: self.renderer.into(),
+            hud_scale: self.hud_scale,
+            aspect_correct: self.aspect_correct,
+        }
+    }
+
+    /// Store the settings changed from the menus
+    pub fn set_settings(&mut self, settings: &Settings) {
+        self.sfx_vol = settings.sfx_volume;
+        self.mus_vol = settings.mus_volume;
+        self.mouse_sensitivity = settings.mouse_sensitivity;
+        self.screen_size = settings.screen_size;
+        self.show_messages = settings.show_messages;
+        self.hi_res = settings.hi_res;
+        self.width = settings.width;
+        self.height = settings.height;
+        self.fullscreen = settings.fullscreen;
+        self.renderer = settings.renderer.into();
+        self.hud_scale = settings.hud_scale;
+        self.aspect_correct = settings.aspect_correct;
+    }
+
     /// Sync the CLI options and UserOptions with each other
     pub fn sync_cli(&mut self, cli: &mut CLIOptions) {
         info!("Checking CLI options");
@@ -215,6 +313,12 @@ impl UserConfig {
             cli.fullscreen = Some(self.fullscreen);
         }
 
+        if let Some(t) = cli.translucency {
+            self.translucency = t;
+        } else {
+            cli.translucency = Some(self.translucency);
+        }
+
         if let Some(f) = cli.music_type {
             if f != self.music_type {
                 self.music_type = f;
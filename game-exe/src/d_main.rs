@@ -10,7 +10,7 @@
 //! - `Intermission`
 //! - `Messages`
 //! - `Finale`
-//! - others may be added such as automap
+//! - `AutoMap`
 //!
 //! Note that the sound system runs on its own thread.
 //!
@@ -20,17 +20,21 @@
 
 use std::error::Error;
 
+use automap_doom::AutoMap;
 use finale_doom::Finale;
 use gameplay::MapObject;
-use gameplay::log::{error, info};
-use gameplay::tic_cmd::{BASELOOKDIRMAX, BASELOOKDIRMIN, LOOKDIRMAX, LOOKDIRMIN, LOOKDIRS};
-use gamestate::Game;
+use gameplay::log::{error, info, warn};
+use gameplay::tic_cmd::{BASELOOKDIRMAX, BASELOOKDIRMIN, LOOKDIRMAX, LOOKDIRMIN, LOOKDIRS, TicCmd};
+use gamestate::net::NetGame;
 use gamestate::subsystems::GameSubsystem;
+use gamestate::{BACKUPTICS, Game};
+use gamestate_traits::scale::{draw_patch, set_screen_scaling};
 use gamestate_traits::sdl2::event::{Event, WindowEvent};
 use gamestate_traits::sdl2::keyboard::Scancode;
 use gamestate_traits::sdl2::video::{DisplayMode, Window, WindowPos};
 use gamestate_traits::{
-    GameState, PixelBuffer, PlayViewRenderer, RenderTrait, SubsystemTrait, sdl2,
+    GameState, GameTraits, PixelBuffer, PlayViewRenderer, RenderTrait, ScreenScale, Settings,
+    SubsystemTrait, sdl2,
 };
 use hud_doom::Messages;
 use input::Input;
@@ -43,6 +47,7 @@ use wad::types::WadPatch;
 
 use crate::CLIOptions;
 use crate::cheats::Cheats;
+use crate::config::{UserConfig, get_screenshot_dir};
 use crate::timestep::TimeStep;
 
 const fn set_lookdirs(options: &CLIOptions) {
@@ -88,14 +93,52 @@ fn assign_window_size(
     Ok(())
 }
 
-/// Never returns until `game.running` is set to false
+/// Change the window to the resolution and fullscreen state in the settings if
+/// they differ from the `options`. Returns true if the window was changed.
+fn set_video_mode(
+    window: &mut Window,
+    current_display_mode: DisplayMode,
+    settings: &Settings,
+    options: &mut CLIOptions,
+) -> Result<bool, Box<dyn Error>> {
+    let fullscreen = options.fullscreen.unwrap_or_default();
+    if settings.fullscreen == fullscreen
+        && settings.width == options.width
+        && settings.height == options.height
+    {
+        return Ok(false);
+    }
+
+    if settings.fullscreen != fullscreen {
+        window.set_fullscreen(if settings.fullscreen {
+            sdl2::video::FullscreenType::Desktop
+        } else {
+            sdl2::video::FullscreenType::Off
+        })?;
+        options.fullscreen = Some(settings.fullscreen);
+    }
+    options.width = settings.width;
+    options.height = settings.height;
+    assign_window_size(window, current_display_mode, options)?;
+    info!(
+        "Video mode changed to {}x{}, fullscreen: {}",
+        options.width, options.height, settings.fullscreen
+    );
+    Ok(true)
+}
+
+/// Never returns until `game.running` is set to false. Settings changed in the
+/// menus are written back to the `user_config` on exit.
+#[allow(clippy::too_many_arguments)]
 pub fn d_doom_loop(
     mut game: Game,
     mut input: Input,
     current_display_mode: DisplayMode,
     window: Window,
     gl_ctx: golem::Context,
-    options: CLIOptions,
+    mut options: CLIOptions,
+    mut user_config: UserConfig,
+    mut net: Option<NetGame>,
 ) -> Result<(), Box<dyn Error>> {
     // TODO: implement an openGL or Vulkan renderer
     // TODO: check res aspect and set widescreen or no
@@ -109,10 +152,16 @@ pub fn d_doom_loop(
         intermission: Intermission::new(game.game_type.mode, &game.wad_data),
         hud_msgs: Messages::new(&game.wad_data),
         finale: Finale::new(&game.wad_data),
+        automap: AutoMap::new(&game.wad_data),
     };
 
     // Start demo playback and titlescreens +
-    if options.episode.is_none() && options.map.is_none() {
+    if options.episode.is_none()
+        && options.map.is_none()
+        && options.record.is_none()
+        && options.playdemo.is_none()
+        && net.is_none()
+    {
         game.start_title();
     }
     let mut canvas = window.into_canvas().accelerated().present_vsync().build()?;
@@ -140,6 +189,7 @@ pub fn d_doom_loop(
         // - drawers, these take a state from above and display it to the user
 
         // Update the game-exe state
+        let mut rebuild = false;
         if let Some(event) = try_run_tics(
             &mut game,
             &mut input,
@@ -147,6 +197,7 @@ pub fn d_doom_loop(
             &mut machines,
             &mut cheats,
             &mut timestep,
+            net.as_mut(),
         ) {
             match event {
                 Event::Window {
@@ -155,18 +206,7 @@ pub fn d_doom_loop(
                     win_event,
                 } => match win_event {
                     sdl2::event::WindowEvent::SizeChanged(..) => {
-                        // BEGIN SETUP
-                        set_lookdirs(&options);
-                        let canvas = render_target.framebuffer.canvas;
-                        render_target = RenderTarget::new(
-                            options.hi_res,
-                            options.dev_parm,
-                            canvas,
-                            &gl_ctx,
-                            options.rendering.unwrap_or_default().into(),
-                            options.shader.unwrap_or_default(),
-                        );
-                        // END
+                        rebuild = true;
                         info!("Resized game window");
                     }
                     _ => {}
@@ -175,6 +215,44 @@ pub fn d_doom_loop(
             }
         }
 
+        // Apply any settings changed in the menus
+        let settings = *game.settings();
+        input
+            .events
+            .set_mouse_scale((settings.mouse_sensitivity, 1));
+        set_screen_scaling(settings.aspect_correct, settings.hud_scale);
+        if settings.hi_res != options.hi_res {
+            options.hi_res = settings.hi_res;
+            rebuild = true;
+        }
+        if Some(settings.renderer) != options.rendering.map(Into::into) {
+            options.rendering = Some(settings.renderer.into());
+            rebuild = true;
+        }
+        if set_video_mode(
+            render_target.framebuffer.canvas.window_mut(),
+            current_display_mode,
+            &settings,
+            &mut options,
+        )? {
+            rebuild = true;
+        }
+
+        if rebuild {
+            // BEGIN SETUP
+            set_lookdirs(&options);
+            let canvas = render_target.framebuffer.canvas;
+            render_target = RenderTarget::new(
+                options.hi_res,
+                options.dev_parm,
+                canvas,
+                &gl_ctx,
+                options.rendering.unwrap_or_default().into(),
+                options.shader.unwrap_or_default(),
+            );
+            // END
+        }
+
         // Update the positional sounds
         // Update the listener of the sound server. Will always be consoleplayer.
         if let Some(mobj) = game.players[game.consoleplayer].mobj() {
@@ -199,38 +277,34 @@ pub fn d_doom_loop(
         }
     }
 
+    // Let the other nodes run the tics this one has already run
+    if let Some(net) = net.as_mut() {
+        net.flush(game.game_tic, &mut game.netcmds);
+    }
+
+    user_config.set_settings(game.settings());
+    user_config.input = game.key_bindings().into();
+    user_config.write();
+
     // Explicit drop to ensure shutdown happens
+    drop(net);
     drop(game);
     drop(gl_ctx);
     Ok(())
 }
 
 fn page_drawer(game: &mut Game, draw_buf: &mut impl PixelBuffer) {
-    let f = draw_buf.size().height() / 200;
-    let mut ytmp = 0;
-    let mut xtmp = f - 1;
-    for column in game.page.cache.columns.iter() {
-        for n in 0..f {
-            for p in column.pixels.iter() {
-                let colour = game.pic_data.palette()[*p];
-                for _ in 0..f {
-                    let x = (xtmp - n) as usize;
-                    let y = (ytmp + column.y_offset * f) as usize;
-                    draw_buf.set_pixel(
-                        x, // - (image.left_offset as i32),
-                        y, /* - image.top_offset as i32 - 30, */
-                        &colour,
-                    );
-                    ytmp += 1;
-                }
-            }
-            ytmp = 0;
-
-            if column.y_offset == 255 {
-                xtmp += 1;
-            }
-        }
-    }
+    // Clear the pillarbox or letterbox bars
+    draw_buf.clear();
+    let scale = ScreenScale::full(draw_buf.size());
+    draw_patch(
+        &game.page.cache,
+        0,
+        0,
+        &scale,
+        game.pic_data.palette(),
+        draw_buf,
+    );
 }
 
 /// Does a bunch of stuff in Doom...
@@ -249,14 +323,14 @@ fn d_display<R>(
         impl SubsystemTrait,
         impl SubsystemTrait,
         impl SubsystemTrait,
+        impl SubsystemTrait,
     >,
     game: &mut Game,
 ) where
     R: RenderTrait + PlayViewRenderer,
 {
     let wipe = game.gamestate != game.wipe_game_state;
-    let automap_active = false;
-    //if (gamestate == GS_LEVEL && !automapactive && gametic)
+    let automap_active = game.automap_active();
 
     // Drawing order is different for RUST4DOOM as the screensize-statusbar is
     // never taken in to account. A full Doom-style statusbar will never be added
@@ -285,11 +359,20 @@ fn d_display<R>(
 
     match game.gamestate {
         GameState::Level => {
-            // TODO: Automap draw
-            machines.statusbar.draw(rend_target.draw_buffer());
+            if automap_active {
+                machines.automap.draw(rend_target.draw_buffer());
+            }
+            // The full screen size hides the statusbar, except on the automap
+            if automap_active || game.settings().screen_size < 11 {
+                machines.statusbar.draw(rend_target.draw_buffer());
+            }
             machines.hud_msgs.draw(rend_target.draw_buffer());
         }
-        GameState::Intermission => machines.intermission.draw(rend_target.draw_buffer()),
+        GameState::Intermission => {
+            // Clear the pillarbox or letterbox bars
+            rend_target.draw_buffer().clear();
+            machines.intermission.draw(rend_target.draw_buffer());
+        }
         GameState::Finale => machines.finale.draw(rend_target.draw_buffer()),
         GameState::DemoScreen => {
             if game.page.cache.name != game.page.name {
@@ -324,11 +407,58 @@ fn d_display<R>(
         menu.draw(rend_target.blit_buffer());
     } else {
         menu.draw(rend_target.draw_buffer());
+        if game.take_screenshot {
+            game.take_screenshot = false;
+            save_screenshot(rend_target.draw_buffer());
+        }
         rend_target.flip();
     }
     rend_target.blit();
 }
 
+/// Write the buffer to the first free `DOOMnn.bmp` in the screenshot dir.
+///
+/// Doom function name `M_ScreenShot`
+fn save_screenshot(pixels: &impl PixelBuffer) {
+    let dir = get_screenshot_dir();
+    let Some(path) = (0..100)
+        .map(|i| dir.join(format!("DOOM{i:02}.bmp")))
+        .find(|path| !path.exists())
+    else {
+        warn!("Couldn't take a screenshot, the screenshot dir is full");
+        return;
+    };
+
+    let width = pixels.size().width_usize();
+    let height = pixels.size().height_usize();
+    // Rows are stored bottom-up, as BGR padded to 4 bytes
+    let row_len = (width * 3).next_multiple_of(4);
+    let image_len = row_len * height;
+    let mut data = Vec::with_capacity(54 + image_len);
+    data.extend_from_slice(b"BM");
+    data.extend_from_slice(&((54 + image_len) as u32).to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&54u32.to_le_bytes());
+    data.extend_from_slice(&40u32.to_le_bytes());
+    data.extend_from_slice(&(width as i32).to_le_bytes());
+    data.extend_from_slice(&(height as i32).to_le_bytes());
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&24u16.to_le_bytes());
+    data.extend_from_slice(&[0; 24]);
+    for y in (0..height).rev() {
+        for x in 0..width {
+            let [r, g, b, ..] = pixels.read_pixel(x, y);
+            data.extend_from_slice(&[b, g, r]);
+        }
+        data.resize(data.len() + row_len - width * 3, 0);
+    }
+
+    match std::fs::write(&path, data) {
+        Ok(_) => info!("Saved screenshot to {:?}", path),
+        Err(e) => error!("Couldn't write screenshot {:?}: {}", path, e),
+    }
+}
+
 fn try_run_tics(
     game: &mut Game,
     input: &mut Input,
@@ -338,27 +468,47 @@ fn try_run_tics(
         impl SubsystemTrait,
         impl SubsystemTrait,
         impl SubsystemTrait,
+        impl SubsystemTrait,
     >,
     cheats: &mut Cheats,
     timestep: &mut TimeStep,
+    mut net: Option<&mut NetGame>,
 ) -> Option<Event> {
-    // TODO: net.c starts here
-    // Build tics here?
     let mut event_return = None;
-    timestep.run_this(|tics| {
+    timestep.run_this(|_| {
         // D_ProcessEvents
         if let Some(e) = process_events(game, input, menu, machinations, cheats) {
             event_return.replace(e);
         }
 
+        let cmd = if game.gamestate == game.wipe_game_state {
+            let player = &game.players[game.consoleplayer];
+            input.events.build_tic_cmd(game.key_bindings(), player)
+        } else {
+            TicCmd::new()
+        };
+
+        if let Some(net) = net.as_deref_mut() {
+            // net update does i/o and buildcmds
+            game.net_update(net, cmd);
+            // The menu can't pause a netgame
+            menu.ticker(game);
+            while net.ready(game.game_tic) {
+                game.ticker(machinations); // G_Ticker
+                game.game_tic += 1;
+            }
+            return;
+        }
+
         if game.demo.advance {
             game.do_advance_demo();
         }
+        game.netcmds[game.consoleplayer][game.game_tic as usize % BACKUPTICS] = cmd;
         // Did menu take control?
         if !menu.ticker(game) {
             game.ticker(machinations); // G_Ticker
         }
-        game.game_tic = tics as u32;
+        game.game_tic += 1;
     });
     event_return
 }
@@ -372,12 +522,16 @@ fn process_events(
         impl SubsystemTrait,
         impl SubsystemTrait,
         impl SubsystemTrait,
+        impl SubsystemTrait,
     >,
     cheats: &mut Cheats,
 ) -> Option<Event> {
+    // Controller buttons stand in for keys in the menus and other responders
+    let pad_keys = input.events.take_pad_keys(game.key_bindings());
+
     // required for cheats and menu so they don't receive multiple key-press fo same
     // key
-    let input_callback = |sc: Scancode| {
+    let mut input_callback = |sc: Scancode| {
         if game.level.is_some() {
             cheats.check_input(sc, game);
         }
@@ -387,6 +541,10 @@ fn process_events(
             return true; // Menu took event
         }
 
+        if game.gamestate == GameState::Level && machinations.automap.responder(sc, game) {
+            return true; // Automap took event
+        }
+
         if machinations.hud_msgs.responder(sc, game) {
             return true; // Menu took event
         }
@@ -425,15 +583,10 @@ fn process_events(
         _ => {}
     };
 
-    input.update(input_callback, event_callback);
-    let console_player = game.consoleplayer;
-    // net update does i/o and buildcmds...
-    // TODO: NetUpdate(); // send out any new accumulation
-    // TODO: Network code would update each player slot with incoming TicCmds...
-    if game.gamestate == game.wipe_game_state {
-        let cmd = input.events.build_tic_cmd(&input.config);
-        game.netcmds[console_player][0] = cmd;
+    for sc in pad_keys {
+        input_callback(sc);
     }
+    input.update(input_callback, event_callback);
 
     event_return
 }
@@ -0,0 +1,150 @@
+//! Runs the `Game` without a window, renderer, or sound. Input comes from a
+//! demo or a tic script, and after the requested number of tics the level
+//! stats and player position are printed. This is useful for checking that
+//! the simulation stays in sync, e.g. in CI.
+//!
+//! A tic script is a text file with one command per line:
+//! `forward side turn buttons [repeat]`. `forward` and `side` are the move
+//! amounts, `turn` is the `angleturn` of the `TicCmd`, and `repeat` is how
+//! many tics the command is held for (default 1). Empty lines and lines
+//! starting with `#` are skipped.
+//!
+//! Several headless netgame nodes can be run on one machine to check that
+//! they stay in sync, they all print the same results if they do.
+
+use std::error::Error;
+use std::time::Duration;
+
+use automap_doom::AutoMap;
+use finale_doom::Finale;
+use gameplay::tic_cmd::TicCmd;
+use gamestate::net::NetGame;
+use gamestate::subsystems::GameSubsystem;
+use gamestate::{BACKUPTICS, Game};
+use gamestate_traits::GameTraits;
+use hud_doom::Messages;
+use intermission_doom::Intermission;
+use statusbar_doom::ClassicStatusbar;
+
+/// Read a tic script in to the list of commands to run, one per tic
+pub fn load_tic_script(path: &str) -> Result<Vec<TicCmd>, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path)?;
+    let mut cmds = Vec::new();
+    for (num, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if !(4..=5).contains(&fields.len()) {
+            return Err(format!(
+                "{path}:{}: expected `forward side turn buttons [repeat]`",
+                num + 1
+            )
+            .into());
+        }
+        let err = |e: std::num::ParseIntError| format!("{path}:{}: {e}", num + 1);
+        let mut cmd = TicCmd::new();
+        cmd.forwardmove = fields[0].parse().map_err(err)?;
+        cmd.sidemove = fields[1].parse().map_err(err)?;
+        cmd.angleturn = fields[2].parse().map_err(err)?;
+        cmd.buttons = fields[3].parse().map_err(err)?;
+        let repeat: usize = match fields.get(4) {
+            Some(n) => n.parse().map_err(err)?,
+            None => 1,
+        };
+        cmds.extend(std::iter::repeat_n(cmd, repeat));
+    }
+    Ok(cmds)
+}
+
+/// Run `tics` game tics as fast as possible then print the results. Returns
+/// early if the game stops running, such as at the end of a `-playdemo`. In a
+/// netgame the tics run as fast as the slowest node.
+pub fn d_headless_loop(mut game: Game, tics: u32, script: Vec<TicCmd>, mut net: Option<NetGame>) {
+    let mut machines = GameSubsystem {
+        statusbar: ClassicStatusbar::new(game.game_type.mode, &game.wad_data),
+        intermission: Intermission::new(game.game_type.mode, &game.wad_data),
+        hud_msgs: Messages::new(&game.wad_data),
+        finale: Finale::new(&game.wad_data),
+        automap: AutoMap::new(&game.wad_data),
+    };
+
+    let mut script = script.into_iter();
+    let mut tic = 0;
+    while tic < tics && game.running() {
+        if let Some(net) = net.as_mut() {
+            // Only take the next command when there is room to send it
+            let cmd = if net.can_build(game.game_tic) {
+                script.next().unwrap_or_default()
+            } else {
+                TicCmd::new()
+            };
+            game.net_update(net, cmd);
+            if !net.ready(game.game_tic) {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            while tic < tics && net.ready(game.game_tic) {
+                game.ticker(&mut machines);
+                tic += 1;
+                game.game_tic = tic;
+            }
+            continue;
+        }
+
+        if game.demo.advance {
+            game.do_advance_demo();
+        }
+        game.netcmds[game.consoleplayer][tic as usize % BACKUPTICS] =
+            script.next().unwrap_or_default();
+        game.ticker(&mut machines);
+        tic += 1;
+        game.game_tic = tic;
+    }
+    if let Some(net) = net.as_mut() {
+        net.flush(game.game_tic, &mut game.netcmds);
+    }
+    // Writes out a demo if one is being recorded
+    game.quit_game();
+
+    print_stats(&game, tic);
+}
+
+fn print_stats(game: &Game, tics: u32) {
+    let (kills, items, secrets) = match game.level.as_ref() {
+        Some(level) => (
+            level.total_level_kills,
+            level.total_level_items,
+            level.total_level_secrets,
+        ),
+        None => {
+            let info = game.level_end_info();
+            (info.maxkills, info.maxitems, info.maxsecret)
+        }
+    };
+
+    println!("tics: {tics}");
+    println!(
+        "episode: {} map: {}",
+        game.options.episode, game.options.map
+    );
+    for (i, player) in game.players.iter().enumerate() {
+        if !game.players_in_game[i] {
+            continue;
+        }
+        println!("player {}:", i + 1);
+        println!("  kills: {}/{}", player.total_kills, kills);
+        println!("  items: {}/{}", player.items_collected, items);
+        println!("  secrets: {}/{}", player.secrets_found, secrets);
+        // The player's map object only exists while a level is loaded
+        match game.level.as_ref().and_then(|_| player.mobj()) {
+            Some(mobj) => println!(
+                "  position: {:.3} {:.3} {:.3}",
+                mobj.xy.x.to_float(),
+                mobj.xy.y.to_float(),
+                mobj.z.to_float()
+            ),
+            _ => println!("  position: none"),
+        }
+    }
+}
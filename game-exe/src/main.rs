@@ -4,11 +4,13 @@ mod cheats;
 mod cli;
 mod config;
 mod d_main;
+mod headless;
 mod timestep;
 
 use cli::*;
 use config::MusicType;
 use dirs::{cache_dir, data_dir};
+use gamestate_traits::GameTraits;
 use gamestate_traits::sdl2::{self};
 use mimalloc::MiMalloc;
 use simplelog::TermLogger;
@@ -16,13 +18,16 @@ use std::env::set_var;
 use std::error::Error;
 use std::fs::File;
 use std::io::Write;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
 use d_main::d_doom_loop;
 use gamestate::Game;
+use gamestate::net::NetGame;
+use headless::{d_headless_loop, load_tic_script};
 
 use crate::config::UserConfig;
-use gameplay::log;
+use gameplay::{GameOptions, log};
 use input::Input;
 use sound_sdl2::timidity::{GusMemSize, make_timidity_cfg};
 
@@ -63,6 +68,38 @@ fn setup_timidity(music_type: MusicType, gus_mem: GusMemSize, wad: &WadData) {
     }
 }
 
+/// Join the netgame if one was asked for. The key player's settings replace
+/// those in `game_options`.
+fn connect_net(
+    options: &CLIOptions,
+    game_options: &mut GameOptions,
+) -> Result<Option<NetGame>, Box<dyn Error>> {
+    let Some(player) = options.net else {
+        return Ok(None);
+    };
+    let player = player
+        .checked_sub(1)
+        .ok_or("netgame players are numbered from 1")?;
+    let nodes = options
+        .node
+        .iter()
+        .map(|node| node.parse())
+        .collect::<Result<Vec<SocketAddr>, _>>()?;
+    Ok(Some(NetGame::connect(player, nodes, game_options)?))
+}
+
+/// Start a netgame, or start recording or playing back a demo, if one was
+/// asked for
+fn start_game(game: &mut Game, options: &CLIOptions, net: Option<&NetGame>) {
+    if let Some(net) = net {
+        game.start_netgame(net);
+    } else if let Some(name) = options.playdemo.as_ref() {
+        game.play_demo(name);
+    } else if let Some(name) = options.record.as_ref() {
+        game.record_demo(name);
+    }
+}
+
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
@@ -83,6 +120,20 @@ fn main() -> Result<(), Box<dyn Error>> {
     user_config.sync_cli(&mut options);
     user_config.write();
 
+    if let Some(tics) = options.headless {
+        let script = match options.tic_script.as_ref() {
+            Some(path) => load_tic_script(path)?,
+            None => Vec::new(),
+        };
+        let mut game_options = options.clone().into();
+        let net = connect_net(&options, &mut game_options)?;
+        let wad = WadData::new(user_config.iwad.clone().into());
+        let mut game = Game::new(game_options, wad, None, user_config.settings());
+        start_game(&mut game, &options, net.as_ref());
+        d_headless_loop(game, tics, script, net);
+        return Ok(());
+    }
+
     let sdl_ctx = sdl2::init()?;
     info!("Init SDL2 main");
     let snd_ctx = sdl_ctx.audio()?;
@@ -90,16 +141,15 @@ fn main() -> Result<(), Box<dyn Error>> {
     let video_ctx = sdl_ctx.video()?;
     info!("Init SDL2 video");
 
+    let mut game_options = options.clone().into();
+    let net = connect_net(&options, &mut game_options)?;
+
     let wad = WadData::new(user_config.iwad.clone().into());
     setup_timidity(user_config.music_type, user_config.gus_mem_size, &wad);
 
-    let game = Game::new(
-        options.clone().into(),
-        wad,
-        snd_ctx,
-        user_config.sfx_vol,
-        user_config.mus_vol,
-    );
+    let mut game = Game::new(game_options, wad, Some(snd_ctx), user_config.settings());
+    game.set_key_bindings((&user_config.input).into());
+    start_game(&mut game, &options, net.as_ref());
 
     let num_disp = video_ctx.num_video_displays()?;
     for n in 0..num_disp {
@@ -132,12 +182,12 @@ fn main() -> Result<(), Box<dyn Error>> {
         .unwrap()
     };
 
-    let input = Input::new(sdl_ctx.event_pump()?, (&user_config.input).into());
+    let input = Input::new(sdl_ctx.event_pump()?, sdl_ctx.game_controller().ok());
 
     sdl_ctx.mouse().show_cursor(false);
     sdl_ctx.mouse().set_relative_mouse_mode(true);
     sdl_ctx.mouse().capture(true);
 
-    d_doom_loop(game, input, cdm, window, gl_ctx, options)?;
+    d_doom_loop(game, input, cdm, window, gl_ctx, options, user_config, net)?;
     Ok(())
 }
@@ -0,0 +1,982 @@
+//! Dehacked (`.deh`) and Boom extended (`.bex`) patches. These change the
+//! thing, frame, weapon, ammo and sound tables, along with text, cheats, par
+//! times and the names of sprites, sounds and music.
+//!
+//! A patch is parsed in to a `Dehacked` and then applied to the runtime copies
+//! of the info tables (`STATES`, `MOBJINFO`, `WEAPON_INFO` etc). This must be
+//! done before sprites, sounds or a level are loaded as those copy from the
+//! tables. Anything that isn't understood is logged and skipped.
+//!
+//! Doom source name `d_deh` (Boom)
+
+use log::{debug, warn};
+use math::fixed_t;
+use sound_traits::{MUS_DATA, SFX_OVERRIDES, SfxName};
+
+use crate::doom_def::{
+    ActFn, AmmoType, CLIP_AMMO, CPARS, MAX_AMMO, PARS, WEAPON_INFO, WeaponInfo, WeaponType,
+};
+use crate::info::{MOBJINFO, MapObjInfo, MapObjKind, SPRNAMES, STATES, SpriteNum, State, StateNum};
+use crate::lang::english::*;
+use crate::player_sprite::*;
+use crate::thing::MapObjFlag;
+use crate::thing::enemy::*;
+
+/// Text replacements, original and new
+static mut DEH_STRINGS: Vec<(&'static str, &'static str)> = Vec::new();
+/// BEX `[STRINGS]` replacements by mnemonic, for text that isn't in `english`
+static mut DEH_BEX_STRINGS: Vec<(&'static str, &'static str)> = Vec::new();
+/// Cheat replacements, original sequence and new
+static mut DEH_CHEATS: Vec<(&'static str, &'static str)> = Vec::new();
+
+/// Mnemonics used by BEX `[STRINGS]` for the strings in `english`
+const BEX_STRINGS: [(&str, &str); 71] = [
+    ("GOTARMOR", GOTARMOR),
+    ("GOTMEGA", GOTMEGA),
+    ("GOTHTHBONUS", GOTHTHBONUS),
+    ("GOTARMBONUS", GOTARMBONUS),
+    ("GOTSTIM", GOTSTIM),
+    ("GOTMEDINEED", GOTMEDINEED),
+    ("GOTMEDIKIT", GOTMEDIKIT),
+    ("GOTSUPER", GOTSUPER),
+    ("GOTBLUECARD", GOTBLUECARD),
+    ("GOTYELWCARD", GOTYELWCARD),
+    ("GOTREDCARD", GOTREDCARD),
+    ("GOTBLUESKUL", GOTBLUESKUL),
+    ("GOTYELWSKUL", GOTYELWSKUL),
+    ("GOTREDSKULL", GOTREDSKULL),
+    ("GOTINVUL", GOTINVUL),
+    ("GOTBERSERK", GOTBERSERK),
+    ("GOTINVIS", GOTINVIS),
+    ("GOTSUIT", GOTSUIT),
+    ("GOTMAP", GOTMAP),
+    ("GOTVISOR", GOTVISOR),
+    ("GOTMSPHERE", GOTMSPHERE),
+    ("GOTCLIP", GOTCLIP),
+    ("GOTCLIPBOX", GOTCLIPBOX),
+    ("GOTROCKET", GOTROCKET),
+    ("GOTROCKBOX", GOTROCKBOX),
+    ("GOTCELL", GOTCELL),
+    ("GOTCELLBOX", GOTCELLBOX),
+    ("GOTSHELLS", GOTSHELLS),
+    ("GOTSHELLBOX", GOTSHELLBOX),
+    ("GOTBACKPACK", GOTBACKPACK),
+    ("GOTBFG9000", GOTBFG9000),
+    ("GOTCHAINGUN", GOTCHAINGUN),
+    ("GOTCHAINSAW", GOTCHAINSAW),
+    ("GOTLAUNCHER", GOTLAUNCHER),
+    ("GOTPLASMA", GOTPLASMA),
+    ("GOTSHOTGUN", GOTSHOTGUN),
+    ("GOTSHOTGUN2", GOTSHOTGUN2),
+    ("PD_BLUEO", PD_BLUEO),
+    ("PD_REDO", PD_REDO),
+    ("PD_YELLOWO", PD_YELLOWO),
+    ("PD_BLUEK", PD_BLUEK),
+    ("PD_REDK", PD_REDK),
+    ("PD_YELLOWK", PD_YELLOWK),
+    ("PD_BLUEC", PD_BLUEC),
+    ("PD_REDC", PD_REDC),
+    ("PD_YELLOWC", PD_YELLOWC),
+    ("PD_BLUES", PD_BLUES),
+    ("PD_REDS", PD_REDS),
+    ("PD_YELLOWS", PD_YELLOWS),
+    ("PD_ANY", PD_ANY),
+    ("PD_ALL3", PD_ALL3),
+    ("PD_ALL6", PD_ALL6),
+    ("STSTR_MUS", STSTR_MUS),
+    ("STSTR_NOMUS", STSTR_NOMUS),
+    ("STSTR_DQDON", STSTR_DQDON),
+    ("STSTR_DQDOFF", STSTR_DQDOFF),
+    ("STSTR_KFAADDED", STSTR_KFAADDED),
+    ("STSTR_FAADDED", STSTR_FAADDED),
+    ("STSTR_NCON", STSTR_NCON),
+    ("STSTR_NCOFF", STSTR_NCOFF),
+    ("STSTR_BEHOLD", STSTR_BEHOLD),
+    ("STSTR_BEHOLDX", STSTR_BEHOLDX),
+    ("STSTR_CHOPPERS", STSTR_CHOPPERS),
+    ("STSTR_CLEV", STSTR_CLEV),
+    ("GGSAVED", GGSAVED),
+    ("AMSTR_FOLLOWON", AMSTR_FOLLOWON),
+    ("AMSTR_FOLLOWOFF", AMSTR_FOLLOWOFF),
+    ("AMSTR_GRIDON", AMSTR_GRIDON),
+    ("AMSTR_GRIDOFF", AMSTR_GRIDOFF),
+    ("AMSTR_MARKEDSPOT", AMSTR_MARKEDSPOT),
+    ("AMSTR_MARKSCLEARED", AMSTR_MARKSCLEARED),
+];
+
+/// Names used in `Cheat` blocks, and the sequence they replace
+const CHEAT_NAMES: [(&str, &str); 17] = [
+    ("change music", "idmus"),
+    ("chainsaw", "idchoppers"),
+    ("god mode", "iddqd"),
+    ("ammo & keys", "idkfa"),
+    ("ammo", "idfa"),
+    ("no clipping 1", "idspispopd"),
+    ("no clipping 2", "idclip"),
+    ("invincibility", "idbeholdv"),
+    ("berserk", "idbeholds"),
+    ("invisibility", "idbeholdi"),
+    ("radiation suit", "idbeholdr"),
+    ("auto-map", "idbeholda"),
+    ("lite-amp goggles", "idbeholdl"),
+    ("behold menu", "idbehold"),
+    ("level warp", "idclev"),
+    ("player position", "idmypos"),
+    ("map cheat", "iddt"),
+];
+
+/// BEX mnemonics for the thing flags
+const THING_BITS: [(&str, u32); 29] = [
+    ("SPECIAL", MapObjFlag::Special as u32),
+    ("SOLID", MapObjFlag::Solid as u32),
+    ("SHOOTABLE", MapObjFlag::Shootable as u32),
+    ("NOSECTOR", MapObjFlag::Nosector as u32),
+    ("NOBLOCKMAP", MapObjFlag::Noblockmap as u32),
+    ("AMBUSH", MapObjFlag::Ambush as u32),
+    ("JUSTHIT", MapObjFlag::Justhit as u32),
+    ("JUSTATTACKED", MapObjFlag::Justattacked as u32),
+    ("SPAWNCEILING", MapObjFlag::Spawnceiling as u32),
+    ("NOGRAVITY", MapObjFlag::Nogravity as u32),
+    ("DROPOFF", MapObjFlag::Dropoff as u32),
+    ("PICKUP", MapObjFlag::Pickup as u32),
+    ("NOCLIP", MapObjFlag::Noclip as u32),
+    ("SLIDE", MapObjFlag::Slide as u32),
+    ("FLOAT", MapObjFlag::Float as u32),
+    ("TELEPORT", MapObjFlag::Teleport as u32),
+    ("MISSILE", MapObjFlag::Missile as u32),
+    ("DROPPED", MapObjFlag::Dropped as u32),
+    ("SHADOW", MapObjFlag::Shadow as u32),
+    ("NOBLOOD", MapObjFlag::Noblood as u32),
+    ("CORPSE", MapObjFlag::Corpse as u32),
+    ("INFLOAT", MapObjFlag::Infloat as u32),
+    ("COUNTKILL", MapObjFlag::Countkill as u32),
+    ("COUNTITEM", MapObjFlag::Countitem as u32),
+    ("SKULLFLY", MapObjFlag::Skullfly as u32),
+    ("NOTDMATCH", MapObjFlag::Notdmatch as u32),
+    ("TRANSLATION1", 1 << MapObjFlag::Transshift as u32),
+    ("TRANSLATION2", 2 << MapObjFlag::Transshift as u32),
+    ("TRANSLUCENT", MapObjFlag::Translucent as u32),
+];
+
+/// Action functions by the name used in BEX `[CODEPTR]`, without the `A_`
+const CODE_POINTERS: [(&str, ActFn); 74] = [
+    ("babymetal", ActFn::A(a_babymetal)),
+    ("bfgspray", ActFn::A(a_bfgspray)),
+    ("bossdeath", ActFn::A(a_bossdeath)),
+    ("brainawake", ActFn::A(a_brainawake)),
+    ("braindie", ActFn::A(a_braindie)),
+    ("brainexplode", ActFn::A(a_brainexplode)),
+    ("brainpain", ActFn::A(a_brainpain)),
+    ("brainscream", ActFn::A(a_brainscream)),
+    ("brainspit", ActFn::A(a_brainspit)),
+    ("bruisattack", ActFn::A(a_bruisattack)),
+    ("bspiattack", ActFn::A(a_bspiattack)),
+    ("chase", ActFn::A(a_chase)),
+    ("cposattack", ActFn::A(a_cposattack)),
+    ("cposrefire", ActFn::A(a_cposrefire)),
+    ("cyberattack", ActFn::A(a_cyberattack)),
+    ("explode", ActFn::A(a_explode)),
+    ("facetarget", ActFn::A(a_facetarget)),
+    ("fall", ActFn::A(a_fall)),
+    ("fatattack1", ActFn::A(a_fatattack1)),
+    ("fatattack2", ActFn::A(a_fatattack2)),
+    ("fatattack3", ActFn::A(a_fatattack3)),
+    ("fatraise", ActFn::A(a_fatraise)),
+    ("fire", ActFn::A(a_fire)),
+    ("firecrackle", ActFn::A(a_firecrackle)),
+    ("headattack", ActFn::A(a_headattack)),
+    ("hoof", ActFn::A(a_hoof)),
+    ("keendie", ActFn::A(a_keendie)),
+    ("look", ActFn::A(a_look)),
+    ("metal", ActFn::A(a_metal)),
+    ("pain", ActFn::A(a_pain)),
+    ("painattack", ActFn::A(a_painattack)),
+    ("paindie", ActFn::A(a_paindie)),
+    ("playerscream", ActFn::A(a_playerscream)),
+    ("posattack", ActFn::A(a_posattack)),
+    ("sargattack", ActFn::A(a_sargattack)),
+    ("scream", ActFn::A(a_scream)),
+    ("skelfist", ActFn::A(a_skelfist)),
+    ("skelmissile", ActFn::A(a_skelmissile)),
+    ("skelwhoosh", ActFn::A(a_skelwhoosh)),
+    ("skullattack", ActFn::A(a_skullattack)),
+    ("spawnfly", ActFn::A(a_spawnfly)),
+    ("spawnsound", ActFn::A(a_spawnsound)),
+    ("spidrefire", ActFn::A(a_spidrefire)),
+    ("sposattack", ActFn::A(a_sposattack)),
+    ("startfire", ActFn::A(a_startfire)),
+    ("tracer", ActFn::A(a_tracer)),
+    ("troopattack", ActFn::A(a_troopattack)),
+    ("vileattack", ActFn::A(a_vileattack)),
+    ("vilechase", ActFn::A(a_vilechase)),
+    ("vilestart", ActFn::A(a_vilestart)),
+    ("viletarget", ActFn::A(a_viletarget)),
+    ("xscream", ActFn::A(a_xscream)),
+    ("bfgsound", ActFn::P(a_bfgsound)),
+    ("checkreload", ActFn::P(a_checkreload)),
+    ("closeshotgun2", ActFn::P(a_closeshotgun2)),
+    ("firebfg", ActFn::P(a_firebfg)),
+    ("firecgun", ActFn::P(a_firecgun)),
+    ("firemissile", ActFn::P(a_firemissile)),
+    ("firepistol", ActFn::P(a_firepistol)),
+    ("fireplasma", ActFn::P(a_fireplasma)),
+    ("fireshotgun", ActFn::P(a_fireshotgun)),
+    ("fireshotgun2", ActFn::P(a_fireshotgun2)),
+    ("gunflash", ActFn::P(a_gunflash)),
+    ("light0", ActFn::P(a_light0)),
+    ("light1", ActFn::P(a_light1)),
+    ("light2", ActFn::P(a_light2)),
+    ("loadshotgun2", ActFn::P(a_loadshotgun2)),
+    ("lower", ActFn::P(a_lower)),
+    ("openshotgun2", ActFn::P(a_openshotgun2)),
+    ("punch", ActFn::P(a_punch)),
+    ("raise", ActFn::P(a_raise)),
+    ("refire", ActFn::P(a_refire)),
+    ("saw", ActFn::P(a_saw)),
+    ("weaponready", ActFn::P(a_weaponready)),
+];
+
+/// The block a line belongs to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Block {
+    None,
+    Thing(usize),
+    Frame(usize),
+    /// The frame the code pointer is for
+    Pointer(usize),
+    Sound(usize),
+    Ammo(usize),
+    Weapon(usize),
+    Cheat,
+    Misc,
+    /// Blocks that are read but have no effect, e.g, `Sprite`
+    Skip,
+    BexStrings,
+    BexPars,
+    BexCodePtr,
+    BexSounds,
+    BexMusic,
+    BexSprites,
+}
+
+/// A `key = value` line in a numbered block
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    index: usize,
+    key: String,
+    value: String,
+}
+
+/// A parsed dehacked or BEX patch
+#[derive(Debug, Default)]
+pub struct Dehacked {
+    things: Vec<Field>,
+    frames: Vec<Field>,
+    /// The frame, and the frame to copy the original action from
+    pointers: Vec<(usize, usize)>,
+    /// BEX `[CODEPTR]`, the frame and action name
+    code_pointers: Vec<(usize, String)>,
+    sounds: Vec<Field>,
+    ammo: Vec<Field>,
+    weapons: Vec<Field>,
+    cheats: Vec<(String, String)>,
+    /// `Text` blocks, original and replacement
+    texts: Vec<(String, String)>,
+    /// BEX `[STRINGS]`, mnemonic and replacement
+    strings: Vec<(String, String)>,
+    sound_names: Vec<(String, String)>,
+    music_names: Vec<(String, String)>,
+    sprite_names: Vec<(String, String)>,
+    /// BEX `[PARS]`, episode (0 for Doom II), map and seconds
+    pars: Vec<(usize, usize, i32)>,
+}
+
+impl Dehacked {
+    /// Parse a patch from a lump or file. Patches are plain ASCII so each byte
+    /// is read as one char, this keeps the lengths in `Text` blocks correct.
+    pub fn parse(data: &[u8]) -> Self {
+        let text: String = data
+            .iter()
+            .map(|b| *b as char)
+            .filter(|c| *c != '\r')
+            .collect();
+        let mut deh = Self::default();
+        let mut block = Block::None;
+        let mut rest = text.as_str();
+
+        while !rest.is_empty() {
+            let line = next_line(&mut rest).trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                block = match line.to_ascii_uppercase().as_str() {
+                    "[STRINGS]" => Block::BexStrings,
+                    "[PARS]" => Block::BexPars,
+                    "[CODEPTR]" => Block::BexCodePtr,
+                    "[SOUNDS]" => Block::BexSounds,
+                    "[MUSIC]" => Block::BexMusic,
+                    "[SPRITES]" => Block::BexSprites,
+                    _ => {
+                        warn!("Dehacked: unsupported section {line}");
+                        Block::Skip
+                    }
+                };
+                continue;
+            }
+
+            if block == Block::BexPars {
+                match parse_par(line) {
+                    Some(par) => deh.pars.push(par),
+                    None => warn!("Dehacked: invalid par line: {line}"),
+                }
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                let mut value = value.trim().to_string();
+                if block == Block::BexStrings {
+                    // Long strings continue on the next line
+                    while value.ends_with('\\') && !rest.is_empty() {
+                        value.pop();
+                        value.push_str(next_line(&mut rest).trim());
+                    }
+                    value = value.replace("\\n", "\n");
+                }
+                deh.add_field(block, key.trim(), value);
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let name = words.next().unwrap_or_default().to_ascii_lowercase();
+            let num = words.next().and_then(|w| w.parse::<usize>().ok());
+            block = match (name.as_str(), num) {
+                ("thing", Some(n)) => Block::Thing(n),
+                ("frame", Some(n)) => Block::Frame(n),
+                ("pointer", Some(_)) => {
+                    // `Pointer 12 (Frame 45)`, only the frame is needed
+                    let frame = line
+                        .to_ascii_lowercase()
+                        .split_once("(frame")
+                        .and_then(|(_, f)| f.trim_end_matches(')').trim().parse().ok());
+                    match frame {
+                        Some(frame) => Block::Pointer(frame),
+                        None => {
+                            warn!("Dehacked: invalid pointer: {line}");
+                            Block::Skip
+                        }
+                    }
+                }
+                ("sound", Some(n)) => Block::Sound(n),
+                ("ammo", Some(n)) => Block::Ammo(n),
+                ("weapon", Some(n)) => Block::Weapon(n),
+                ("sprite", Some(_)) => Block::Skip,
+                ("cheat", _) => Block::Cheat,
+                ("misc", _) => Block::Misc,
+                ("text", Some(from_len)) => {
+                    if let Some(to_len) = words.next().and_then(|w| w.parse::<usize>().ok()) {
+                        let from = take_chars(&mut rest, from_len);
+                        let to = take_chars(&mut rest, to_len);
+                        deh.texts.push((from, to));
+                    } else {
+                        warn!("Dehacked: invalid text: {line}");
+                    }
+                    Block::None
+                }
+                ("patch", _) => Block::None,
+                _ => {
+                    warn!("Dehacked: unknown line: {line}");
+                    block
+                }
+            };
+        }
+        deh
+    }
+
+    fn add_field(&mut self, block: Block, key: &str, value: String) {
+        let field = |index| Field {
+            index,
+            key: key.to_ascii_lowercase(),
+            value: value.clone(),
+        };
+        match block {
+            Block::Thing(n) => self.things.push(field(n)),
+            Block::Frame(n) => self.frames.push(field(n)),
+            Block::Pointer(frame) => {
+                if key.eq_ignore_ascii_case("codep frame")
+                    && let Ok(from) = value.parse()
+                {
+                    self.pointers.push((frame, from));
+                } else {
+                    warn!("Dehacked: Pointer {frame}: unknown line {key} = {value}");
+                }
+            }
+            Block::Sound(n) => self.sounds.push(field(n)),
+            Block::Ammo(n) => self.ammo.push(field(n)),
+            Block::Weapon(n) => self.weapons.push(field(n)),
+            Block::Cheat => self.cheats.push((key.to_ascii_lowercase(), value)),
+            Block::Misc => warn!("Dehacked: Misc {key} is not supported"),
+            Block::BexStrings => self.strings.push((key.to_ascii_uppercase(), value)),
+            Block::BexCodePtr => {
+                // `FRAME 45 = Chase`
+                let frame = key
+                    .split_whitespace()
+                    .nth(1)
+                    .and_then(|n| n.parse::<usize>().ok());
+                match frame {
+                    Some(frame) => self.code_pointers.push((frame, value)),
+                    None => warn!("Dehacked: invalid code pointer {key} = {value}"),
+                }
+            }
+            Block::BexSounds => self.sound_names.push((key.to_string(), value)),
+            Block::BexMusic => self.music_names.push((key.to_string(), value)),
+            Block::BexSprites => self.sprite_names.push((key.to_string(), value)),
+            Block::None | Block::Skip | Block::BexPars => {
+                debug!("Dehacked: ignored {key} = {value}");
+            }
+        }
+    }
+
+    /// Apply the patch to the info tables. Must be done before anything that
+    /// copies from them is loaded (sprites, sounds, levels).
+    #[allow(static_mut_refs)]
+    pub fn apply(&self) {
+        // Code pointers are copied from the unpatched frames
+        let actions: Vec<ActFn> = (0..StateNum::Count as usize)
+            .map(|i| unsafe { STATES[i].action })
+            .collect();
+
+        for f in self.things.iter() {
+            // Things are numbered from 1 in patches
+            let info = f
+                .index
+                .checked_sub(1)
+                .filter(|i| *i < MapObjKind::Count as usize)
+                .map(|i| unsafe { &mut MOBJINFO[i] });
+            if info
+                .and_then(|info| patch_thing(info, &f.key, &f.value))
+                .is_none()
+            {
+                warn!(
+                    "Dehacked: Thing {}: can't set {} = {}",
+                    f.index, f.key, f.value
+                );
+            }
+        }
+
+        for f in self.frames.iter() {
+            let state =
+                (f.index < StateNum::Count as usize).then(|| unsafe { &mut STATES[f.index] });
+            if state
+                .and_then(|state| patch_frame(state, &f.key, &f.value))
+                .is_none()
+            {
+                warn!(
+                    "Dehacked: Frame {}: can't set {} = {}",
+                    f.index, f.key, f.value
+                );
+            }
+        }
+
+        for (frame, from) in self.pointers.iter() {
+            if *frame < actions.len() && *from < actions.len() {
+                unsafe { STATES[*frame].action = actions[*from] };
+            } else {
+                warn!("Dehacked: invalid code pointer from frame {from} to {frame}");
+            }
+        }
+
+        for (frame, name) in self.code_pointers.iter() {
+            let action = code_pointer(name);
+            if *frame < actions.len()
+                && let Some(action) = action
+            {
+                unsafe { STATES[*frame].action = action };
+            } else {
+                warn!("Dehacked: invalid code pointer {name} for frame {frame}");
+            }
+        }
+
+        for f in self.weapons.iter() {
+            let weapon = (f.index < WeaponType::NumWeapons as usize)
+                .then(|| unsafe { &mut WEAPON_INFO[f.index] });
+            if weapon
+                .and_then(|weapon| patch_weapon(weapon, &f.key, &f.value))
+                .is_none()
+            {
+                warn!(
+                    "Dehacked: Weapon {}: can't set {} = {}",
+                    f.index, f.key, f.value
+                );
+            }
+        }
+
+        for f in self.ammo.iter() {
+            if patch_ammo(f.index, &f.key, &f.value).is_none() {
+                warn!(
+                    "Dehacked: Ammo {}: can't set {} = {}",
+                    f.index, f.key, f.value
+                );
+            }
+        }
+
+        for f in self.sounds.iter() {
+            let value = f.value.parse::<i32>().ok();
+            if f.index < SfxName::NumSfx as usize
+                && f.key == "value"
+                && let Some(priority) = value
+            {
+                unsafe { SFX_OVERRIDES[f.index].priority = Some(priority) };
+            } else {
+                debug!(
+                    "Dehacked: Sound {}: ignored {} = {}",
+                    f.index, f.key, f.value
+                );
+            }
+        }
+
+        for (name, sequence) in self.cheats.iter() {
+            let original = CHEAT_NAMES.iter().find(|(n, _)| n == name);
+            // Old patches pad the sequence with 0xff
+            let sequence: String = sequence.chars().filter(|c| c.is_ascii_graphic()).collect();
+            match original {
+                Some((_, original)) if !sequence.is_empty() => {
+                    set_entry(unsafe { &mut DEH_CHEATS }, original, leak(&sequence));
+                }
+                _ => warn!("Dehacked: can't set cheat {name} = {sequence}"),
+            }
+        }
+
+        for (from, to) in self.texts.iter() {
+            if !rename_sprite(from, to) && !rename_sound(from, to) && !rename_music(from, to) {
+                set_entry(unsafe { &mut DEH_STRINGS }, leak(from), leak(to));
+            }
+        }
+
+        for (name, to) in self.strings.iter() {
+            set_entry(unsafe { &mut DEH_BEX_STRINGS }, leak(name), leak(to));
+            if let Some((_, original)) = BEX_STRINGS.iter().find(|(n, _)| n == name) {
+                set_entry(unsafe { &mut DEH_STRINGS }, original, leak(to));
+            }
+        }
+
+        for (from, to) in self.sprite_names.iter() {
+            if !rename_sprite(from, to) {
+                warn!("Dehacked: no sprite named {from}");
+            }
+        }
+        for (from, to) in self.sound_names.iter() {
+            if !rename_sound(from, to) {
+                warn!("Dehacked: no sound named {from}");
+            }
+        }
+        for (from, to) in self.music_names.iter() {
+            if !rename_music(from, to) {
+                warn!("Dehacked: no music named {from}");
+            }
+        }
+
+        for (episode, map, secs) in self.pars.iter().copied() {
+            unsafe {
+                if episode == 0 && (1..=32).contains(&map) {
+                    CPARS[map - 1] = secs;
+                } else if (1..=3).contains(&episode) && (1..=9).contains(&map) {
+                    PARS[episode][map] = secs;
+                } else {
+                    warn!("Dehacked: no par for episode {episode} map {map}");
+                }
+            }
+        }
+    }
+}
+
+/// The replacement for a string if a dehacked patch changed it
+#[allow(static_mut_refs)]
+pub fn deh_string(original: &'static str) -> &'static str {
+    lookup(unsafe { &DEH_STRINGS }, original).unwrap_or(original)
+}
+
+/// The replacement for a string using its BEX mnemonic, e.g, `E1TEXT`. For
+/// text that isn't in `english`.
+#[allow(static_mut_refs)]
+pub fn deh_bex_string(mnemonic: &str, original: &'static str) -> &'static str {
+    lookup(unsafe { &DEH_BEX_STRINGS }, mnemonic).unwrap_or_else(|| deh_string(original))
+}
+
+/// The replacement for a cheat sequence if a dehacked patch changed it
+#[allow(static_mut_refs)]
+pub fn deh_cheat(sequence: &'static str) -> &'static str {
+    lookup(unsafe { &DEH_CHEATS }, sequence).unwrap_or(sequence)
+}
+
+fn lookup(table: &[(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    table.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+fn set_entry(
+    table: &mut Vec<(&'static str, &'static str)>,
+    key: &'static str,
+    value: &'static str,
+) {
+    if let Some(entry) = table.iter_mut().find(|(k, _)| *k == key) {
+        entry.1 = value;
+    } else {
+        table.push((key, value));
+    }
+}
+
+/// Patched names live as long as the tables they're put in
+fn leak(s: &str) -> &'static str {
+    Box::leak(s.to_string().into_boxed_str())
+}
+
+fn next_line<'a>(rest: &mut &'a str) -> &'a str {
+    let (line, remaining) = rest.split_once('\n').unwrap_or((rest, ""));
+    *rest = remaining;
+    line
+}
+
+fn take_chars(rest: &mut &str, count: usize) -> String {
+    let end = rest
+        .char_indices()
+        .nth(count)
+        .map_or(rest.len(), |(i, _)| i);
+    let (taken, remaining) = rest.split_at(end);
+    *rest = remaining;
+    taken.to_string()
+}
+
+/// `par <episode> <map> <seconds>` or `par <map> <seconds>` for Doom II
+fn parse_par(line: &str) -> Option<(usize, usize, i32)> {
+    let mut words = line.split_whitespace();
+    if !words.next()?.eq_ignore_ascii_case("par") {
+        return None;
+    }
+    let nums: Vec<i32> = words.map(|w| w.parse().ok()).collect::<Option<_>>()?;
+    match nums[..] {
+        [episode, map, secs] => Some((
+            usize::try_from(episode).ok()?,
+            usize::try_from(map).ok()?,
+            secs,
+        )),
+        [map, secs] => Some((0, usize::try_from(map).ok()?, secs)),
+        _ => None,
+    }
+}
+
+/// Thing flags as a number or BEX mnemonics, e.g, `SOLID+SHOOTABLE`
+fn parse_bits(value: &str) -> Option<u32> {
+    if let Ok(bits) = value.parse::<i64>() {
+        return Some(bits as u32);
+    }
+    value
+        .split(|c: char| c == '+' || c == '|' || c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .try_fold(0, |bits, name| {
+            let bit = match name.parse::<i64>() {
+                Ok(bit) => bit as u32,
+                Err(_) => {
+                    THING_BITS
+                        .iter()
+                        .find(|(n, _)| n.eq_ignore_ascii_case(name))?
+                        .1
+                }
+            };
+            Some(bits | bit)
+        })
+}
+
+fn code_pointer(name: &str) -> Option<ActFn> {
+    let name = name.to_ascii_lowercase();
+    let name = name.strip_prefix("a_").unwrap_or(&name);
+    if name == "null" {
+        return Some(ActFn::N);
+    }
+    CODE_POINTERS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, action)| *action)
+}
+
+fn state(value: i32) -> Option<StateNum> {
+    u16::try_from(value)
+        .ok()
+        .filter(|v| *v < StateNum::Count as u16)
+        .map(StateNum::from)
+}
+
+fn sound(value: i32) -> Option<SfxName> {
+    u8::try_from(value)
+        .ok()
+        .filter(|v| *v < SfxName::NumSfx as u8)
+        .map(SfxName::from)
+}
+
+/// Monster speeds are whole units, missile speeds are fixed point
+fn speed(value: i32) -> fixed_t {
+    if value.abs() >= 1 << 16 {
+        fixed_t::new(value)
+    } else {
+        fixed_t::from_int(value)
+    }
+}
+
+fn patch_thing(info: &mut MapObjInfo, key: &str, value: &str) -> Option<()> {
+    if key == "bits" {
+        info.flags = parse_bits(value)?;
+        return Some(());
+    }
+    let v = value.parse::<i32>().ok()?;
+    match key {
+        "id #" => info.doomednum = v,
+        "initial frame" => info.spawnstate = state(v)?,
+        "hit points" => info.spawnhealth = v,
+        "first moving frame" => info.seestate = state(v)?,
+        "alert sound" => info.seesound = sound(v)?,
+        "reaction time" => info.reactiontime = v,
+        "attack sound" => info.attacksound = sound(v)?,
+        "injury frame" => info.painstate = state(v)?,
+        "pain chance" => info.painchance = v,
+        "pain sound" => info.painsound = sound(v)?,
+        "close attack frame" => info.meleestate = state(v)?,
+        "far attack frame" => info.missilestate = state(v)?,
+        "death frame" => info.deathstate = state(v)?,
+        "exploding frame" => info.xdeathstate = state(v)?,
+        "death sound" => info.deathsound = sound(v)?,
+        "speed" => info.speed = speed(v),
+        "width" => info.radius = fixed_t::new(v),
+        "height" => info.height = fixed_t::new(v),
+        "mass" => info.mass = v,
+        "missile damage" => info.damage = v,
+        "action sound" => info.activesound = sound(v)?,
+        "respawn frame" => info.raisestate = state(v)?,
+        _ => return None,
+    }
+    Some(())
+}
+
+fn patch_frame(state_info: &mut State, key: &str, value: &str) -> Option<()> {
+    let v = value.parse::<i32>().ok()?;
+    match key {
+        "sprite number" => {
+            state_info.sprite = u16::try_from(v)
+                .ok()
+                .filter(|v| *v < SpriteNum::Count as u16)
+                .map(SpriteNum::from)?
+        }
+        "sprite subnumber" => state_info.frame = u32::try_from(v).ok()?,
+        "duration" => state_info.tics = v,
+        "next frame" => state_info.next_state = state(v)?,
+        "unknown 1" => state_info.misc1 = v,
+        "unknown 2" => state_info.misc2 = v,
+        _ => return None,
+    }
+    Some(())
+}
+
+fn patch_weapon(weapon: &mut WeaponInfo, key: &str, value: &str) -> Option<()> {
+    let v = value.parse::<i32>().ok()?;
+    match key {
+        "ammo type" => {
+            weapon.ammo = match v {
+                0..=3 => AmmoType::from(v as usize),
+                5 => AmmoType::NoAmmo,
+                _ => return None,
+            }
+        }
+        // Dehacked has these two the wrong way around
+        "deselect frame" => weapon.upstate = state(v)?,
+        "select frame" => weapon.downstate = state(v)?,
+        "bobbing frame" => weapon.readystate = state(v)?,
+        "shooting frame" => weapon.atkstate = state(v)?,
+        "firing frame" => weapon.flashstate = state(v)?,
+        _ => return None,
+    }
+    Some(())
+}
+
+fn patch_ammo(index: usize, key: &str, value: &str) -> Option<()> {
+    let v = value.parse::<u32>().ok()?;
+    if index >= AmmoType::NumAmmo as usize {
+        return None;
+    }
+    match key {
+        "max ammo" => unsafe { MAX_AMMO[index] = v },
+        "per ammo" => unsafe { CLIP_AMMO[index] = v },
+        _ => return None,
+    }
+    Some(())
+}
+
+#[allow(static_mut_refs)]
+fn rename_sprite(from: &str, to: &str) -> bool {
+    if from.len() != 4 || to.len() != 4 {
+        return false;
+    }
+    let names = unsafe { &mut SPRNAMES };
+    if let Some(name) = names.iter_mut().find(|n| n.eq_ignore_ascii_case(from)) {
+        *name = leak(&to.to_ascii_uppercase());
+        return true;
+    }
+    false
+}
+
+fn rename_sound(from: &str, to: &str) -> bool {
+    for i in 1..SfxName::NumSfx as u8 {
+        // The variants are named after the lumps
+        if format!("{:?}", SfxName::from(i)).eq_ignore_ascii_case(from) {
+            unsafe { SFX_OVERRIDES[i as usize].name = Some(leak(&to.to_ascii_lowercase())) };
+            return true;
+        }
+    }
+    false
+}
+
+fn rename_music(from: &str, to: &str) -> bool {
+    unsafe {
+        #[allow(static_mut_refs)]
+        for mus in MUS_DATA.iter_mut().skip(1) {
+            if mus.name().eq_ignore_ascii_case(from) {
+                mus.set_name(leak(&to.to_ascii_lowercase()));
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dehacked, Field, parse_bits, patch_frame, patch_thing, patch_weapon};
+    use crate::doom_def::{AmmoType, WEAPON_INFO, WeaponType};
+    use crate::info::{MOBJINFO, MapObjKind, SpriteNum, State, StateNum};
+    use crate::thing::MapObjFlag;
+    use math::fixed_t;
+
+    const PATCH: &str = "Patch File for DeHackEd v3.0
+# A comment
+Doom version = 21
+Patch format = 6
+
+Thing 2 (Zombieman)
+Hit points = 40
+Speed = 12
+Bits = SOLID+SHOOTABLE|COUNTKILL
+
+Frame 174
+Duration = 3
+Next frame = 175
+
+Pointer 12 (Frame 176)
+Codep Frame = 180
+
+Text 6 7
+pistolblaster
+
+Weapon 1 (Pistol)
+Ammo type = 1
+
+[CODEPTR]
+FRAME 177 = Chase
+
+[STRINGS]
+GOTARMOR = Picked up \\
+the armor.
+E1TEXT = The end\\n
+
+[PARS]
+par 1 2 40
+par 7 300
+";
+
+    #[test]
+    fn parse_blocks() {
+        let deh = Dehacked::parse(PATCH.as_bytes());
+        assert_eq!(
+            deh.things,
+            vec![
+                Field {
+                    index: 2,
+                    key: "hit points".into(),
+                    value: "40".into()
+                },
+                Field {
+                    index: 2,
+                    key: "speed".into(),
+                    value: "12".into()
+                },
+                Field {
+                    index: 2,
+                    key: "bits".into(),
+                    value: "SOLID+SHOOTABLE|COUNTKILL".into()
+                },
+            ]
+        );
+        assert_eq!(deh.frames.len(), 2);
+        assert_eq!(deh.pointers, vec![(176, 180)]);
+        assert_eq!(deh.code_pointers, vec![(177, "Chase".to_string())]);
+        assert_eq!(
+            deh.texts,
+            vec![("pistol".to_string(), "blaster".to_string())]
+        );
+        assert_eq!(deh.weapons.len(), 1);
+        assert_eq!(
+            deh.strings,
+            vec![
+                ("GOTARMOR".to_string(), "Picked up the armor.".to_string()),
+                ("E1TEXT".to_string(), "The end\n".to_string()),
+            ]
+        );
+        assert_eq!(deh.pars, vec![(1, 2, 40), (0, 7, 300)]);
+    }
+
+    #[test]
+    fn text_spans_lines() {
+        let deh = Dehacked::parse(b"Text 3 5\r\nab\r\ncd\r\nef\r\nThing 1\r\nMass = 5\r\n");
+        assert_eq!(deh.texts, vec![("ab\n".to_string(), "cd\nef".to_string())]);
+        assert_eq!(deh.things.len(), 1);
+    }
+
+    #[test]
+    fn thing_bits() {
+        assert_eq!(
+            parse_bits("SOLID+SHOOTABLE"),
+            Some(MapObjFlag::Solid as u32 | MapObjFlag::Shootable as u32)
+        );
+        assert_eq!(parse_bits("4194310"), Some(4194310));
+        assert_eq!(parse_bits("SOLID | 4"), Some(6));
+        assert_eq!(parse_bits("NOTAFLAG"), None);
+    }
+
+    #[test]
+    fn patch_copies() {
+        let mut info = unsafe { MOBJINFO[MapObjKind::MT_POSSESSED as usize] };
+        assert!(patch_thing(&mut info, "hit points", "40").is_some());
+        assert!(patch_thing(&mut info, "speed", "12").is_some());
+        assert!(patch_thing(&mut info, "speed", "1310720").is_some());
+        assert_eq!(info.speed, fixed_t::from_int(20));
+        assert!(patch_thing(&mut info, "initial frame", "99999").is_none());
+        assert!(patch_thing(&mut info, "not a field", "1").is_none());
+        assert_eq!(info.spawnhealth, 40);
+
+        let mut state = State {
+            sprite: SpriteNum::TROO,
+            frame: 0,
+            tics: 1,
+            action: crate::doom_def::ActFn::N,
+            next_state: StateNum::None,
+            misc1: 0,
+            misc2: 0,
+        };
+        assert!(patch_frame(&mut state, "duration", "8").is_some());
+        assert!(patch_frame(&mut state, "sprite number", "1").is_some());
+        assert_eq!(state.tics, 8);
+        assert_eq!(state.sprite, SpriteNum::SHTG);
+
+        let mut weapon = unsafe { WEAPON_INFO[WeaponType::Pistol as usize] };
+        assert!(patch_weapon(&mut weapon, "ammo type", "5").is_some());
+        assert_eq!(weapon.ammo, AmmoType::NoAmmo);
+        assert!(patch_weapon(&mut weapon, "ammo type", "4").is_none());
+    }
+}
@@ -150,8 +150,28 @@ impl From<u8> for WeaponType {
     }
 }
 
-pub const MAX_AMMO: [u32; 4] = [200, 50, 300, 50];
-pub const CLIP_AMMO: [u32; 4] = [10, 4, 20, 1];
+/// Par times in seconds for Doom 1 levels, indexed by `[episode][map]`. Can
+/// be changed by dehacked patches
+pub static mut PARS: [[i32; 10]; 4] = [
+    [0; 10],
+    [0, 30, 75, 120, 90, 165, 180, 180, 30, 165],
+    [0, 90, 90, 90, 120, 90, 360, 240, 30, 170],
+    [0, 90, 45, 90, 150, 90, 90, 165, 30, 135],
+];
+
+/// Par times in seconds for Doom II levels, indexed by `map - 1`. Can be
+/// changed by dehacked patches
+pub static mut CPARS: [i32; 32] = [
+    30, 90, 120, 120, 90, 150, 120, 120, 270, 90, // 1-10
+    210, 150, 150, 150, 210, 150, 420, 150, 210, 150, // 11-20
+    240, 150, 180, 150, 150, 300, 330, 420, 300, 180, // 21-30
+    120, 30, // 31-32
+];
+
+/// Maximum ammo without a backpack. Can be changed by dehacked patches
+pub static mut MAX_AMMO: [u32; 4] = [200, 50, 300, 50];
+/// Ammo in a clip, boxes are 5 times this. Can be changed by dehacked patches
+pub static mut CLIP_AMMO: [u32; 4] = [10, 4, 20, 1];
 
 /// Ammunition types defined.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -209,6 +229,7 @@ pub enum PowerDuration {
 }
 
 /// Definition for player sprites (HUD weapon) actions
+#[derive(Debug, Clone, Copy)]
 pub struct WeaponInfo {
     /// Amto type required
     pub ammo: AmmoType,
@@ -224,7 +245,7 @@ pub struct WeaponInfo {
     pub flashstate: StateNum,
 }
 
-pub const WEAPON_INFO: [WeaponInfo; 9] = [
+pub static mut WEAPON_INFO: [WeaponInfo; 9] = [
     // fist
     WeaponInfo {
         ammo: AmmoType::NoAmmo,
@@ -3,6 +3,7 @@
 //! Doom source name `p_ceiling`
 use std::ptr::null_mut;
 
+use log::error;
 use sound_traits::SfxName;
 
 use crate::MapPtr;
@@ -16,7 +17,7 @@ use crate::env::switch::start_sector_sound;
 
 use math::{FT_EIGHT, FT_TWO, FT_ZERO, fixed_t};
 
-const CEILSPEED: fixed_t = fixed_t(1 << 16);
+pub(crate) const CEILSPEED: fixed_t = fixed_t(1 << 16);
 
 #[derive(Debug, Clone, Copy)]
 pub enum CeilKind {
@@ -26,6 +27,14 @@ pub enum CeilKind {
     CrushAndRaise,
     FastCrushAndRaise,
     SilentCrushAndRaise,
+    /// BOOM generalized ceiling
+    Generalized,
+    /// BOOM generalized ceiling, changes texture and special at the destination
+    GenChange,
+    /// BOOM generalized crusher, keeps its speed after each cycle
+    GenCrusher,
+    /// BOOM generalized crusher without movement sounds
+    GenSilentCrusher,
 }
 
 pub struct CeilingMove {
@@ -41,6 +50,12 @@ pub struct CeilingMove {
     // ID
     pub tag: i16,
     pub olddirection: i32,
+    /// Speed to return to after slowing down on things (BOOM crushers)
+    pub oldspeed: fixed_t,
+    /// Ceiling texture to change to at the destination
+    pub texture: usize,
+    /// Sector special to change to at the destination
+    pub newspecial: i16,
 }
 
 // TODO: track activeceilings
@@ -80,6 +95,9 @@ pub fn ev_do_ceiling(line: MapPtr<LineDef>, kind: CeilKind, level: &mut Level) -
             topheight: FT_ZERO,
             tag: sec.tag,
             olddirection: 0,
+            oldspeed: CEILSPEED,
+            texture: sec.ceilingpic,
+            newspecial: sec.special,
         };
 
         match kind {
@@ -113,6 +131,13 @@ pub fn ev_do_ceiling(line: MapPtr<LineDef>, kind: CeilKind, level: &mut Level) -
                 ceiling.direction = -1;
                 ceiling.speed *= FT_TWO;
             }
+            CeilKind::Generalized
+            | CeilKind::GenChange
+            | CeilKind::GenCrusher
+            | CeilKind::GenSilentCrusher => {
+                error!("ev_do_ceiling: generalized ceilings are started by ev_do_gen_ceiling");
+                continue;
+            }
         }
 
         ret = true;
@@ -138,7 +163,12 @@ impl Think for CeilingMove {
         }
         let line = ceiling.sector.lines[0].as_ref();
 
-        if level.level_time & 7 == 0 && !matches!(ceiling.kind, CeilKind::SilentCrushAndRaise) {
+        if level.level_time & 7 == 0
+            && !matches!(
+                ceiling.kind,
+                CeilKind::SilentCrushAndRaise | CeilKind::GenSilentCrusher
+            )
+        {
             start_sector_sound(line, SfxName::Stnmov, &level.snd_command);
         }
 
@@ -156,14 +186,22 @@ impl Think for CeilingMove {
 
                 if matches!(res, PlaneResult::PastDest) {
                     match ceiling.kind {
-                        CeilKind::RaiseToHighest => unsafe {
+                        CeilKind::RaiseToHighest | CeilKind::Generalized => unsafe {
+                            ceiling.sector.specialdata = None;
+                            (*ceiling.thinker).mark_remove();
+                        },
+                        CeilKind::GenChange => unsafe {
+                            ceiling.sector.ceilingpic = ceiling.texture;
+                            ceiling.sector.special = ceiling.newspecial;
                             ceiling.sector.specialdata = None;
                             (*ceiling.thinker).mark_remove();
                         },
-                        CeilKind::CrushAndRaise | CeilKind::FastCrushAndRaise => {
+                        CeilKind::CrushAndRaise
+                        | CeilKind::FastCrushAndRaise
+                        | CeilKind::GenCrusher => {
                             ceiling.direction = -1;
                         }
-                        CeilKind::SilentCrushAndRaise => {
+                        CeilKind::SilentCrushAndRaise | CeilKind::GenSilentCrusher => {
                             start_sector_sound(line, SfxName::Pstop, &level.snd_command);
                             ceiling.direction = -1;
                         }
@@ -184,10 +222,27 @@ impl Think for CeilingMove {
 
                 if matches!(res, PlaneResult::PastDest) {
                     match ceiling.kind {
-                        CeilKind::LowerToFloor | CeilKind::LowerAndCrush => unsafe {
+                        CeilKind::LowerToFloor
+                        | CeilKind::LowerAndCrush
+                        | CeilKind::Generalized => unsafe {
+                            ceiling.sector.specialdata = None;
+                            (*ceiling.thinker).mark_remove();
+                        },
+                        CeilKind::GenChange => unsafe {
+                            ceiling.sector.ceilingpic = ceiling.texture;
+                            ceiling.sector.special = ceiling.newspecial;
                             ceiling.sector.specialdata = None;
                             (*ceiling.thinker).mark_remove();
                         },
+                        CeilKind::GenCrusher => {
+                            ceiling.speed = ceiling.oldspeed;
+                            ceiling.direction = 1;
+                        }
+                        CeilKind::GenSilentCrusher => {
+                            ceiling.speed = ceiling.oldspeed;
+                            ceiling.direction = 1;
+                            start_sector_sound(line, SfxName::Pstop, &level.snd_command);
+                        }
                         CeilKind::CrushAndRaise => {
                             ceiling.speed = CEILSPEED;
                             ceiling.direction = 1;
@@ -208,8 +263,14 @@ impl Think for CeilingMove {
                         CeilKind::SilentCrushAndRaise
                         | CeilKind::CrushAndRaise
                         | CeilKind::LowerAndCrush => {
-                            ceiling.speed = fixed_t::from_float(0.2);
+                            ceiling.speed = CEILSPEED / FT_EIGHT;
+                        }
+                        CeilKind::GenCrusher | CeilKind::GenSilentCrusher => {
+                            if ceiling.oldspeed < CEILSPEED * fixed_t::from_int(3) {
+                                ceiling.speed = CEILSPEED / FT_EIGHT;
+                            }
                         }
+                        CeilKind::Generalized | CeilKind::GenChange => {}
                         _ => ceiling.speed = CEILSPEED,
                     }
                 }
@@ -20,8 +20,8 @@ use crate::env::specials::{PlaneResult, find_lowest_ceiling_surrounding, move_pl
 use crate::env::switch::start_sector_sound;
 
 const VDOOR: fixed_t = FT_TWO;
-const VDOORWAIT: i32 = 150;
-const VDOORSPEED: fixed_t = fixed_t(2 << 16);
+pub(crate) const VDOORWAIT: i32 = 150;
+pub(crate) const VDOORSPEED: fixed_t = fixed_t(2 << 16);
 
 #[derive(Debug, Clone, Copy)]
 pub enum DoorKind {
@@ -139,7 +139,7 @@ impl Think for VerticalDoor {
                         },
                         DoorKind::Close30ThenOpen => {
                             door.direction = 0;
-                            door.topcountdown = TICRATE * 30;
+                            door.topcountdown = door.topwait;
                         }
                         _ => {}
                     }
@@ -263,6 +263,7 @@ pub fn ev_do_door(line: MapPtr<LineDef>, kind: DoorKind, level: &mut Level) -> b
             DoorKind::Close30ThenOpen => {
                 door.topheight = sec.ceilingheight;
                 door.direction = -1;
+                door.topwait = TICRATE * 30;
                 start_sector_sound(&line, SfxName::Dorcls, &level.snd_command);
             }
             DoorKind::Close => {
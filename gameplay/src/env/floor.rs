@@ -3,6 +3,7 @@
 //! Doom source name `p_floor`
 use std::ptr::{self, null_mut};
 
+use log::error;
 use sound_traits::SfxName;
 
 use crate::MapPtr;
@@ -14,13 +15,14 @@ use crate::thinker::{Think, Thinker, ThinkerData};
 
 use crate::env::specials::{
     PlaneResult, find_highest_floor_surrounding, find_lowest_ceiling_surrounding,
-    find_lowest_floor_surrounding, find_next_highest_floor, get_next_sector, move_plane,
+    find_lowest_floor_surrounding, find_next_highest_floor, find_shortest_lower_texture,
+    get_next_sector, move_plane,
 };
 use crate::env::switch::start_sector_sound;
 
 use math::{FT_EIGHT, FT_FOUR, FT_ONE, FT_SIXTEEN, FT_TWO, FT_ZERO, fixed_t};
 
-const FLOORSPEED: fixed_t = FT_ONE;
+pub(crate) const FLOORSPEED: fixed_t = FT_ONE;
 
 #[derive(Debug, Clone, Copy)]
 pub enum FloorKind {
@@ -50,6 +52,10 @@ pub enum FloorKind {
     DonutRaise,
     /// Raise floor 512 units from start
     RaiseFloor512,
+    /// BOOM generalized floor
+    Generalized,
+    /// BOOM generalized floor, changes texture and special at the destination
+    GenChange,
 }
 
 /// Very special kind of thinker used specifically for building a set of stairs
@@ -138,33 +144,9 @@ pub fn ev_do_floor(line: MapPtr<LineDef>, kind: FloorKind, level: &mut Level) ->
                 floor.destheight = find_highest_floor_surrounding(sec.clone());
             }
             FloorKind::RaiseToTexture => {
-                // TODO: int minsize = INT_MAX;
-                let mut min = sec.floorheight;
                 floor.direction = 1;
-                for line in sec.lines.iter() {
-                    if line.flags & LineDefFlags::TwoSided as u32 != 0 {
-                        if let Some(bottomtexture) = line.front_sidedef.bottomtexture {
-                            let tmp = fixed_t::from_int(
-                                level.animations[bottomtexture].num_pics() as i32
-                            );
-                            if tmp < min {
-                                min = tmp;
-                            }
-                        }
-                        if let Some(side) = line.back_sidedef.as_ref() {
-                            if let Some(bottomtexture) = side.bottomtexture {
-                                let tmp = fixed_t::from_int(
-                                    level.animations[bottomtexture].num_pics() as i32,
-                                );
-                                if tmp < min {
-                                    min = tmp;
-                                }
-                            }
-                        }
-                        //todo!("side = getSide(secnum, i, 0); and stuff");
-                    }
-                }
-                floor.destheight = sec.floorheight + min;
+                floor.destheight = sec.floorheight
+                    + find_shortest_lower_texture(sec.clone(), &level.texture_heights);
             }
             FloorKind::LowerAndChange => {
                 floor.direction = -1;
@@ -212,6 +194,10 @@ pub fn ev_do_floor(line: MapPtr<LineDef>, kind: FloorKind, level: &mut Level) ->
                 floor.direction = 1;
                 floor.destheight = sec.floorheight + fixed_t::from_int(512);
             }
+            FloorKind::Generalized | FloorKind::GenChange => {
+                error!("ev_do_floor: generalized floors are started by ev_do_gen_floor");
+                continue;
+            }
         }
 
         ret = true;
@@ -240,7 +226,7 @@ impl Think for FloorMove {
             floor.sector.clone(),
             floor.speed,
             floor.destheight,
-            false,
+            floor.crush,
             0,
             floor.direction,
         );
@@ -253,6 +239,7 @@ impl Think for FloorMove {
         if matches!(res, PlaneResult::PastDest) {
             if floor.direction == 1 && matches!(floor.kind, FloorKind::DonutRaise)
                 || floor.direction == -1 && matches!(floor.kind, FloorKind::LowerAndChange)
+                || matches!(floor.kind, FloorKind::GenChange)
             {
                 floor.sector.special = floor.newspecial;
                 floor.sector.floorpic = floor.texture;
@@ -0,0 +1,828 @@
+//! BOOM generalized linedef types. The special number is a bit field that
+//! describes the trigger, speed, target and so on, which is decoded in to the
+//! regular floor, ceiling, door, and platform thinkers.
+//!
+//! BOOM source name `p_genlin`
+
+use std::ptr::null_mut;
+
+use log::debug;
+use math::{FT_FOUR, FT_TWO, fixed_t, p_random};
+use sound_traits::SfxName;
+
+use crate::MapPtr;
+use crate::doom_def::{Card, TICRATE};
+use crate::env::ceiling::{CEILSPEED, CeilKind, CeilingMove};
+use crate::env::doors::{DoorKind, VDOORSPEED, VDOORWAIT, VerticalDoor};
+use crate::env::floor::{FLOORSPEED, FloorKind, FloorMove};
+use crate::env::platforms::{PLATSPEED, PlatKind, PlatStatus, Platform};
+use crate::env::specials::{
+    find_highest_ceiling_surrounding, find_highest_floor_surrounding,
+    find_lowest_ceiling_surrounding, find_lowest_floor_surrounding, find_model_ceiling_sector,
+    find_model_floor_sector, find_next_highest_ceiling, find_next_highest_floor,
+    find_next_lowest_ceiling, find_next_lowest_floor, find_shortest_lower_texture,
+    find_shortest_upper_texture,
+};
+use crate::env::switch::{change_switch_texture, start_sector_sound};
+use crate::lang::english::{
+    PD_ALL3, PD_ALL6, PD_ANY, PD_BLUEC, PD_BLUEK, PD_BLUES, PD_REDC, PD_REDK, PD_REDS, PD_YELLOWC,
+    PD_YELLOWK, PD_YELLOWS,
+};
+use crate::level::Level;
+use crate::level::flags::LineDefFlags;
+use crate::level::map_defs::{LineDef, Sector};
+use crate::player::Player;
+use crate::thing::MapObject;
+use crate::thinker::{Think, ThinkerData};
+
+const GEN_FLOOR_BASE: i16 = 0x6000;
+const GEN_CEILING_BASE: i16 = 0x4000;
+const GEN_DOOR_BASE: i16 = 0x3c00;
+const GEN_LOCKED_BASE: i16 = 0x3800;
+const GEN_LIFT_BASE: i16 = 0x3400;
+const GEN_STAIRS_BASE: i16 = 0x3000;
+const GEN_CRUSHER_BASE: i16 = 0x2f80;
+
+/// Common to all generalized types
+const TRIGGER: i16 = 0x0007;
+const SPEED: i16 = 0x0018;
+const SPEED_SHIFT: i16 = 3;
+
+/// Floors and ceilings share the same layout
+const CRUSH: i16 = 0x1000;
+const CHANGE: i16 = 0x0c00;
+const CHANGE_SHIFT: i16 = 10;
+const TARGET: i16 = 0x0380;
+const TARGET_SHIFT: i16 = 7;
+const DIRECTION: i16 = 0x0040;
+/// Numeric model if set, or if there is no change then monsters may activate
+const MODEL: i16 = 0x0020;
+
+const DOOR_DELAY: i16 = 0x0300;
+const DOOR_DELAY_SHIFT: i16 = 8;
+const DOOR_MONSTER: i16 = 0x0080;
+const DOOR_KIND: i16 = 0x0060;
+const DOOR_KIND_SHIFT: i16 = 5;
+
+const LOCKED_SKULL_IS_CARD: i16 = 0x0200;
+const LOCKED_KEY: i16 = 0x01c0;
+const LOCKED_KEY_SHIFT: i16 = 6;
+const LOCKED_KIND: i16 = 0x0020;
+
+const LIFT_TARGET: i16 = 0x0300;
+const LIFT_TARGET_SHIFT: i16 = 8;
+const LIFT_DELAY: i16 = 0x00c0;
+const LIFT_DELAY_SHIFT: i16 = 6;
+const LIFT_MONSTER: i16 = 0x0020;
+
+const STAIR_IGNORE: i16 = 0x0200;
+const STAIR_DIRECTION: i16 = 0x0100;
+const STAIR_STEP: i16 = 0x00c0;
+const STAIR_STEP_SHIFT: i16 = 6;
+const STAIR_MONSTER: i16 = 0x0020;
+
+const CRUSHER_SILENT: i16 = 0x0040;
+const CRUSHER_MONSTER: i16 = 0x0020;
+
+/// How a line is being activated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Activation {
+    Cross,
+    Use,
+    Shoot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Trigger {
+    WalkOnce,
+    WalkMany,
+    SwitchOnce,
+    SwitchMany,
+    GunOnce,
+    GunMany,
+    PushOnce,
+    PushMany,
+}
+
+impl Trigger {
+    fn from_special(special: i16) -> Self {
+        match special & TRIGGER {
+            0 => Self::WalkOnce,
+            1 => Self::WalkMany,
+            2 => Self::SwitchOnce,
+            3 => Self::SwitchMany,
+            4 => Self::GunOnce,
+            5 => Self::GunMany,
+            6 => Self::PushOnce,
+            _ => Self::PushMany,
+        }
+    }
+
+    /// Push triggers act on the sector behind the line instead of by tag
+    fn is_manual(self) -> bool {
+        matches!(self, Self::PushOnce | Self::PushMany)
+    }
+
+    fn activated_by(self, activation: Activation) -> bool {
+        match activation {
+            Activation::Cross => matches!(self, Self::WalkOnce | Self::WalkMany),
+            Activation::Use => matches!(
+                self,
+                Self::SwitchOnce | Self::SwitchMany | Self::PushOnce | Self::PushMany
+            ),
+            Activation::Shoot => matches!(self, Self::GunOnce | Self::GunMany),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GenKind {
+    Floor,
+    Ceiling,
+    Door,
+    LockedDoor,
+    Lift,
+    Stairs,
+    Crusher,
+}
+
+impl GenKind {
+    fn from_special(special: i16) -> Option<Self> {
+        if special >= GEN_FLOOR_BASE {
+            Some(Self::Floor)
+        } else if special >= GEN_CEILING_BASE {
+            Some(Self::Ceiling)
+        } else if special >= GEN_DOOR_BASE {
+            Some(Self::Door)
+        } else if special >= GEN_LOCKED_BASE {
+            Some(Self::LockedDoor)
+        } else if special >= GEN_LIFT_BASE {
+            Some(Self::Lift)
+        } else if special >= GEN_STAIRS_BASE {
+            Some(Self::Stairs)
+        } else if special >= GEN_CRUSHER_BASE {
+            Some(Self::Crusher)
+        } else {
+            None
+        }
+    }
+
+    fn monster_allowed(self, line: &LineDef) -> bool {
+        let special = line.special;
+        match self {
+            Self::Floor | Self::Ceiling => special & CHANGE == 0 && special & MODEL != 0,
+            Self::Door => {
+                special & DOOR_MONSTER != 0 && line.flags & LineDefFlags::Secret as u32 == 0
+            }
+            Self::LockedDoor => false,
+            Self::Lift => special & LIFT_MONSTER != 0,
+            Self::Stairs => special & STAIR_MONSTER != 0,
+            Self::Crusher => special & CRUSHER_MONSTER != 0,
+        }
+    }
+}
+
+/// Is the special in the BOOM generalized range, `0x2F80` to `0x7FFF`
+pub fn is_generalized(special: i16) -> bool {
+    special >= GEN_CRUSHER_BASE
+}
+
+fn field(special: i16, mask: i16, shift: i16) -> usize {
+    ((special & mask) >> shift) as usize
+}
+
+/// Speed multiplier of 1, 2, 4, or 8 selected by the speed bits
+fn speed(base: fixed_t, special: i16) -> fixed_t {
+    base * fixed_t::from_int(1 << field(special, SPEED, SPEED_SHIFT))
+}
+
+/// Generalized lines run the function for the bits in the special, then clear
+/// the special or flip the switch depending on the trigger. Returns `false` if
+/// the line can't be activated this way, for use lines this means nothing was
+/// used.
+///
+/// This is the generalized part of `P_CrossSpecialLine`, `P_UseSpecialLine`,
+/// and `P_ShootSpecialLine`
+pub fn activate_gen_line(
+    mut line: MapPtr<LineDef>,
+    side: usize,
+    activation: Activation,
+    thing: &mut MapObject,
+) -> bool {
+    let Some(kind) = GenKind::from_special(line.special) else {
+        return false;
+    };
+    let trigger = Trigger::from_special(line.special);
+    if !trigger.activated_by(activation) {
+        return false;
+    }
+    if thing.player().is_none() && !kind.monster_allowed(&line) {
+        return false;
+    }
+    // Everything except push lines needs a tag
+    if line.tag == 0 && !trigger.is_manual() {
+        return false;
+    }
+    if trigger.is_manual() && side != 0 {
+        return true;
+    }
+    if kind == GenKind::LockedDoor
+        && let Some(player) = thing.player_mut()
+        && !can_unlock_door(&line, player)
+    {
+        player.start_sound(SfxName::Oof);
+        return false;
+    }
+
+    if thing.level.is_null() {
+        panic!("Thing had a bad level pointer");
+    }
+    let level = unsafe { &mut *thing.level };
+    debug!("generalized line-special #{:#x}: {kind:?}", line.special);
+    let ok = match kind {
+        GenKind::Floor => ev_do_gen_floor(line.clone(), level),
+        GenKind::Ceiling => ev_do_gen_ceiling(line.clone(), level),
+        GenKind::Door => ev_do_gen_door(line.clone(), level),
+        GenKind::LockedDoor => ev_do_gen_locked_door(line.clone(), level),
+        GenKind::Lift => ev_do_gen_lift(line.clone(), level),
+        GenKind::Stairs => ev_do_gen_stairs(line.clone(), level),
+        GenKind::Crusher => ev_do_gen_crusher(line.clone(), level),
+    };
+    if !ok {
+        return true;
+    }
+
+    match trigger {
+        Trigger::WalkOnce | Trigger::PushOnce => line.special = 0,
+        Trigger::SwitchOnce | Trigger::GunOnce | Trigger::SwitchMany | Trigger::GunMany => {
+            change_switch_texture(
+                line,
+                matches!(trigger, Trigger::SwitchMany | Trigger::GunMany),
+                &level.switch_list,
+                &mut level.button_list,
+                &level.snd_command,
+            );
+        }
+        Trigger::WalkMany | Trigger::PushMany => {}
+    }
+    true
+}
+
+/// The sectors a generalized line acts on. Push lines use the sector behind the
+/// line, everything else the tagged sectors.
+fn gen_sectors(line: &LineDef, level: &mut Level) -> Vec<MapPtr<Sector>> {
+    if Trigger::from_special(line.special).is_manual() {
+        return line.backsector.clone().into_iter().collect();
+    }
+    level
+        .map_data
+        .sectors_mut()
+        .iter_mut()
+        .filter(|s| s.tag == line.tag)
+        .map(MapPtr::new)
+        .collect()
+}
+
+/// Clamp a height moved by a texture size to the BOOM limits
+fn clamp_height(height: fixed_t) -> fixed_t {
+    fixed_t::from_int(height.to_int().clamp(-32000, 32000))
+}
+
+/// EV_DoGenFloor
+pub fn ev_do_gen_floor(line: MapPtr<LineDef>, level: &mut Level) -> bool {
+    let special = line.special;
+    let direction = if special & DIRECTION != 0 { 1 } else { -1 };
+    let target = field(special, TARGET, TARGET_SHIFT);
+    let change = field(special, CHANGE, CHANGE_SHIFT);
+    let mut ret = false;
+
+    for mut sec in gen_sectors(&line, level) {
+        if sec.specialdata.is_some() {
+            continue;
+        }
+        ret = true;
+
+        let destheight = match target {
+            // Highest neighbour floor
+            0 => find_highest_floor_surrounding(sec.clone()),
+            // Lowest neighbour floor
+            1 => find_lowest_floor_surrounding(sec.clone()),
+            // Next neighbour floor
+            2 if direction == 1 => find_next_highest_floor(sec.clone(), sec.floorheight),
+            2 => find_next_lowest_floor(sec.clone(), sec.floorheight),
+            // Lowest neighbour ceiling
+            3 => find_lowest_ceiling_surrounding(sec.clone()),
+            4 => sec.ceilingheight,
+            // By shortest lower texture
+            5 => {
+                let size = find_shortest_lower_texture(sec.clone(), &level.texture_heights);
+                clamp_height(sec.floorheight + size * fixed_t::from_int(direction))
+            }
+            6 => sec.floorheight + fixed_t::from_int(24 * direction),
+            _ => sec.floorheight + fixed_t::from_int(32 * direction),
+        };
+
+        let mut floor = FloorMove {
+            thinker: null_mut(),
+            sector: sec.clone(),
+            kind: FloorKind::Generalized,
+            speed: speed(FLOORSPEED, special),
+            crush: special & CRUSH != 0,
+            direction,
+            newspecial: sec.special,
+            texture: sec.floorpic,
+            destheight,
+        };
+
+        if change != 0 {
+            let model = if special & MODEL != 0 {
+                // Ceiling targets take the model from a ceiling at that height
+                if target == 3 || target == 4 {
+                    find_model_ceiling_sector(sec.clone(), destheight)
+                } else {
+                    find_model_floor_sector(sec.clone(), destheight)
+                }
+            } else {
+                Some(line.frontsector.clone())
+            };
+            if let Some(model) = model {
+                floor.kind = FloorKind::GenChange;
+                floor.texture = model.floorpic;
+                floor.newspecial = match change {
+                    1 => 0,
+                    2 => sec.special,
+                    _ => model.special,
+                };
+            }
+        }
+
+        let thinker = MapObject::create_thinker(ThinkerData::FloorMove(floor), FloorMove::think);
+        if let Some(ptr) = level.thinkers.push::<FloorMove>(thinker) {
+            ptr.set_obj_thinker_ptr();
+            sec.specialdata = Some(ptr);
+        }
+    }
+
+    ret
+}
+
+/// EV_DoGenCeiling
+pub fn ev_do_gen_ceiling(line: MapPtr<LineDef>, level: &mut Level) -> bool {
+    let special = line.special;
+    let direction = if special & DIRECTION != 0 { 1 } else { -1 };
+    let target = field(special, TARGET, TARGET_SHIFT);
+    let change = field(special, CHANGE, CHANGE_SHIFT);
+    let mut ret = false;
+
+    for mut sec in gen_sectors(&line, level) {
+        if sec.specialdata.is_some() {
+            continue;
+        }
+        ret = true;
+
+        let height = match target {
+            // Highest neighbour ceiling
+            0 => find_highest_ceiling_surrounding(sec.clone()),
+            // Lowest neighbour ceiling
+            1 => find_lowest_ceiling_surrounding(sec.clone()),
+            // Next neighbour ceiling
+            2 if direction == 1 => find_next_highest_ceiling(sec.clone(), sec.ceilingheight),
+            2 => find_next_lowest_ceiling(sec.clone(), sec.ceilingheight),
+            // Highest neighbour floor
+            3 => find_highest_floor_surrounding(sec.clone()),
+            4 => sec.floorheight,
+            // By shortest upper texture
+            5 => {
+                let size = find_shortest_upper_texture(sec.clone(), &level.texture_heights);
+                clamp_height(sec.ceilingheight + size * fixed_t::from_int(direction))
+            }
+            6 => sec.ceilingheight + fixed_t::from_int(24 * direction),
+            _ => sec.ceilingheight + fixed_t::from_int(32 * direction),
+        };
+
+        let mut ceiling = CeilingMove {
+            thinker: null_mut(),
+            sector: sec.clone(),
+            kind: CeilKind::Generalized,
+            bottomheight: sec.floorheight,
+            topheight: sec.ceilingheight,
+            speed: speed(CEILSPEED, special),
+            crush: special & CRUSH != 0,
+            direction,
+            tag: sec.tag,
+            olddirection: 0,
+            oldspeed: CEILSPEED,
+            texture: sec.ceilingpic,
+            newspecial: sec.special,
+        };
+        if direction == 1 {
+            ceiling.topheight = height;
+        } else {
+            ceiling.bottomheight = height;
+        }
+
+        if change != 0 {
+            let model = if special & MODEL != 0 {
+                // Floor targets take the model from a floor at that height
+                if target == 3 || target == 4 {
+                    find_model_floor_sector(sec.clone(), height)
+                } else {
+                    find_model_ceiling_sector(sec.clone(), height)
+                }
+            } else {
+                Some(line.frontsector.clone())
+            };
+            if let Some(model) = model {
+                ceiling.kind = CeilKind::GenChange;
+                ceiling.texture = model.ceilingpic;
+                ceiling.newspecial = match change {
+                    1 => 0,
+                    2 => sec.special,
+                    _ => model.special,
+                };
+            }
+        }
+
+        let thinker =
+            MapObject::create_thinker(ThinkerData::CeilingMove(ceiling), CeilingMove::think);
+        if let Some(ptr) = level.thinkers.push::<CeilingMove>(thinker) {
+            ptr.set_obj_thinker_ptr();
+            sec.specialdata = Some(ptr);
+        }
+    }
+
+    ret
+}
+
+/// EV_DoGenCrusher
+pub fn ev_do_gen_crusher(line: MapPtr<LineDef>, level: &mut Level) -> bool {
+    let special = line.special;
+    let kind = if special & CRUSHER_SILENT != 0 {
+        CeilKind::GenSilentCrusher
+    } else {
+        CeilKind::GenCrusher
+    };
+    // TODO: P_ActivateInStasisCeiling(line);
+    let mut ret = false;
+
+    for mut sec in gen_sectors(&line, level) {
+        if sec.specialdata.is_some() {
+            continue;
+        }
+        ret = true;
+
+        let speed = speed(CEILSPEED, special);
+        let ceiling = CeilingMove {
+            thinker: null_mut(),
+            sector: sec.clone(),
+            kind,
+            bottomheight: sec.floorheight + fixed_t::from_int(8),
+            topheight: sec.ceilingheight,
+            speed,
+            crush: true,
+            direction: -1,
+            tag: sec.tag,
+            olddirection: 0,
+            oldspeed: speed,
+            texture: sec.ceilingpic,
+            newspecial: sec.special,
+        };
+
+        let thinker =
+            MapObject::create_thinker(ThinkerData::CeilingMove(ceiling), CeilingMove::think);
+        if let Some(ptr) = level.thinkers.push::<CeilingMove>(thinker) {
+            ptr.set_obj_thinker_ptr();
+            sec.specialdata = Some(ptr);
+        }
+    }
+
+    ret
+}
+
+/// Start a generalized door in each sector. `kind` is 0 for open-wait-close,
+/// 1 open, 2 close-wait-open, and 3 close.
+fn gen_door(line: MapPtr<LineDef>, kind: usize, topwait: i32, level: &mut Level) -> bool {
+    let door_speed = speed(VDOORSPEED, line.special);
+    let blazing = door_speed >= VDOORSPEED * FT_FOUR;
+    let mut ret = false;
+
+    for mut sec in gen_sectors(&line, level) {
+        if sec.specialdata.is_some() {
+            continue;
+        }
+        ret = true;
+
+        let mut door = VerticalDoor {
+            thinker: null_mut(),
+            sector: sec.clone(),
+            kind: DoorKind::Normal,
+            topheight: find_lowest_ceiling_surrounding(sec.clone()) - FT_FOUR,
+            speed: door_speed,
+            direction: 1,
+            topwait,
+            topcountdown: 0,
+        };
+
+        match kind {
+            0 | 1 => {
+                door.kind = match (kind, blazing) {
+                    (0, true) => DoorKind::BlazeRaise,
+                    (0, false) => DoorKind::Normal,
+                    (_, true) => DoorKind::BlazeOpen,
+                    (_, false) => DoorKind::Open,
+                };
+                if door.topheight != sec.ceilingheight {
+                    let sfx = if blazing {
+                        SfxName::Bdopn
+                    } else {
+                        SfxName::Doropn
+                    };
+                    start_sector_sound(&line, sfx, &level.snd_command);
+                }
+            }
+            2 => {
+                door.kind = DoorKind::Close30ThenOpen;
+                door.topheight = sec.ceilingheight;
+                door.direction = -1;
+            }
+            _ => {
+                door.kind = if blazing {
+                    DoorKind::BlazeClose
+                } else {
+                    DoorKind::Close
+                };
+                door.direction = -1;
+            }
+        }
+        if door.direction == -1 {
+            let sfx = if blazing {
+                SfxName::Bdcls
+            } else {
+                SfxName::Dorcls
+            };
+            start_sector_sound(&line, sfx, &level.snd_command);
+        }
+
+        let thinker =
+            MapObject::create_thinker(ThinkerData::VerticalDoor(door), VerticalDoor::think);
+        if let Some(ptr) = level.thinkers.push::<VerticalDoor>(thinker) {
+            ptr.set_obj_thinker_ptr();
+            sec.specialdata = Some(ptr);
+        }
+    }
+
+    ret
+}
+
+/// EV_DoGenDoor
+pub fn ev_do_gen_door(line: MapPtr<LineDef>, level: &mut Level) -> bool {
+    let topwait = match field(line.special, DOOR_DELAY, DOOR_DELAY_SHIFT) {
+        0 => TICRATE,
+        1 => VDOORWAIT,
+        2 => VDOORWAIT * 2,
+        _ => VDOORWAIT * 7,
+    };
+    let kind = field(line.special, DOOR_KIND, DOOR_KIND_SHIFT);
+    gen_door(line, kind, topwait, level)
+}
+
+/// EV_DoGenLockedDoor
+pub fn ev_do_gen_locked_door(line: MapPtr<LineDef>, level: &mut Level) -> bool {
+    let kind = if line.special & LOCKED_KIND != 0 {
+        1
+    } else {
+        0
+    };
+    gen_door(line, kind, VDOORWAIT, level)
+}
+
+/// P_CanUnlockGenDoor. Sets the player message if a key is missing.
+fn can_unlock_door(line: &LineDef, player: &mut Player) -> bool {
+    let cards = &player.status.cards;
+    let has = |card: Card| cards[card as usize];
+    let skull_is_card = line.special & LOCKED_SKULL_IS_CARD != 0;
+    // Either of a card and skull pair, or only the one if they are different
+    let either = |key: Card, other: Card| has(key) || skull_is_card && has(other);
+
+    let message = match field(line.special, LOCKED_KEY, LOCKED_KEY_SHIFT) {
+        0 if !cards.iter().any(|c| *c) => Some(PD_ANY),
+        1 if !either(Card::Redcard, Card::Redskull) => {
+            Some(if skull_is_card { PD_REDK } else { PD_REDC })
+        }
+        2 if !either(Card::Bluecard, Card::Blueskull) => {
+            Some(if skull_is_card { PD_BLUEK } else { PD_BLUEC })
+        }
+        3 if !either(Card::Yellowcard, Card::Yellowskull) => Some(if skull_is_card {
+            PD_YELLOWK
+        } else {
+            PD_YELLOWC
+        }),
+        4 if !either(Card::Redskull, Card::Redcard) => {
+            Some(if skull_is_card { PD_REDK } else { PD_REDS })
+        }
+        5 if !either(Card::Blueskull, Card::Bluecard) => {
+            Some(if skull_is_card { PD_BLUEK } else { PD_BLUES })
+        }
+        6 if !either(Card::Yellowskull, Card::Yellowcard) => Some(if skull_is_card {
+            PD_YELLOWK
+        } else {
+            PD_YELLOWS
+        }),
+        7 if skull_is_card
+            && !(either(Card::Redcard, Card::Redskull)
+                && either(Card::Bluecard, Card::Blueskull)
+                && either(Card::Yellowcard, Card::Yellowskull)) =>
+        {
+            Some(PD_ALL3)
+        }
+        7 if !skull_is_card && !cards.iter().all(|c| *c) => Some(PD_ALL6),
+        _ => None,
+    };
+
+    if let Some(message) = message {
+        player.message = Some(message);
+        return false;
+    }
+    true
+}
+
+/// EV_DoGenLift
+pub fn ev_do_gen_lift(line: MapPtr<LineDef>, level: &mut Level) -> bool {
+    let special = line.special;
+    let target = field(special, LIFT_TARGET, LIFT_TARGET_SHIFT);
+    let wait = TICRATE
+        * match field(special, LIFT_DELAY, LIFT_DELAY_SHIFT) {
+            0 => 1,
+            1 => 3,
+            2 => 5,
+            _ => 10,
+        };
+    // Lifts start at twice the platform speed
+    let lift_speed = speed(PLATSPEED * FT_TWO, special);
+    let perpetual = target == 3;
+    if perpetual {
+        level.activate_platform_in_stasis(line.tag);
+    }
+
+    let mut ret = false;
+    let mut plats = Vec::new();
+    for mut sec in gen_sectors(&line, level) {
+        if sec.specialdata.is_some() {
+            continue;
+        }
+        ret = true;
+
+        let low = match target {
+            // Lowest neighbour floor
+            0 | 3 => find_lowest_floor_surrounding(sec.clone()),
+            // Next lowest neighbour floor
+            1 => find_next_lowest_floor(sec.clone(), sec.floorheight),
+            // Lowest neighbour ceiling
+            _ => find_lowest_ceiling_surrounding(sec.clone()),
+        };
+
+        let mut platform = Platform {
+            thinker: null_mut(),
+            sector: sec.clone(),
+            speed: lift_speed,
+            low: low.min(sec.floorheight),
+            high: sec.floorheight,
+            wait,
+            count: 0,
+            status: PlatStatus::Down,
+            old_status: PlatStatus::Down,
+            crush: false,
+            tag: line.tag,
+            kind: PlatKind::DownWaitUpStay,
+        };
+        if perpetual {
+            platform.kind = PlatKind::PerpetualRaise;
+            platform.high = find_highest_floor_surrounding(sec.clone()).max(sec.floorheight);
+            platform.status = if (p_random() & 1) == 0 {
+                PlatStatus::Up
+            } else {
+                PlatStatus::Down
+            };
+        }
+        start_sector_sound(&line, SfxName::Pstart, &level.snd_command);
+
+        let thinker = MapObject::create_thinker(ThinkerData::Platform(platform), Platform::think);
+        if let Some(ptr) = level.thinkers.push::<Platform>(thinker) {
+            ptr.set_obj_thinker_ptr();
+            sec.specialdata = Some(ptr);
+            plats.push(ptr.platform_mut() as *mut Platform);
+        }
+    }
+
+    for p in plats {
+        level.add_active_platform(p);
+    }
+    ret
+}
+
+/// EV_DoGenStairs
+pub fn ev_do_gen_stairs(mut line: MapPtr<LineDef>, level: &mut Level) -> bool {
+    let special = line.special;
+    let direction = if special & STAIR_DIRECTION != 0 {
+        1
+    } else {
+        -1
+    };
+    let ignore_texture = special & STAIR_IGNORE != 0;
+    let stair_speed = [
+        FLOORSPEED / FT_FOUR,
+        FLOORSPEED / FT_TWO,
+        FLOORSPEED * FT_TWO,
+        FLOORSPEED * FT_FOUR,
+    ][field(special, SPEED, SPEED_SHIFT)];
+    let stair_size =
+        fixed_t::from_int([4, 8, 16, 24][field(special, STAIR_STEP, STAIR_STEP_SHIFT)] * direction);
+    let mut ret = false;
+
+    for mut sec in gen_sectors(&line, level) {
+        if sec.specialdata.is_some() {
+            continue;
+        }
+        ret = true;
+
+        let texture = sec.floorpic;
+        let mut height = sec.floorheight + stair_size;
+        loop {
+            let floor = FloorMove {
+                thinker: null_mut(),
+                sector: sec.clone(),
+                kind: FloorKind::Generalized,
+                speed: stair_speed,
+                crush: false,
+                direction,
+                newspecial: 0,
+                texture,
+                destheight: height,
+            };
+            let thinker =
+                MapObject::create_thinker(ThinkerData::FloorMove(floor), FloorMove::think);
+            if let Some(ptr) = level.thinkers.push::<FloorMove>(thinker) {
+                ptr.set_obj_thinker_ptr();
+                sec.specialdata = Some(ptr);
+            }
+
+            // Find the next step, the back side of a line facing out of this one
+            let mut next = None;
+            for line in sec.lines.iter() {
+                let Some(back) = line.backsector.clone() else {
+                    continue;
+                };
+                if line.frontsector != sec || (!ignore_texture && back.floorpic != texture) {
+                    continue;
+                }
+                if back.specialdata.is_some() {
+                    continue;
+                }
+                next = Some(back);
+                break;
+            }
+            let Some(next) = next else {
+                break;
+            };
+            height += stair_size;
+            sec = next;
+        }
+    }
+
+    // Retriggerable stairs build up and down alternately
+    if ret {
+        line.special ^= STAIR_DIRECTION;
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GenKind, Trigger, field, is_generalized};
+
+    #[test]
+    fn decode_ranges() {
+        assert!(!is_generalized(141));
+        assert_eq!(GenKind::from_special(0x2f80), Some(GenKind::Crusher));
+        assert_eq!(GenKind::from_special(0x3000), Some(GenKind::Stairs));
+        assert_eq!(GenKind::from_special(0x3400), Some(GenKind::Lift));
+        assert_eq!(GenKind::from_special(0x3800), Some(GenKind::LockedDoor));
+        assert_eq!(GenKind::from_special(0x3c00), Some(GenKind::Door));
+        assert_eq!(GenKind::from_special(0x4000), Some(GenKind::Ceiling));
+        assert_eq!(GenKind::from_special(0x6000), Some(GenKind::Floor));
+        assert_eq!(GenKind::from_special(0x7fff), Some(GenKind::Floor));
+    }
+
+    #[test]
+    fn decode_fields() {
+        // Floor, S1, fast, numeric model, up, lowest neighbour ceiling, change
+        // texture, crush
+        let special = 0x6000 | 2 | 2 << 3 | 0x20 | 0x40 | 3 << 7 | 2 << 10 | 0x1000;
+        assert_eq!(Trigger::from_special(special), Trigger::SwitchOnce);
+        assert_eq!(field(special, super::SPEED, super::SPEED_SHIFT), 2);
+        assert_eq!(field(special, super::TARGET, super::TARGET_SHIFT), 3);
+        assert_eq!(field(special, super::CHANGE, super::CHANGE_SHIFT), 2);
+        assert!(special & super::CRUSH != 0);
+        assert!(Trigger::from_special(0x3c00 | 7).is_manual());
+    }
+}
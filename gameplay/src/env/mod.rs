@@ -4,6 +4,7 @@
 pub(crate) mod ceiling;
 pub(crate) mod doors;
 pub(crate) mod floor;
+pub(crate) mod generalized;
 pub(crate) mod lights;
 pub(crate) mod platforms;
 pub(crate) mod specials;
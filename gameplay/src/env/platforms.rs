@@ -19,7 +19,7 @@ use crate::thing::MapObject;
 use crate::thinker::{Think, Thinker, ThinkerData};
 use math::{FT_EIGHT, FT_FOUR, FT_ONE, FT_TWO, FT_ZERO, fixed_t, p_random};
 
-const PLATSPEED: fixed_t = FT_ONE;
+pub(crate) const PLATSPEED: fixed_t = FT_ONE;
 const PLATWAIT: i32 = 3;
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
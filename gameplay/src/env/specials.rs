@@ -8,6 +8,7 @@ use crate::doom_def::{ONCEILINGZ, ONFLOORZ};
 use crate::env::ceiling::{CeilKind, ev_do_ceiling};
 use crate::env::doors::{DoorKind, ev_do_door};
 use crate::env::floor::{FloorKind, StairKind, ev_build_stairs, ev_do_floor};
+use crate::env::generalized::{Activation, activate_gen_line, is_generalized};
 use crate::env::lights::{
     FASTDARK, FireFlicker, Glow, LightFlash, SLOWDARK, StrobeFlash, ev_start_light_strobing,
     ev_turn_light_on, ev_turn_tag_lights_off,
@@ -17,10 +18,10 @@ use crate::env::switch::{change_switch_texture, start_sector_sound};
 use crate::env::teleport::teleport;
 use crate::info::{MOBJINFO, MapObjKind};
 use crate::level::Level;
-use crate::level::flags::LineDefFlags;
+use crate::level::flags::{LineDefFlags, SectorFlags};
 use crate::level::map_defs::{LineDef, Sector};
 use crate::pic::ButtonWhere;
-use crate::thing::MapObject;
+use crate::thing::{FRICTION, MapObject};
 use crate::{Angle, MapObjFlag, MapPtr, PicData, TICRATE};
 use glam::Vec2;
 use log::{debug, error, trace};
@@ -124,15 +125,13 @@ pub fn find_highest_floor_surrounding(sec: MapPtr<Sector>) -> fixed_t {
 
 /// P_FindNextHighestFloor
 pub fn find_next_highest_floor(sec: MapPtr<Sector>, current: fixed_t) -> fixed_t {
-    let mut height = current;
     let mut height_list = Vec::new();
 
     for line in &sec.lines {
         if let Some(other) = get_next_sector(line.clone(), sec.clone()) {
-            if other.floorheight > height {
-                height = other.floorheight;
+            if other.floorheight > current {
+                height_list.push(other.floorheight);
             }
-            height_list.push(other.floorheight);
         }
     }
 
@@ -150,6 +149,107 @@ pub fn find_next_highest_floor(sec: MapPtr<Sector>, current: fixed_t) -> fixed_t
     min
 }
 
+/// P_FindNextLowestFloor
+pub fn find_next_lowest_floor(sec: MapPtr<Sector>, current: fixed_t) -> fixed_t {
+    let mut height = fixed_t::new(i32::MIN);
+    let mut found = false;
+    for line in &sec.lines {
+        if let Some(other) = get_next_sector(line.clone(), sec.clone()) {
+            if other.floorheight < current && other.floorheight > height {
+                height = other.floorheight;
+                found = true;
+            }
+        }
+    }
+    if found { height } else { current }
+}
+
+/// P_FindNextHighestCeiling
+pub fn find_next_highest_ceiling(sec: MapPtr<Sector>, current: fixed_t) -> fixed_t {
+    let mut height = fixed_t::new(i32::MAX);
+    let mut found = false;
+    for line in &sec.lines {
+        if let Some(other) = get_next_sector(line.clone(), sec.clone()) {
+            if other.ceilingheight > current && other.ceilingheight < height {
+                height = other.ceilingheight;
+                found = true;
+            }
+        }
+    }
+    if found { height } else { current }
+}
+
+/// P_FindNextLowestCeiling
+pub fn find_next_lowest_ceiling(sec: MapPtr<Sector>, current: fixed_t) -> fixed_t {
+    let mut height = fixed_t::new(i32::MIN);
+    let mut found = false;
+    for line in &sec.lines {
+        if let Some(other) = get_next_sector(line.clone(), sec.clone()) {
+            if other.ceilingheight < current && other.ceilingheight > height {
+                height = other.ceilingheight;
+                found = true;
+            }
+        }
+    }
+    if found { height } else { current }
+}
+
+/// P_FindShortestTextureAround. The height of the shortest lower texture on
+/// the two-sided lines of the sector. `heights` is indexed by texture number.
+pub fn find_shortest_lower_texture(sec: MapPtr<Sector>, heights: &[fixed_t]) -> fixed_t {
+    let mut min = fixed_t::from_int(i16::MAX as i32);
+    for line in sec.lines.iter() {
+        if line.flags & LineDefFlags::TwoSided as u32 == 0 {
+            continue;
+        }
+        let back = line.back_sidedef.as_ref().and_then(|s| s.bottomtexture);
+        for texture in [line.front_sidedef.bottomtexture, back]
+            .into_iter()
+            .flatten()
+        {
+            if heights[texture] < min {
+                min = heights[texture];
+            }
+        }
+    }
+    min
+}
+
+/// P_FindShortestUpperAround. The height of the shortest upper texture on the
+/// two-sided lines of the sector. `heights` is indexed by texture number.
+pub fn find_shortest_upper_texture(sec: MapPtr<Sector>, heights: &[fixed_t]) -> fixed_t {
+    let mut min = fixed_t::from_int(i16::MAX as i32);
+    for line in sec.lines.iter() {
+        if line.flags & LineDefFlags::TwoSided as u32 == 0 {
+            continue;
+        }
+        let back = line.back_sidedef.as_ref().and_then(|s| s.toptexture);
+        for texture in [line.front_sidedef.toptexture, back].into_iter().flatten() {
+            if heights[texture] < min {
+                min = heights[texture];
+            }
+        }
+    }
+    min
+}
+
+/// P_FindModelFloorSector. A neighbouring sector with its floor at `height`
+pub fn find_model_floor_sector(sec: MapPtr<Sector>, height: fixed_t) -> Option<MapPtr<Sector>> {
+    sec.lines
+        .iter()
+        .filter_map(|line| get_next_sector(line.clone(), sec.clone()))
+        .find(|other| other.floorheight == height)
+}
+
+/// P_FindModelCeilingSector. A neighbouring sector with its ceiling at
+/// `height`
+pub fn find_model_ceiling_sector(sec: MapPtr<Sector>, height: fixed_t) -> Option<MapPtr<Sector>> {
+    sec.lines
+        .iter()
+        .filter_map(|line| get_next_sector(line.clone(), sec.clone()))
+        .find(|other| other.ceilingheight == height)
+}
+
 /// P_ChangeSector
 fn change_sector(mut sector: MapPtr<Sector>, crunch: bool) -> bool {
     let mut no_fit = false;
@@ -367,11 +467,16 @@ pub fn cross_special_line(side: usize, mut line: MapPtr<LineDef>, thing: &mut Ma
             ok = true;
         }
 
-        if !ok {
+        if !ok && !is_generalized(line.special) {
             return;
         }
     }
 
+    if is_generalized(line.special) {
+        activate_gen_line(line, side, Activation::Cross, thing);
+        return;
+    }
+
     if thing.level.is_null() {
         panic!("Thing had a bad level pointer");
     }
@@ -731,6 +836,11 @@ pub fn cross_special_line(side: usize, mut line: MapPtr<LineDef>, thing: &mut Ma
 pub fn shoot_special_line(line: MapPtr<LineDef>, thing: &mut MapObject) {
     let mut ok = false;
 
+    if is_generalized(line.special) {
+        activate_gen_line(line, 0, Activation::Shoot, thing);
+        return;
+    }
+
     if thing.level.is_null() {
         panic!("Thing had a bad level pointer");
     }
@@ -796,7 +906,12 @@ pub fn spawn_specials(level: &mut Level) {
         .iter_mut()
         .filter(|s| s.special != 0)
     {
-        match sector.special {
+        if sector.special & SectorFlags::Secret as i16 != 0 {
+            debug!("sector-special #{}: generalized secret", sector.special);
+            level.total_level_secrets += 1;
+        }
+        // BOOM generalized sectors keep the lighting type in the low bits
+        match sector.special & 31 {
             1 => {
                 debug!("sector-special #{}: light flicker!", sector.special);
                 LightFlash::spawn(sector, level);
@@ -815,9 +930,10 @@ pub fn spawn_specials(level: &mut Level) {
                     sector.special
                 );
                 StrobeFlash::spawn(sector, FASTDARK, false, level);
-                sector.special = 4;
+                // Keep the generalized bits
+                sector.special = (sector.special & !31) | 4;
             }
-            9 => {
+            9 if sector.special < 32 => {
                 debug!("sector-special #{}: secret", sector.special);
                 level.total_level_secrets += 1;
             }
@@ -857,16 +973,69 @@ pub fn spawn_specials(level: &mut Level) {
             level.line_special_list.push(MapPtr::new(line));
         }
     }
+
+    spawn_friction(level);
+    spawn_translucent_lines(level);
 }
 
-/// Doom function name `P_UpdateSpecials`
-pub fn update_specials(level: &mut Level, pic_data: &mut PicData) {
-    // Used mostly for deathmatch as far as I know
-    if level.level_timer && level.level_time == 0 {
-        // exit
-        level.do_exit_level();
+/// Line special 260 makes the middle texture of itself translucent, or of
+/// every line sharing its tag.
+fn spawn_translucent_lines(level: &mut Level) {
+    let lines = &mut level.map_data.linedefs;
+    for i in 0..lines.len() {
+        if lines[i].special != 260 {
+            continue;
+        }
+        debug!("line-special #260: translucent");
+        let tag = lines[i].tag;
+        if tag == 0 {
+            lines[i].translucent = true;
+        } else {
+            for line in lines.iter_mut().filter(|l| l.tag == tag) {
+                line.translucent = true;
+            }
+        }
     }
+}
 
+/// Line special 223 sets the friction of the tagged sectors from the length
+/// of the line. The sectors only use it if they have the friction bit set.
+///
+/// BOOM function name `P_SpawnFriction`
+fn spawn_friction(level: &mut Level) {
+    let level_iter = unsafe { &mut *(level as *mut Level) };
+    for line in level_iter
+        .map_data
+        .linedefs
+        .iter()
+        .filter(|l| l.special == 223)
+    {
+        let length = line.delta.length().to_int();
+        let friction = ((0x1eb8 * length) / 0x80 + 0xd000).clamp(0, 0x10000);
+        // The thrust is scaled to match, so that ice is harder to move on and
+        // mud slower
+        let move_factor = if friction > FRICTION.0 {
+            ((0x10092 - friction) * 0x70) / 0x158
+        } else {
+            ((friction - 0xdb34) * 0xa) / 0x80
+        }
+        .max(32);
+        debug!("line-special #223: friction {friction:#x}, factor {move_factor}");
+
+        for sector in level
+            .map_data
+            .sectors_mut()
+            .iter_mut()
+            .filter(|s| s.tag == line.tag)
+        {
+            sector.friction = fixed_t::new(friction);
+            sector.move_factor = move_factor;
+        }
+    }
+}
+
+/// Doom function name `P_UpdateSpecials`
+pub fn update_specials(level: &mut Level, pic_data: &mut PicData) {
     // Flats and wall texture animations (switching between series)
     for anim in level.animations.iter_mut() {
         anim.update(pic_data, level.level_time as usize);
@@ -909,8 +1078,8 @@ pub fn update_specials(level: &mut Level, pic_data: &mut PicData) {
 
 /// P_RespawnSpecials
 pub fn respawn_specials(level: &mut Level) {
-    // only respawn items in deathmatch
-    if level.options.deathmatch == 0 && !level.options.respawn_monsters {
+    // only respawn items in altdeath
+    if level.options.deathmatch != 2 {
         return;
     }
 
@@ -936,7 +1105,7 @@ pub fn respawn_specials(level: &mut Level) {
 
         let mut i = 0;
         for n in 0..MapObjKind::Count as u16 {
-            if mthing.1.kind == MOBJINFO[n as usize].doomednum as i16 {
+            if mthing.1.kind == unsafe { MOBJINFO[n as usize].doomednum } as i16 {
                 i = n;
                 break;
             }
@@ -951,7 +1120,7 @@ pub fn respawn_specials(level: &mut Level) {
 
         let kind = MapObjKind::from(i);
 
-        let z = if MOBJINFO[i as usize].flags & MapObjFlag::Spawnceiling as u32 != 0 {
+        let z = if unsafe { MOBJINFO[i as usize].flags } & MapObjFlag::Spawnceiling as u32 != 0 {
             ONCEILINGZ
         } else {
             ONFLOORZ
@@ -11,6 +11,7 @@ use crate::doom_def::Card;
 use crate::env::ceiling::{CeilKind, ev_do_ceiling};
 use crate::env::doors::{DoorKind, ev_do_door, ev_vertical_door};
 use crate::env::floor::{FloorKind, StairKind, ev_build_stairs, ev_do_donut, ev_do_floor};
+use crate::env::generalized::{Activation, activate_gen_line, is_generalized};
 use crate::env::lights::ev_turn_light_on;
 use crate::env::platforms::{PlatKind, ev_do_platform};
 use crate::lang::english::{PD_BLUEO, PD_REDO, PD_YELLOWO};
@@ -137,7 +138,11 @@ pub fn change_switch_texture(
 /// P_UseSpecialLine
 /// Called when a thing uses a special line.
 /// Only the front sides of lines are usable.
-pub fn p_use_special_line(_side: i32, line: MapPtr<LineDef>, thing: &mut MapObject) -> bool {
+pub fn p_use_special_line(side: i32, line: MapPtr<LineDef>, thing: &mut MapObject) -> bool {
+    if is_generalized(line.special) {
+        return activate_gen_line(line, side as usize, Activation::Use, thing);
+    }
+
     //  Switches that other things can activate
     if thing.player().is_none() {
         // never open secret doors
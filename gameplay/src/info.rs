@@ -8,7 +8,7 @@
 use math::{FT_EIGHT, FT_ZERO, fixed_t};
 
 const NUM_SPRNAMES: usize = SpriteNum::Count as usize;
-pub const SPRNAMES: [&str; NUM_SPRNAMES] = [
+pub static mut SPRNAMES: [&str; NUM_SPRNAMES] = [
     "TROO", "SHTG", "PUNG", "PISG", "PISF", "SHTF", "SHT2", "CHGG", "CHGF", "MISG", "MISF", "SAWG",
     "PLSG", "PLSF", "BFGG", "BFGF", "BLUD", "PUFF", "BAL1", "BAL2", "PLSS", "PLSE", "MISL", "BFS1",
     "BFE1", "BFE2", "TFOG", "IFOG", "PLAY", "POSS", "SPOS", "VILE", "FIRE", "FATB", "FBXP", "SKEL",
@@ -172,6 +172,14 @@ impl Default for SpriteNum {
         SpriteNum::TROO
     }
 }
+impl From<u16> for SpriteNum {
+    fn from(w: u16) -> Self {
+        if w >= SpriteNum::Count as u16 {
+            panic!("{} is not a variant of SpriteNum", w);
+        }
+        unsafe { std::mem::transmute(w) }
+    }
+}
 #[repr(u16)]
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[allow(non_camel_case_types, dead_code)]
@@ -11033,7 +11041,7 @@ pub struct MapObjInfo {
 }
 
 const NUM_CATEGORIES: usize = MapObjKind::Count as usize;
-pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
+pub static mut MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
     // MT_PLAYER
     MapObjInfo {
         doomednum: -1,
@@ -11171,7 +11179,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Noblockmap as u32 | MapObjFlag::Nogravity as u32,
+        flags: MapObjFlag::Noblockmap as u32
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_UNDEAD
@@ -11254,7 +11264,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Noblockmap as u32 | MapObjFlag::Nogravity as u32,
+        flags: MapObjFlag::Noblockmap as u32
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_FATSO
@@ -11311,7 +11323,8 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         flags: MapObjFlag::Noblockmap as u32
             | MapObjFlag::Missile as u32
             | MapObjFlag::Dropoff as u32
-            | MapObjFlag::Nogravity as u32,
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_CHAINGUY
@@ -11511,7 +11524,8 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         flags: MapObjFlag::Noblockmap as u32
             | MapObjFlag::Missile as u32
             | MapObjFlag::Dropoff as u32
-            | MapObjFlag::Nogravity as u32,
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_KNIGHT
@@ -11874,7 +11888,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Noblockmap as u32 | MapObjFlag::Nogravity as u32,
+        flags: MapObjFlag::Noblockmap as u32
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_BARREL
@@ -11929,7 +11945,8 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         flags: MapObjFlag::Noblockmap as u32
             | MapObjFlag::Missile as u32
             | MapObjFlag::Dropoff as u32
-            | MapObjFlag::Nogravity as u32,
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_HEADSHOT
@@ -11958,7 +11975,8 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         flags: MapObjFlag::Noblockmap as u32
             | MapObjFlag::Missile as u32
             | MapObjFlag::Dropoff as u32
-            | MapObjFlag::Nogravity as u32,
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_ROCKET
@@ -12016,7 +12034,8 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         flags: MapObjFlag::Noblockmap as u32
             | MapObjFlag::Missile as u32
             | MapObjFlag::Dropoff as u32
-            | MapObjFlag::Nogravity as u32,
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_BFG
@@ -12045,7 +12064,8 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         flags: MapObjFlag::Noblockmap as u32
             | MapObjFlag::Missile as u32
             | MapObjFlag::Dropoff as u32
-            | MapObjFlag::Nogravity as u32,
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_ARACHPLAZ
@@ -12074,7 +12094,8 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         flags: MapObjFlag::Noblockmap as u32
             | MapObjFlag::Missile as u32
             | MapObjFlag::Dropoff as u32
-            | MapObjFlag::Nogravity as u32,
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_PUFF
@@ -12152,7 +12173,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Noblockmap as u32 | MapObjFlag::Nogravity as u32,
+        flags: MapObjFlag::Noblockmap as u32
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_IFOG
@@ -12178,7 +12201,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Noblockmap as u32 | MapObjFlag::Nogravity as u32,
+        flags: MapObjFlag::Noblockmap as u32
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_TELEPORTMAN
@@ -12568,7 +12593,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Special as u32 | MapObjFlag::Countitem as u32,
+        flags: MapObjFlag::Special as u32
+            | MapObjFlag::Countitem as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_INV
@@ -12594,7 +12621,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Special as u32 | MapObjFlag::Countitem as u32,
+        flags: MapObjFlag::Special as u32
+            | MapObjFlag::Countitem as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_MISC13
@@ -12646,7 +12675,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Special as u32 | MapObjFlag::Countitem as u32,
+        flags: MapObjFlag::Special as u32
+            | MapObjFlag::Countitem as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_MISC14
@@ -12750,7 +12781,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Special as u32 | MapObjFlag::Countitem as u32,
+        flags: MapObjFlag::Special as u32
+            | MapObjFlag::Countitem as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_CLIP
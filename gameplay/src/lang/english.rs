@@ -46,6 +46,15 @@ pub const PD_YELLOWO: &str = "You need a yellow key to activate this object";
 pub const PD_BLUEK: &str = "You need a blue key to open this door";
 pub const PD_REDK: &str = "You need a red key to open this door";
 pub const PD_YELLOWK: &str = "You need a yellow key to open this door";
+pub const PD_BLUEC: &str = "You need a blue card to open this door";
+pub const PD_REDC: &str = "You need a red card to open this door";
+pub const PD_YELLOWC: &str = "You need a yellow card to open this door";
+pub const PD_BLUES: &str = "You need a blue skull to open this door";
+pub const PD_REDS: &str = "You need a red skull to open this door";
+pub const PD_YELLOWS: &str = "You need a yellow skull to open this door";
+pub const PD_ANY: &str = "Any key will open this door";
+pub const PD_ALL3: &str = "You need all three keys to open this door";
+pub const PD_ALL6: &str = "You need all six keys to open this door";
 
 pub const STSTR_MUS: &str = "Music Change";
 pub const STSTR_NOMUS: &str = "IMPOSSIBLE SELECTION";
@@ -63,3 +72,104 @@ pub const STSTR_BEHOLDX: &str = "Power-up Toggled";
 
 pub const STSTR_CHOPPERS: &str = "... doesn't suck - GM";
 pub const STSTR_CLEV: &str = "Changing Level...";
+
+pub const GGSAVED: &str = "game saved.";
+
+pub const QUITMSG: &str = "are you sure you want to\nquit this great game?";
+pub const DOSY: &str = "(press y to quit to dos.)";
+pub const QSPROMPT: &str = "quicksave over your game named\n\n'%s'?\n\npress y or n.";
+pub const QLPROMPT: &str = "do you want to quickload the game named\n\n'%s'?\n\npress y or n.";
+pub const QSAVESPOT: &str = "you haven't picked a quicksave slot yet!\n\npress a key.";
+pub const QLOADNET: &str = "you can't quickload during a netgame!\n\npress a key.";
+pub const LOADNET: &str = "you can't do load while in a net game!\n\npress a key.";
+pub const SAVEDEAD: &str = "you can't save if you aren't playing!\n\npress a key.";
+pub const NETEND: &str = "you can't end a netgame!\n\npress a key.";
+pub const ENDGAME: &str = "are you sure you want to end the game?\n\npress y or n.";
+pub const MSGOFF: &str = "Messages OFF";
+pub const MSGON: &str = "Messages ON";
+pub const DETAILHI: &str = "High detail";
+pub const DETAILLO: &str = "Low detail";
+pub const EMPTYSTRING: &str = "empty slot";
+
+pub const AMSTR_FOLLOWON: &str = "Follow Mode ON";
+pub const AMSTR_FOLLOWOFF: &str = "Follow Mode OFF";
+pub const AMSTR_GRIDON: &str = "Grid ON";
+pub const AMSTR_GRIDOFF: &str = "Grid OFF";
+pub const AMSTR_MARKEDSPOT: &str = "Marked Spot";
+pub const AMSTR_MARKSCLEARED: &str = "All Marks Cleared";
+
+/// Automap level names for Doom, indexed by `(episode - 1) * 9 + map - 1`
+pub const MAPNAMES: [&str; 36] = [
+    "E1M1: Hangar",
+    "E1M2: Nuclear Plant",
+    "E1M3: Toxin Refinery",
+    "E1M4: Command Control",
+    "E1M5: Phobos Lab",
+    "E1M6: Central Processing",
+    "E1M7: Computer Station",
+    "E1M8: Phobos Anomaly",
+    "E1M9: Military Base",
+    "E2M1: Deimos Anomaly",
+    "E2M2: Containment Area",
+    "E2M3: Refinery",
+    "E2M4: Deimos Lab",
+    "E2M5: Command Center",
+    "E2M6: Halls of the Damned",
+    "E2M7: Spawning Vats",
+    "E2M8: Tower of Babel",
+    "E2M9: Fortress of Mystery",
+    "E3M1: Hell Keep",
+    "E3M2: Slough of Despair",
+    "E3M3: Pandemonium",
+    "E3M4: House of Pain",
+    "E3M5: Unholy Cathedral",
+    "E3M6: Mt. Erebus",
+    "E3M7: Limbo",
+    "E3M8: Dis",
+    "E3M9: Warrens",
+    "E4M1: Hell Beneath",
+    "E4M2: Perfect Hatred",
+    "E4M3: Sever The Wicked",
+    "E4M4: Unruly Evil",
+    "E4M5: They Will Repent",
+    "E4M6: Against Thee Wickedly",
+    "E4M7: And Hell Followed",
+    "E4M8: Unto The Cruel",
+    "E4M9: Fear",
+];
+
+/// Automap level names for Doom II, indexed by `map - 1`
+pub const MAPNAMES2: [&str; 32] = [
+    "level 1: entryway",
+    "level 2: underhalls",
+    "level 3: the gantlet",
+    "level 4: the focus",
+    "level 5: the waste tunnels",
+    "level 6: the crusher",
+    "level 7: dead simple",
+    "level 8: tricks and traps",
+    "level 9: the pit",
+    "level 10: refueling base",
+    "level 11: 'o' of destruction!",
+    "level 12: the factory",
+    "level 13: downtown",
+    "level 14: the inmost dens",
+    "level 15: industrial zone",
+    "level 16: suburbs",
+    "level 17: tenements",
+    "level 18: the courtyard",
+    "level 19: the citadel",
+    "level 20: gotcha!",
+    "level 21: nirvana",
+    "level 22: the catacombs",
+    "level 23: barrels o' fun",
+    "level 24: the chasm",
+    "level 25: bloodfalls",
+    "level 26: the abandoned mines",
+    "level 27: monster condo",
+    "level 28: the spirit world",
+    "level 29: the living end",
+    "level 30: icon of sin",
+    "level 31: wolfenstein",
+    "level 32: grosse",
+];
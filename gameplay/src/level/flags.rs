@@ -40,6 +40,20 @@ pub enum LineDefFlags {
     Mapped = 1 << 8,
 }
 
+/// BOOM generalized sector bits. The low five bits of a sector special keep
+/// their vanilla meaning (lighting and damage types)
+pub enum SectorFlags {
+    /// Two bits selecting 5, 10, or 20 damage every 32 tics
+    Damage = 0x60,
+    /// Standing in the sector counts as finding a secret
+    Secret = 1 << 7,
+    /// Things on the floor use the sector friction set by linedef 223
+    Friction = 1 << 8,
+}
+
+/// Shift for the value masked by `SectorFlags::Damage`
+pub const SECTOR_DAMAGE_SHIFT: i16 = 5;
+
 #[test]
 fn check_flags_enum() {
     let flag = 28; // upper and lower unpegged, twosided
@@ -14,7 +14,7 @@ use math::{
     fixed_to_float, point_to_angle_2,
 };
 use wad::WadData;
-use wad::extended::{ExtendedNodeType, NodeLumpType, WadExtendedMap};
+use wad::extended::{NodeLumpType, WadExtendedMap};
 use wad::types::*;
 
 use super::map_defs::Blockmap;
@@ -97,6 +97,14 @@ impl MapData {
         &self.linedefs
     }
 
+    pub fn blockmap(&self) -> &Blockmap {
+        &self.blockmap
+    }
+
+    pub fn blockmap_mut(&mut self) -> &mut Blockmap {
+        &mut self.blockmap
+    }
+
     pub fn sectors(&self) -> &[Sector] {
         &self.sectors
     }
@@ -109,6 +117,10 @@ impl MapData {
         &self.sidedefs
     }
 
+    pub fn sidedefs_mut(&mut self) -> &mut [SideDef] {
+        &mut self.sidedefs
+    }
+
     pub fn subsectors(&self) -> &[SubSector] {
         &self.subsectors
     }
@@ -163,23 +175,53 @@ impl MapData {
             tex_order.append(&mut pnames2);
         }
 
-        self.things = wad.thing_iter(map_name).collect();
+        let udmf = wad.read_udmf(map_name).map(|map| {
+            map.unwrap_or_else(|e| panic!("{}: Could not parse TEXTMAP: {}", map_name, e))
+        });
+
+        self.things = match udmf.as_ref() {
+            Some(udmf) => udmf.things.clone(),
+            None => wad.thing_iter(map_name).collect(),
+        };
         info!("{}: Loaded {} things", map_name, self.things.len());
 
         // We may need to append ZDoom vertices to the vertexes, so check and lod now
-        let node_type = wad.node_lump_type(map_name);
-        let extended = match node_type {
-            NodeLumpType::OGDoom => None,
-            NodeLumpType::Extended(ExtendedNodeType::XNOD) => WadExtendedMap::parse(wad, map_name),
-            _ => panic!("Unsupported zddom node type (yet)"),
+        let mut node_type = NodeLumpType::OGDoom;
+        let extended = if udmf.is_some() {
+            // There is no node builder, so UDMF maps must have ZDoom nodes
+            Some(
+                WadExtendedMap::parse_udmf(wad, map_name)
+                    .unwrap_or_else(|| panic!("{}: UDMF map has no usable ZNODES", map_name)),
+            )
+        } else {
+            node_type = wad.node_lump_type(map_name);
+            // ZDBSP may write GL nodes to SSECTORS and leave NODES empty or unusable,
+            // so that is the fallback if NODES can't be used
+            match node_type {
+                NodeLumpType::OGDoom => WadExtendedMap::parse_gl_subsectors(wad, map_name),
+                NodeLumpType::Extended(_) => WadExtendedMap::parse(wad, map_name)
+                    .or_else(|| WadExtendedMap::parse_gl_subsectors(wad, map_name)),
+            }
         };
+        if let Some(ext) = extended.as_ref() {
+            node_type = NodeLumpType::Extended(ext.node_type);
+        } else if node_type != NodeLumpType::OGDoom {
+            panic!("{}: Could not load {:?} nodes", map_name, node_type);
+        }
         // The overall level information. You can rebuild a BSP from this.
         // A lot of what happens here is using the wad data to fill in
         // structures, and then creating (unsafe) internal pointers to everything
-        self.load_vertexes(map_name, wad, extended.as_ref());
-        self.load_sectors(map_name, wad, pic_data);
-        self.load_sidedefs(map_name, wad, &tex_order);
-        self.load_linedefs(map_name, wad);
+        if let Some(udmf) = udmf {
+            self.load_vertexes(map_name, udmf.vertexes.into_iter(), extended.as_ref());
+            self.load_sectors(map_name, udmf.sectors.into_iter(), pic_data);
+            self.load_sidedefs(map_name, udmf.sidedefs.into_iter(), &tex_order);
+            self.load_linedefs(map_name, udmf.linedefs.into_iter());
+        } else {
+            self.load_vertexes(map_name, wad.vertex_iter(map_name), extended.as_ref());
+            self.load_sectors(map_name, wad.sector_iter(map_name), pic_data);
+            self.load_sidedefs(map_name, wad.sidedef_iter(map_name), &tex_order);
+            self.load_linedefs(map_name, wad.linedef_iter(map_name));
+        }
         self.load_blockmap(map_name, wad);
         self.load_devils_rejects(map_name, wad);
         // TODO: iterate sector lines to find max bounding box for sector
@@ -198,11 +240,13 @@ impl MapData {
         self.fix_vertices();
     }
 
-    fn load_vertexes(&mut self, map_name: &str, wad: &WadData, extended: Option<&WadExtendedMap>) {
-        self.vertexes = wad
-            .vertex_iter(map_name)
-            .map(|v| VecF2::new(v.x, v.y))
-            .collect();
+    fn load_vertexes(
+        &mut self,
+        map_name: &str,
+        vertexes: impl Iterator<Item = WadVertex>,
+        extended: Option<&WadExtendedMap>,
+    ) {
+        self.vertexes = vertexes.map(|v| VecF2::new(v.x, v.y)).collect();
         info!("{}: Loaded {} vertexes", map_name, self.vertexes.len());
 
         if let Some(ext) = extended.as_ref() {
@@ -214,9 +258,13 @@ impl MapData {
         }
     }
 
-    fn load_sectors(&mut self, map_name: &str, wad: &WadData, pic_data: &PicData) {
-        self.sectors = wad
-            .sector_iter(map_name)
+    fn load_sectors(
+        &mut self,
+        map_name: &str,
+        sectors: impl Iterator<Item = WadSector>,
+        pic_data: &PicData,
+    ) {
+        self.sectors = sectors
             .enumerate()
             .map(|(i, s)| {
                 Sector::new(
@@ -242,13 +290,17 @@ impl MapData {
         info!("{}: Loaded {} sectors", map_name, self.sectors.len());
     }
 
-    fn load_sidedefs(&mut self, map_name: &str, wad: &WadData, tex_order: &[WadTexture]) {
+    fn load_sidedefs(
+        &mut self,
+        map_name: &str,
+        sidedefs: impl Iterator<Item = WadSideDef>,
+        tex_order: &[WadTexture],
+    ) {
         if self.sectors.is_empty() {
             panic!("sectors must be loaded before sidedefs");
         }
         // dbg!(tex_order.iter().position(|n| n.name == "METAL"));
-        self.sidedefs = wad
-            .sidedef_iter(map_name)
+        self.sidedefs = sidedefs
             .map(|s| {
                 let sector = &mut self.sectors[s.sector as usize];
                 SideDef {
@@ -270,15 +322,14 @@ impl MapData {
         info!("{}: Loaded {} sidedefs", map_name, self.sidedefs.len());
     }
 
-    fn load_linedefs(&mut self, map_name: &str, wad: &WadData) {
+    fn load_linedefs(&mut self, map_name: &str, linedefs: impl Iterator<Item = WadLineDef>) {
         if self.vertexes.is_empty() {
             panic!("Vertexes must be loaded before linedefs");
         }
         if self.sidedefs.is_empty() {
             panic!("sidedefs must be loaded before linedefs");
         }
-        self.linedefs = wad
-            .linedef_iter(map_name)
+        self.linedefs = linedefs
             .map(|l| {
                 let v1 = self.vertexes[l.start_vertex as usize];
                 let v2 = self.vertexes[l.end_vertex as usize];
@@ -329,6 +380,7 @@ impl MapData {
                     frontsector: front.sector.clone(),
                     backsector: back_sector,
                     valid_count: 0,
+                    translucent: false,
                     sides: l.sides,
                 }
             })
@@ -456,20 +508,34 @@ impl MapData {
 
     fn load_blockmap(&mut self, map_name: &str, wad: &WadData) {
         if let Some(wadblock) = wad.read_blockmap(map_name) {
+            let columns = wadblock.columns as u16 as usize;
+            let rows = wadblock.rows as u16 as usize;
             let mut blockmap = Blockmap {
                 x_origin: fixed_t::from_i16(wadblock.x_origin),
                 y_origin: fixed_t::from_i16(wadblock.y_origin),
-                columns: wadblock.columns as usize,
-                rows: wadblock.rows as usize,
-                lines: Vec::with_capacity(wadblock.line_indexes.len()),
+                columns,
+                rows,
+                lines: Vec::with_capacity(columns * rows),
+                leading_zero: Vec::with_capacity(columns * rows),
+                things: vec![None; columns * rows],
             };
 
-            for l in wadblock.line_indexes {
-                if l != 0 && l != -1 {
-                    let linedef = MapPtr::new(&mut self.linedefs[l as usize]);
-                    blockmap.lines.push(linedef);
+            // Each block's list normally starts with a 0 and ends with -1. The
+            // 0 is kept as vanilla checks line 0 in every block, the iterator
+            // skips it when not using vanilla physics.
+            for start in wadblock.block_starts {
+                let list = &wadblock.line_indexes[start..];
+                let mut lines = Vec::new();
+                for l in list.iter().take_while(|l| **l != -1) {
+                    if let Some(linedef) = self.linedefs.get_mut(*l as u16 as usize) {
+                        lines.push(MapPtr::new(linedef));
+                    }
                 }
+                blockmap.lines.push(lines);
+                blockmap.leading_zero.push(list.first() == Some(&0));
             }
+            blockmap.lines.resize_with(columns * rows, Vec::new);
+            blockmap.leading_zero.resize(columns * rows, false);
 
             info!(
                 "{}: Loaded blockmap, {} blocks",
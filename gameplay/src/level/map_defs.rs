@@ -1,5 +1,6 @@
 use crate::MapPtr;
-use crate::thing::MapObject;
+use crate::level::flags::SectorFlags;
+use crate::thing::{FRICTION, FRICTION_FACTOR, MapObject};
 use crate::thinker::{Thinker, ThinkerData};
 use log::error;
 use math::{Angle, FT_ZERO, point_on_side};
@@ -47,6 +48,11 @@ pub struct Sector {
 
     // thing that made a sound (or null)
     sound_target: Option<*mut Thinker>,
+
+    /// BOOM friction, only used if the generalized friction bit is set
+    pub friction: fixed_t,
+    /// BOOM scale applied to player thrust, paired with `friction`
+    pub move_factor: i32,
 }
 
 impl std::fmt::Debug for Sector {
@@ -82,10 +88,21 @@ impl Sector {
             lightlevel,
             special,
             tag,
+            friction: FRICTION,
+            move_factor: FRICTION_FACTOR,
             ..Self::default()
         }
     }
 
+    /// The friction and thrust factor for things on the floor of this sector
+    pub fn floor_friction(&self) -> (fixed_t, i32) {
+        if self.special & SectorFlags::Friction as i16 != 0 {
+            (self.friction, self.move_factor)
+        } else {
+            (FRICTION, FRICTION_FACTOR)
+        }
+    }
+
     /// Returns false if `func` returns false
     pub fn run_mut_func_on_thinglist(
         &mut self,
@@ -307,6 +324,8 @@ pub struct LineDef {
 
     // if == validcount, already checked
     pub valid_count: usize,
+    /// BOOM translucent middle texture, set by line special 260
+    pub translucent: bool,
     // thinker_t for reversable actions
     // TODO: void*	specialdata: Option<MapPtr<Thinker>>,
 }
@@ -455,18 +474,163 @@ pub struct Node {
     pub children: [u32; 2],
 }
 
+/// Shift to convert a fixed point map coordinate to a 128 unit block
+pub const MAPBLOCKSHIFT: usize = 16 + 7;
+
+/// The map divided in to 128x128 unit blocks. Each block has a list of the
+/// lines that touch it, and the things with their centre in it, so collision
+/// checks only need to look at the blocks around a point.
 #[derive(Default)]
 pub struct Blockmap {
     pub x_origin: fixed_t,
     pub y_origin: fixed_t,
     pub columns: usize,
     pub rows: usize,
-    pub lines: Vec<MapPtr<LineDef>>,
+    /// The lines in each block, indexed by `row * columns + column`
+    pub lines: Vec<Vec<MapPtr<LineDef>>>,
+    /// If the block's list starts with the 0 marker, which is also line 0
+    pub leading_zero: Vec<bool>,
+    /// The first thing in each block, the rest are linked through `b_next`
+    pub things: Vec<Option<*mut Thinker>>,
+}
+
+impl Blockmap {
+    /// A map with no `BLOCKMAP` lump
+    pub fn is_empty(&self) -> bool {
+        self.columns == 0 || self.rows == 0
+    }
+
+    /// The column of the block containing `x`, which may be outside the map
+    pub fn block_x(&self, x: fixed_t) -> i32 {
+        (x - self.x_origin).0 >> MAPBLOCKSHIFT
+    }
+
+    /// The row of the block containing `y`, which may be outside the map
+    pub fn block_y(&self, y: fixed_t) -> i32 {
+        (y - self.y_origin).0 >> MAPBLOCKSHIFT
+    }
+
+    fn block_index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x as usize >= self.columns || y as usize >= self.rows {
+            return None;
+        }
+        Some(y as usize * self.columns + x as usize)
+    }
+
+    /// Run `func` on each line in the block that hasn't been checked yet
+    /// during `valid_count`. Returns false if `func` returns false.
+    ///
+    /// The leading 0 of each list is only checked as line 0 when `vanilla` is
+    /// set, as the original does and demos depend on.
+    ///
+    /// Doom function name `P_BlockLinesIterator`
+    pub fn lines_iterator(
+        &mut self,
+        x: i32,
+        y: i32,
+        valid_count: usize,
+        vanilla: bool,
+        mut func: impl FnMut(&mut LineDef) -> bool,
+    ) -> bool {
+        let Some(index) = self.block_index(x, y) else {
+            return true;
+        };
+        let skip = !vanilla && self.leading_zero[index];
+        for line in self.lines[index].iter_mut().skip(skip as usize) {
+            if line.valid_count == valid_count {
+                continue; // line has already been checked
+            }
+            line.valid_count = valid_count;
+            if !func(line.as_mut()) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Run `func` on each thing in the block. Returns false if `func` returns
+    /// false.
+    ///
+    /// Doom function name `P_BlockThingsIterator`
+    pub fn things_iterator(
+        &mut self,
+        x: i32,
+        y: i32,
+        mut func: impl FnMut(&mut MapObject) -> bool,
+    ) -> bool {
+        let Some(index) = self.block_index(x, y) else {
+            return true;
+        };
+        let mut next = self.things[index];
+        while let Some(thing) = next {
+            #[cfg(feature = "null_check")]
+            if thing.is_null() {
+                std::panic!("blockmap thing is null when it shouldn't be");
+            }
+            unsafe {
+                // Thing might remove itself so grab a copy of b_next here
+                next = (*thing).mobj().b_next;
+                if (*thing).should_remove() {
+                    continue;
+                }
+                if !func((*thing).mobj_mut()) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Link the thing in to the block its centre is in. Things outside the
+    /// map aren't in any block.
+    ///
+    /// # Safety
+    /// The `Thinker` pointer *must* be valid and hold a `MapObject`
+    pub unsafe fn add_to_thinglist(&mut self, thing: *mut Thinker) {
+        let mobj = unsafe { &mut *thing }.mobj_mut();
+        let index = self.block_index(self.block_x(mobj.xy.x), self.block_y(mobj.xy.y));
+        mobj.block = index;
+        mobj.b_prev = None;
+        mobj.b_next = None;
+        let Some(index) = index else {
+            return;
+        };
+        mobj.b_next = self.things[index];
+        if let Some(other) = self.things[index] {
+            unsafe { &mut *other }.mobj_mut().b_prev = Some(thing);
+        }
+        self.things[index] = Some(thing);
+    }
+
+    /// Empty every block's thing list, such as when the thinkers are replaced
+    pub fn clear_things(&mut self) {
+        self.things.fill(None);
+    }
+
+    /// Unlink the thing from the block it was linked in to
+    pub fn remove_from_thinglist(&mut self, thing: &mut Thinker) {
+        let mobj = thing.mobj_mut();
+        let Some(index) = mobj.block.take() else {
+            return;
+        };
+        if let Some(next) = mobj.b_next {
+            unsafe { &mut *next }.mobj_mut().b_prev = mobj.b_prev;
+        }
+        if let Some(prev) = mobj.b_prev {
+            unsafe { &mut *prev }.mobj_mut().b_next = mobj.b_next;
+        } else {
+            self.things[index] = mobj.b_next;
+        }
+        mobj.b_next = None;
+        mobj.b_prev = None;
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Blockmap;
     use glam::Vec2;
+    use math::fixed_t;
 
     fn point_on_side(v1: Vec2, v2: Vec2, v: Vec2) -> usize {
         let r = (v2.x - v1.x) * (v.y - v1.y) - (v2.y - v1.y) * (v.x - v1.x);
@@ -492,4 +656,27 @@ mod tests {
         let r = point_on_side(v1, v2, v);
         assert_eq!(r, 0);
     }
+
+    #[test]
+    fn blockmap_blocks() {
+        let blockmap = Blockmap {
+            x_origin: fixed_t::from_int(-768),
+            y_origin: fixed_t::from_int(-4864),
+            columns: 36,
+            rows: 23,
+            lines: vec![Vec::new(); 36 * 23],
+            leading_zero: vec![true; 36 * 23],
+            things: vec![None; 36 * 23],
+        };
+        assert!(!blockmap.is_empty());
+        assert_eq!(blockmap.block_x(fixed_t::from_int(-768)), 0);
+        assert_eq!(blockmap.block_x(fixed_t::from_int(-641)), 0);
+        assert_eq!(blockmap.block_x(fixed_t::from_int(-640)), 1);
+        assert_eq!(blockmap.block_x(fixed_t::from_int(-769)), -1);
+        assert_eq!(blockmap.block_y(fixed_t::from_int(-4864 + 128 * 22)), 22);
+        assert_eq!(blockmap.block_index(35, 22), Some(36 * 23 - 1));
+        assert_eq!(blockmap.block_index(36, 0), None);
+        assert_eq!(blockmap.block_index(0, -1), None);
+        assert!(Blockmap::default().is_empty());
+    }
 }
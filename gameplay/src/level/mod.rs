@@ -41,7 +41,6 @@ pub struct Level {
 
     pub options: GameOptions,
 
-    pub level_timer: bool,
     /// Time spent in level
     pub level_time: u32,
 
@@ -69,6 +68,8 @@ pub struct Level {
     pub animations: Vec<PicAnimation>,
     /// List of switch textures in ordered pairs
     pub switch_list: Vec<usize>,
+    /// Height of each wall texture, for moves relative to texture size
+    pub texture_heights: Vec<fixed_t>,
 
     /// Tracks which players are currently active, set by d_net.c loop.
     /// This is a raw pointer to the array in `Game`, and must not be modified
@@ -123,7 +124,6 @@ impl Level {
             options,
             respawn_queue: VecDeque::with_capacity(MAX_RESPAWNS),
             level_time: 0,
-            level_timer: false,
             player_starts: [None; MAXPLAYERS],
             deathmatch_starts: [None; MAX_DEATHMATCH_STARTS],
             deathmatch_p: Vec::with_capacity(MAX_DEATHMATCH_STARTS),
@@ -135,6 +135,7 @@ impl Level {
             valid_count: 0,
             switch_list: Default::default(),
             animations: Default::default(),
+            texture_heights: Default::default(),
             button_list: Vec::with_capacity(50),
             line_special_list: Vec::with_capacity(50),
             game_mode,
@@ -214,6 +215,9 @@ impl Level {
         self.map_data.load(map_name, pic_data, wad_data);
         self.animations = animations;
         self.switch_list = switch_list;
+        self.texture_heights = (0..pic_data.num_textures())
+            .map(|t| fixed_t::from_int(pic_data.wall_pic_height(t) as i32))
+            .collect();
         unsafe {
             self.thinkers = ThinkerAlloc::new(self.map_data.things().len() * 2);
         }
@@ -12,11 +12,13 @@
 use std::f32::consts::TAU;
 use std::fmt::{self, Debug};
 use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
 
 #[cfg(feature = "null_check")]
 use std::panic;
 use std::ptr::null_mut;
 
+mod dehacked;
 mod doom_def;
 pub(crate) mod env;
 #[rustfmt::skip]
@@ -26,29 +28,33 @@ mod level;
 mod pic;
 mod player;
 mod player_sprite;
+mod save;
 mod thing;
 mod thinker;
 pub mod tic_cmd;
 pub(crate) mod utilities;
 
+pub use dehacked::{Dehacked, deh_bex_string, deh_cheat, deh_string};
 pub use doom_def::{
-    AmmoType, Card, DOOM_VERSION, GameAction, GameMission, GameMode, MAXPLAYERS, PowerType,
-    TICRATE, WEAPON_INFO, WeaponType,
+    AmmoType, CPARS, Card, DOOM_VERSION, GameAction, GameMission, GameMode, MAXPLAYERS, PARS,
+    PowerType, TICRATE, WEAPON_INFO, WeaponType,
 };
 pub use env::specials::{respawn_specials, spawn_specials, update_specials};
 pub use env::teleport::teleport_move;
-pub use info::{MapObjKind, STATES, StateNum};
+pub use info::{MOBJINFO, MapObjInfo, MapObjKind, STATES, StateNum};
 pub use lang::english;
 pub use level::Level;
 pub use level::flags::LineDefFlags;
 pub use level::map_data::MapData;
 pub use level::map_defs::{Node, Sector, Segment, SubSector};
 pub use math::{Angle, m_clear_random, m_random, p_random, point_to_angle_2};
-pub use pic::{FlatPic, PicAnimation, PicData, Switches, WallPic};
+pub use pic::{FlatPic, PicAnimation, PicData, SpritePic, Switches, WallPic};
 pub use player::{Player, PlayerCheat, PlayerState, PlayerStatus, WorldEndPlayerInfo};
 pub use player_sprite::PspDef;
+pub use save::{LevelArchive, SAVE_END_MARKER, SAVE_VERSION, SaveError, SaveReader, SaveWriter};
 use std::error::Error;
 use std::str::FromStr;
+pub use thing::enemy::set_fast_monsters;
 pub use thing::{MapObjFlag, MapObject};
 // re-export
 pub use {glam, log};
@@ -77,7 +83,13 @@ pub struct GameOptions {
     pub respawn_parm: bool,
     pub fast_parm: bool,
     pub dev_parm: bool,
+    /// 0 = cooperative, 1 = deathmatch with weapons staying in place, 2 =
+    /// altdeath where items are picked up and respawn
     pub deathmatch: u8,
+    /// Deathmatch levels end after this many minutes
+    pub time_limit: Option<i32>,
+    /// Deathmatch levels end when a player has this many frags
+    pub frag_limit: Option<i32>,
     pub warp: bool,
     pub skill: Skill,
     pub episode: usize,
@@ -89,6 +101,26 @@ pub struct GameOptions {
     pub enable_demos: bool,
     /// only true if packets are broadcast
     pub netgame: bool,
+    /// Where savegames are written to and loaded from
+    pub save_dir: PathBuf,
+    /// Dehacked or BEX patch files applied after the wads are loaded
+    pub deh: Vec<String>,
+    /// Draw things flagged `Translucent` with the translucency table
+    pub translucency: bool,
+    /// Use the original collision and movement rules instead of the more
+    /// forgiving ones. Demo playback always turns this on.
+    pub vanilla_physics: bool,
+    /// Let pain elementals spit out any number of lost souls, as BOOM does.
+    /// Demo playback always turns this off.
+    pub no_skull_limit: bool,
+}
+
+impl GameOptions {
+    /// Monsters attack more often and some are faster, in nightmare or with
+    /// `-fast`
+    pub fn fast_monsters(&self) -> bool {
+        self.fast_parm || self.skill == Skill::Nightmare
+    }
 }
 
 impl Default for GameOptions {
@@ -101,6 +133,8 @@ impl Default for GameOptions {
             fast_parm: Default::default(),
             dev_parm: Default::default(),
             deathmatch: Default::default(),
+            time_limit: None,
+            frag_limit: None,
             skill: Default::default(),
             episode: Default::default(),
             map: Default::default(),
@@ -111,6 +145,11 @@ impl Default for GameOptions {
             verbose: log::LevelFilter::Info,
             enable_demos: false,
             netgame: false,
+            save_dir: PathBuf::from("."),
+            deh: Vec::new(),
+            translucency: false,
+            vanilla_physics: false,
+            no_skull_limit: false,
         }
     }
 }
@@ -1,4 +1,5 @@
 use log::info;
+use wad::types::WadAnimation;
 
 use crate::PicData;
 #[derive(Debug, Default)]
@@ -28,21 +29,22 @@ impl PicAnimation {
 
     /// Doom function name `P_InitPicAnims`
     pub fn init(pic_data: &PicData) -> Vec<PicAnimation> {
-        let mut anims = Vec::with_capacity(ANIM_DEFS.len());
+        let defs = pic_data.animation_defs();
+        let mut anims = Vec::with_capacity(defs.len());
 
-        for def in ANIM_DEFS {
+        for def in defs {
             let mut animation = PicAnimation::default();
             if def.is_texture {
-                if let Some(start_num) = pic_data.wallpic_num_for_name(def.start_name) {
-                    if let Some(end_num) = pic_data.wallpic_num_for_name(def.end_name) {
+                if let Some(start_num) = pic_data.wallpic_num_for_name(&def.start_name) {
+                    if let Some(end_num) = pic_data.wallpic_num_for_name(&def.end_name) {
                         animation.picnum = end_num;
                         animation.basepic = start_num;
                     }
                 } else {
                     continue;
                 }
-            } else if let Some(start_num) = pic_data.flat_num_for_name(def.start_name) {
-                if let Some(end_num) = pic_data.flat_num_for_name(def.end_name) {
+            } else if let Some(start_num) = pic_data.flat_num_for_name(&def.start_name) {
+                if let Some(end_num) = pic_data.flat_num_for_name(&def.end_name) {
                     animation.picnum = end_num;
                     animation.basepic = start_num;
                 }
@@ -69,7 +71,15 @@ impl PicAnimation {
     }
 }
 
-pub struct AnimationDef {
+/// The vanilla animations, used if no wad has an `ANIMATED` lump
+pub(super) fn builtin_animations() -> Vec<WadAnimation> {
+    ANIM_DEFS
+        .iter()
+        .map(|d| WadAnimation::new(d.is_texture, d.end_name, d.start_name, d.speed))
+        .collect()
+}
+
+struct AnimationDef {
     is_texture: bool,
     end_name: &'static str,
     start_name: &'static str,
@@ -16,7 +16,7 @@ use std::mem::{size_of, size_of_val};
 
 use log::{debug, warn};
 use wad::WadData;
-use wad::types::{WadColour, WadPalette, WadPatch, WadTexture};
+use wad::types::{WadAnimation, WadColour, WadPalette, WadPatch, WadSwitch, WadTexture};
 
 use crate::Player;
 use crate::doom_def::{GameMode, PowerType};
@@ -48,7 +48,7 @@ pub struct WallPic {
     pub data: Vec<Vec<usize>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SpritePic {
     pub name: String,
     pub left_offset: i32,
@@ -89,6 +89,15 @@ pub struct PicData {
     /// like take-damage.
     use_pallette: usize,
     double_res: bool,
+    /// Animated flats and textures, from `ANIMATED` or the vanilla list
+    animation_defs: Vec<WadAnimation>,
+    /// Switch texture pairs, from `SWITCHES` or the vanilla list
+    switch_defs: Vec<WadSwitch>,
+    /// BOOM translucency table indexed by `background << 8 | foreground`,
+    /// from `TRANMAP` or generated from the palette
+    tranmap: Vec<u8>,
+    /// Draw things flagged `Translucent` through the translucency table
+    translucent_missiles: bool,
 }
 
 impl Default for PicData {
@@ -109,6 +118,10 @@ impl Default for PicData {
             sprite_defs: Default::default(),
             use_pallette: Default::default(),
             double_res: Default::default(),
+            animation_defs: Default::default(),
+            switch_defs: Default::default(),
+            tranmap: Default::default(),
+            translucent_missiles: Default::default(),
         }
     }
 }
@@ -162,7 +175,21 @@ impl PicData {
                 }
             })
             .collect();
-        let sprite_defs = init_spritedefs(&SPRNAMES, &sprite_patches);
+        let sprite_defs = init_spritedefs(&unsafe { SPRNAMES }, &sprite_patches);
+
+        let animation_defs = wad.read_animated().unwrap_or_else(|| {
+            debug!("No ANIMATED lump, using built-in animations");
+            builtin_animations()
+        });
+        let switch_defs = wad.read_switches().unwrap_or_else(|| {
+            debug!("No SWITCHES lump, using built-in switches");
+            builtin_switches()
+        });
+
+        let tranmap = wad.read_tranmap().unwrap_or_else(|| {
+            debug!("No TRANMAP lump, generating a 50% table");
+            Self::init_tranmap(&palettes[0])
+        });
 
         println!(".]");
 
@@ -182,6 +209,10 @@ impl PicData {
             sprite_defs,
             use_pallette: 0,
             double_res,
+            animation_defs,
+            switch_defs,
+            tranmap,
+            translucent_missiles: false,
         }
     }
 
@@ -194,6 +225,38 @@ impl PicData {
         tmp
     }
 
+    /// Build a 50% translucency table from the palette for when there is no
+    /// `TRANMAP` lump. Each blend is matched to the nearest palette colour.
+    ///
+    /// BOOM function name `R_InitTranMap`
+    fn init_tranmap(palette: &WadPalette) -> Vec<u8> {
+        print!(".");
+        let mut tranmap = vec![0u8; 256 * 256];
+        for bg in 0..256 {
+            // An even blend is the same either way around
+            for fg in 0..=bg {
+                let (a, b) = (palette.0[bg], palette.0[fg]);
+                let blend = [0, 1, 2].map(|c| (a[c] as i32 + b[c] as i32) / 2);
+
+                let mut best = 0;
+                let mut best_dist = i32::MAX;
+                for (i, colour) in palette.0.iter().enumerate() {
+                    let dist: i32 = (0..3).map(|c| (colour[c] as i32 - blend[c]).pow(2)).sum();
+                    if dist < best_dist {
+                        best = i;
+                        best_dist = dist;
+                        if dist == 0 {
+                            break;
+                        }
+                    }
+                }
+                tranmap[bg << 8 | fg] = best as u8;
+                tranmap[fg << 8 | bg] = best as u8;
+            }
+        }
+        tranmap
+    }
+
     fn init_colourmap(wad: &WadData) -> [Colourmap; COLOURMAP_LEN] {
         print!(".");
         let mut tmp = [[0; 256]; COLOURMAP_LEN];
@@ -510,6 +573,16 @@ impl PicData {
         }
     }
 
+    /// Use this texture for the sky instead of the default, e.g, from
+    /// UMAPINFO. Returns false if there is no texture by that name
+    pub fn set_sky_texture(&mut self, name: &str) -> bool {
+        if let Some(num) = self.wallpic_num_for_name(name) {
+            self.sky_pic = num;
+            return true;
+        }
+        false
+    }
+
     #[inline]
     pub fn colourmap(&self, index: usize) -> &[usize] {
         &self.colourmap[index]
@@ -606,6 +679,29 @@ impl PicData {
         }
     }
 
+    /// Blend the palette index `fg` over the palette index `bg` with the
+    /// translucency table, giving the palette index to draw.
+    #[inline]
+    pub fn translucent_index(&self, fg: usize, bg: usize) -> usize {
+        self.tranmap[bg << 8 | fg] as usize
+    }
+
+    pub const fn translucent_missiles(&self) -> bool {
+        self.translucent_missiles
+    }
+
+    pub const fn set_translucent_missiles(&mut self, translucent: bool) {
+        self.translucent_missiles = translucent;
+    }
+
+    pub fn animation_defs(&self) -> &[WadAnimation] {
+        &self.animation_defs
+    }
+
+    pub fn switch_defs(&self) -> &[WadSwitch] {
+        &self.switch_defs
+    }
+
     #[inline]
     pub fn wallpic_num_for_name(&self, name: &str) -> Option<usize> {
         for (i, tex) in self.walls.iter().enumerate() {
@@ -659,6 +755,14 @@ impl PicData {
         &texture.data[texture_column]
     }
 
+    /// Height in pixels of the texture, without animation translation
+    pub fn wall_pic_height(&self, texture: usize) -> usize {
+        self.walls[texture]
+            .data
+            .first()
+            .map_or(0, |column| column.len())
+    }
+
     #[inline]
     pub fn num_textures(&self) -> usize {
         self.walls.len()
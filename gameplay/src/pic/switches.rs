@@ -1,4 +1,5 @@
-use log::info;
+use log::{info, warn};
+use wad::types::WadSwitch;
 
 use crate::doom_def::GameMode;
 use crate::level::map_defs::LineDef;
@@ -23,11 +24,11 @@ pub struct Button {
 struct ButtonDef {
     name1: &'static str,
     name2: &'static str,
-    episode: i32,
+    episode: i16,
 }
 
 impl ButtonDef {
-    const fn new(name1: &'static str, name2: &'static str, episode: i32) -> Self {
+    const fn new(name1: &'static str, name2: &'static str, episode: i16) -> Self {
         ButtonDef {
             name1,
             name2,
@@ -83,6 +84,14 @@ const BUTTON_DEFS: [ButtonDef; 40] = [
     ButtonDef::new("SW1SKULL", "SW2SKULL", 3),
 ];
 
+/// The vanilla switches, used if no wad has a `SWITCHES` lump
+pub(super) fn builtin_switches() -> Vec<WadSwitch> {
+    BUTTON_DEFS
+        .iter()
+        .map(|d| WadSwitch::new(d.name1, d.name2, d.episode))
+        .collect()
+}
+
 pub struct Switches;
 
 impl Switches {
@@ -95,18 +104,23 @@ impl Switches {
         };
 
         let mut switch_list = Vec::new();
-        for def in BUTTON_DEFS {
-            if def.episode <= episode {
-                switch_list.push(
-                    pic_data
-                        .wallpic_num_for_name(def.name1)
-                        .unwrap_or_else(|| panic!("No texture for {}", def.name1)),
-                );
-                switch_list.push(
-                    pic_data
-                        .wallpic_num_for_name(def.name2)
-                        .unwrap_or_else(|| panic!("No texture for {}", def.name2)),
-                );
+        for def in pic_data.switch_defs() {
+            if def.episode > episode {
+                continue;
+            }
+            // The pair is kept together as a switch is flipped to its partner
+            match (
+                pic_data.wallpic_num_for_name(&def.name1),
+                pic_data.wallpic_num_for_name(&def.name2),
+            ) {
+                (Some(on), Some(off)) => {
+                    switch_list.push(on);
+                    switch_list.push(off);
+                }
+                _ => warn!(
+                    "No texture for switch {}/{}, skipping",
+                    def.name1, def.name2
+                ),
             }
         }
         info!("Initialised switch list");
@@ -10,12 +10,14 @@ use crate::doom_def::{
 };
 use crate::info::{STATES, SpriteNum, StateNum};
 use crate::level::Level;
+use crate::level::flags::{SECTOR_DAMAGE_SHIFT, SectorFlags};
+use crate::level::map_defs::Sector;
 use crate::pic::INVERSECOLORMAP;
 use crate::player_sprite::{PspDef, WEAPONBOTTOM};
 use crate::thing::enemy::noise_alert;
 use crate::thing::{BONUSADD, MapObjFlag, MapObject};
 use crate::tic_cmd::{LOOKDIRMAX, LOOKDIRMIN, TIC_CMD_BUTTONS, TicCmd};
-use crate::{GameMode, Skill};
+use crate::{GameMode, GameOptions, MapPtr, Skill};
 use math::{
     ANG5, ANG90, ANG180, Angle, FINEANGLES, FINEMASK, FT_FOUR, FT_FOURTH, FT_ONE, FT_TWO, FT_ZERO,
     VecF2, bam_to_radian, fixed_t, fixed_to_float, p_random, point_to_angle_2,
@@ -72,11 +74,26 @@ pub struct WorldEndPlayerInfo {
     pub secrets_found: i32,
     /// stime
     pub level_time: u32,
-    pub frags: [i32; 4],
+    pub frags: [i32; MAXPLAYERS],
     /// current score on entry, modified on return
     pub score: i32,
 }
 
+impl WorldEndPlayerInfo {
+    /// The frag total shown at the end of the level, see `Player::frag_count`
+    pub fn frag_count(&self, player_num: usize) -> i32 {
+        frag_total(&self.frags, player_num)
+    }
+}
+
+fn frag_total(frags: &[i32; MAXPLAYERS], player_num: usize) -> i32 {
+    frags
+        .iter()
+        .enumerate()
+        .map(|(i, frags)| if i == player_num { -frags } else { *frags })
+        .sum()
+}
+
 /// Contains the players current status such as attacking, loadout, health. This
 /// is also used by the statusbar to show the player what their current status
 /// is.
@@ -134,7 +151,7 @@ impl Default for PlayerStatus {
             cheats: 0,
         };
         tmp.ammo[AmmoType::Clip as usize] = 50;
-        tmp.maxammo.copy_from_slice(&MAX_AMMO);
+        tmp.maxammo = unsafe { MAX_AMMO };
         tmp.weaponowned[WeaponType::Fist as usize] = true;
         tmp.weaponowned[WeaponType::Pistol as usize] = true;
         tmp
@@ -143,7 +160,7 @@ impl Default for PlayerStatus {
 
 /// player_t
 pub struct Player {
-    mobj: Option<*mut MapObject>,
+    pub(crate) mobj: Option<*mut MapObject>,
     pub player_state: PlayerState,
     pub cmd: TicCmd,
 
@@ -277,6 +294,28 @@ impl Player {
         self.mobj = Some(mobj);
     }
 
+    /// The frags shown for this player, the kills of other players less any
+    /// suicides. `player_num` is this player's index in the game.
+    ///
+    /// Doom function name `ST_calcFrags`
+    pub fn frag_count(&self, player_num: usize) -> i32 {
+        frag_total(&self.frags, player_num)
+    }
+
+    /// Forget the player's `MapObject` without removing it, such as when the
+    /// level it was in has gone
+    pub(crate) const fn clear_mobj(&mut self) {
+        self.mobj = None;
+    }
+
+    /// Remove the player's `MapObject` from the level, used when a player
+    /// leaves a netgame
+    pub fn remove_mobj(&mut self) {
+        if let Some(mobj) = self.mobj.take() {
+            unsafe { (*mobj).remove() }
+        }
+    }
+
     /// Unchecked access to the raw `MapObject` pointer cast to ref
     ///
     /// # Safety
@@ -436,15 +475,19 @@ impl Player {
             }
 
             self.onground = mobj.z <= mobj.floorz;
+            let move_factor = mobj.subsector.sector.floor_friction().1;
 
             if self.cmd.forwardmove != 0 && self.onground {
                 let angle = mobj.angle;
-                self.thrust(angle, self.cmd.forwardmove as i32 * 2048);
+                self.thrust(angle, self.cmd.forwardmove as i32 * move_factor);
             }
 
             if self.cmd.sidemove != 0 && self.onground {
                 let angle = mobj.angle;
-                self.thrust(angle - Angle::new(ANG90), self.cmd.sidemove as i32 * 2048);
+                self.thrust(
+                    angle - Angle::new(ANG90),
+                    self.cmd.sidemove as i32 * move_factor,
+                );
             }
 
             if (self.cmd.forwardmove != 0 || self.cmd.sidemove != 0)
@@ -526,6 +569,11 @@ impl Player {
                 return;
             }
 
+            if sector.special >= 32 {
+                self.in_generalized_sector(mobj, &mut sector, level);
+                return;
+            }
+
             match sector.special {
                 // HELLSLIME DAMAGE
                 5 => {
@@ -575,6 +623,32 @@ impl Player {
         }
     }
 
+    /// BOOM generalized sector damage and secrets, the lighting bits are
+    /// handled by the light thinkers
+    fn in_generalized_sector(
+        &mut self,
+        mobj: &mut MapObject,
+        sector: &mut MapPtr<Sector>,
+        level: &mut Level,
+    ) {
+        let no_suit = self.status.powers[PowerType::IronFeet as usize] == 0;
+        let damage = match (sector.special & SectorFlags::Damage as i16) >> SECTOR_DAMAGE_SHIFT {
+            1 if no_suit => 5,
+            2 if no_suit => 10,
+            3 if no_suit || p_random() < 5 => 20,
+            _ => 0,
+        };
+        if damage != 0 && level.level_time & 0x1F == 0 {
+            debug!("Generalized sector damage: {damage}");
+            mobj.p_take_damage(None, None, false, damage);
+        }
+
+        if sector.special & SectorFlags::Secret as i16 != 0 {
+            self.secrets_found += 1;
+            sector.special &= !(SectorFlags::Secret as i16);
+        }
+    }
+
     pub(crate) fn give_ammo(&mut self, ammo: AmmoType, mut num: u32, skill: Skill) -> bool {
         if ammo == AmmoType::NoAmmo {
             return false;
@@ -589,9 +663,9 @@ impl Player {
         }
 
         if num != 0 {
-            num *= CLIP_AMMO[ammo as usize];
+            num *= unsafe { CLIP_AMMO[ammo as usize] };
         } else {
-            num = CLIP_AMMO[ammo as usize] / 2;
+            num = unsafe { CLIP_AMMO[ammo as usize] } / 2;
         }
 
         if skill == Skill::Baby || skill == Skill::Nightmare {
@@ -648,11 +722,31 @@ impl Player {
         true
     }
 
-    pub(crate) fn give_weapon(&mut self, weapon: WeaponType, dropped: bool, skill: Skill) -> bool {
+    pub(crate) fn give_weapon(
+        &mut self,
+        weapon: WeaponType,
+        dropped: bool,
+        options: &GameOptions,
+    ) -> bool {
+        let skill = options.skill;
         let mut gave_ammo = false;
         let mut gave_weapon = false;
-        // TODO: if (netgame && (deathmatch != 2) && !dropped) {
-        let ammo = WEAPON_INFO[weapon as usize].ammo;
+        let ammo = unsafe { WEAPON_INFO[weapon as usize].ammo };
+
+        if options.netgame && options.deathmatch != 2 && !dropped {
+            // leave placed weapons forever on net games
+            if self.status.weaponowned[weapon as usize] {
+                return false;
+            }
+            self.status.bonuscount += BONUSADD;
+            self.status.weaponowned[weapon as usize] = true;
+            let clips = if options.deathmatch != 0 { 5 } else { 2 };
+            self.give_ammo(ammo, clips, skill);
+            self.pendingweapon = weapon;
+            self.start_sound(SfxName::Wpnup);
+            return false;
+        }
+
         if ammo != AmmoType::NoAmmo {
             if dropped {
                 gave_ammo = self.give_ammo(ammo, 1, skill);
@@ -740,7 +834,7 @@ impl Player {
             return;
         }
 
-        let new_state = WEAPON_INFO[self.status.readyweapon as usize].atkstate;
+        let new_state = unsafe { WEAPON_INFO[self.status.readyweapon as usize].atkstate };
         self.set_psprite(PsprNum::Weapon as usize, new_state);
         if let Some(mobj) = self.mobj_mut() {
             noise_alert(mobj);
@@ -748,7 +842,7 @@ impl Player {
     }
 
     pub(crate) fn check_ammo(&mut self) -> bool {
-        let ammo = &WEAPON_INFO[self.status.readyweapon as usize].ammo;
+        let ammo = unsafe { WEAPON_INFO[self.status.readyweapon as usize].ammo };
         // Minimum for one shot varies with weapon
         let count = if self.status.readyweapon == WeaponType::BFG {
             BFGCELLS
@@ -759,7 +853,7 @@ impl Player {
         };
 
         // Punch and chainsaw don't need ammo.
-        if *ammo == AmmoType::NoAmmo || self.status.ammo[*ammo as usize] >= count {
+        if ammo == AmmoType::NoAmmo || self.status.ammo[ammo as usize] >= count {
             return true;
         }
 
@@ -805,10 +899,9 @@ impl Player {
             }
         }
 
-        self.set_psprite(
-            PsprNum::Weapon as usize,
-            WEAPON_INFO[self.status.readyweapon as usize].downstate,
-        );
+        self.set_psprite(PsprNum::Weapon as usize, unsafe {
+            WEAPON_INFO[self.status.readyweapon as usize].downstate
+        });
 
         false
     }
@@ -822,7 +915,7 @@ impl Player {
             // TODO: StartSound(player->mo, sfx_sawup);
         }
 
-        let new_state = WEAPON_INFO[self.pendingweapon as usize].upstate;
+        let new_state = unsafe { WEAPON_INFO[self.pendingweapon as usize].upstate };
         self.pendingweapon = WeaponType::NoChange;
         self.psprites[PsprNum::Weapon as usize].sy = WEAPONBOTTOM;
 
@@ -837,17 +930,17 @@ impl Player {
     }
 
     pub(crate) fn subtract_readyweapon_ammo(&mut self, num: u32) {
-        if self.status.ammo[WEAPON_INFO[self.status.readyweapon as usize].ammo as usize] != 0 {
-            self.status.ammo[WEAPON_INFO[self.status.readyweapon as usize].ammo as usize] -= num;
+        let ammo = unsafe { WEAPON_INFO[self.status.readyweapon as usize].ammo } as usize;
+        if self.status.ammo[ammo] != 0 {
+            self.status.ammo[ammo] -= num;
         }
     }
 
     /// P_DropWeapon
     pub(crate) fn drop_weapon(&mut self) {
-        self.set_psprite(
-            PsprNum::Weapon as usize,
-            WEAPON_INFO[self.status.readyweapon as usize].downstate,
-        );
+        self.set_psprite(PsprNum::Weapon as usize, unsafe {
+            WEAPON_INFO[self.status.readyweapon as usize].downstate
+        });
     }
 }
 
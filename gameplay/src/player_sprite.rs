@@ -72,7 +72,7 @@ pub(crate) fn a_weaponready(player: &mut Player, pspr: &mut PspDef) {
         // change weapon
         //  (pending weapon should allready be validated)
         if player.status.readyweapon != WeaponType::NoChange {
-            let new_state = WEAPON_INFO[player.status.readyweapon as usize].downstate;
+            let new_state = unsafe { WEAPON_INFO[player.status.readyweapon as usize].downstate };
             player.set_psprite(PsprNum::Weapon as usize, new_state);
         }
         return;
@@ -132,7 +132,7 @@ pub(crate) fn a_raise(player: &mut Player, pspr: &mut PspDef) {
     }
     pspr.sy = WEAPONTOP;
 
-    let new_state = WEAPON_INFO[player.status.readyweapon as usize].readystate;
+    let new_state = unsafe { WEAPON_INFO[player.status.readyweapon as usize].readystate };
     player.set_psprite(PsprNum::Weapon as usize, new_state);
 }
 
@@ -151,11 +151,11 @@ fn shoot_bullet(player: &mut Player) {
 
 pub(crate) fn a_firepistol(player: &mut Player, _pspr: &mut PspDef) {
     shoot_bullet(player);
-    player.status.ammo[WEAPON_INFO[player.status.readyweapon as usize].ammo as usize] -= 1;
-    player.set_psprite(
-        PsprNum::Flash as usize,
-        WEAPON_INFO[player.status.readyweapon as usize].flashstate,
-    );
+    let ammo = unsafe { WEAPON_INFO[player.status.readyweapon as usize].ammo } as usize;
+    player.status.ammo[ammo] -= 1;
+    player.set_psprite(PsprNum::Flash as usize, unsafe {
+        WEAPON_INFO[player.status.readyweapon as usize].flashstate
+    });
 }
 
 pub(crate) fn a_fireshotgun(player: &mut Player, _pspr: &mut PspDef) {
@@ -174,10 +174,9 @@ pub(crate) fn a_fireshotgun(player: &mut Player, _pspr: &mut PspDef) {
     }
 
     player.subtract_readyweapon_ammo(1);
-    player.set_psprite(
-        PsprNum::Flash as usize,
-        WEAPON_INFO[player.status.readyweapon as usize].flashstate,
-    );
+    player.set_psprite(PsprNum::Flash as usize, unsafe {
+        WEAPON_INFO[player.status.readyweapon as usize].flashstate
+    });
 }
 
 pub(crate) fn a_fireshotgun2(player: &mut Player, _pspr: &mut PspDef) {
@@ -205,10 +204,9 @@ pub(crate) fn a_fireshotgun2(player: &mut Player, _pspr: &mut PspDef) {
     }
 
     player.subtract_readyweapon_ammo(2);
-    player.set_psprite(
-        PsprNum::Flash as usize,
-        WEAPON_INFO[player.status.readyweapon as usize].flashstate,
-    );
+    player.set_psprite(PsprNum::Flash as usize, unsafe {
+        WEAPON_INFO[player.status.readyweapon as usize].flashstate
+    });
 }
 
 pub(crate) fn a_firecgun(player: &mut Player, pspr: &mut PspDef) {
@@ -217,7 +215,7 @@ pub(crate) fn a_firecgun(player: &mut Player, pspr: &mut PspDef) {
     }
     shoot_bullet(player);
     let state = StateNum::from(
-        WEAPON_INFO[player.status.readyweapon as usize].flashstate as u16
+        unsafe { WEAPON_INFO[player.status.readyweapon as usize].flashstate } as u16
             + pspr.state.unwrap().next_state as u16
             - StateNum::CHAIN1 as u16
             - 1,
@@ -230,7 +228,9 @@ pub(crate) fn a_firecgun(player: &mut Player, pspr: &mut PspDef) {
 pub(crate) fn a_fireplasma(player: &mut Player, _pspr: &mut PspDef) {
     player.subtract_readyweapon_ammo(1);
     let state = StateNum::from(
-        (WEAPON_INFO[player.status.readyweapon as usize].flashstate as u16 + p_random() as u16) & 1,
+        (unsafe { WEAPON_INFO[player.status.readyweapon as usize].flashstate } as u16
+            + p_random() as u16)
+            & 1,
     );
     player.set_psprite(PsprNum::Flash as usize, state);
     if let Some(mobj) = player.mobj_raw() {
@@ -311,10 +311,9 @@ pub(crate) fn a_bfgspray(player: &mut MapObject) {
 
 pub(crate) fn a_gunflash(player: &mut Player, _pspr: &mut PspDef) {
     player.set_mobj_state(StateNum::PLAY_ATK2);
-    player.set_psprite(
-        PsprNum::Flash as usize,
-        WEAPON_INFO[player.status.readyweapon as usize].flashstate,
-    );
+    player.set_psprite(PsprNum::Flash as usize, unsafe {
+        WEAPON_INFO[player.status.readyweapon as usize].flashstate
+    });
 }
 
 pub(crate) fn a_punch(player: &mut Player, _pspr: &mut PspDef) {
@@ -0,0 +1,1421 @@
+//! Archiving and restoring of the level state for savegames.
+//!
+//! The format is a flat little-endian byte stream. Unlike Doom, which dumps
+//! structs directly, each field is written explicitly so the layout doesn't
+//! depend on the in-memory representation. Pointers between objects are
+//! written as indexes in to the thinker list (or sector array) and are resolved
+//! back to pointers after all thinkers are re-created.
+//!
+//! Doom source name `p_saveg`
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::ptr::null_mut;
+
+use log::{debug, warn};
+use math::{Angle, VecF2, fixed_t};
+use wad::types::WadThing;
+
+use crate::MapPtr;
+use crate::doom_def::{MAXPLAYERS, WeaponType};
+use crate::env::ceiling::{CeilKind, CeilingMove};
+use crate::env::doors::{DoorKind, VerticalDoor};
+use crate::env::floor::{FloorKind, FloorMove};
+use crate::env::lights::{FireFlicker, Glow, LightFlash, StrobeFlash};
+use crate::env::platforms::{PlatKind, PlatStatus, Platform};
+use crate::info::{MOBJINFO, MapObjKind, STATES, State, StateNum};
+use crate::level::Level;
+use crate::level::map_data::MapData;
+use crate::player::{Player, PlayerState};
+use crate::thing::{MapObject, MoveDir};
+use crate::thinker::{Think, Thinker, ThinkerAlloc, ThinkerData};
+
+/// Bump this whenever the layout of the archive changes
+pub const SAVE_VERSION: u32 = 2;
+/// Written at the end of a complete archive
+pub const SAVE_END_MARKER: u8 = 0x1d;
+
+const TC_MOBJ: u8 = 1;
+const TC_DOOR: u8 = 2;
+const TC_FLOOR: u8 = 3;
+const TC_CEILING: u8 = 4;
+const TC_PLATFORM: u8 = 5;
+const TC_FLASH: u8 = 6;
+const TC_STROBE: u8 = 7;
+const TC_FLICKER: u8 = 8;
+const TC_GLOW: u8 = 9;
+
+const DOOR_KINDS: [DoorKind; 8] = [
+    DoorKind::Normal,
+    DoorKind::Close30ThenOpen,
+    DoorKind::Close,
+    DoorKind::Open,
+    DoorKind::RaiseIn5Mins,
+    DoorKind::BlazeRaise,
+    DoorKind::BlazeOpen,
+    DoorKind::BlazeClose,
+];
+
+const FLOOR_KINDS: [FloorKind; 15] = [
+    FloorKind::LowerFloor,
+    FloorKind::LowerFloorToLowest,
+    FloorKind::TurboLower,
+    FloorKind::RaiseFloor,
+    FloorKind::RaiseFloorToNearest,
+    FloorKind::RaiseToTexture,
+    FloorKind::LowerAndChange,
+    FloorKind::RaiseFloor24,
+    FloorKind::RaiseFloor24andChange,
+    FloorKind::RaiseFloorCrush,
+    FloorKind::RaiseFloorTurbo,
+    FloorKind::DonutRaise,
+    FloorKind::RaiseFloor512,
+    FloorKind::Generalized,
+    FloorKind::GenChange,
+];
+
+const CEIL_KINDS: [CeilKind; 10] = [
+    CeilKind::LowerToFloor,
+    CeilKind::RaiseToHighest,
+    CeilKind::LowerAndCrush,
+    CeilKind::CrushAndRaise,
+    CeilKind::FastCrushAndRaise,
+    CeilKind::SilentCrushAndRaise,
+    CeilKind::Generalized,
+    CeilKind::GenChange,
+    CeilKind::GenCrusher,
+    CeilKind::GenSilentCrusher,
+];
+
+const PLAT_KINDS: [PlatKind; 5] = [
+    PlatKind::PerpetualRaise,
+    PlatKind::DownWaitUpStay,
+    PlatKind::RaiseAndChange,
+    PlatKind::RaiseToNearestAndChange,
+    PlatKind::BlazeDWUS,
+];
+
+const PLAT_STATUS: [PlatStatus; 4] = [
+    PlatStatus::Up,
+    PlatStatus::Down,
+    PlatStatus::Waiting,
+    PlatStatus::InStasis,
+];
+
+#[derive(Debug)]
+pub enum SaveError {
+    /// The archive ended before all data was read
+    UnexpectedEnd,
+    /// The archive was written by an incompatible version
+    Version(u32),
+    /// Data in the archive doesn't fit the loaded level or game
+    Invalid(String),
+}
+
+impl Error for SaveError {}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::UnexpectedEnd => write!(f, "savegame ended unexpectedly"),
+            SaveError::Version(v) => write!(
+                f,
+                "savegame version {} is not supported (expected {})",
+                v, SAVE_VERSION
+            ),
+            SaveError::Invalid(m) => write!(f, "savegame is invalid: {}", m),
+        }
+    }
+}
+
+/// Accumulates an archive in memory. The owner writes it to disk.
+#[derive(Default)]
+pub struct SaveWriter {
+    buf: Vec<u8>,
+}
+
+impl SaveWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.buf
+    }
+
+    pub fn into_data(self) -> Vec<u8> {
+        self.buf
+    }
+
+    pub fn write_u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    pub fn write_bool(&mut self, v: bool) {
+        self.buf.push(v as u8);
+    }
+
+    pub fn write_i16(&mut self, v: i16) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_u16(&mut self, v: u16) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_i32(&mut self, v: i32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, v: u32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_fixed(&mut self, v: fixed_t) {
+        self.write_i32(v.0);
+    }
+
+    pub fn write_vec(&mut self, v: VecF2) {
+        self.write_fixed(v.x);
+        self.write_fixed(v.y);
+    }
+
+    pub fn write_angle(&mut self, v: Angle) {
+        self.write_u32(v.0);
+    }
+
+    /// Write a string padded or truncated to exactly `len` bytes
+    pub fn write_str(&mut self, s: &str, len: usize) {
+        let bytes = s.as_bytes();
+        for i in 0..len {
+            self.buf.push(bytes.get(i).copied().unwrap_or(0));
+        }
+    }
+
+    pub fn write_wad_thing(&mut self, t: &WadThing) {
+        self.write_i16(t.x);
+        self.write_i16(t.y);
+        self.write_i16(t.angle);
+        self.write_i16(t.kind);
+        self.write_i16(t.flags);
+    }
+}
+
+/// Reads back an archive written with `SaveWriter`
+pub struct SaveReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SaveReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], SaveError> {
+        let end = self.pos + N;
+        if end > self.data.len() {
+            return Err(SaveError::UnexpectedEnd);
+        }
+        let mut out = [0u8; N];
+        out.copy_from_slice(&self.data[self.pos..end]);
+        self.pos = end;
+        Ok(out)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, SaveError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, SaveError> {
+        Ok(self.read_u8()? != 0)
+    }
+
+    pub fn read_i16(&mut self) -> Result<i16, SaveError> {
+        Ok(i16::from_le_bytes(self.take()?))
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, SaveError> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, SaveError> {
+        Ok(i32::from_le_bytes(self.take()?))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, SaveError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    pub fn read_fixed(&mut self) -> Result<fixed_t, SaveError> {
+        Ok(fixed_t(self.read_i32()?))
+    }
+
+    pub fn read_vec(&mut self) -> Result<VecF2, SaveError> {
+        Ok(VecF2::new(self.read_fixed()?, self.read_fixed()?))
+    }
+
+    pub fn read_angle(&mut self) -> Result<Angle, SaveError> {
+        Ok(Angle::new(self.read_u32()?))
+    }
+
+    /// Read a fixed length string, trailing zeroes are dropped
+    pub fn read_str(&mut self, len: usize) -> Result<String, SaveError> {
+        let end = self.pos + len;
+        if end > self.data.len() {
+            return Err(SaveError::UnexpectedEnd);
+        }
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        let used = bytes.iter().position(|b| *b == 0).unwrap_or(len);
+        Ok(String::from_utf8_lossy(&bytes[..used]).to_string())
+    }
+
+    pub fn read_wad_thing(&mut self) -> Result<WadThing, SaveError> {
+        Ok(WadThing::new(
+            self.read_i16()?,
+            self.read_i16()?,
+            self.read_i16()?,
+            self.read_i16()?,
+            self.read_i16()?,
+        ))
+    }
+
+    fn read_index<T: Copy>(&mut self, table: &[T], what: &str) -> Result<T, SaveError> {
+        let i = self.read_u8()? as usize;
+        table
+            .get(i)
+            .copied()
+            .ok_or_else(|| SaveError::Invalid(format!("{} {} out of range", what, i)))
+    }
+}
+
+/// Index of a `State` in `STATES`, used in place of the `&'static State`
+fn state_index(state: &State) -> i32 {
+    unsafe { (state as *const State).offset_from(&raw const STATES as *const State) as i32 }
+}
+
+fn state_from_index(index: i32) -> Result<&'static State, SaveError> {
+    if index < 0 || index >= StateNum::Count as i32 {
+        return Err(SaveError::Invalid(format!("state {} out of range", index)));
+    }
+    Ok(unsafe { &*(&raw const STATES as *const State).add(index as usize) })
+}
+
+fn player_state_from(v: u8) -> Result<PlayerState, SaveError> {
+    match v {
+        0 => Ok(PlayerState::Live),
+        1 => Ok(PlayerState::Dead),
+        2 => Ok(PlayerState::Reborn),
+        _ => Err(SaveError::Invalid(format!("player state {}", v))),
+    }
+}
+
+fn weapon_from(v: u8) -> Result<WeaponType, SaveError> {
+    if v == WeaponType::NoChange as u8 {
+        Ok(WeaponType::NoChange)
+    } else if v < WeaponType::NumWeapons as u8 {
+        Ok(WeaponType::from(v))
+    } else {
+        Err(SaveError::Invalid(format!("weapon {}", v)))
+    }
+}
+
+impl Level {
+    /// Archive the complete level state. Players are archived with the level
+    /// as their `MapObject` is owned by it.
+    ///
+    /// Doom functions `P_ArchivePlayers`, `P_ArchiveWorld`,
+    /// `P_ArchiveThinkers`, `P_ArchiveSpecials`
+    pub fn archive(&mut self, out: &mut SaveWriter) {
+        out.write_u32(self.level_time);
+        out.write_i32(self.total_level_kills);
+        out.write_i32(self.total_level_items);
+        out.write_i32(self.total_level_secrets);
+
+        out.write_u32(self.respawn_queue.len() as u32);
+        for (time, thing) in self.respawn_queue.iter() {
+            out.write_u32(*time);
+            out.write_wad_thing(thing);
+        }
+
+        self.archive_world(out);
+
+        // Every live thinker gets an index which is used in place of a pointer
+        let mut thinkers = Vec::with_capacity(self.thinkers.len());
+        self.thinkers.run_fn_on_things(|t| {
+            if !matches!(t.data(), ThinkerData::TestObject(_)) {
+                thinkers.push(t as *mut Thinker);
+            }
+            true
+        });
+        let index: HashMap<*mut Thinker, i32> = thinkers
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (*t, i as i32))
+            .collect();
+        let idx = |t: Option<*mut Thinker>| -> i32 {
+            t.and_then(|t| index.get(&t).copied()).unwrap_or(-1)
+        };
+
+        out.write_u32(thinkers.len() as u32);
+        for thinker in thinkers.iter() {
+            let thinker = unsafe { &**thinker };
+            let owns_special = |sector: &MapPtr<crate::level::map_defs::Sector>| {
+                sector.specialdata.is_some_and(|s| std::ptr::eq(s, thinker))
+            };
+            match thinker.data() {
+                ThinkerData::MapObject(mobj) => {
+                    out.write_u8(TC_MOBJ);
+                    self.archive_mobj(mobj, &idx, out);
+                }
+                ThinkerData::VerticalDoor(door) => {
+                    out.write_u8(TC_DOOR);
+                    out.write_u32(door.sector.num as u32);
+                    out.write_bool(owns_special(&door.sector));
+                    out.write_u8(door.kind as u8);
+                    out.write_fixed(door.topheight);
+                    out.write_fixed(door.speed);
+                    out.write_i32(door.direction);
+                    out.write_i32(door.topwait);
+                    out.write_i32(door.topcountdown);
+                }
+                ThinkerData::FloorMove(floor) => {
+                    out.write_u8(TC_FLOOR);
+                    out.write_u32(floor.sector.num as u32);
+                    out.write_bool(owns_special(&floor.sector));
+                    out.write_u8(floor.kind as u8);
+                    out.write_fixed(floor.speed);
+                    out.write_bool(floor.crush);
+                    out.write_i32(floor.direction);
+                    out.write_i16(floor.newspecial);
+                    out.write_u32(floor.texture as u32);
+                    out.write_fixed(floor.destheight);
+                }
+                ThinkerData::CeilingMove(ceiling) => {
+                    out.write_u8(TC_CEILING);
+                    out.write_u32(ceiling.sector.num as u32);
+                    out.write_bool(owns_special(&ceiling.sector));
+                    out.write_u8(ceiling.kind as u8);
+                    out.write_fixed(ceiling.bottomheight);
+                    out.write_fixed(ceiling.topheight);
+                    out.write_fixed(ceiling.speed);
+                    out.write_bool(ceiling.crush);
+                    out.write_i32(ceiling.direction);
+                    out.write_i16(ceiling.tag);
+                    out.write_i32(ceiling.olddirection);
+                    out.write_fixed(ceiling.oldspeed);
+                    out.write_u32(ceiling.texture as u32);
+                    out.write_i16(ceiling.newspecial);
+                }
+                ThinkerData::Platform(plat) => {
+                    out.write_u8(TC_PLATFORM);
+                    out.write_u32(plat.sector.num as u32);
+                    out.write_bool(owns_special(&plat.sector));
+                    out.write_fixed(plat.speed);
+                    out.write_fixed(plat.low);
+                    out.write_fixed(plat.high);
+                    out.write_i32(plat.wait);
+                    out.write_i32(plat.count);
+                    out.write_u8(plat.status as u8);
+                    out.write_u8(plat.old_status as u8);
+                    out.write_bool(plat.crush);
+                    out.write_i16(plat.tag);
+                    out.write_u8(plat.kind as u8);
+                }
+                ThinkerData::LightFlash(light) => {
+                    out.write_u8(TC_FLASH);
+                    out.write_u32(light.sector.num as u32);
+                    out.write_i32(light.count);
+                    out.write_u32(light.max_light as u32);
+                    out.write_u32(light.min_light as u32);
+                    out.write_i32(light.max_time);
+                    out.write_i32(light.min_time);
+                }
+                ThinkerData::StrobeFlash(light) => {
+                    out.write_u8(TC_STROBE);
+                    out.write_u32(light.sector.num as u32);
+                    out.write_i32(light.count);
+                    out.write_u32(light.min_light as u32);
+                    out.write_u32(light.max_light as u32);
+                    out.write_i32(light.dark_time);
+                    out.write_i32(light.bright_time);
+                }
+                ThinkerData::FireFlicker(light) => {
+                    out.write_u8(TC_FLICKER);
+                    out.write_u32(light.sector.num as u32);
+                    out.write_i32(light.count);
+                    out.write_u32(light.max_light as u32);
+                    out.write_u32(light.min_light as u32);
+                }
+                ThinkerData::Glow(light) => {
+                    out.write_u8(TC_GLOW);
+                    out.write_u32(light.sector.num as u32);
+                    out.write_u32(light.min_light as u32);
+                    out.write_u32(light.max_light as u32);
+                    out.write_i32(light.direction);
+                }
+                ThinkerData::TestObject(_) | ThinkerData::Remove | ThinkerData::Free => {}
+            }
+        }
+
+        for (i, player) in self.players().iter().enumerate() {
+            if !self.players_in_game()[i] {
+                continue;
+            }
+            Self::archive_player(player, &idx, out);
+        }
+    }
+
+    fn archive_world(&self, out: &mut SaveWriter) {
+        let map = &self.map_data;
+        out.write_u32(map.sectors().len() as u32);
+        for sector in map.sectors() {
+            out.write_fixed(sector.floorheight);
+            out.write_fixed(sector.ceilingheight);
+            out.write_u32(sector.floorpic as u32);
+            out.write_u32(sector.ceilingpic as u32);
+            out.write_u32(sector.lightlevel as u32);
+            out.write_i16(sector.special);
+            out.write_i16(sector.tag);
+        }
+
+        out.write_u32(map.linedefs().len() as u32);
+        for line in map.linedefs() {
+            out.write_u32(line.flags);
+            out.write_i16(line.special);
+            out.write_i16(line.tag);
+        }
+
+        let tex = |t: Option<usize>| t.map(|t| t as i32).unwrap_or(-1);
+        out.write_u32(map.sidedefs().len() as u32);
+        for side in map.sidedefs() {
+            out.write_fixed(side.textureoffset);
+            out.write_fixed(side.rowoffset);
+            out.write_i32(tex(side.toptexture));
+            out.write_i32(tex(side.bottomtexture));
+            out.write_i32(tex(side.midtexture));
+        }
+    }
+
+    fn archive_mobj(
+        &self,
+        mobj: &MapObject,
+        idx: &impl Fn(Option<*mut Thinker>) -> i32,
+        out: &mut SaveWriter,
+    ) {
+        out.write_u16(mobj.kind as u16);
+        out.write_vec(mobj.xy);
+        out.write_fixed(mobj.z);
+        out.write_angle(mobj.angle);
+        out.write_u32(mobj.frame);
+        out.write_fixed(mobj.floorz);
+        out.write_fixed(mobj.ceilingz);
+        out.write_fixed(mobj.radius);
+        out.write_fixed(mobj.height);
+        out.write_vec(mobj.momxy);
+        out.write_fixed(mobj.momz);
+        out.write_i32(mobj.tics);
+        out.write_i32(state_index(mobj.state));
+        out.write_u32(mobj.flags);
+        out.write_i32(mobj.health);
+        out.write_u8(mobj.movedir as u8);
+        out.write_i32(mobj.movecount);
+        out.write_i16(mobj.strafecount);
+        out.write_i32(idx(mobj.target));
+        out.write_i32(idx(mobj.tracer));
+        out.write_i32(mobj.reactiontime);
+        out.write_i32(mobj.threshold);
+        let player = mobj
+            .player
+            .and_then(|p| {
+                self.players()
+                    .iter()
+                    .position(|other| std::ptr::eq(other, p))
+            })
+            .map(|p| p as u8 + 1)
+            .unwrap_or(0);
+        out.write_u8(player);
+        out.write_u8(mobj.lastlook as u8);
+        out.write_wad_thing(&mobj.spawnpoint);
+        out.write_u32(mobj.boss_targets.len() as u32);
+        for t in mobj.boss_targets.iter() {
+            out.write_i32(idx(Some(*t)));
+        }
+        out.write_u32(mobj.boss_target_on as u32);
+    }
+
+    fn archive_player(
+        player: &Player,
+        idx: &impl Fn(Option<*mut Thinker>) -> i32,
+        out: &mut SaveWriter,
+    ) {
+        out.write_u8(match player.player_state {
+            PlayerState::Live => 0,
+            PlayerState::Dead => 1,
+            PlayerState::Reborn => 2,
+        });
+        out.write_fixed(player.viewz);
+        out.write_fixed(player.viewheight);
+        out.write_fixed(player.deltaviewheight);
+        out.write_fixed(player.bob);
+        out.write_bool(player.onground);
+
+        let status = &player.status;
+        out.write_bool(status.attackdown);
+        out.write_bool(status.usedown);
+        out.write_u8(status.readyweapon as u8);
+        out.write_i32(status.health);
+        out.write_i32(status.armorpoints);
+        out.write_i32(status.armortype);
+        for card in status.cards.iter() {
+            out.write_bool(*card);
+        }
+        for owned in status.weaponowned.iter() {
+            out.write_bool(*owned);
+        }
+        for ammo in status.ammo.iter() {
+            out.write_u32(*ammo);
+        }
+        for ammo in status.maxammo.iter() {
+            out.write_u32(*ammo);
+        }
+        out.write_bool(status.backpack);
+        for power in status.powers.iter() {
+            out.write_i32(*power);
+        }
+        out.write_i32(status.damagecount);
+        out.write_i32(status.bonuscount);
+        out.write_angle(status.attacked_from);
+        out.write_angle(status.own_angle);
+        out.write_u32(status.attacked_angle_count);
+        out.write_u32(status.cheats);
+
+        for frag in player.frags.iter() {
+            out.write_i32(*frag);
+        }
+        out.write_u8(player.pendingweapon as u8);
+        out.write_i32(player.refire);
+        out.write_i32(player.total_kills);
+        out.write_i32(player.items_collected);
+        out.write_i32(player.secrets_found);
+        out.write_i32(idx(player.attacker.map(|a| unsafe { (*a).thinker })));
+        out.write_u32(player.extralight as u32);
+        out.write_i32(player.fixedcolormap);
+        for psp in player.psprites.iter() {
+            out.write_i32(psp.state.map(state_index).unwrap_or(-1));
+            out.write_i32(psp.tics);
+            out.write_fixed(psp.sx);
+            out.write_fixed(psp.sy);
+        }
+        out.write_bool(player.didsecret);
+        out.write_i16(player.lookdir);
+    }
+
+    /// Restore a level from an archive read with `LevelArchive::read`. The
+    /// level must have been loaded with `load()` and had specials spawned, but
+    /// *no* map things. All existing thinkers are dropped and replaced with
+    /// those in the archive.
+    ///
+    /// Doom functions `P_UnArchivePlayers`, `P_UnArchiveWorld`,
+    /// `P_UnArchiveThinkers`, `P_UnArchiveSpecials`
+    pub fn unarchive(&mut self, archive: LevelArchive) -> Result<(), SaveError> {
+        archive.check_map(&self.map_data)?;
+
+        self.level_time = archive.level_time;
+        self.total_level_kills = archive.total_level_kills;
+        self.total_level_items = archive.total_level_items;
+        self.total_level_secrets = archive.total_level_secrets;
+        self.respawn_queue = archive.respawn_queue.into();
+
+        let map = &mut self.map_data;
+        for (sector, saved) in map.sectors_mut().iter_mut().zip(archive.sectors) {
+            sector.floorheight = saved.floorheight;
+            sector.ceilingheight = saved.ceilingheight;
+            sector.floorpic = saved.floorpic;
+            sector.ceilingpic = saved.ceilingpic;
+            sector.lightlevel = saved.lightlevel;
+            sector.special = saved.special;
+            sector.tag = saved.tag;
+        }
+        for (line, saved) in map.linedefs.iter_mut().zip(archive.linedefs) {
+            line.flags = saved.flags;
+            line.special = saved.special;
+            line.tag = saved.tag;
+        }
+        for (side, saved) in map.sidedefs_mut().iter_mut().zip(archive.sidedefs) {
+            side.textureoffset = saved.textureoffset;
+            side.rowoffset = saved.rowoffset;
+            side.toptexture = saved.toptexture;
+            side.bottomtexture = saved.bottomtexture;
+            side.midtexture = saved.midtexture;
+        }
+
+        let count = archive.thinkers.len();
+        let capacity = self
+            .thinkers
+            .capacity()
+            .max(count + self.map_data.things().len());
+        // Nothing may hold a pointer in to the old allocation past this point
+        for sector in self.map_data.sectors_mut() {
+            sector.specialdata = None;
+        }
+        for player in self.players_mut().iter_mut() {
+            player.mobj = None;
+        }
+        self.map_data.blockmap_mut().clear_things();
+        self.thinkers = unsafe { ThinkerAlloc::new(capacity) };
+
+        let mut thinkers: Vec<*mut Thinker> = Vec::with_capacity(count);
+        let mut links = Vec::new();
+        for thinker in archive.thinkers {
+            let ptr = match thinker {
+                ThinkerArchive::Mobj(mobj) => {
+                    let ptr = self.restore_mobj(&mobj)?;
+                    links.push((ptr, mobj));
+                    ptr
+                }
+                ThinkerArchive::Special {
+                    sector,
+                    owns_special,
+                    data,
+                } => self.restore_special(sector, owns_special, data)?,
+            };
+            thinkers.push(ptr);
+        }
+
+        // Indexes were checked when the archive was read
+        let lookup = |i: i32| (i >= 0).then(|| thinkers[i as usize]);
+        for (thinker, saved) in links {
+            let mobj = unsafe { (*thinker).mobj_mut() };
+            mobj.target = lookup(saved.target);
+            mobj.tracer = lookup(saved.tracer);
+            mobj.boss_targets = saved
+                .boss_targets
+                .iter()
+                .filter_map(|t| lookup(*t))
+                .collect();
+        }
+
+        for (i, saved, attacker) in archive.players {
+            let player = &mut self.players_mut()[i];
+            // Only what isn't archived is kept
+            let (mobj, cmd, head_bob) = (player.mobj, player.cmd, player.head_bob);
+            *player = saved;
+            player.mobj = mobj;
+            player.cmd = cmd;
+            player.head_bob = head_bob;
+            player.attacker = lookup(attacker)
+                .filter(|t| unsafe { (**t).is_mobj() })
+                .map(|t| unsafe { (*t).mobj_mut() as *mut MapObject });
+            if player.mobj.is_none() && player.player_state != PlayerState::Reborn {
+                warn!("Restored player has no map object, forcing reborn");
+                player.player_state = PlayerState::Reborn;
+            }
+        }
+
+        debug!("Unarchived {} thinkers", count);
+        Ok(())
+    }
+
+    fn restore_mobj(&mut self, saved: &MobjArchive) -> Result<*mut Thinker, SaveError> {
+        let mut mobj = MapObject::new(
+            saved.xy.x,
+            saved.xy.y,
+            saved.z,
+            saved.reactiontime,
+            saved.kind,
+            unsafe { MOBJINFO[saved.kind as usize] },
+            saved.state,
+            self as *mut Level,
+        );
+        mobj.angle = saved.angle;
+        mobj.frame = saved.frame;
+        mobj.floorz = saved.floorz;
+        mobj.ceilingz = saved.ceilingz;
+        mobj.radius = saved.radius;
+        mobj.height = saved.height;
+        mobj.momxy = saved.momxy;
+        mobj.momz = saved.momz;
+        mobj.tics = saved.tics;
+        mobj.flags = saved.flags;
+        mobj.health = saved.health;
+        mobj.movedir = saved.movedir;
+        mobj.movecount = saved.movecount;
+        mobj.strafecount = saved.strafecount;
+        mobj.threshold = saved.threshold;
+        mobj.lastlook = saved.lastlook;
+        mobj.spawnpoint = saved.spawnpoint;
+        mobj.boss_target_on = saved.boss_target_on;
+
+        let thinker = MapObject::create_thinker(ThinkerData::MapObject(mobj), MapObject::think);
+        let Some(ptr) = self.thinkers.push::<MapObject>(thinker) else {
+            return Err(SaveError::Invalid("out of thinker slots".into()));
+        };
+        let ptr = ptr as *mut Thinker;
+        let mobj = unsafe { (*ptr).mobj_mut() };
+        // Links in to the subsector and the sector thing list
+        unsafe { mobj.set_thing_position() };
+
+        if saved.player > 0 {
+            let p = &mut self.players_mut()[saved.player - 1];
+            mobj.player = Some(p as *mut Player);
+            p.set_mobj(mobj);
+        }
+        Ok(ptr)
+    }
+
+    fn restore_special(
+        &mut self,
+        sector_num: usize,
+        owns_special: bool,
+        mut data: ThinkerData,
+    ) -> Result<*mut Thinker, SaveError> {
+        let mut sector = MapPtr::new(&mut self.map_data.sectors_mut()[sector_num]);
+        match &mut data {
+            ThinkerData::VerticalDoor(door) => door.sector = sector.clone(),
+            ThinkerData::FloorMove(floor) => floor.sector = sector.clone(),
+            ThinkerData::CeilingMove(ceiling) => ceiling.sector = sector.clone(),
+            ThinkerData::Platform(plat) => plat.sector = sector.clone(),
+            ThinkerData::LightFlash(light) => light.sector = sector.clone(),
+            ThinkerData::StrobeFlash(light) => light.sector = sector.clone(),
+            ThinkerData::FireFlicker(light) => light.sector = sector.clone(),
+            ThinkerData::Glow(light) => light.sector = sector.clone(),
+            _ => unreachable!("only specials are read with a sector"),
+        }
+        let thinker = match data {
+            ThinkerData::VerticalDoor(_) => VerticalDoor::create_thinker(data, VerticalDoor::think),
+            ThinkerData::FloorMove(_) => FloorMove::create_thinker(data, FloorMove::think),
+            ThinkerData::CeilingMove(_) => CeilingMove::create_thinker(data, CeilingMove::think),
+            ThinkerData::Platform(_) => Platform::create_thinker(data, Platform::think),
+            ThinkerData::LightFlash(_) => LightFlash::create_thinker(data, LightFlash::think),
+            ThinkerData::StrobeFlash(_) => StrobeFlash::create_thinker(data, StrobeFlash::think),
+            ThinkerData::FireFlicker(_) => FireFlicker::create_thinker(data, FireFlicker::think),
+            _ => Glow::create_thinker(data, Glow::think),
+        };
+        let platform = matches!(thinker.data(), ThinkerData::Platform(_));
+
+        let Some(ptr) = self.thinkers.push::<VerticalDoor>(thinker) else {
+            return Err(SaveError::Invalid("out of thinker slots".into()));
+        };
+        let ptr = ptr as *mut Thinker;
+        if owns_special {
+            sector.specialdata = Some(ptr);
+        }
+        if platform {
+            let plat = unsafe { (*ptr).platform_mut() as *mut Platform };
+            self.add_active_platform(plat);
+        }
+        Ok(ptr)
+    }
+}
+
+/// A level archive read in full and checked before any of it is applied with
+/// `Level::unarchive`, so that a bad savegame doesn't leave the game half
+/// loaded. Pointers between objects are kept as thinker indexes until then.
+pub struct LevelArchive {
+    level_time: u32,
+    total_level_kills: i32,
+    total_level_items: i32,
+    total_level_secrets: i32,
+    respawn_queue: Vec<(u32, WadThing)>,
+    sectors: Vec<SectorArchive>,
+    linedefs: Vec<LineArchive>,
+    sidedefs: Vec<SideArchive>,
+    thinkers: Vec<ThinkerArchive>,
+    /// Each player in the game by number, with the index of their attacker
+    players: Vec<(usize, Player, i32)>,
+}
+
+struct SectorArchive {
+    floorheight: fixed_t,
+    ceilingheight: fixed_t,
+    floorpic: usize,
+    ceilingpic: usize,
+    lightlevel: usize,
+    special: i16,
+    tag: i16,
+}
+
+struct LineArchive {
+    flags: u32,
+    special: i16,
+    tag: i16,
+}
+
+struct SideArchive {
+    textureoffset: fixed_t,
+    rowoffset: fixed_t,
+    toptexture: Option<usize>,
+    bottomtexture: Option<usize>,
+    midtexture: Option<usize>,
+}
+
+enum ThinkerArchive {
+    Mobj(Box<MobjArchive>),
+    /// A sector special, its `MapPtr` to the sector is set when restored
+    Special {
+        sector: usize,
+        owns_special: bool,
+        data: ThinkerData,
+    },
+}
+
+struct MobjArchive {
+    kind: MapObjKind,
+    xy: VecF2,
+    z: fixed_t,
+    angle: Angle,
+    frame: u32,
+    floorz: fixed_t,
+    ceilingz: fixed_t,
+    radius: fixed_t,
+    height: fixed_t,
+    momxy: VecF2,
+    momz: fixed_t,
+    tics: i32,
+    state: &'static State,
+    flags: u32,
+    health: i32,
+    movedir: MoveDir,
+    movecount: i32,
+    strafecount: i16,
+    target: i32,
+    tracer: i32,
+    reactiontime: i32,
+    threshold: i32,
+    /// Player number plus one, or 0 for none
+    player: usize,
+    lastlook: usize,
+    spawnpoint: WadThing,
+    boss_targets: Vec<i32>,
+    boss_target_on: usize,
+}
+
+impl LevelArchive {
+    /// Read and check a level archived with `Level::archive()`. Players are
+    /// only archived for those in `players_in_game`.
+    pub fn read(
+        input: &mut SaveReader,
+        players_in_game: &[bool; MAXPLAYERS],
+    ) -> Result<Self, SaveError> {
+        let level_time = input.read_u32()?;
+        let total_level_kills = input.read_i32()?;
+        let total_level_items = input.read_i32()?;
+        let total_level_secrets = input.read_i32()?;
+
+        let mut respawn_queue = Vec::new();
+        for _ in 0..input.read_u32()? {
+            let time = input.read_u32()?;
+            let thing = input.read_wad_thing()?;
+            respawn_queue.push((time, thing));
+        }
+
+        let mut sectors = Vec::new();
+        for _ in 0..input.read_u32()? {
+            sectors.push(SectorArchive {
+                floorheight: input.read_fixed()?,
+                ceilingheight: input.read_fixed()?,
+                floorpic: input.read_u32()? as usize,
+                ceilingpic: input.read_u32()? as usize,
+                lightlevel: input.read_u32()? as usize,
+                special: input.read_i16()?,
+                tag: input.read_i16()?,
+            });
+        }
+        let mut linedefs = Vec::new();
+        for _ in 0..input.read_u32()? {
+            linedefs.push(LineArchive {
+                flags: input.read_u32()?,
+                special: input.read_i16()?,
+                tag: input.read_i16()?,
+            });
+        }
+        let tex = |t: i32| if t < 0 { None } else { Some(t as usize) };
+        let mut sidedefs = Vec::new();
+        for _ in 0..input.read_u32()? {
+            sidedefs.push(SideArchive {
+                textureoffset: input.read_fixed()?,
+                rowoffset: input.read_fixed()?,
+                toptexture: tex(input.read_i32()?),
+                bottomtexture: tex(input.read_i32()?),
+                midtexture: tex(input.read_i32()?),
+            });
+        }
+
+        let count = input.read_u32()? as usize;
+        let mut thinkers = Vec::new();
+        for _ in 0..count {
+            let tc = input.read_u8()?;
+            thinkers.push(if tc == TC_MOBJ {
+                ThinkerArchive::Mobj(Box::new(Self::read_mobj(input)?))
+            } else {
+                Self::read_special(tc, input, sectors.len())?
+            });
+        }
+
+        let check = |i: i32| -> Result<i32, SaveError> {
+            if i >= 0 && i as usize >= count {
+                return Err(SaveError::Invalid(format!("thinker {} out of range", i)));
+            }
+            Ok(i)
+        };
+        for thinker in thinkers.iter() {
+            if let ThinkerArchive::Mobj(mobj) = thinker {
+                check(mobj.target)?;
+                check(mobj.tracer)?;
+                for t in mobj.boss_targets.iter() {
+                    check(*t)?;
+                }
+            }
+        }
+
+        let mut players = Vec::new();
+        for (i, _) in players_in_game.iter().enumerate().filter(|(_, p)| **p) {
+            let (player, attacker) = Self::read_player(input)?;
+            players.push((i, player, check(attacker)?));
+        }
+
+        Ok(Self {
+            level_time,
+            total_level_kills,
+            total_level_items,
+            total_level_secrets,
+            respawn_queue,
+            sectors,
+            linedefs,
+            sidedefs,
+            thinkers,
+            players,
+        })
+    }
+
+    /// The archive must be of the same map, as sectors, lines and sides are
+    /// restored by their index
+    pub fn check_map(&self, map: &MapData) -> Result<(), SaveError> {
+        if self.sectors.len() != map.sectors().len() {
+            return Err(SaveError::Invalid("sector count does not match map".into()));
+        }
+        if self.linedefs.len() != map.linedefs().len() {
+            return Err(SaveError::Invalid(
+                "linedef count does not match map".into(),
+            ));
+        }
+        if self.sidedefs.len() != map.sidedefs().len() {
+            return Err(SaveError::Invalid(
+                "sidedef count does not match map".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn read_mobj(input: &mut SaveReader) -> Result<MobjArchive, SaveError> {
+        let kind = input.read_u16()?;
+        if kind >= MapObjKind::Count as u16 {
+            return Err(SaveError::Invalid(format!("map object kind {}", kind)));
+        }
+        let kind = MapObjKind::from(kind);
+        let xy = input.read_vec()?;
+        let z = input.read_fixed()?;
+        let angle = input.read_angle()?;
+        let frame = input.read_u32()?;
+        let floorz = input.read_fixed()?;
+        let ceilingz = input.read_fixed()?;
+        let radius = input.read_fixed()?;
+        let height = input.read_fixed()?;
+        let momxy = input.read_vec()?;
+        let momz = input.read_fixed()?;
+        let tics = input.read_i32()?;
+        let state = state_from_index(input.read_i32()?)?;
+        let flags = input.read_u32()?;
+        let health = input.read_i32()?;
+        let movedir = input.read_u8()? as usize;
+        if movedir >= MoveDir::NumDirs as usize {
+            return Err(SaveError::Invalid(format!("move direction {}", movedir)));
+        }
+        let movecount = input.read_i32()?;
+        let strafecount = input.read_i16()?;
+        let target = input.read_i32()?;
+        let tracer = input.read_i32()?;
+        let reactiontime = input.read_i32()?;
+        let threshold = input.read_i32()?;
+        let player = input.read_u8()? as usize;
+        if player > MAXPLAYERS {
+            return Err(SaveError::Invalid(format!(
+                "player {} out of range",
+                player
+            )));
+        }
+        let lastlook = input.read_u8()? as usize;
+        let spawnpoint = input.read_wad_thing()?;
+        let mut boss_targets = Vec::new();
+        for _ in 0..input.read_u32()? {
+            boss_targets.push(input.read_i32()?);
+        }
+        let boss_target_on = input.read_u32()? as usize;
+
+        Ok(MobjArchive {
+            kind,
+            xy,
+            z,
+            angle,
+            frame,
+            floorz,
+            ceilingz,
+            radius,
+            height,
+            momxy,
+            momz,
+            tics,
+            state,
+            flags,
+            health,
+            movedir: MoveDir::from(movedir),
+            movecount,
+            strafecount,
+            target,
+            tracer,
+            reactiontime,
+            threshold,
+            player,
+            lastlook,
+            spawnpoint,
+            boss_targets,
+            boss_target_on,
+        })
+    }
+
+    fn read_special(
+        tc: u8,
+        input: &mut SaveReader,
+        num_sectors: usize,
+    ) -> Result<ThinkerArchive, SaveError> {
+        let sector_num = input.read_u32()? as usize;
+        if sector_num >= num_sectors {
+            return Err(SaveError::Invalid(format!(
+                "sector {} out of range",
+                sector_num
+            )));
+        }
+        let sector = unsafe { MapPtr::new_null() };
+
+        let mut owns_special = false;
+        let data = match tc {
+            TC_DOOR => {
+                owns_special = input.read_bool()?;
+                ThinkerData::VerticalDoor(VerticalDoor {
+                    thinker: null_mut(),
+                    sector,
+                    kind: input.read_index(&DOOR_KINDS, "door kind")?,
+                    topheight: input.read_fixed()?,
+                    speed: input.read_fixed()?,
+                    direction: input.read_i32()?,
+                    topwait: input.read_i32()?,
+                    topcountdown: input.read_i32()?,
+                })
+            }
+            TC_FLOOR => {
+                owns_special = input.read_bool()?;
+                ThinkerData::FloorMove(FloorMove {
+                    thinker: null_mut(),
+                    sector,
+                    kind: input.read_index(&FLOOR_KINDS, "floor kind")?,
+                    speed: input.read_fixed()?,
+                    crush: input.read_bool()?,
+                    direction: input.read_i32()?,
+                    newspecial: input.read_i16()?,
+                    texture: input.read_u32()? as usize,
+                    destheight: input.read_fixed()?,
+                })
+            }
+            TC_CEILING => {
+                owns_special = input.read_bool()?;
+                ThinkerData::CeilingMove(CeilingMove {
+                    thinker: null_mut(),
+                    sector,
+                    kind: input.read_index(&CEIL_KINDS, "ceiling kind")?,
+                    bottomheight: input.read_fixed()?,
+                    topheight: input.read_fixed()?,
+                    speed: input.read_fixed()?,
+                    crush: input.read_bool()?,
+                    direction: input.read_i32()?,
+                    tag: input.read_i16()?,
+                    olddirection: input.read_i32()?,
+                    oldspeed: input.read_fixed()?,
+                    texture: input.read_u32()? as usize,
+                    newspecial: input.read_i16()?,
+                })
+            }
+            TC_PLATFORM => {
+                owns_special = input.read_bool()?;
+                ThinkerData::Platform(Platform {
+                    thinker: null_mut(),
+                    sector,
+                    speed: input.read_fixed()?,
+                    low: input.read_fixed()?,
+                    high: input.read_fixed()?,
+                    wait: input.read_i32()?,
+                    count: input.read_i32()?,
+                    status: input.read_index(&PLAT_STATUS, "platform status")?,
+                    old_status: input.read_index(&PLAT_STATUS, "platform status")?,
+                    crush: input.read_bool()?,
+                    tag: input.read_i16()?,
+                    kind: input.read_index(&PLAT_KINDS, "platform kind")?,
+                })
+            }
+            TC_FLASH => ThinkerData::LightFlash(LightFlash {
+                thinker: null_mut(),
+                sector,
+                count: input.read_i32()?,
+                max_light: input.read_u32()? as usize,
+                min_light: input.read_u32()? as usize,
+                max_time: input.read_i32()?,
+                min_time: input.read_i32()?,
+            }),
+            TC_STROBE => ThinkerData::StrobeFlash(StrobeFlash {
+                thinker: null_mut(),
+                sector,
+                count: input.read_i32()?,
+                min_light: input.read_u32()? as usize,
+                max_light: input.read_u32()? as usize,
+                dark_time: input.read_i32()?,
+                bright_time: input.read_i32()?,
+            }),
+            TC_FLICKER => ThinkerData::FireFlicker(FireFlicker {
+                thinker: null_mut(),
+                sector,
+                count: input.read_i32()?,
+                max_light: input.read_u32()? as usize,
+                min_light: input.read_u32()? as usize,
+            }),
+            TC_GLOW => ThinkerData::Glow(Glow {
+                thinker: null_mut(),
+                sector,
+                min_light: input.read_u32()? as usize,
+                max_light: input.read_u32()? as usize,
+                direction: input.read_i32()?,
+            }),
+            _ => return Err(SaveError::Invalid(format!("unknown thinker class {}", tc))),
+        };
+        Ok(ThinkerArchive::Special {
+            sector: sector_num,
+            owns_special,
+            data,
+        })
+    }
+
+    /// Read a player, and the thinker index of their attacker
+    fn read_player(input: &mut SaveReader) -> Result<(Player, i32), SaveError> {
+        let mut player = Player::new();
+        player.player_state = player_state_from(input.read_u8()?)?;
+        player.viewz = input.read_fixed()?;
+        player.viewheight = input.read_fixed()?;
+        player.deltaviewheight = input.read_fixed()?;
+        player.bob = input.read_fixed()?;
+        player.onground = input.read_bool()?;
+
+        let status = &mut player.status;
+        status.attackdown = input.read_bool()?;
+        status.usedown = input.read_bool()?;
+        status.readyweapon = weapon_from(input.read_u8()?)?;
+        status.health = input.read_i32()?;
+        status.armorpoints = input.read_i32()?;
+        status.armortype = input.read_i32()?;
+        for card in status.cards.iter_mut() {
+            *card = input.read_bool()?;
+        }
+        for owned in status.weaponowned.iter_mut() {
+            *owned = input.read_bool()?;
+        }
+        for ammo in status.ammo.iter_mut() {
+            *ammo = input.read_u32()?;
+        }
+        for ammo in status.maxammo.iter_mut() {
+            *ammo = input.read_u32()?;
+        }
+        status.backpack = input.read_bool()?;
+        for power in status.powers.iter_mut() {
+            *power = input.read_i32()?;
+        }
+        status.damagecount = input.read_i32()?;
+        status.bonuscount = input.read_i32()?;
+        status.attacked_from = input.read_angle()?;
+        status.own_angle = input.read_angle()?;
+        status.attacked_angle_count = input.read_u32()?;
+        status.cheats = input.read_u32()?;
+
+        for frag in player.frags.iter_mut() {
+            *frag = input.read_i32()?;
+        }
+        player.pendingweapon = weapon_from(input.read_u8()?)?;
+        player.refire = input.read_i32()?;
+        player.total_kills = input.read_i32()?;
+        player.items_collected = input.read_i32()?;
+        player.secrets_found = input.read_i32()?;
+        let attacker = input.read_i32()?;
+        player.extralight = input.read_u32()? as usize;
+        player.fixedcolormap = input.read_i32()?;
+        for psp in player.psprites.iter_mut() {
+            let state = input.read_i32()?;
+            psp.state = if state < 0 {
+                None
+            } else {
+                Some(state_from_index(state)?)
+            };
+            psp.tics = input.read_i32()?;
+            psp.sx = input.read_fixed()?;
+            psp.sy = input.read_fixed()?;
+        }
+        player.didsecret = input.read_bool()?;
+        player.lookdir = input.read_i16()?;
+        Ok((player, attacker))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::channel;
+
+    use math::{Angle, VecF2, fixed_t};
+    use wad::WadData;
+    use wad::types::WadThing;
+
+    use crate::doom_def::GameMode;
+    use crate::level::Level;
+    use crate::thinker::ThinkerData;
+    use crate::{GameOptions, MAXPLAYERS, MapObject, PicData, Player, spawn_specials};
+
+    use super::{LevelArchive, SaveError, SaveReader, SaveWriter, TC_DOOR};
+
+    #[test]
+    fn primitives_round_trip() {
+        let mut w = SaveWriter::new();
+        w.write_u8(7);
+        w.write_bool(true);
+        w.write_i16(-1234);
+        w.write_i32(-99_999);
+        w.write_u32(0xdead_beef);
+        w.write_fixed(fixed_t::from_int(-42));
+        w.write_vec(VecF2::new(fixed_t::from_int(3), fixed_t::from_float(0.5)));
+        w.write_angle(Angle::new(0x4000_0000));
+        w.write_str("quicksave", 24);
+        w.write_wad_thing(&WadThing::new(1, -2, 90, 3004, 7));
+
+        let data = w.into_data();
+        let mut r = SaveReader::new(&data);
+        assert_eq!(r.read_u8().unwrap(), 7);
+        assert!(r.read_bool().unwrap());
+        assert_eq!(r.read_i16().unwrap(), -1234);
+        assert_eq!(r.read_i32().unwrap(), -99_999);
+        assert_eq!(r.read_u32().unwrap(), 0xdead_beef);
+        assert_eq!(r.read_fixed().unwrap(), fixed_t::from_int(-42));
+        assert_eq!(
+            r.read_vec().unwrap(),
+            VecF2::new(fixed_t::from_int(3), fixed_t::from_float(0.5))
+        );
+        assert_eq!(r.read_angle().unwrap().0, 0x4000_0000);
+        assert_eq!(r.read_str(24).unwrap(), "quicksave");
+        let thing = r.read_wad_thing().unwrap();
+        assert_eq!((thing.x, thing.y, thing.kind), (1, -2, 3004));
+        assert!(matches!(r.read_u8(), Err(SaveError::UnexpectedEnd)));
+    }
+
+    #[test]
+    fn archive_checked_without_level() {
+        let in_game = [true, false, false, false];
+        let mut w = SaveWriter::new();
+        w.write_u32(35);
+        for _ in 0..3 {
+            w.write_i32(0);
+        }
+        // No respawns, sectors, lines or sides, then a door in sector 0
+        for _ in 0..4 {
+            w.write_u32(0);
+        }
+        w.write_u32(1);
+        w.write_u8(TC_DOOR);
+        w.write_u32(0);
+        let data = w.into_data();
+
+        let err = LevelArchive::read(&mut SaveReader::new(&data), &in_game).err();
+        assert!(matches!(err, Some(SaveError::Invalid(m)) if m == "sector 0 out of range"));
+        let err = LevelArchive::read(&mut SaveReader::new(&data[..20]), &in_game).err();
+        assert!(matches!(err, Some(SaveError::UnexpectedEnd)));
+    }
+
+    fn new_level(players: &mut [Player; MAXPLAYERS], in_game: &[bool; MAXPLAYERS]) -> Box<Level> {
+        let (tx, _rx) = channel();
+        let options = GameOptions {
+            episode: 1,
+            map: 1,
+            ..GameOptions::default()
+        };
+        Box::new(unsafe { Level::new_empty(options, GameMode::Shareware, tx, in_game, players) })
+    }
+
+    #[test]
+    fn level_round_trip() {
+        let wad = WadData::new("../doom1.wad".into());
+        let mut pic_data = PicData::init(false, &wad);
+        let in_game = [true, false, false, false];
+
+        let mut players = [
+            Player::default(),
+            Player::default(),
+            Player::default(),
+            Player::default(),
+        ];
+        let mut level = new_level(&mut players, &in_game);
+        level.load("E1M1", GameMode::Shareware, &mut pic_data, &wad);
+        let things = level.map_data.things().to_owned();
+        for thing in things {
+            MapObject::p_spawn_map_thing(thing, false, &mut level, &mut players, &in_game);
+        }
+        spawn_specials(&mut level);
+        level.level_time = 1234;
+        level.map_data.sectors_mut()[3].floorheight = fixed_t::from_int(-72);
+        players[0].status.ammo[0] = 123;
+
+        let mut w = SaveWriter::new();
+        level.archive(&mut w);
+        let count = level.thinkers.len();
+
+        let mut restored_players = [
+            Player::default(),
+            Player::default(),
+            Player::default(),
+            Player::default(),
+        ];
+        let mut restored = new_level(&mut restored_players, &in_game);
+        restored.load("E1M1", GameMode::Shareware, &mut pic_data, &wad);
+        spawn_specials(&mut restored);
+        let data = w.into_data();
+        let archive = LevelArchive::read(&mut SaveReader::new(&data), &in_game).unwrap();
+        restored.unarchive(archive).unwrap();
+
+        assert_eq!(restored.level_time, 1234);
+        assert_eq!(restored.thinkers.len(), count);
+        assert_eq!(
+            restored.map_data.sectors()[3].floorheight,
+            fixed_t::from_int(-72)
+        );
+        assert_eq!(restored_players[0].status.ammo[0], 123);
+        let player_mobj = restored_players[0].mobj().unwrap();
+        assert_eq!(player_mobj.xy, players[0].mobj().unwrap().xy);
+        assert!(std::ptr::eq(
+            player_mobj.player().unwrap(),
+            &restored_players[0]
+        ));
+
+        let mut mobjs = 0;
+        restored.thinkers.run_fn_on_things(|t| {
+            if let ThinkerData::MapObject(m) = t.data() {
+                assert!(!m.subsector.is_null());
+                mobjs += 1;
+            }
+            true
+        });
+        assert!(mobjs > 0);
+    }
+}
@@ -12,11 +12,12 @@ use log::trace;
 use sound_traits::SfxName;
 use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
 use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use crate::doom_def::{MISSILERANGE, SKULLSPEED};
 use crate::env::doors::{DoorKind, ev_do_door};
 use crate::env::floor::{FloorKind, ev_do_floor};
-use crate::info::{MOBJINFO, StateNum};
+use crate::info::{MOBJINFO, STATES, StateNum};
 use crate::level::map_defs::{LineDef, SlopeType};
 use crate::thing::{MapObjFlag, MapObject, MoveDir};
 use crate::thinker::{Thinker, ThinkerData};
@@ -31,6 +32,32 @@ use math::{
 
 use super::movement::SubSectorMinMax;
 
+/// Are the demon states and imp, baron, and cacodemon shots sped up?
+static FAST_MONSTERS: AtomicBool = AtomicBool::new(false);
+
+/// Speed up or restore demon attacks and the imp, baron, and cacodemon shots.
+/// The original does this in `G_InitNew` when switching to or from nightmare,
+/// here the current state is tracked so `-fast` can be turned on and off with
+/// any skill without the tics being halved twice.
+pub fn set_fast_monsters(fast: bool) {
+    if FAST_MONSTERS.swap(fast, Ordering::Relaxed) == fast {
+        return;
+    }
+    unsafe {
+        for i in StateNum::SARG_RUN1 as usize..=StateNum::SARG_PAIN2 as usize {
+            if fast {
+                STATES[i].tics >>= 1;
+            } else {
+                STATES[i].tics <<= 1;
+            }
+        }
+        let (baron, other) = if fast { (20, 20) } else { (15, 10) };
+        MOBJINFO[MapObjKind::MT_BRUISERSHOT as usize].speed = fixed_t::from_int(baron);
+        MOBJINFO[MapObjKind::MT_HEADSHOT as usize].speed = fixed_t::from_int(other);
+        MOBJINFO[MapObjKind::MT_TROOPSHOT as usize].speed = fixed_t::from_int(other);
+    }
+}
+
 /// This was only ever called with the player as the target, so it never follows
 /// the original comment stating that if a monster yells it alerts surrounding
 /// monsters
@@ -163,9 +190,7 @@ pub(crate) fn a_chase(actor: &mut MapObject) {
 
     if actor.flags & MapObjFlag::Justattacked as u32 != 0 {
         actor.flags &= !(MapObjFlag::Justattacked as u32);
-        // TODO: if (gameskill != sk_nightmare && !fastparm)
-        let skill = unsafe { (*actor.level).options.skill };
-        if skill != Skill::Nightmare {
+        if unsafe { !(*actor.level).options.fast_monsters() } {
             actor.new_chase_dir();
         }
         return;
@@ -181,11 +206,7 @@ pub(crate) fn a_chase(actor: &mut MapObject) {
 
     // Missile attack?
     if actor.info.missilestate != StateNum::None {
-        let skill = unsafe { (*actor.level).options.skill };
-        if skill == Skill::Nightmare || actor.movecount <= 0 {
-            // if (gameskill < sk_nightmare && !fastparm && actor->movecount) {
-            // goto nomissile;
-            // }
+        if unsafe { (*actor.level).options.fast_monsters() } || actor.movecount <= 0 {
             if actor.check_missile_range() {
                 actor.flags |= MapObjFlag::Justattacked as u32;
                 actor.set_state(actor.info.missilestate);
@@ -356,6 +377,7 @@ pub(crate) fn a_keendie(actor: &mut MapObject) {
         frontsector: sector,
         backsector: None,
         valid_count: 0,
+        translucent: false,
         sides: [0, 0],
     };
     ev_do_door(MapPtr::new(&mut junk), DoorKind::BlazeOpen, level);
@@ -804,11 +826,29 @@ pub(crate) fn a_pain(actor: &mut MapObject) {
     }
 }
 
+/// Pain elementals stop spitting out lost souls once the map has this many
+const MAX_SKULLS: usize = 20;
+
+/// Doom function name `A_PainShootSkull`
 fn a_painshootskull(actor: &mut MapObject, angle: Angle) {
     a_facetarget(actor);
-    // TODO: limit amount of skulls
-    //
-    let skull_radius = MOBJINFO[MapObjKind::MT_SKULL as usize].radius;
+    let level = unsafe { &mut *actor.level };
+    if !level.options.no_skull_limit {
+        let mut count = 0;
+        level.thinkers.run_fn_on_things(|thinker| {
+            if let ThinkerData::MapObject(mobj) = thinker.data()
+                && mobj.kind == MapObjKind::MT_SKULL
+            {
+                count += 1;
+            }
+            true
+        });
+        if count > MAX_SKULLS {
+            return;
+        }
+    }
+
+    let skull_radius = unsafe { MOBJINFO[MapObjKind::MT_SKULL as usize].radius };
     let prestep = FT_FOUR + fixed_t::from_int(3) * (actor.radius + skull_radius) / FT_TWO;
 
     let an = angle >> 19;
@@ -817,7 +857,6 @@ fn a_painshootskull(actor: &mut MapObject, angle: Angle) {
     let y = actor.xy.y + angle.sin() * prestep;
     let z = actor.z + FT_EIGHT;
 
-    let level = unsafe { &mut *actor.level };
     unsafe {
         let skull = &mut (*MapObject::spawn_map_object(x, y, z, MapObjKind::MT_SKULL, level));
         let mut ctrl = SubSectorMinMax::default();
@@ -1017,6 +1056,7 @@ pub(crate) fn a_bossdeath(actor: &mut MapObject) {
         frontsector: sector,
         backsector: None,
         valid_count: 0,
+        translucent: false,
         sides: [0, 0],
     };
 
@@ -11,7 +11,7 @@ use crate::lang::english::*;
 use crate::player::{PlayerCheat, PlayerState};
 use crate::thing::MapObjFlag;
 use crate::{MapObject, Skill};
-use math::{FT_FOUR, FT_ZERO, VecF2, fixed_t, p_random, point_to_angle_2};
+use math::{FT_FOUR, FT_ONE, FT_ZERO, VecF2, fixed_t, p_random, point_to_angle_2};
 
 pub const BONUSADD: i32 = 6;
 
@@ -89,7 +89,10 @@ impl MapObject {
 
             if self.flags & MapObjFlag::Noclip as u32 == 0 && do_push {
                 let angle = point_to_angle_2(self.xy, inflict.xy);
-                let mut thrust = fixed_t::from_float(damage as f32 * 16.66 / self.info.mass as f32);
+                // Telefrag damage overflows this, the same as the original
+                let mut thrust = fixed_t::new(
+                    damage.wrapping_mul(FT_ONE.0 >> 3).wrapping_mul(100) / self.info.mass,
+                );
                 // make fall forwards sometimes
                 if damage < 40
                     && damage > self.health
@@ -201,31 +204,27 @@ impl MapObject {
         self.flags |= MapObjFlag::Corpse as u32 | MapObjFlag::Dropoff as u32;
         self.height /= FT_FOUR;
 
-        if let Some(source) = source.as_mut() {
-            if let Some(player) = source.player_mut() {
-                if self.flags & MapObjFlag::Countkill as u32 != 0 {
-                    player.total_kills += 1;
-                }
-
-                if self.player.is_some() {
-                    // TODO: set correct player for frags
-                    player.frags[0] += 1;
-                }
-            }
-        } else {
-            // TODO: Need to increment killcount for first player
-            if let Some(player) = self.player_mut() {
-                // TODO: players[0].killcount++; ??
+        let count_kill = self.flags & MapObjFlag::Countkill as u32 != 0;
+        let target_num = self.player_num();
+        if let Some(player) = source.as_mut().and_then(|s| s.player_mut()) {
+            if count_kill {
                 player.total_kills += 1;
             }
+            if let Some(num) = target_num {
+                player.frags[num] += 1;
+            }
+        } else if !self.level().options.netgame && count_kill {
+            // count all monster deaths, even those caused by other monsters
+            self.level_mut().players_mut()[0].total_kills += 1;
         }
 
         if let Some(player) = self.player_mut() {
             info!("Killing player");
             // Environment kills count against you
-            if source.is_none() {
-                // TODO: set correct player for frags
-                player.frags[0] += 1;
+            if source.is_none()
+                && let Some(num) = target_num
+            {
+                player.frags[num] += 1;
             }
 
             player.player_state = PlayerState::Dead;
@@ -288,7 +287,8 @@ impl MapObject {
                 return;
             }
 
-            let skill = unsafe { (*self.level).options.skill };
+            let options = unsafe { &(*self.level).options };
+            let skill = options.skill;
             match special.sprite {
                 SpriteNum::ARM1 => {
                     if !player.give_armour(1) {
@@ -341,42 +341,60 @@ impl MapObject {
                         player.message = Some(GOTBLUECARD);
                     }
                     player.give_key(Card::Bluecard);
-                    // TODO: if (netgame) return;
+                    if options.netgame {
+                        // keys stay for the other players
+                        return;
+                    }
                 }
                 SpriteNum::YKEY => {
                     if !player.status.cards[Card::Yellowcard as usize] {
                         player.message = Some(GOTYELWCARD);
                     }
                     player.give_key(Card::Yellowcard);
-                    // TODO: if (netgame) return;
+                    if options.netgame {
+                        // keys stay for the other players
+                        return;
+                    }
                 }
                 SpriteNum::RKEY => {
                     if !player.status.cards[Card::Redcard as usize] {
                         player.message = Some(GOTREDCARD);
                     }
                     player.give_key(Card::Redcard);
-                    // TODO: if (netgame) return;
+                    if options.netgame {
+                        // keys stay for the other players
+                        return;
+                    }
                 }
                 SpriteNum::BSKU => {
                     if !player.status.cards[Card::Blueskull as usize] {
                         player.message = Some(GOTBLUESKUL);
                     }
                     player.give_key(Card::Blueskull);
-                    // TODO: if (netgame) return;
+                    if options.netgame {
+                        // keys stay for the other players
+                        return;
+                    }
                 }
                 SpriteNum::YSKU => {
                     if !player.status.cards[Card::Yellowskull as usize] {
                         player.message = Some(GOTYELWSKUL);
                     }
                     player.give_key(Card::Yellowskull);
-                    // TODO: if (netgame) return;
+                    if options.netgame {
+                        // keys stay for the other players
+                        return;
+                    }
                 }
                 SpriteNum::RSKU => {
                     if !player.status.cards[Card::Redskull as usize] {
                         player.message = Some(GOTREDSKULL);
                     }
                     player.give_key(Card::Redskull);
-                    // TODO: if (netgame) return;
+                    if options.netgame {
+                        // keys stay for the other players
+                        return;
+                    }
                 }
                 SpriteNum::STIM => {
                     if !player.give_body(10) {
@@ -510,7 +528,7 @@ impl MapObject {
 
                 // Weapons
                 SpriteNum::BFUG => {
-                    if !player.give_weapon(WeaponType::BFG, false, skill) {
+                    if !player.give_weapon(WeaponType::BFG, false, options) {
                         return;
                     }
                     player.message = Some(GOTBFG9000);
@@ -520,7 +538,7 @@ impl MapObject {
                     if !player.give_weapon(
                         WeaponType::Chaingun,
                         special.flags & MapObjFlag::Dropped as u32 != 0,
-                        skill,
+                        options,
                     ) {
                         return;
                     }
@@ -528,21 +546,21 @@ impl MapObject {
                     sound = SfxName::Wpnup;
                 }
                 SpriteNum::CSAW => {
-                    if !player.give_weapon(WeaponType::Chainsaw, false, skill) {
+                    if !player.give_weapon(WeaponType::Chainsaw, false, options) {
                         return;
                     }
                     player.message = Some(GOTCHAINSAW);
                     sound = SfxName::Wpnup;
                 }
                 SpriteNum::LAUN => {
-                    if !player.give_weapon(WeaponType::Missile, false, skill) {
+                    if !player.give_weapon(WeaponType::Missile, false, options) {
                         return;
                     }
                     player.message = Some(GOTLAUNCHER);
                     sound = SfxName::Wpnup;
                 }
                 SpriteNum::PLAS => {
-                    if !player.give_weapon(WeaponType::Plasma, false, skill) {
+                    if !player.give_weapon(WeaponType::Plasma, false, options) {
                         return;
                     }
                     player.message = Some(GOTPLASMA);
@@ -552,7 +570,7 @@ impl MapObject {
                     if !player.give_weapon(
                         WeaponType::Shotgun,
                         special.flags & MapObjFlag::Dropped as u32 != 0,
-                        skill,
+                        options,
                     ) {
                         return;
                     }
@@ -563,7 +581,7 @@ impl MapObject {
                     if !player.give_weapon(
                         WeaponType::SuperShotgun,
                         special.flags & MapObjFlag::Dropped as u32 != 0,
-                        skill,
+                        options,
                     ) {
                         return;
                     }
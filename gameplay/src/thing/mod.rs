@@ -102,6 +102,8 @@ pub enum MapObjFlag {
     /// lookup table for re-indexing. If 0x4 0x8 or 0xc, use a translation
     /// table for player colormaps
     Translation = 0xC000000,
+    /// BOOM: draw through the translucency table when translucency is enabled.
+    Translucent = 0x80000000,
     /// Hmm ???.
     Transshift = 26,
 }
@@ -136,6 +138,12 @@ pub struct MapObject {
     pub(super) s_next: Option<*mut Thinker>,
     /// Link to the previous `Thinker` in this sector
     pub(super) s_prev: Option<*mut Thinker>,
+    /// Link to the next `Thinker` in the same blockmap block
+    pub(super) b_next: Option<*mut Thinker>,
+    /// Link to the previous `Thinker` in the same blockmap block
+    pub(super) b_prev: Option<*mut Thinker>,
+    /// The blockmap block this is linked in to, if any
+    pub(super) block: Option<usize>,
     /// The subsector this object is currently in. When a map object is spawned
     /// `set_thing_position()` is called which then sets this to a valid
     /// subsector, making this safe in 99% of cases.
@@ -184,9 +192,9 @@ pub struct MapObject {
     /// Additional info record for player avatars only. Only valid if type ==
     /// MT_PLAYER. RUST: If this is not `None` then the pointer is
     /// guaranteed to point to a player
-    player: Option<*mut Player>,
+    pub(crate) player: Option<*mut Player>,
     /// Player number last looked for, 1-4 (does not start at 0)
-    lastlook: usize,
+    pub(crate) lastlook: usize,
     /// For nightmare respawn.
     pub(crate) spawnpoint: WadThing,
     // Thing being chased/attacked for tracers.
@@ -226,7 +234,7 @@ impl Debug for MapObject {
 
 impl MapObject {
     #[allow(clippy::too_many_arguments)]
-    fn new(
+    pub(crate) fn new(
         x: fixed_t,
         y: fixed_t,
         z: fixed_t,
@@ -268,6 +276,9 @@ impl MapObject {
             tracer: None,
             s_next: None,
             s_prev: None,
+            b_next: None,
+            b_prev: None,
+            block: None,
             subsector: unsafe { MapPtr::new_null() },
             state,
             info,
@@ -302,6 +313,15 @@ impl MapObject {
         })
     }
 
+    /// The index of the player this `MapObject` belongs to, if any
+    pub(crate) fn player_num(&self) -> Option<usize> {
+        let player = self.player?;
+        self.level()
+            .players()
+            .iter()
+            .position(|p| std::ptr::eq(p, player))
+    }
+
     pub(crate) fn player_mut(&mut self) -> Option<&mut Player> {
         self.player.map(|p| unsafe {
             #[cfg(feature = "null_check")]
@@ -354,9 +374,8 @@ impl MapObject {
             return;
         }
 
-        // TODO: Properly sort this out
-        let player = &mut players[0];
-        trace!("Spawing player 1");
+        let player = &mut players[(mthing.kind - 1) as usize];
+        trace!("Spawing player {}", mthing.kind);
 
         if player.player_state == PlayerState::Reborn {
             player.reborn();
@@ -397,12 +416,10 @@ impl MapObject {
         // // setup gun psprite
         // TODO: P_SetupPsprites(p);
 
-        // // give all cards in death match mode
-        // if deathmatch {
-        //     for i in 0..Card::NUMCARDS as usize {
-        //         p.cards[i] = true;
-        //     }
-        // }
+        // give all cards in death match mode
+        if level.options.deathmatch != 0 {
+            player.status.cards.fill(true);
+        }
 
         // if mthing.kind - 1 == consoleplayer {
         //     // wake up the status bar
@@ -412,6 +429,137 @@ impl MapObject {
         // }
     }
 
+    /// Respawn a dead player in a netgame. The player spawns at their own
+    /// start, or at the first free start of another player if their own is
+    /// blocked. If every start is blocked they spawn at their own and
+    /// telefrag whatever is there. In deathmatch a random deathmatch start is
+    /// used instead. The old body is left as a corpse.
+    ///
+    /// Doom function name `G_DoReborn`, the netgame part
+    pub fn p_reborn_player(
+        player_num: usize,
+        level: &mut Level,
+        players: &mut [Player],
+        active_players: &[bool; MAXPLAYERS],
+    ) {
+        // first dissasociate the corpse
+        if let Some(mobj) = players[player_num].mobj_mut() {
+            mobj.player = None;
+        }
+
+        if level.options.deathmatch != 0 {
+            Self::p_deathmatch_spawn_player(player_num, level, players, active_players);
+            return;
+        }
+
+        let own = level.player_starts[player_num];
+        let spot = own
+            .filter(|start| Self::check_spot(player_num, start, level, players))
+            .or_else(|| {
+                level
+                    .player_starts
+                    .into_iter()
+                    .flatten()
+                    .find(|start| Self::check_spot(player_num, start, level, players))
+            })
+            .or(own);
+        let Some(mut mthing) = spot else {
+            error!("No player start for player {}", player_num + 1);
+            return;
+        };
+        // Spawn as this player, even when at another player's start
+        mthing.kind = player_num as i16 + 1;
+        Self::p_spawn_player(&mthing, level, players, active_players);
+    }
+
+    /// Spawn every player in the game at a deathmatch start, used once the map
+    /// things are spawned. The player starts are only used if there are no
+    /// free deathmatch starts.
+    pub fn p_spawn_deathmatch_players(
+        level: &mut Level,
+        players: &mut [Player],
+        active_players: &[bool; MAXPLAYERS],
+    ) {
+        for player_num in 0..MAXPLAYERS {
+            if active_players[player_num] {
+                players[player_num].clear_mobj();
+                Self::p_deathmatch_spawn_player(player_num, level, players, active_players);
+            }
+        }
+    }
+
+    /// Spawn the player at a random free deathmatch start, falling back to
+    /// their own player start if none is found in 20 tries.
+    ///
+    /// Doom function name `G_DeathMatchSpawnPlayer`
+    fn p_deathmatch_spawn_player(
+        player_num: usize,
+        level: &mut Level,
+        players: &mut [Player],
+        active_players: &[bool; MAXPLAYERS],
+    ) {
+        let selections = level.deathmatch_p.len();
+        if selections < 4 {
+            warn!("Only {selections} deathmatch spots, 4 required");
+        }
+
+        if selections > 0 {
+            for _ in 0..20 {
+                let mut mthing = level.deathmatch_p[p_random() as usize % selections];
+                if Self::check_spot(player_num, &mthing, level, players) {
+                    mthing.kind = player_num as i16 + 1;
+                    Self::p_spawn_player(&mthing, level, players, active_players);
+                    return;
+                }
+            }
+        }
+
+        // no good spot, so the player will probably get stuck
+        match level.player_starts[player_num] {
+            Some(mthing) => Self::p_spawn_player(&mthing, level, players, active_players),
+            None => error!("No start for player {}", player_num + 1),
+        }
+    }
+
+    /// Returns false if the player cannot be respawned at the given start
+    /// because something is occupying it. If the spot is free a teleport fog
+    /// is spawned there.
+    ///
+    /// Doom function name `G_CheckSpot`
+    fn check_spot(
+        player_num: usize,
+        mthing: &WadThing,
+        level: &mut Level,
+        players: &mut [Player],
+    ) -> bool {
+        let xy = VecF2::new(fixed_t::from_i16(mthing.x), fixed_t::from_i16(mthing.y));
+        let Some(mobj) = players[player_num].mobj_mut() else {
+            // first spawn of the level, check no other player is on the spot
+            return players[..player_num]
+                .iter()
+                .all(|p| p.mobj().is_none_or(|m| m.xy != xy));
+        };
+        let mut ctrl = SubSectorMinMax::default();
+        if !mobj.p_check_position(xy, &mut ctrl) {
+            return false;
+        }
+
+        // spawn a teleport fog
+        let floor = level.map_data.point_in_subsector(xy).sector.floorheight;
+        let an = Angle::from_i16(mthing.angle);
+        let fog = MapObject::spawn_map_object(
+            xy.x + fixed_t::from_int(20) * an.cos(),
+            xy.y + fixed_t::from_int(20) * an.sin(),
+            floor,
+            MapObjKind::MT_TFOG,
+            level,
+        );
+        unsafe {
+            (*fog).start_sound(SfxName::Telept);
+        }
+        true
+    }
+
     /// P_SpawnMapThing
     pub fn p_spawn_map_thing(
         mthing: WadThing,
@@ -439,7 +587,7 @@ impl MapObject {
         }
 
         // check for appropriate skill level
-        if level.options.deathmatch == 0 && mthing.flags & MTF_SINGLE_PLAYER != 0 {
+        if !level.options.netgame && mthing.flags & MTF_SINGLE_PLAYER != 0 {
             return;
         }
         let bit: i16;
@@ -458,7 +606,7 @@ impl MapObject {
         // find which type to spawn
         let mut i = 0;
         for n in 0..MapObjKind::Count as u16 {
-            if mthing.kind == MOBJINFO[n as usize].doomednum as i16 {
+            if mthing.kind == unsafe { MOBJINFO[n as usize].doomednum } as i16 {
                 i = n;
                 break;
             }
@@ -473,7 +621,7 @@ impl MapObject {
 
         // don't spawn keycards and players in deathmatch
         if level.options.deathmatch != 0
-            && MOBJINFO[i as usize].flags & MapObjFlag::Notdmatch as u32 != 0
+            && unsafe { MOBJINFO[i as usize].flags } & MapObjFlag::Notdmatch as u32 != 0
         {
             return;
         }
@@ -482,14 +630,14 @@ impl MapObject {
         let kind = MapObjKind::from(i);
         if no_monsters
             && (kind == MapObjKind::MT_SKULL
-                || MOBJINFO[i as usize].flags & MapObjFlag::Countkill as u32 != 0)
+                || unsafe { MOBJINFO[i as usize].flags } & MapObjFlag::Countkill as u32 != 0)
         {
             return;
         }
 
         let x = fixed_t::from_i16(mthing.x);
         let y = fixed_t::from_i16(mthing.y);
-        let z = if MOBJINFO[i as usize].flags & MapObjFlag::Spawnceiling as u32 != 0 {
+        let z = if unsafe { MOBJINFO[i as usize].flags } & MapObjFlag::Spawnceiling as u32 != 0 {
             ONCEILINGZ
         } else {
             ONFLOORZ
@@ -676,7 +824,7 @@ impl MapObject {
         kind: MapObjKind,
         level: &mut Level,
     ) -> *mut MapObject {
-        let info = MOBJINFO[kind as usize];
+        let info = unsafe { MOBJINFO[kind as usize] };
         let reactiontime = if level.options.skill != Skill::Nightmare {
             info.reactiontime
         } else {
@@ -761,39 +909,50 @@ impl MapObject {
         true
     }
 
-    /// P_UnsetThingPosition, unlink the thing from the sector
+    /// P_UnsetThingPosition, unlink the thing from the sector and blockmap
     ///
     /// # Safety
     /// Thing must have had a SubSector set on creation.
     pub(crate) unsafe fn unset_thing_position(&mut self) {
-        if MOBJINFO[self.kind as usize].flags & MapObjFlag::Nosector as u32 == 0 {
+        if unsafe { MOBJINFO[self.kind as usize].flags } & MapObjFlag::Nosector as u32 == 0 {
             let mut ss = self.subsector.clone();
             unsafe {
                 ss.sector.remove_from_thinglist(self.thinker_mut());
             }
         }
+        if self.block.is_some() {
+            let level = unsafe { &mut *self.level };
+            level
+                .map_data
+                .blockmap_mut()
+                .remove_from_thinglist(self.thinker_mut());
+        }
     }
 
-    /// P_SetThingPosition, unlink the thing from the sector
+    /// P_SetThingPosition, link the thing in to the sector and blockmap
     ///
     /// # Safety
     /// Thing must have had a SubSector set on creation.
     pub(crate) unsafe fn set_thing_position(&mut self) {
         let level = unsafe { &mut *self.level };
         let mut subsector = level.map_data.point_in_subsector_raw(self.xy);
-        if MOBJINFO[self.kind as usize].flags & MapObjFlag::Nosector as u32 == 0 {
+        if unsafe { MOBJINFO[self.kind as usize].flags } & MapObjFlag::Nosector as u32 == 0 {
             unsafe { subsector.sector.add_to_thinglist(self.thinker) }
         }
         self.subsector = subsector;
+        if self.flags & MapObjFlag::Noblockmap as u32 == 0 && !level.map_data.blockmap().is_empty()
+        {
+            unsafe { level.map_data.blockmap_mut().add_to_thinglist(self.thinker) }
+        }
     }
 
     /// P_RemoveMobj
     pub(crate) fn remove(&mut self) {
-        // Respawn specials for nightmare/deathmatch
+        // Respawn specials for altdeath
         if (self.flags & MapObjFlag::Special as u32 != 0
             && self.flags & MapObjFlag::Dropped as u32 == 0)
             && (self.kind != MapObjKind::MT_INV && self.kind != MapObjKind::MT_INS)
-            && (self.level().options.respawn_monsters || self.level().options.deathmatch != 0)
+            && self.level().options.deathmatch == 2
         {
             let time = self.level().level_time;
             let respawn = self.spawnpoint;
@@ -874,8 +1033,8 @@ impl MapObject {
                 unsafe { &mut *self.level },
             );
             unsafe {
-                (*mobj).momxy.x = fixed_t::from_int(p_subrandom()) * fixed_t::from_float(0.6); // P_SubRandom() << 12;
-                (*mobj).momxy.y = fixed_t::from_int(p_subrandom()) * fixed_t::from_float(0.6);
+                (*mobj).momxy.x = fixed_t::new(p_subrandom() << 12);
+                (*mobj).momxy.y = fixed_t::new(p_subrandom() << 12);
             }
         }
 
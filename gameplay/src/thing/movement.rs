@@ -8,7 +8,7 @@ use std::ptr;
 use glam::Vec2;
 use log::{debug, error, info};
 
-use crate::doom_def::{FLOATSPEED, USERANGE, VIEWHEIGHT};
+use crate::doom_def::{FLOATSPEED, MAXRADIUS, USERANGE, VIEWHEIGHT};
 use crate::env::specials::cross_special_line;
 use crate::env::switch::p_use_special_line;
 use crate::info::StateNum;
@@ -28,6 +28,8 @@ pub const GRAVITY: fixed_t = fixed_t::from_int(1);
 pub const MAXMOVE: fixed_t = fixed_t::from_int(30);
 pub const STOPSPEED: fixed_t = fixed_t::new(0x00001000);
 pub const FRICTION: fixed_t = fixed_t::new(0x0000E800);
+/// Default multiplier for player thrust, BOOM sectors with friction change it
+pub const FRICTION_FACTOR: i32 = 2048;
 
 //const MAXSPECIALCROSS: i32 = 8;
 pub const PT_ADDLINES: i32 = 1;
@@ -100,7 +102,7 @@ impl MapObject {
                     // and utter appropriate sound.
                     unsafe {
                         let player = &mut *(self.player.unwrap());
-                        player.viewheight = self.momz / FT_EIGHT;
+                        player.deltaviewheight = self.momz >> 3;
                     }
                 }
                 self.momz = FT_ZERO;
@@ -174,9 +176,17 @@ impl MapObject {
         let mut ymove = self.momxy.y;
         let mut ptryx;
         let mut ptryy;
+        let vanilla = self.level().options.vanilla_physics;
 
         while xmove != FT_ZERO || ymove != FT_ZERO {
-            if xmove.abs() > MAXMOVE / FT_TWO || ymove.abs() > MAXMOVE / FT_TWO {
+            if vanilla && (xmove > MAXMOVE / FT_TWO || ymove > MAXMOVE / FT_TWO) {
+                // The original only splits positive moves, and halves with a shift
+                ptryx = self.xy.x + xmove / FT_TWO;
+                ptryy = self.xy.y + ymove / FT_TWO;
+                xmove = xmove >> 1;
+                ymove = ymove >> 1;
+            } else if !vanilla && (xmove.abs() > MAXMOVE / FT_TWO || ymove.abs() > MAXMOVE / FT_TWO)
+            {
                 ptryx = self.xy.x + xmove / FT_TWO;
                 ptryy = self.xy.y + ymove / FT_TWO;
                 xmove /= FT_TWO;
@@ -257,7 +267,7 @@ impl MapObject {
             }
             self.momxy = VecF2::default();
         } else {
-            self.momxy *= FRICTION;
+            self.momxy *= self.subsector.sector.floor_friction().0;
         }
     }
 
@@ -319,7 +329,8 @@ impl MapObject {
         }
 
         if self.flags & (MapObjFlag::Teleport as u32 | MapObjFlag::Noclip as u32) == 0 {
-            for ld in &ctrl.spec_hits {
+            // Checked last to first, as the original does
+            for ld in ctrl.spec_hits.iter().rev() {
                 // see if the line was crossed
                 let side = ld.point_on_side(self.xy);
                 let old_side = ld.point_on_side(old_xy);
@@ -359,6 +370,11 @@ impl MapObject {
             return true;
         }
 
+        if !level.map_data.blockmap().is_empty() {
+            return self.block_check_position(&tmbbox, endpoint, ctrl);
+        }
+
+        // Maps without a blockmap fall back to a
         // BSP walk to find all subsectors between two points
         // Pretty much replaces the block iterators
         //
@@ -383,13 +399,32 @@ impl MapObject {
         // TODO: modify so that first line that is blocking makes function return
         bsp_trace.find_intercepts(level.map_data.start_node(), &level.map_data, &mut count);
 
+        // The original checks every thing before any line, and each only once
+        if level.options.vanilla_physics {
+            let mut checked = Vec::new();
+            for n in bsp_trace.intercepted_subsectors() {
+                let ssect = &mut level.map_data.subsectors_mut()[*n as usize];
+                if checked.contains(&ssect.sector.inner) {
+                    continue;
+                }
+                checked.push(ssect.sector.inner);
+                if !ssect
+                    .sector
+                    .run_mut_func_on_thinglist(|thing| self.pit_check_thing(thing, endpoint, ctrl))
+                {
+                    return false;
+                }
+            }
+        }
+
         for n in bsp_trace.intercepted_subsectors() {
             let ssect = &mut level.map_data.subsectors_mut()[*n as usize];
 
             // Check things in subsectors
-            if !ssect
-                .sector
-                .run_mut_func_on_thinglist(|thing| self.pit_check_thing(thing, endpoint, ctrl))
+            if !level.options.vanilla_physics
+                && !ssect
+                    .sector
+                    .run_mut_func_on_thinglist(|thing| self.pit_check_thing(thing, endpoint, ctrl))
             {
                 return false;
             }
@@ -406,6 +441,52 @@ impl MapObject {
         true
     }
 
+    /// Check the things and then the lines in the blocks that `tmbbox` touches.
+    /// Things are checked in blocks extended by `MAXRADIUS` as a thing is only
+    /// linked in to the block its centre is in.
+    fn block_check_position(
+        &mut self,
+        tmbbox: &BBox,
+        endpoint: VecF2,
+        ctrl: &mut SubSectorMinMax,
+    ) -> bool {
+        let level = unsafe { &mut *self.level };
+        let blockmap = level.map_data.blockmap_mut();
+
+        let x_low = blockmap.block_x(tmbbox.left - MAXRADIUS);
+        let x_high = blockmap.block_x(tmbbox.right + MAXRADIUS);
+        let y_low = blockmap.block_y(tmbbox.bottom - MAXRADIUS);
+        let y_high = blockmap.block_y(tmbbox.top + MAXRADIUS);
+        for bx in x_low..=x_high {
+            for by in y_low..=y_high {
+                if !blockmap
+                    .things_iterator(bx, by, |thing| self.pit_check_thing(thing, endpoint, ctrl))
+                {
+                    return false;
+                }
+            }
+        }
+
+        level.valid_count = level.valid_count.wrapping_add(1);
+        let valid_count = level.valid_count;
+        let vanilla = level.options.vanilla_physics;
+        let blockmap = level.map_data.blockmap_mut();
+        let x_low = blockmap.block_x(tmbbox.left);
+        let x_high = blockmap.block_x(tmbbox.right);
+        let y_low = blockmap.block_y(tmbbox.bottom);
+        let y_high = blockmap.block_y(tmbbox.top);
+        for bx in x_low..=x_high {
+            for by in y_low..=y_high {
+                if !blockmap.lines_iterator(bx, by, valid_count, vanilla, |line| {
+                    self.pit_check_line(tmbbox, ctrl, line)
+                }) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
     /// Thing is generally the target.
     ///
     /// Function is intended to function similar to `PIT_CheckThing`
@@ -450,7 +531,7 @@ impl MapObject {
             if self.z > thing.z + thing.height {
                 return true; // over
             }
-            if self.z + thing.height < thing.z {
+            if self.z + self.height < thing.z {
                 return true; // under
             }
 
@@ -497,6 +578,7 @@ impl MapObject {
         if (thing.flags & MapObjFlag::Shootable as u32 != 0
             || thing.flags & MapObjFlag::Solid as u32 != 0)
             && self.player().is_some()
+            && !self.level().options.vanilla_physics
         {
             // Already over it?
             let thing_top_z = thing.z + thing.height;
@@ -584,17 +666,9 @@ impl MapObject {
             ctrl.max_dropoff = portal.lowest_z;
         }
 
-        if ld.special != 0 {
-            for l in ctrl.spec_hits.iter() {
-                let ptr = MapPtr::new(ld);
-                if l.inner as usize != ptr.inner as usize {
-                    ctrl.spec_hits.push(ptr);
-                    break;
-                }
-            }
-            if ctrl.spec_hits.is_empty() {
-                ctrl.spec_hits.push(MapPtr::new(ld));
-            }
+        // A line can be in more than one of the subsectors checked
+        if ld.special != 0 && !ctrl.spec_hits.iter().any(|l| ptr::eq(l.inner, ld)) {
+            ctrl.spec_hits.push(MapPtr::new(ld));
         }
 
         true
@@ -606,7 +680,6 @@ impl MapObject {
     fn p_slide_move(&mut self) {
         // let ctrl = &mut level.mobj_ctrl;
         let mut hitcount = 0;
-        self.best_slide = BestSlide::new();
 
         let leadx;
         let leady;
@@ -635,6 +708,7 @@ impl MapObject {
                 self.stair_step();
                 return;
             }
+            self.best_slide = BestSlide::new();
 
             // tail to front, centered
             let mut bsp_trace = BSPTrace::new_line(self.xy, self.xy + self.momxy, self.radius);
@@ -666,7 +740,7 @@ impl MapObject {
                 &mut bsp_trace,
             );
 
-            if self.best_slide.best_slide_frac == FT_TWO {
+            if self.best_slide.best_slide_frac == BestSlide::new().best_slide_frac {
                 // The move most have hit the middle, so stairstep.
                 self.stair_step();
                 return;
@@ -780,7 +854,7 @@ impl MapObject {
 
         let mut line_angle = point_to_angle_2(VecF2::default(), line.delta);
 
-        let side = line.point_on_side(*slide_move);
+        let side = line.point_on_side(self.xy);
         if side == 1 {
             line_angle += Angle::new(ANG180);
         }
@@ -796,10 +870,10 @@ impl MapObject {
         //     move_angle -= FRAC_PI_2;
         // }
 
-        let delta_angle = move_angle - line_angle;
-        // if delta_angle.rad() > FRAC_PI_2 {
-        //     delta_angle += FRAC_PI_2;
-        // }
+        let mut delta_angle = move_angle - line_angle;
+        if delta_angle.0 > ANG180 {
+            delta_angle += Angle::new(ANG180);
+        }
 
         let move_dist = slide_move.length();
         let new_dist = move_dist * delta_angle.cos();
@@ -1093,24 +1167,143 @@ const DIR_DIAGONALS: [MoveDir; 4] = [
     MoveDir::SouthEast,
 ];
 
+/// The diagonals are 47000, a little more than 0.707, same as the original
 const DIR_XFSPEED: [fixed_t; 8] = [
     FT_ONE,
-    fixed_t::from_float(0.47),
+    fixed_t::new(47000),
     FT_ZERO,
-    fixed_t::from_float(-0.47),
+    fixed_t::new(-47000),
     fixed_t::from_int(-1),
-    fixed_t::from_float(0.47),
+    fixed_t::new(-47000),
     FT_ZERO,
-    fixed_t::from_float(0.47),
+    fixed_t::new(47000),
 ];
 
 const DIR_YFSPEED: [fixed_t; 8] = [
     FT_ZERO,
-    fixed_t::from_float(0.47),
+    fixed_t::new(47000),
     FT_ONE,
-    fixed_t::from_float(0.47),
+    fixed_t::new(47000),
     FT_ZERO,
-    fixed_t::from_float(-0.47),
+    fixed_t::new(-47000),
     fixed_t::from_int(-1),
-    fixed_t::from_float(-0.47),
+    fixed_t::new(-47000),
 ];
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::channel;
+
+    use math::m_clear_random;
+    use wad::WadData;
+
+    use crate::doom_def::GameMode;
+    use crate::level::Level;
+    use crate::tic_cmd::TicCmd;
+    use crate::{
+        GameOptions, MapObject, PicData, Player, PlayerState, Skill, respawn_specials,
+        spawn_specials, update_specials,
+    };
+
+    /// Where the player ended up, in the raw fixed point and BAM values
+    #[derive(Debug, PartialEq)]
+    struct DemoEnd {
+        x: i32,
+        y: i32,
+        z: i32,
+        angle: u32,
+        tics: usize,
+    }
+
+    /// Play a v1.9 demo lump without any display or sound, the same way the
+    /// game ticker does. Stops early if the level ends or the player dies,
+    /// either is a desync as vanilla plays the IWAD demos to the end marker.
+    fn play_demo(wad: &WadData, name: &str) -> DemoEnd {
+        let demo = &wad.get_lump(name).unwrap().data;
+        assert_eq!(demo[0], 109);
+        let in_game = [demo[9] == 1, demo[10] == 1, demo[11] == 1, demo[12] == 1];
+        assert_eq!(in_game, [true, false, false, false]);
+
+        let options = GameOptions {
+            skill: Skill::from(demo[1]),
+            episode: demo[2] as usize,
+            map: demo[3] as usize,
+            vanilla_physics: true,
+            ..GameOptions::default()
+        };
+        let map_name = format!("E{}M{}", options.episode, options.map);
+
+        let mut pic_data = PicData::init(false, wad);
+        let mut players = [
+            Player::default(),
+            Player::default(),
+            Player::default(),
+            Player::default(),
+        ];
+        let (tx, _rx) = channel();
+        let mut level = Box::new(unsafe {
+            Level::new_empty(options, GameMode::Shareware, tx, &in_game, &mut players)
+        });
+        m_clear_random();
+        level.load(&map_name, GameMode::Shareware, &mut pic_data, wad);
+        let things = level.map_data.things().to_owned();
+        for thing in things {
+            MapObject::p_spawn_map_thing(thing, false, &mut level, &mut players, &in_game);
+        }
+        spawn_specials(&mut level);
+
+        let mut tics = 0;
+        for cmd in demo[13..].chunks_exact(4).take_while(|cmd| cmd[0] != 0x80) {
+            players[0].cmd = TicCmd {
+                forwardmove: cmd[0] as i8,
+                sidemove: cmd[1] as i8,
+                angleturn: (cmd[2] as i16) << 8,
+                buttons: cmd[3],
+                ..TicCmd::new()
+            };
+            players[0].think(&mut level);
+            unsafe {
+                let lev = &mut *(level.as_mut() as *mut Level);
+                level.thinkers.run_thinkers(lev);
+            }
+            level.level_time += 1;
+            update_specials(&mut level, &mut pic_data);
+            respawn_specials(&mut level);
+            tics += 1;
+
+            if level.game_action.is_some() || players[0].player_state == PlayerState::Dead {
+                break;
+            }
+        }
+        assert_ne!(players[0].player_state, PlayerState::Dead, "{name} died");
+        let mobj = players[0].mobj().unwrap();
+        DemoEnd {
+            x: mobj.xy.x.0,
+            y: mobj.xy.y.0,
+            z: mobj.z.0,
+            angle: mobj.angle.0,
+            tics,
+        }
+    }
+
+    /// The tic of the `0x80` end marker, vanilla plays every tic up to it
+    fn demo_end_tic(wad: &WadData, name: &str) -> usize {
+        let demo = &wad.get_lump(name).unwrap().data;
+        demo[13..]
+            .chunks_exact(4)
+            .take_while(|cmd| cmd[0] != 0x80)
+            .count()
+    }
+
+    #[test]
+    fn demo_playback_is_deterministic() {
+        let wad = WadData::new("../doom1.wad".into());
+        for name in ["DEMO1", "DEMO2", "DEMO3"] {
+            let end = play_demo(&wad, name);
+            // Ending the level early stops before the marker
+            assert_eq!(end.tics, demo_end_tic(&wad, name), "{name}");
+            // Every run must end in exactly the same place
+            assert_eq!(play_demo(&wad, name), end, "{name}");
+        }
+    }
+}
@@ -25,7 +25,7 @@ impl MapObject {
     pub(crate) fn p_explode_missile(&mut self) {
         self.momxy = VecF2::default();
         self.momz = FT_ZERO;
-        self.set_state(MOBJINFO[self.kind as usize].deathstate);
+        self.set_state(unsafe { MOBJINFO[self.kind as usize].deathstate });
 
         self.tics -= p_random() & 3;
 
@@ -216,7 +216,7 @@ impl MapObject {
         bullet_slope: Option<AimResult>,
         bsp_trace: &mut BSPTrace,
     ) {
-        let damage = 5.0 * (p_random() % 3 + 1) as f32;
+        let damage = 5 * (p_random() % 3 + 1);
         let mut angle = self.angle;
 
         if !accurate {
@@ -224,9 +224,9 @@ impl MapObject {
         }
 
         if let Some(res) = bullet_slope {
-            self.shoot_line_attack(distance, angle, res.aimslope, damage as i32, bsp_trace);
+            self.shoot_line_attack(distance, angle, res.aimslope, damage, bsp_trace);
         } else {
-            self.shoot_line_attack(distance, angle, FT_ZERO, damage as i32, bsp_trace);
+            self.shoot_line_attack(distance, angle, FT_ZERO, damage, bsp_trace);
         }
     }
 
@@ -1,9 +1,8 @@
 #[cfg(feature = "hprof")]
 use coarse_prof::profile;
 use log::{debug, error, trace};
-use std::alloc::{Layout, alloc, dealloc};
+use std::alloc::{Layout, alloc, dealloc, handle_alloc_error};
 use std::fmt::{self, Debug};
-use std::mem::{align_of, size_of};
 use std::ptr::{self, null_mut};
 
 use crate::env::ceiling::CeilingMove;
@@ -42,29 +41,46 @@ impl Think for TestObject {
     }
 }
 
-/// A custom allocation for `Thinker` objects. This intends to keep them in a
-/// contiguous zone of memory.
+/// The fewest slots added when a `ThinkerAlloc` runs out of space
+const MIN_GROWTH: usize = 128;
+
+/// One block of `Thinker` slots. A block is never moved or freed until the
+/// `ThinkerAlloc` is dropped.
+struct Chunk {
+    ptr: *mut Thinker,
+    len: usize,
+}
+
+/// A custom allocation for `Thinker` objects. This intends to keep them in
+/// contiguous zones of memory. When every slot is used another chunk is
+/// allocated, the existing chunks never move so raw pointers to a `Thinker`
+/// stay valid.
 pub struct ThinkerAlloc {
-    /// The main AllocPool buffer
-    buf_ptr: *mut Thinker,
-    /// Total capacity. Not possible to allocate over this.
+    /// The AllocPool buffers, in slot index order
+    chunks: Vec<Chunk>,
+    /// Total slots across all chunks. Grows when a push needs more.
     capacity: usize,
     /// Actual used AllocPool
     len: usize,
-    /// The next free slot to insert in
-    next_free: *mut Thinker,
+    /// Index of the next slot to check when inserting
+    next_free: usize,
     head: *mut Thinker,
 }
 
 impl Drop for ThinkerAlloc {
     fn drop(&mut self) {
         unsafe {
-            for idx in 0..self.capacity {
-                self.drop_item(idx);
+            for chunk in self.chunks.iter() {
+                for idx in 0..chunk.len {
+                    if std::mem::needs_drop::<Thinker>() {
+                        ptr::drop_in_place(chunk.ptr.add(idx));
+                    }
+                }
+                dealloc(
+                    chunk.ptr as *mut _,
+                    Layout::array::<Thinker>(chunk.len).unwrap(),
+                );
             }
-            let size = self.capacity * size_of::<Thinker>();
-            let layout = Layout::from_size_align_unchecked(size, align_of::<Thinker>());
-            dealloc(self.buf_ptr as *mut _, layout);
         }
     }
 }
@@ -76,32 +92,64 @@ impl ThinkerAlloc {
     /// `ThinkerAlloc` never moves. Accessor methods are available to get the
     /// inner data.
     ///
+    /// `capacity` is only the starting size, a `push` when full allocates more.
+    ///
     /// # Safety
     /// Once allocated the owner of this `ThinkerAlloc` must not move.
     pub(crate) unsafe fn new(capacity: usize) -> Self {
+        let mut thinkers = Self {
+            chunks: Vec::new(),
+            capacity: 0,
+            len: 0,
+            next_free: 0,
+            head: null_mut(),
+        };
+        if capacity > 0 {
+            thinkers.grow(capacity);
+        }
+        thinkers
+    }
+
+    /// Add a chunk of `size` free slots after the existing ones
+    fn grow(&mut self, size: usize) {
+        let layout = Layout::array::<Thinker>(size).unwrap();
         unsafe {
-            let size = capacity * size_of::<Thinker>();
-            let layout = Layout::from_size_align_unchecked(size, align_of::<Thinker>());
-            let buf_ptr = alloc(layout) as *mut Thinker;
+            let ptr = alloc(layout) as *mut Thinker;
+            if ptr.is_null() {
+                handle_alloc_error(layout);
+            }
 
             // Need to initialise everything to a blank slate
-            for n in 0..capacity {
-                buf_ptr.add(n).write(Thinker {
+            for n in 0..size {
+                ptr.add(n).write(Thinker {
                     prev: null_mut(),
                     next: null_mut(),
                     data: ThinkerData::Free,
                     func: Thinker::placeholder,
                 })
             }
-
-            Self {
-                buf_ptr,
-                capacity,
-                len: 0,
-                next_free: buf_ptr,
-                head: null_mut(),
-            }
+            self.chunks.push(Chunk { ptr, len: size });
         }
+        debug!(
+            "Thinker capacity grown from {} to {}",
+            self.capacity,
+            self.capacity + size
+        );
+        self.capacity += size;
+    }
+
+    /// The number of live thinkers in the allocation
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Total slots available
+    pub fn capacity(&self) -> usize {
+        self.capacity
     }
 
     pub unsafe fn run_thinkers(&mut self, level: &mut Level) {
@@ -130,6 +178,9 @@ impl ThinkerAlloc {
 
     /// Returns false if `func` returns false
     pub fn run_fn_on_things(&mut self, mut func: impl FnMut(&mut Thinker) -> bool) -> bool {
+        if self.head.is_null() {
+            return true;
+        }
         unsafe {
             let mut current = &mut *self.head;
             let mut next;
@@ -153,6 +204,27 @@ impl ThinkerAlloc {
         true
     }
 
+    /// Immutable version of `run_fn_on_things` for readers of the level state
+    /// such as the automap. Returns false if `func` returns false
+    pub fn for_each_thing(&self, mut func: impl FnMut(&Thinker) -> bool) -> bool {
+        if self.head.is_null() {
+            return true;
+        }
+        unsafe {
+            let mut current = &*self.head;
+            loop {
+                if !current.should_remove() && !func(current) {
+                    return false;
+                }
+                current = &*current.next;
+                if ptr::eq(current, self.head) {
+                    break;
+                }
+            }
+        }
+        true
+    }
+
     /// Iterates through the list of thinkers until either the closure returns
     /// true or the end is reached.
     pub(crate) fn find_thinker<F>(&self, finder: F) -> Option<&mut Thinker>
@@ -203,60 +275,59 @@ impl ThinkerAlloc {
         }
     }
 
-    unsafe fn drop_item(&mut self, idx: usize) {
+    fn ptr_for_idx(&self, mut idx: usize) -> *mut Thinker {
         debug_assert!(idx < self.capacity);
-        let ptr = self.ptr_for_idx(idx);
-        if std::mem::needs_drop::<Thinker>() {
-            unsafe {
-                ptr::drop_in_place(ptr);
+        for chunk in self.chunks.iter() {
+            if idx < chunk.len {
+                return unsafe { chunk.ptr.add(idx) };
             }
+            idx -= chunk.len;
         }
+        null_mut()
     }
 
-    fn ptr_for_idx(&self, idx: usize) -> *mut Thinker {
-        unsafe { self.buf_ptr.add(idx) }
+    fn idx_for_ptr(&self, ptr: *const Thinker) -> Option<usize> {
+        let mut start = 0;
+        for chunk in self.chunks.iter() {
+            let end = unsafe { chunk.ptr.add(chunk.len) };
+            if ptr >= chunk.ptr && ptr < end {
+                return Some(start + unsafe { ptr.offset_from(chunk.ptr) } as usize);
+            }
+            start += chunk.len;
+        }
+        None
     }
 
-    /// `final_loop` should always be false when called the first time
-    fn find_first_free(&mut self, mut final_loop: bool) -> Option<*mut Thinker> {
+    /// Find a free slot, starting from `next_free` and wrapping around. The
+    /// pool is grown if every slot is in use.
+    fn find_first_free(&mut self) -> *mut Thinker {
         if self.len >= self.capacity {
-            return None;
+            self.next_free = self.capacity;
+            self.grow(self.capacity.max(MIN_GROWTH));
+            return self.ptr_for_idx(self.next_free);
         }
 
-        unsafe {
-            let max = self.buf_ptr.add(self.capacity - 1);
-            loop {
-                if self.next_free == max {
-                    break;
-                }
-                if matches!((*self.next_free).data, ThinkerData::Free) {
-                    return Some(self.next_free);
-                }
-                self.next_free = self.next_free.add(1);
+        for n in 0..self.capacity {
+            let idx = (self.next_free + n) % self.capacity;
+            let ptr = self.ptr_for_idx(idx);
+            if matches!(unsafe { &(*ptr).data }, ThinkerData::Free) {
+                self.next_free = idx;
+                return ptr;
             }
         }
-        if !final_loop {
-            final_loop = true;
-            self.next_free = self.buf_ptr;
-            return self.find_first_free(final_loop);
-        }
-
-        panic!("No more thinker slots");
+        panic!("No free thinker slots while len < capacity");
     }
 
-    /// Push a Thinker to the `ThinkerAlloc`. Returns a mutable ref to the
-    /// Thinker.
+    /// Push a Thinker to the `ThinkerAlloc`, growing it if it is full. Returns
+    /// a mutable ref to the Thinker.
     pub(crate) fn push<T: Think>(&mut self, thinker: Thinker) -> Option<&mut Thinker> {
-        if self.len == self.capacity {
-            return None;
-        }
         if matches!(thinker.data, ThinkerData::Free) {
             panic!(
                 "Can't push a thinker with `Thinker::Free` as the inner data. Please use `Thinker::Remove` to initiate removal, or use `remove()`"
             );
         }
 
-        let root_ptr = self.find_first_free(false)?;
+        let root_ptr = self.find_first_free();
         trace!("Pushing: {:?}", root_ptr);
         match &thinker.data {
             ThinkerData::MapObject(mobj) => {
@@ -284,7 +355,7 @@ impl ThinkerAlloc {
 
         current.set_obj_thinker_ptr();
         self.len += 1;
-        self.next_free = unsafe { self.next_free.add(1) };
+        self.next_free += 1;
         unsafe { Some(&mut *root_ptr) }
     }
 
@@ -307,7 +378,9 @@ impl ThinkerAlloc {
             (*thinker.prev).next = thinker.next;
 
             self.len -= 1;
-            self.next_free = thinker; // reuse the slot on next insert
+            if let Some(idx) = self.idx_for_ptr(thinker) {
+                self.next_free = idx; // reuse the slot on next insert
+            }
             self.maybe_reset_head();
 
             ptr::write(
@@ -722,20 +795,66 @@ mod tests {
         }
 
         unsafe {
-            dbg!(&*links.buf_ptr.add(0));
-            dbg!(&*links.buf_ptr.add(1));
-            dbg!(&*links.buf_ptr.add(2));
-            dbg!(&*links.buf_ptr.add(62));
+            dbg!(&*links.ptr_for_idx(0));
+            dbg!(&*links.ptr_for_idx(1));
+            dbg!(&*links.ptr_for_idx(2));
+            dbg!(&*links.ptr_for_idx(62));
 
-            assert!(matches!((*links.buf_ptr.add(0)).data, ThinkerData::Remove));
-            assert!(matches!((*links.buf_ptr.add(1)).data, ThinkerData::Free));
-            assert!(matches!((*links.buf_ptr.add(2)).data, ThinkerData::Free));
+            assert!(matches!((*links.ptr_for_idx(0)).data, ThinkerData::Remove));
+            assert!(matches!((*links.ptr_for_idx(1)).data, ThinkerData::Free));
+            assert!(matches!((*links.ptr_for_idx(2)).data, ThinkerData::Free));
 
             links.remove(&mut *think);
             assert_eq!(links.len, 0);
         }
     }
 
+    #[test]
+    fn push_past_capacity() {
+        let mut links = unsafe { ThinkerAlloc::new(2) };
+        let mut ptrs = Vec::new();
+        for x in 0..5 {
+            let think = links
+                .push::<TestObject>(TestObject::create_thinker(
+                    ThinkerData::TestObject(TestObject {
+                        x,
+                        thinker: null_mut(),
+                    }),
+                    TestObject::think,
+                ))
+                .unwrap() as *mut Thinker;
+            ptrs.push(think);
+        }
+        assert_eq!(links.len, 5);
+        assert!(links.capacity >= 5);
+
+        // Earlier thinkers didn't move, and think order is push order
+        let mut order = Vec::new();
+        links.run_fn_on_things(|t| {
+            order.push(t as *mut Thinker);
+            true
+        });
+        assert_eq!(order, ptrs);
+        unsafe {
+            assert_eq!((*ptrs[0]).data.bad_ref::<TestObject>().x, 0);
+            assert_eq!((*ptrs[4]).data.bad_ref::<TestObject>().x, 4);
+
+            // A freed slot in the first chunk is used again
+            links.remove(&mut *ptrs[1]);
+        }
+        let think = links
+            .push::<TestObject>(TestObject::create_thinker(
+                ThinkerData::TestObject(TestObject {
+                    x: 5,
+                    thinker: null_mut(),
+                }),
+                TestObject::think,
+            ))
+            .unwrap() as *mut Thinker;
+        assert_eq!(think, ptrs[1]);
+        assert_eq!(links.len, 5);
+    }
+
     #[test]
     fn check_next_prev_links() {
         let mut links = unsafe { ThinkerAlloc::new(64) };
@@ -779,23 +898,29 @@ mod tests {
 
         unsafe {
             // forward
-            assert!((*links.buf_ptr).should_remove());
-            assert_eq!((*(*links.buf_ptr).next).data.bad_ref::<TestObject>().x, 666);
+            assert!((*links.ptr_for_idx(0)).should_remove());
+            assert_eq!(
+                (*(*links.ptr_for_idx(0)).next)
+                    .data
+                    .bad_ref::<TestObject>()
+                    .x,
+                666
+            );
             assert_eq!(
-                (*(*(*links.buf_ptr).next).next)
+                (*(*(*links.ptr_for_idx(0)).next).next)
                     .data
                     .bad_ref::<TestObject>()
                     .x,
                 123
             );
             assert_eq!(
-                (*(*(*(*links.buf_ptr).next).next).next)
+                (*(*(*(*links.ptr_for_idx(0)).next).next).next)
                     .data
                     .bad_ref::<TestObject>()
                     .x,
                 333
             );
-            assert!((*(*(*(*(*links.buf_ptr).next).next).next).next).should_remove());
+            assert!((*(*(*(*(*links.ptr_for_idx(0)).next).next).next).next).should_remove());
             // back
             assert!((*links.head).should_remove());
             assert_eq!((*(*links.head).prev).data.bad_ref::<TestObject>().x, 333);
@@ -878,7 +1003,7 @@ mod tests {
     //     }
     //     unsafe {
     //         assert_eq!(
-    //             (*links.buf_ptr.add(3))
+    //             (*links.ptr_for_idx(3))
     //                 .as_ref()
     //                 .unwrap()
     //                 .object
@@ -83,10 +83,12 @@ pub struct BestSlide {
 }
 
 impl BestSlide {
+    /// The fraction starts just past a full move, if it is unchanged after a
+    /// trace then nothing was hit
     #[inline]
     pub fn new() -> Self {
         BestSlide {
-            best_slide_frac: FT_ONE,
+            best_slide_frac: fixed_t::new(FT_ONE.0 + 1),
             ..Default::default()
         }
     }
@@ -2,20 +2,26 @@
 //! intermission screens to get certain information they require or cause a
 //! gamestate change.
 
+pub mod scale;
 pub mod util;
 
 use gameplay::MAXPLAYERS;
 pub use gameplay::{
-    AmmoType, Card, GameMode, PlayerCheat, PlayerStatus, PowerType, Skill, TICRATE, WEAPON_INFO,
-    WeaponType, WorldEndPlayerInfo, m_random,
+    AmmoType, Card, GameMode, Level, LineDefFlags, MOBJINFO, MapObjInfo, MapObjKind, PicData,
+    Player, PlayerCheat, PlayerState, PlayerStatus, PowerType, STATES, Skill, SpritePic, StateNum,
+    TICRATE, WEAPON_INFO, WeaponType, WorldEndPlayerInfo, deh_bex_string, deh_cheat, deh_string,
+    english, m_random,
 };
-pub use render_trait::{PixelBuffer, PlayViewRenderer, RenderTrait};
+pub use input::config::{Action, InputConfigSdl};
+pub use render_trait::{BufferSize, PixelBuffer, PlayViewRenderer, RenderApiType, RenderTrait};
+pub use scale::{Anchor, ScreenScale};
 pub use sdl2::keyboard::Scancode;
 pub use sdl2::{self};
 pub use sound_traits::{MusTrack, SfxName};
 
 use wad::WadData;
 use wad::types::{WadPalette, WadPatch};
+use wad::umapinfo::MapInfo;
 
 /// The current state of the game-exe: whether we are playing, gazing at the
 /// intermission screen, the game-exe final animation, or a demo.
@@ -33,6 +39,63 @@ pub enum GameState {
     DemoScreen,
 }
 
+/// Savegame files are named `doomsav<slot>.dsg`
+pub const SAVEGAME_NAME: &str = "doomsav";
+
+/// The file name used for a savegame slot
+pub fn savegame_file(slot: usize) -> String {
+    format!("{SAVEGAME_NAME}{slot}.dsg")
+}
+
+/// Settings that can be changed from the menus. The game-exe is expected to
+/// persist these in the user config.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Settings {
+    /// Sound effect volume, 0-128
+    pub sfx_volume: i32,
+    /// Music volume, 0-128
+    pub mus_volume: i32,
+    /// Mouse sensitivity, 0-9
+    pub mouse_sensitivity: i32,
+    /// Screen size, 10 shows the statusbar and 11 hides it. The player view is
+    /// never shrunk as the statusbar is an overlay, so the smaller vanilla
+    /// sizes aren't used
+    pub screen_size: i32,
+    /// Show player messages such as item pickups
+    pub show_messages: bool,
+    /// Render at double resolution (the detail setting)
+    pub hi_res: bool,
+    /// The window size when not fullscreen
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+    pub renderer: RenderApiType,
+    /// Size of the statusbar and messages as a percentage of the full screen
+    /// scale, 50-100
+    pub hud_scale: i32,
+    /// Stretch the 320x200 screens to 4:3 as they were originally shown
+    pub aspect_correct: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            sfx_volume: 80,
+            mus_volume: 70,
+            mouse_sensitivity: 5,
+            screen_size: 10,
+            show_messages: true,
+            hi_res: true,
+            width: 640,
+            height: 480,
+            fullscreen: true,
+            renderer: RenderApiType::Software,
+            hud_scale: 100,
+            aspect_correct: true,
+        }
+    }
+}
+
 /// parms for world level / intermission
 #[derive(Default, Clone)]
 pub struct WorldInfo {
@@ -43,6 +106,17 @@ pub struct WorldInfo {
     /// previous and next levels, origin 0
     pub last: usize,
     pub next: usize,
+    /// episode of the next level, origin 0. Only differs from `episode` if
+    /// UMAPINFO moves to another episode
+    pub next_episode: usize,
+    /// left the level by a secret exit
+    pub secret_exit: bool,
+    /// the game ends after this level, by default or by UMAPINFO
+    pub end_game: bool,
+    /// UMAPINFO for the level just finished
+    pub last_info: Option<MapInfo>,
+    /// UMAPINFO for the next level
+    pub next_info: Option<MapInfo>,
     pub maxkills: i32,
     pub maxitems: i32,
     pub maxsecret: i32,
@@ -51,6 +125,8 @@ pub struct WorldInfo {
     pub partime: i32,
     /// index of this player in game-exe
     pub pnum: usize,
+    /// show the frags of each player instead of the level stats
+    pub deathmatch: bool,
     pub plyr: [WorldEndPlayerInfo; MAXPLAYERS],
 }
 
@@ -65,12 +141,24 @@ pub trait GameTraits {
     /// screens that Doom II doesn't have (for example).
     fn get_mode(&self) -> GameMode;
 
-    /// Ask the game to load this save
+    /// Ask the game to load this save. The name is a path, or a file name in
+    /// the save directory such as one from `savegame_file()`
     fn load_game(&mut self, name: String);
 
     /// Ask the game to save to this slot with this name
     fn save_game(&mut self, name: String, slot: usize);
 
+    /// The description of the savegame in this slot, if there is one
+    fn savegame_description(&self, slot: usize) -> Option<String>;
+
+    /// Is a game being played that was started by the user (not a demo)?
+    fn is_user_game(&self) -> bool;
+
+    fn is_netgame(&self) -> bool;
+
+    /// End the current game and go back to the title screen
+    fn end_game(&mut self);
+
     /// Pauses the game-loop (generally stops gameplay input and thinkers
     /// running)
     fn toggle_pause_game(&mut self);
@@ -84,10 +172,16 @@ pub trait GameTraits {
     /// Change to or play this music track
     fn change_music(&self, music: MusTrack);
 
+    /// Play the song in this lump, e.g, one named by UMAPINFO. Returns false
+    /// if there is no lump by that name
+    fn change_music_lump(&self, name: &str) -> bool;
+
     /// Tell the game that the level is completed and the next level or state
     /// should begin
     fn level_done(&mut self);
 
+    /// Tell the game the finale text is done. The next level begins unless the
+    /// game has ended, in which case the finale stays up with its end screens
     fn finale_done(&mut self);
 
     /// Fetch the end-of-level information
@@ -99,12 +193,49 @@ pub trait GameTraits {
     /// Fetch the basic player statistics (player 1)
     fn player_status(&self) -> PlayerStatus;
 
+    /// The frag count of player 1, or `None` if this isn't a deathmatch
+    fn player_frags(&self) -> Option<i32>;
+
     /// Takes the player message waiting and replaces with None
     fn player_msg_take(&mut self) -> Option<String>;
 
+    /// Set a message to show the player (player 1)
+    fn player_msg_set(&mut self, msg: &'static str);
+
     fn get_wad_data(&self) -> &WadData;
 
-    // TODO: get and set settings Struct
+    /// The textures, flats and sprites loaded from the WAD
+    fn pic_data(&self) -> &PicData;
+
+    /// The level currently being played, if one is loaded
+    fn level(&self) -> Option<&Level>;
+
+    /// The level name shown on the automap, e.g, `E1M1: Hangar`
+    fn level_title(&self) -> String;
+
+    /// The player being viewed (player 1)
+    fn player(&self) -> &Player;
+
+    /// Is the automap being shown in place of the player view?
+    fn automap_active(&self) -> bool;
+
+    /// Show or hide the automap
+    fn set_automap_active(&mut self, active: bool);
+
+    fn settings(&self) -> &Settings;
+
+    /// Replace the settings, applying any that the game itself controls such
+    /// as the sound volumes
+    fn set_settings(&mut self, settings: Settings);
+
+    /// The keys and mouse buttons bound to each action
+    fn key_bindings(&self) -> &InputConfigSdl;
+
+    /// Replace the key bindings, such as after rebinding in the menu
+    fn set_key_bindings(&mut self, bindings: InputConfigSdl);
+
+    /// Save the next frame drawn as a screenshot
+    fn screenshot(&mut self);
 }
 
 /// To be implemented by machination type things (HUD, Map, Statusbar)
@@ -123,32 +254,15 @@ pub trait SubsystemTrait {
     /// Draw this Machination to the `PixelBuf`.
     fn draw(&mut self, buffer: &mut impl PixelBuffer);
 
+    /// Where the 320x200 screen this machination draws on sits in the buffer.
+    /// The default fills as much of the buffer as possible.
+    fn screen_scale(&self, size: &BufferSize) -> ScreenScale {
+        ScreenScale::full(size)
+    }
+
     /// Free method, requires `get_palette()` to be implemented
     fn draw_patch_pixels(&self, patch: &WadPatch, x: i32, y: i32, pixels: &mut impl PixelBuffer) {
-        let f = pixels.size().height() / 200;
-        let fx = pixels.size().width() / 320;
-
-        let mut xtmp = 0;
-        let mut ytmp = 0;
-
-        for column in patch.columns.iter() {
-            if column.y_offset == 255 {
-                xtmp += 1;
-            } else {
-                for p in column.pixels.iter() {
-                    for ny in 0..f {
-                        for nx in 0..fx {
-                            let colour = self.get_palette().0[*p];
-                            let x = ((x + xtmp - patch.left_offset as i32) * fx + nx).unsigned_abs()
-                                as usize;
-                            let y = ((y + ytmp + column.y_offset) * f + ny).unsigned_abs() as usize;
-                            pixels.set_pixel(x, y, &colour);
-                        }
-                    }
-                    ytmp += 1;
-                }
-            }
-            ytmp = 0;
-        }
+        let scale = self.screen_scale(pixels.size());
+        scale::draw_patch(patch, x, y, &scale, &self.get_palette().0, pixels);
     }
 }
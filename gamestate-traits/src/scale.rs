@@ -0,0 +1,170 @@
+//! Fitting the 320x200 screen that Doom draws its menus, HUD, and other screens
+//! on to a pixel buffer of any size. The screen is scaled by a fractional
+//! amount to fit, and centred with black bars at the sides (pillarbox) or top
+//! and bottom (letterbox).
+
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+use render_trait::{BufferSize, PixelBuffer};
+use wad::types::{WadColour, WadPatch};
+
+/// Doom's 320x200 screen was shown on 4:3 displays, so each pixel was drawn
+/// 1.2 times taller than it was wide
+const PIXEL_ASPECT: f32 = 1.2;
+
+static ASPECT_CORRECT: AtomicBool = AtomicBool::new(true);
+/// Percentage of the full screen scale that the HUD is drawn at
+static HUD_SCALE: AtomicI32 = AtomicI32::new(100);
+
+/// Set how screens are fitted to the buffer, typically from the `Settings`.
+/// `hud_scale` is a percentage of the full screen scale.
+pub fn set_screen_scaling(aspect_correct: bool, hud_scale: i32) {
+    ASPECT_CORRECT.store(aspect_correct, Ordering::Relaxed);
+    HUD_SCALE.store(hud_scale.clamp(10, 100), Ordering::Relaxed);
+}
+
+/// The point that a HUD drawn smaller than the full screen stays at
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    BottomLeft,
+    /// Bottom centre, such as for the statusbar
+    Bottom,
+}
+
+/// Maps a position on the 320x200 Doom screen to the buffer
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ScreenScale {
+    /// Buffer pixels per Doom screen pixel
+    pub x: f32,
+    pub y: f32,
+    /// Where the Doom screen starts in the buffer
+    pub x_offset: f32,
+    pub y_offset: f32,
+}
+
+impl ScreenScale {
+    /// The largest the Doom screen can be drawn while fitting in the buffer,
+    /// centred
+    pub fn full(size: &BufferSize) -> Self {
+        let aspect = if ASPECT_CORRECT.load(Ordering::Relaxed) {
+            PIXEL_ASPECT
+        } else {
+            1.0
+        };
+        // Fit to the height unless the buffer is too narrow
+        let y = (size.height_f32() / 200.0).min(size.width_f32() * aspect / 320.0);
+        let x = y / aspect;
+        Self {
+            x,
+            y,
+            x_offset: (size.width_f32() - 320.0 * x) / 2.0,
+            y_offset: (size.height_f32() - 200.0 * y) / 2.0,
+        }
+    }
+
+    /// The full screen scale reduced by the HUD scale, kept at `anchor`
+    pub fn hud(size: &BufferSize, anchor: Anchor) -> Self {
+        let full = Self::full(size);
+        let f = HUD_SCALE.load(Ordering::Relaxed) as f32 / 100.0;
+        let (x, y) = (full.x * f, full.y * f);
+        let bottom = full.y_offset + 200.0 * (full.y - y);
+        match anchor {
+            Anchor::TopLeft => Self {
+                x,
+                y,
+                x_offset: full.x_offset,
+                y_offset: full.y_offset,
+            },
+            Anchor::BottomLeft => Self {
+                x,
+                y,
+                x_offset: full.x_offset,
+                y_offset: bottom,
+            },
+            Anchor::Bottom => Self {
+                x,
+                y,
+                x_offset: (size.width_f32() - 320.0 * x) / 2.0,
+                y_offset: bottom,
+            },
+        }
+    }
+
+    /// The buffer position of a point on the Doom screen
+    pub fn to_buffer(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.x_offset + x * self.x, self.y_offset + y * self.y)
+    }
+
+    /// The Doom screen position of a point in the buffer
+    pub fn from_buffer(&self, x: f32, y: f32) -> (f32, f32) {
+        ((x - self.x_offset) / self.x, (y - self.y_offset) / self.y)
+    }
+}
+
+/// Draw one pixel of the Doom screen. It covers every buffer pixel whose
+/// centre it contains, so fractional scales have no gaps. Anything outside the
+/// buffer is clipped.
+pub fn draw_pixel(
+    x: i32,
+    y: i32,
+    scale: &ScreenScale,
+    colour: &WadColour,
+    pixels: &mut impl PixelBuffer,
+) {
+    let (x, y) = (x as f32, y as f32);
+    let x0 = ((scale.x_offset + x * scale.x).round() as i32).max(0);
+    let x1 = ((scale.x_offset + (x + 1.0) * scale.x).round() as i32).min(pixels.size().width());
+    let y0 = ((scale.y_offset + y * scale.y).round() as i32).max(0);
+    let y1 = ((scale.y_offset + (y + 1.0) * scale.y).round() as i32).min(pixels.size().height());
+    for py in y0..y1 {
+        for px in x0..x1 {
+            pixels.set_pixel(px as usize, py as usize, colour);
+        }
+    }
+}
+
+/// Draw a patch at a position on the Doom screen
+pub fn draw_patch(
+    patch: &WadPatch,
+    x: i32,
+    y: i32,
+    scale: &ScreenScale,
+    palette: &[WadColour],
+    pixels: &mut impl PixelBuffer,
+) {
+    let mut xtmp = 0;
+    for column in patch.columns.iter() {
+        if column.y_offset == 255 {
+            xtmp += 1;
+            continue;
+        }
+        let sx = x + xtmp - patch.left_offset as i32;
+        for (ytmp, p) in column.pixels.iter().enumerate() {
+            let sy = y + column.y_offset + ytmp as i32;
+            draw_pixel(sx, sy, scale, &palette[*p], pixels);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_scale_pillarboxes_widescreen() {
+        set_screen_scaling(true, 100);
+        // 16:9 hi-res buffer
+        let scale = ScreenScale::full(&BufferSize::new(711, 400));
+        assert_eq!(scale.y, 2.0);
+        assert!((scale.x - 2.0 / 1.2).abs() < 0.001);
+        assert!((scale.x_offset - (711.0 - 320.0 * scale.x) / 2.0).abs() < 0.001);
+        assert_eq!(scale.y_offset, 0.0);
+        let (x, y) = scale.to_buffer(320.0, 200.0);
+        assert!((x - (711.0 - scale.x_offset)).abs() < 0.001);
+        assert_eq!(y, 400.0);
+        let (x, y) = scale.from_buffer(scale.x_offset, 400.0);
+        assert!(x.abs() < 0.001);
+        assert_eq!(y, 200.0);
+    }
+}
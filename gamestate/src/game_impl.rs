@@ -1,8 +1,12 @@
 use crate::Game;
-use gameplay::{GameAction, GameMode, Skill, WorldEndPlayerInfo};
-use gamestate_traits::{GameTraits, PlayerStatus, WorldInfo};
+use gameplay::english::{MAPNAMES, MAPNAMES2, MSGOFF, MSGON};
+use gameplay::log::warn;
+use gameplay::{
+    GameAction, GameMode, Level, PicData, Player, Skill, WorldEndPlayerInfo, deh_string,
+};
+use gamestate_traits::{GameTraits, InputConfigSdl, PlayerStatus, Settings, WorldInfo};
 use math::FT_ZERO;
-use sound_traits::{EPISODE4_MUS, MusTrack, SfxName, SoundAction};
+use sound_traits::{EPISODE4_MUS, MUS_DATA, MusTrack, SfxName, SoundAction};
 use wad::WadData;
 
 impl GameTraits for Game {
@@ -26,12 +30,36 @@ impl GameTraits for Game {
         self.game_type.mode
     }
 
-    fn load_game(&mut self, _name: String) {
-        todo!()
+    /// Doom function name `G_LoadGame`
+    fn load_game(&mut self, name: String) {
+        self.save_name = name;
+        self.pending_action = GameAction::LoadGame;
     }
 
-    fn save_game(&mut self, _name: String, _slot: usize) {
-        todo!()
+    /// Doom function name `G_SaveGame`
+    fn save_game(&mut self, name: String, slot: usize) {
+        self.save_description = name;
+        self.save_slot = slot;
+        self.pending_action = GameAction::SaveGame;
+    }
+
+    fn savegame_description(&self, slot: usize) -> Option<String> {
+        self.read_savegame_description(slot)
+    }
+
+    fn is_user_game(&self) -> bool {
+        self.usergame
+    }
+
+    fn is_netgame(&self) -> bool {
+        self.options.netgame
+    }
+
+    /// Doom function name `M_EndGameResponse`
+    fn end_game(&mut self) {
+        self.level = None;
+        self.usergame = false;
+        self.start_title();
     }
 
     fn toggle_pause_game(&mut self) {
@@ -39,6 +67,7 @@ impl GameTraits for Game {
     }
 
     fn quit_game(&mut self) {
+        self.check_demo_status();
         self.set_running(false);
     }
 
@@ -53,6 +82,13 @@ impl GameTraits for Game {
     }
 
     fn change_music(&self, mus: MusTrack) {
+        if mus == MusTrack::None
+            && let Some(music) = self.map_info().and_then(|info| info.music.as_ref())
+            && self.change_music_lump(music)
+        {
+            return;
+        }
+
         let music = if mus == MusTrack::None {
             if self.game_type.mode == GameMode::Commercial {
                 MusTrack::Runnin as usize + self.options.map - 1
@@ -70,6 +106,31 @@ impl GameTraits for Game {
             .unwrap();
     }
 
+    fn change_music_lump(&self, name: &str) -> bool {
+        // Songs that replace one of the tracks are already loaded
+        let track =
+            (0..MusTrack::NumMus as usize).find(|i| unsafe { MUS_DATA[*i].lump_name() } == name);
+        if let Some(track) = track {
+            self.sound_cmd
+                .send(SoundAction::ChangeMusic(track, true))
+                .unwrap();
+            return true;
+        }
+
+        let Some(lump) = self.wad_data.get_lump(name) else {
+            warn!("Music lump {name} is missing");
+            return false;
+        };
+        self.sound_cmd
+            .send(SoundAction::ChangeMusicLump(
+                name.to_string(),
+                lump.data.clone(),
+                true,
+            ))
+            .unwrap();
+        true
+    }
+
     /// Doom function name `G_WorldDone`
     fn level_done(&mut self) {
         self.pending_action = GameAction::WorldDone;
@@ -78,6 +139,24 @@ impl GameTraits for Game {
                 p.didsecret = true;
             }
         }
+        if self.world_info.end_game {
+            self.pending_action = GameAction::Victory;
+            return;
+        }
+        if let Some(info) = &self.world_info.last_info {
+            let text = if self.world_info.secret_exit {
+                &info.inter_text_secret
+            } else {
+                &info.inter_text
+            };
+            // Empty if the default text was cleared
+            if let Some(text) = text {
+                if !text.is_empty() {
+                    self.pending_action = GameAction::Victory;
+                }
+                return;
+            }
+        }
         if matches!(self.game_type.mode, GameMode::Commercial) {
             match self.world_info.last {
                 6 | 11 | 15 | 20 | 30 | 31 => {
@@ -95,7 +174,9 @@ impl GameTraits for Game {
     }
 
     fn finale_done(&mut self) {
-        self.pending_action = GameAction::WorldDone;
+        if !self.world_info.end_game {
+            self.pending_action = GameAction::WorldDone;
+        }
     }
 
     fn level_end_info(&self) -> &WorldInfo {
@@ -110,14 +191,110 @@ impl GameTraits for Game {
         self.players[self.consoleplayer].status.clone()
     }
 
+    fn player_frags(&self) -> Option<i32> {
+        (self.options.deathmatch != 0)
+            .then(|| self.players[self.consoleplayer].frag_count(self.consoleplayer))
+    }
+
     fn player_msg_take(&mut self) -> Option<String> {
-        self.players[self.consoleplayer]
-            .message
-            .take()
-            .map(|s| s.to_string())
+        let msg = self.players[self.consoleplayer].message.take()?;
+        if !self.settings.show_messages && !std::mem::take(&mut self.force_message) {
+            return None;
+        }
+        Some(deh_string(msg).to_string())
+    }
+
+    fn player_msg_set(&mut self, msg: &'static str) {
+        self.players[self.consoleplayer].message = Some(msg);
     }
 
     fn get_wad_data(&self) -> &WadData {
         &self.wad_data
     }
+
+    fn pic_data(&self) -> &PicData {
+        &self.pic_data
+    }
+
+    fn level(&self) -> Option<&Level> {
+        self.level.as_ref()
+    }
+
+    fn level_title(&self) -> String {
+        if let Some(info) = self.map_info()
+            && let Some(name) = &info.level_name
+        {
+            return match &info.label {
+                Some(label) if label.is_empty() => name.clone(),
+                Some(label) => format!("{label}: {name}"),
+                None => format!("{}: {name}", info.map_name),
+            };
+        }
+
+        let (episode, map) = (self.options.episode, self.options.map);
+        let name = if self.game_type.mode == GameMode::Commercial {
+            MAPNAMES2.get(map - 1)
+        } else if map <= 9 {
+            MAPNAMES.get((episode - 1) * 9 + map - 1)
+        } else {
+            None
+        };
+        name.map(|name| deh_string(name).to_string())
+            .unwrap_or_else(|| self.map_name(episode, map))
+    }
+
+    fn player(&self) -> &Player {
+        &self.players[self.consoleplayer]
+    }
+
+    fn automap_active(&self) -> bool {
+        self.automap
+    }
+
+    fn set_automap_active(&mut self, active: bool) {
+        self.automap = active;
+    }
+
+    fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Doom function names `M_SfxVol`, `M_MusicVol`, `M_ChangeMessages`
+    fn set_settings(&mut self, settings: Settings) {
+        if settings.sfx_volume != self.settings.sfx_volume {
+            self.sound_cmd
+                .send(SoundAction::SfxVolume(settings.sfx_volume))
+                .unwrap();
+        }
+        if settings.mus_volume != self.settings.mus_volume {
+            self.sound_cmd
+                .send(SoundAction::MusicVolume(settings.mus_volume))
+                .unwrap();
+        }
+        if settings.show_messages != self.settings.show_messages {
+            self.players[self.consoleplayer].message = Some(if settings.show_messages {
+                MSGON
+            } else {
+                MSGOFF
+            });
+            self.force_message = true;
+        }
+        self.settings = settings;
+    }
+
+    fn key_bindings(&self) -> &InputConfigSdl {
+        &self.key_bindings
+    }
+
+    fn set_key_bindings(&mut self, bindings: InputConfigSdl) {
+        self.key_bindings = bindings;
+    }
+
+    /// Set directly instead of with `GameAction::Screenshot` so that it also
+    /// works while the menu has paused the game.
+    ///
+    /// Doom function name `G_ScreenShot`
+    fn screenshot(&mut self) {
+        self.take_screenshot = true;
+    }
 }
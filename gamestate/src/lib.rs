@@ -21,20 +21,25 @@
 //! player view is drawn.
 
 pub mod game_impl;
+pub mod net;
+mod savegame;
 pub mod subsystems;
 
 use crate::subsystems::GameSubsystem;
 use gameplay::log::{debug, error, info, trace, warn};
 use gameplay::tic_cmd::{TIC_CMD_BUTTONS, TicCmd};
 use gameplay::{
-    GameAction, GameMission, GameMode, GameOptions, Level, MAXPLAYERS, MapObject, PicData, Player,
-    PlayerState, STATES, Skill, StateNum, m_clear_random, respawn_specials, spawn_specials,
-    update_specials,
+    CPARS, DOOM_VERSION, Dehacked, GameAction, GameMission, GameMode, GameOptions, Level,
+    MAXPLAYERS, MapObject, PARS, PicData, Player, PlayerState, Skill, TICRATE, m_clear_random,
+    respawn_specials, set_fast_monsters, spawn_specials, update_specials,
 };
 use gamestate_traits::sdl2::AudioSubsystem;
-use gamestate_traits::{GameState, GameTraits, SubsystemTrait, WorldInfo};
+use gamestate_traits::{
+    GameState, GameTraits, InputConfigSdl, Settings, SubsystemTrait, WorldInfo,
+};
 use sound_nosnd::SndServerTx;
 use std::iter::Peekable;
+use std::path::PathBuf;
 use std::thread::JoinHandle;
 use std::time::Duration;
 use std::vec::IntoIter;
@@ -43,6 +48,7 @@ use math::FT_ONE;
 use sound_traits::{MusTrack, SoundAction, SoundServer, SoundServerTic};
 use wad::WadData;
 use wad::types::WadPatch;
+use wad::umapinfo::{MapInfo, UMapInfo, map_numbers};
 
 pub const DEMO_MARKER: u8 = 0x80;
 pub const BACKUPTICS: usize = 12;
@@ -64,6 +70,17 @@ pub struct DemoData {
     sequence: i8,
     buffer: Peekable<IntoIter<u8>>,
     name: String,
+    /// Demo being recorded?
+    recording: bool,
+    /// The header and tic commands recorded so far
+    record_buffer: Vec<u8>,
+    /// Where the recording is written when it ends
+    record_path: PathBuf,
+    /// Quit when the demo ends instead of going back to the demo loop
+    single: bool,
+    /// The respawn, fast, and no monsters params from the command line. A
+    /// demo sets its own and these are put back when it ends.
+    params: (bool, bool, bool),
 }
 
 /// Details used for the demo screens (title, help, ordering)
@@ -163,21 +180,38 @@ pub struct Game {
     /// The state is picked up in `d_main`.
     pub wipe_game_state: GameState,
 
-    /// If non-zero, exit the level after this number of minutes.
-    _time_limit: Option<i32>,
+    /// If set, exit deathmatch levels after this number of minutes.
+    time_limit: Option<i32>,
     /// Intermission and world/map end data, used to show map and world stats,
     /// and queue up the next map or episode.
     world_info: WorldInfo,
+    /// Map progression, names, music etc. from `UMAPINFO` lumps
+    umapinfo: UMapInfo,
     /// d_net.c
     pub netcmds: [[TicCmd; BACKUPTICS]; MAXPLAYERS],
-    /// d_net.c
-    _localcmds: [TicCmd; BACKUPTICS],
+    /// The state of each player when each buffered tic was run, to check
+    /// netgame nodes stay in sync. d_net.c
+    consistancy: [[i16; BACKUPTICS]; MAXPLAYERS],
     usergame: bool,
     game_skill: Skill,
     pub paused: bool,
 
     /// The options the game-exe exe was started with
     pub options: GameOptions,
+    /// Savegame to load on the next `GameAction::LoadGame`
+    save_name: String,
+    /// Description and slot for the next `GameAction::SaveGame`
+    save_description: String,
+    save_slot: usize,
+    /// Settings changed from the menus
+    settings: Settings,
+    /// The keys and mouse buttons bound to each action
+    key_bindings: InputConfigSdl,
+    /// Save the next frame drawn as a screenshot
+    pub take_screenshot: bool,
+    /// Show the next player message even if messages are off, such as the
+    /// one saying messages were turned off
+    force_message: bool,
     /// Sound tx
     pub sound_cmd: SndServerTx,
     snd_thread: Option<JoinHandle<()>>,
@@ -193,12 +227,12 @@ impl Drop for Game {
 }
 
 impl Game {
+    /// Set up the game. Without an `snd_ctx` the game runs with no sound.
     pub fn new(
         mut options: GameOptions,
         mut wad: WadData,
-        snd_ctx: AudioSubsystem,
-        sfx_vol: i32,
-        mus_vol: i32,
+        snd_ctx: Option<AudioSubsystem>,
+        settings: Settings,
     ) -> Game {
         let game_type = GameType::identify_version(&wad);
 
@@ -258,6 +292,25 @@ impl Game {
             }
         }
 
+        for lump in wad.get_lumps("DEHACKED") {
+            info!("Applying DEHACKED lump");
+            Dehacked::parse(&lump.data).apply();
+        }
+        for path in options.deh.iter() {
+            match std::fs::read(path) {
+                Ok(data) => {
+                    Dehacked::parse(&data).apply();
+                    info!("Applied: {}", path);
+                }
+                Err(e) => error!("Could not read dehacked file {path}: {e}"),
+            }
+        }
+
+        let umapinfo = wad.read_umapinfo();
+        if !umapinfo.is_empty() {
+            info!("Loaded UMAPINFO");
+        }
+
         // Mimic the OG output
         println!(
             "\nROOM-4-DOOM v{}. Playing {}",
@@ -288,9 +341,22 @@ impl Game {
 
         info!("Init playloop state.");
 
+        let snd_sdl = match snd_ctx {
+            Some(snd_ctx) => match sound_sdl2::Snd::new(snd_ctx, &wad) {
+                Ok(s) => Some(s),
+                Err(e) => {
+                    warn!("Could not set up sound server: {e}");
+                    None
+                }
+            },
+            None => {
+                info!("No audio device, sound is disabled");
+                None
+            }
+        };
         let snd_thread;
-        let snd_tx = match sound_sdl2::Snd::new(snd_ctx, &wad) {
-            Ok(mut s) => {
+        let snd_tx = match snd_sdl {
+            Some(mut s) => {
                 let tx = s.init().unwrap();
                 snd_thread = std::thread::spawn(move || {
                     loop {
@@ -299,12 +365,13 @@ impl Game {
                         }
                     }
                 });
-                tx.send(SoundAction::SfxVolume(sfx_vol)).unwrap();
-                tx.send(SoundAction::MusicVolume(mus_vol)).unwrap();
+                tx.send(SoundAction::SfxVolume(settings.sfx_volume))
+                    .unwrap();
+                tx.send(SoundAction::MusicVolume(settings.mus_volume))
+                    .unwrap();
                 tx
             }
-            Err(e) => {
-                warn!("Could not set up sound server: {e}");
+            None => {
                 let mut s = sound_nosnd::Snd::new(&wad).unwrap();
                 let tx = s.init().unwrap();
                 snd_thread = std::thread::spawn(move || {
@@ -329,7 +396,8 @@ impl Game {
 
         let lump = wad.get_lump("TITLEPIC").expect("TITLEPIC missing");
         let page_cache = WadPatch::from_lump(lump);
-        let pic_data = PicData::init(false, &wad);
+        let mut pic_data = PicData::init(false, &wad);
+        pic_data.set_translucent_missiles(options.translucency);
 
         Game {
             wad_data: wad,
@@ -341,6 +409,11 @@ impl Game {
                 name: String::new(),
                 advance: false,
                 sequence: 0,
+                recording: false,
+                record_buffer: Vec::new(),
+                record_path: PathBuf::new(),
+                single: false,
+                params: (options.respawn_parm, options.fast_parm, options.no_monsters),
             },
             page: PageData {
                 name: "TITLEPIC",
@@ -371,16 +444,24 @@ impl Game {
             gamestate: GameState::ForceWipe,
             // Initial state is changed later, here doesn't matter
             wipe_game_state: GameState::DemoScreen,
-            _time_limit: None,
+            time_limit: options.time_limit,
             world_info: WorldInfo::default(),
+            umapinfo,
 
             netcmds: [[TicCmd::new(); BACKUPTICS]; MAXPLAYERS],
-            _localcmds: [TicCmd::new(); BACKUPTICS],
+            consistancy: [[0; BACKUPTICS]; MAXPLAYERS],
 
             usergame: false,
             game_skill: Skill::default(),
             paused: false,
             options,
+            save_name: String::new(),
+            save_description: String::new(),
+            save_slot: 0,
+            settings,
+            key_bindings: InputConfigSdl::default(),
+            take_screenshot: false,
+            force_message: false,
             sound_cmd: snd_tx,
             snd_thread: Some(snd_thread),
         }
@@ -394,10 +475,6 @@ impl Game {
         self.running = run;
     }
 
-    pub fn is_netgame(&self) -> bool {
-        self.options.netgame
-    }
-
     pub fn game_skill(&self) -> Skill {
         self.game_skill
     }
@@ -417,10 +494,21 @@ impl Game {
 
         self.init_new();
         self.pending_action = GameAction::None;
+        if self.demo.recording && self.demo.record_buffer.is_empty() {
+            self.begin_recording();
+        }
     }
 
     fn init_new(&mut self) {
-        debug!("Entered init_new");
+        self.prepare_new();
+        info!("Begin new game!");
+        self.do_load_level();
+    }
+
+    /// Reset the game state and skill adjustments ready for a level load. This
+    /// is the first half of `G_InitNew`, the level load is left to the caller.
+    fn prepare_new(&mut self) {
+        debug!("Entered prepare_new");
 
         if self.paused {
             self.paused = false;
@@ -472,29 +560,7 @@ impl Game {
         self.options.respawn_monsters =
             self.options.skill == Skill::Nightmare || self.options.respawn_parm;
 
-        let game_skill = self.game_skill();
-        let skill = self.options.skill;
-
-        if skill == Skill::Nightmare && game_skill != Skill::Nightmare {
-            for i in StateNum::SARG_RUN1 as usize..StateNum::SARG_PAIN2 as usize {
-                unsafe {
-                    STATES[i].tics >>= 1;
-                }
-            }
-            // TODO: mut mobj info
-            // mobjinfo[MT_BRUISERSHOT].speed = 20 * FRACUNIT;
-            // mobjinfo[MT_HEADSHOT].speed = 20 * FRACUNIT;
-            // mobjinfo[MT_TROOPSHOT].speed = 20 * FRACUNIT;
-        } else if skill != Skill::Nightmare && game_skill == Skill::Nightmare {
-            for i in StateNum::SARG_RUN1 as usize..StateNum::SARG_PAIN2 as usize {
-                unsafe {
-                    STATES[i].tics <<= 1;
-                }
-            }
-            // mobjinfo[MT_BRUISERSHOT].speed = 15 * FRACUNIT;
-            // mobjinfo[MT_HEADSHOT].speed = 10 * FRACUNIT;
-            // mobjinfo[MT_TROOPSHOT].speed = 10 * FRACUNIT;
-        }
+        set_fast_monsters(self.options.fast_monsters());
 
         // force players to be initialized upon first level load
         for player in self.players.iter_mut() {
@@ -506,14 +572,17 @@ impl Game {
         self.demo.playback = false;
         self.automap = false;
         self.usergame = true; // will be set false if a demo
-
-        info!("Begin new game!");
-        self.do_load_level();
     }
 
     /// Doom function name `G_DoLoadLevel`
     fn do_load_level(&mut self) {
-        debug!("Entered do_load_level");
+        self.load_level(true);
+    }
+
+    /// Load the current map. If `spawn_things` is false the map things are not
+    /// spawned, this is used when the thinkers are to be restored from a save.
+    fn load_level(&mut self, spawn_things: bool) {
+        debug!("Entered load_level");
         if self.wipe_game_state == GameState::Level {
             self.wipe_game_state = GameState::ForceWipe;
         }
@@ -522,10 +591,8 @@ impl Game {
         for player in self.players.iter_mut() {
             if player.player_state == PlayerState::Dead {
                 player.player_state = PlayerState::Reborn;
-                for i in 0..player.frags.len() {
-                    player.frags[i] = 0;
-                }
             }
+            player.frags.fill(0);
             // Player setup from P_SetupLevel
             player.total_kills = 0;
             player.secrets_found = 0;
@@ -538,19 +605,15 @@ impl Game {
         self.pending_action = GameAction::None;
 
         // Verify and set the map number + name
-        let map_name = if self.game_type.mode == GameMode::Commercial {
-            if self.options.map < 10 {
-                format!("MAP0{}", self.options.map)
-            } else {
-                format!("MAP{}", self.options.map)
-            }
-        } else {
-            format!("E{}M{}", self.options.episode, self.options.map)
-        };
+        let map_name = self.map_name(self.options.episode, self.options.map);
 
+        // Demos only stay in sync with the original physics
+        let mut options = self.options.clone();
+        options.vanilla_physics |= self.demo.playback;
+        options.no_skull_limit &= !self.demo.playback;
         let level = unsafe {
             Level::new_empty(
-                self.options.clone(),
+                options,
                 self.game_type.mode,
                 self.sound_cmd.clone(),
                 &self.players_in_game,
@@ -571,19 +634,36 @@ impl Game {
                 &mut self.pic_data,
                 &self.wad_data,
             );
+            if let Some(sky) = self
+                .umapinfo
+                .get(&map_name)
+                .and_then(|info| info.sky_texture.as_ref())
+                && !self.pic_data.set_sky_texture(sky)
+            {
+                warn!("UMAPINFO: sky texture {sky} for {map_name} is missing");
+            }
 
             // Pointer stuff must be set up *AFTER* the level data has been allocated
             // (it moves when punted to Some<Level>)
-            let thing_list = (*level.map_data.things()).to_owned();
-
-            for thing in &thing_list {
-                MapObject::p_spawn_map_thing(
-                    *thing,
-                    self.options.no_monsters,
-                    level,
-                    &mut self.players,
-                    &self.players_in_game,
-                );
+            if spawn_things {
+                let thing_list = (*level.map_data.things()).to_owned();
+
+                for thing in &thing_list {
+                    MapObject::p_spawn_map_thing(
+                        *thing,
+                        self.options.no_monsters,
+                        level,
+                        &mut self.players,
+                        &self.players_in_game,
+                    );
+                }
+                if self.options.deathmatch != 0 {
+                    MapObject::p_spawn_deathmatch_players(
+                        level,
+                        &mut self.players,
+                        &self.players_in_game,
+                    );
+                }
             }
             spawn_specials(level);
 
@@ -604,21 +684,32 @@ impl Game {
         self.change_music(MusTrack::None);
     }
 
-    fn do_reborn(&mut self, _player_num: usize) {
-        info!("Player respawned");
-        self.pending_action = GameAction::LoadLevel;
-        // self.players[player_num].
-        // TODO: deathmatch spawns
+    /// The lump name of a map, e.g, `E1M1` or `MAP01`
+    fn map_name(&self, episode: usize, map: usize) -> String {
+        if self.game_type.mode == GameMode::Commercial {
+            format!("MAP{map:0>2}")
+        } else {
+            format!("E{episode}M{map}")
+        }
     }
 
-    /// G_DoLoadGame
-    fn do_load_game(&mut self) {
-        todo!("do_load_game");
+    /// The UMAPINFO entry for the current map, if there is one
+    fn map_info(&self) -> Option<&MapInfo> {
+        self.umapinfo
+            .get(&self.map_name(self.options.episode, self.options.map))
     }
 
-    /// G_DoSaveGame
-    fn do_save_game(&mut self) {
-        todo!("do_save_game");
+    /// Doom function name `G_DoReborn`
+    fn do_reborn(&mut self, player_num: usize) {
+        info!("Player {} respawned", player_num + 1);
+        if !self.options.netgame {
+            // reload the level from scratch
+            self.pending_action = GameAction::LoadLevel;
+            return;
+        }
+        if let Some(level) = self.level.as_mut() {
+            MapObject::p_reborn_player(player_num, level, &mut self.players, &self.players_in_game);
+        }
     }
 
     pub fn start_title(&mut self) {
@@ -627,7 +718,24 @@ impl Game {
         self.advance_demo();
     }
 
+    /// Doom function name `G_CheckDemoStatus`
     fn check_demo_status(&mut self) -> bool {
+        if self.demo.recording {
+            self.demo.recording = false;
+            if self.demo.record_buffer.is_empty() {
+                return false;
+            }
+            self.demo.record_buffer.push(DEMO_MARKER);
+            match std::fs::write(&self.demo.record_path, &self.demo.record_buffer) {
+                Ok(_) => info!("Demo {} recorded", self.demo.record_path.display()),
+                Err(e) => error!(
+                    "Could not write demo {}: {e}",
+                    self.demo.record_path.display()
+                ),
+            }
+            return true;
+        }
+
         if self.demo.playback {
             self.demo.playback = false;
             self.options.netgame = false;
@@ -635,12 +743,18 @@ impl Game {
             for p in self.players_in_game.iter_mut() {
                 *p = false;
             }
-            self.options.respawn_parm = false;
-            self.options.fast_parm = false;
-            self.options.no_monsters = false;
+            (
+                self.options.respawn_parm,
+                self.options.fast_parm,
+                self.options.no_monsters,
+            ) = self.demo.params;
             self.consoleplayer = 0;
 
-            self.advance_demo();
+            if self.demo.single {
+                self.running = false;
+            } else {
+                self.advance_demo();
+            }
 
             return true;
         }
@@ -673,6 +787,69 @@ impl Game {
         }
     }
 
+    /// Record a demo to the file `name`, `.lmp` is added if it has no
+    /// extension. A new game is started with the current options, and the
+    /// demo is written out when the game quits.
+    ///
+    /// Doom function name `G_RecordDemo`
+    pub fn record_demo(&mut self, name: &str) {
+        let mut path = PathBuf::from(name);
+        if path.extension().is_none() {
+            path.set_extension("lmp");
+        }
+        self.usergame = false;
+        // Playback always uses the original physics, so recording must too
+        self.options.vanilla_physics = true;
+        self.options.no_skull_limit = false;
+        self.demo.record_path = path;
+        self.demo.record_buffer.clear();
+        self.demo.recording = true;
+        self.pending_action = GameAction::NewGame;
+    }
+
+    /// Write the demo header for the game that is starting
+    ///
+    /// Doom function name `G_BeginRecording`
+    fn begin_recording(&mut self) {
+        let buffer = &mut self.demo.record_buffer;
+        buffer.clear();
+        buffer.push(DOOM_VERSION);
+        buffer.push(self.options.skill as u8);
+        buffer.push(self.options.episode as u8);
+        buffer.push(self.options.map as u8);
+        buffer.push(self.options.deathmatch);
+        buffer.push(self.options.respawn_parm as u8);
+        buffer.push(self.options.fast_parm as u8);
+        buffer.push(self.options.no_monsters as u8);
+        buffer.push(self.consoleplayer as u8);
+        for in_game in self.players_in_game {
+            buffer.push(in_game as u8);
+        }
+    }
+
+    /// Record the command, then change it to what playback will read so the
+    /// recording stays in sync. Anything not in the demo, such as looking up
+    /// and down, is dropped. The consistancy is kept for the net check.
+    ///
+    /// Doom function name `G_WriteDemoTiccmd`
+    fn write_demo_tic_cmd(&mut self, cmd: &mut TicCmd) {
+        let turn = ((cmd.angleturn as i32 + 128) >> 8) as u8;
+        self.demo.record_buffer.extend_from_slice(&[
+            cmd.forwardmove as u8,
+            cmd.sidemove as u8,
+            turn,
+            cmd.buttons,
+        ]);
+        *cmd = TicCmd {
+            forwardmove: cmd.forwardmove,
+            sidemove: cmd.sidemove,
+            angleturn: (turn as i16) << 8,
+            buttons: cmd.buttons,
+            consistancy: cmd.consistancy,
+            ..TicCmd::new()
+        };
+    }
+
     pub fn advance_demo(&mut self) {
         self.demo.advance = true;
     }
@@ -757,6 +934,13 @@ impl Game {
         }
     }
 
+    /// Play the demo `name` and quit when it ends. `name` is a lump in the
+    /// wads or a demo file.
+    pub fn play_demo(&mut self, name: &str) {
+        self.demo.single = true;
+        self.defered_play_demo(name.into());
+    }
+
     /// G_DeferedPlayDemo
     fn defered_play_demo(&mut self, name: String) {
         self.demo.name = name;
@@ -767,11 +951,16 @@ impl Game {
     fn do_play_demo(&mut self) {
         self.pending_action = GameAction::None;
 
-        if let Some(demo) = self.wad_data.get_lump(&self.demo.name) {
-            self.demo.buffer = demo.data.clone().into_iter().peekable();
+        let data = self
+            .wad_data
+            .get_lump(&self.demo.name)
+            .map(|lump| lump.data.clone())
+            .or_else(|| std::fs::read(&self.demo.name).ok());
+        if let Some(data) = data {
+            self.demo.buffer = data.into_iter().peekable();
 
             if let Some(byte) = self.demo.buffer.next() {
-                if byte != 109 {
+                if byte != DOOM_VERSION {
                     self.pending_action = GameAction::None;
                     return;
                 }
@@ -813,6 +1002,10 @@ impl Game {
             self.init_new();
             self.usergame = false;
             self.demo.playback = true;
+            if let Some(level) = self.level.as_mut() {
+                level.options.vanilla_physics = true;
+                level.options.no_skull_limit = false;
+            }
         } else {
             error!("Demo {} does not exist", self.demo.name);
             self.pending_action = GameAction::None;
@@ -823,6 +1016,7 @@ impl Game {
     ///
     /// Doom function name `G_DoWorldDone`
     fn do_world_done(&mut self) {
+        self.options.episode = self.world_info.next_episode + 1;
         self.options.map = self.world_info.next + 1;
         self.do_load_level();
         self.gamestate = GameState::Level;
@@ -834,6 +1028,7 @@ impl Game {
     /// info that can be displayed on the intermission screene.
     fn do_completed(&mut self) {
         self.pending_action = GameAction::None;
+        self.automap = false;
 
         for (i, in_game) in self.players_in_game.iter().enumerate() {
             if *in_game {
@@ -842,25 +1037,68 @@ impl Game {
             }
         }
 
+        let secret_exit = self.level.as_ref().unwrap().secret_exit;
+        let info = self.map_info().cloned();
+
         self.world_info.didsecret = self.players[self.consoleplayer].didsecret;
         self.world_info.episode = self.options.episode - 1;
         self.world_info.last = self.options.map;
-
-        if !matches!(self.game_type.mode, GameMode::Commercial) {
-            if self.options.map == 8 {
-                self.pending_action = GameAction::Victory;
-                return;
-            }
-            if self.options.map == 8 {
-                for p in self.players.iter_mut() {
-                    p.didsecret = true;
+        self.world_info.secret_exit = secret_exit;
+        self.world_info.pnum = self.consoleplayer;
+        self.world_info.deathmatch = self.options.deathmatch != 0;
+        self.world_info.end_game = match &info {
+            Some(info) if info.ends_game() => true,
+            Some(info) if info.end_game == Some(false) => false,
+            _ => {
+                if matches!(self.game_type.mode, GameMode::Commercial) {
+                    self.options.map == 30
+                } else {
+                    self.options.map == 8
                 }
             }
+        };
+
+        // Doom goes straight to the finale after the last level of an episode
+        let skip_intermission = match &info {
+            Some(info) if info.ends_game() => info.no_intermission,
+            _ => !matches!(self.game_type.mode, GameMode::Commercial),
+        };
+        if self.world_info.end_game && skip_intermission {
+            self.pending_action = GameAction::Victory;
+            return;
+        }
+        if !matches!(self.game_type.mode, GameMode::Commercial) && self.options.map == 9 {
+            for p in self.players.iter_mut() {
+                p.didsecret = true;
+            }
         }
 
+        // UMAPINFO `nextsecret` falls back to `next`
+        let umapinfo_next = info.as_ref().and_then(|info| {
+            if secret_exit {
+                info.next_secret.as_ref().or(info.next.as_ref())
+            } else {
+                info.next.as_ref()
+            }
+        });
+        self.world_info.next_episode = self.world_info.episode;
+
+        // A UMAPINFO next map that isn't in the WAD falls back to the normal order
+        let umapinfo_next = umapinfo_next.and_then(|name| {
+            let numbers = map_numbers(name)
+                .filter(|(episode, map)| self.wad_data.lump_exists(&self.map_name(*episode, *map)));
+            if numbers.is_none() {
+                warn!("UMAPINFO: next map {name} is not in the WAD");
+            }
+            numbers
+        });
+
         // wminfo.next is 0 biased, unlike gamemap, which is just bloody confusing...
-        if matches!(self.game_type.mode, GameMode::Commercial) {
-            if self.level.as_ref().unwrap().secret_exit {
+        if let Some((episode, map)) = umapinfo_next {
+            self.world_info.next_episode = episode - 1;
+            self.world_info.next = map - 1;
+        } else if matches!(self.game_type.mode, GameMode::Commercial) {
+            if secret_exit {
                 if self.options.map == 15 {
                     self.world_info.next = 30;
                 } else if self.options.map == 31 {
@@ -871,7 +1109,7 @@ impl Game {
             } else {
                 self.world_info.next = self.options.map;
             }
-        } else if self.level.as_ref().unwrap().secret_exit {
+        } else if secret_exit {
             // go to secret level
             self.world_info.next = 8;
         } else if self.options.map == 9 {
@@ -885,13 +1123,29 @@ impl Game {
         } else {
             self.world_info.next = self.options.map;
         }
+        self.world_info.next_info = self
+            .umapinfo
+            .get(&self.map_name(self.world_info.next_episode + 1, self.world_info.next + 1))
+            .cloned();
 
         self.world_info.maxkills = self.level.as_ref().unwrap().total_level_kills;
         self.world_info.maxitems = self.level.as_ref().unwrap().total_level_items;
         self.world_info.maxsecret = self.level.as_ref().unwrap().total_level_secrets;
         self.world_info.maxfrags = 0;
 
-        // TODO: par times
+        self.world_info.partime = if let Some(par) = info.as_ref().and_then(|info| info.par_time) {
+            par
+        } else if matches!(self.game_type.mode, GameMode::Commercial) {
+            if self.options.map <= 32 {
+                unsafe { CPARS[self.options.map - 1] }
+            } else {
+                0
+            }
+        } else if self.options.episode <= 3 && self.options.map <= 9 {
+            unsafe { PARS[self.options.episode][self.options.map] }
+        } else {
+            0
+        };
 
         for (i, in_game) in self.players_in_game.iter().enumerate() {
             self.world_info.plyr[i].inn = *in_game;
@@ -914,7 +1168,7 @@ impl Game {
 
     fn start_finale(&mut self) {
         self.world_info.didsecret = self.players[self.consoleplayer].didsecret;
-        self.world_info.episode = self.options.episode;
+        self.world_info.episode = self.options.episode - 1;
         self.world_info.last = self.options.map;
 
         self.gamestate = GameState::Finale;
@@ -928,12 +1182,13 @@ impl Game {
     /// change the game-exe state or cause an action through `GameAction`.
     ///
     /// Doom function name `G_Ticker`
-    pub fn ticker<I, S, H, F>(&mut self, machinations: &mut GameSubsystem<I, S, H, F>)
+    pub fn ticker<I, S, H, F, A>(&mut self, machinations: &mut GameSubsystem<I, S, H, F, A>)
     where
         I: SubsystemTrait,
         S: SubsystemTrait,
         H: SubsystemTrait,
         F: SubsystemTrait,
+        A: SubsystemTrait,
     {
         trace!("Entered ticker");
         // do player reborns if needed
@@ -955,6 +1210,7 @@ impl Game {
             GameAction::LoadLevel => {
                 machinations.hud_msgs.init(self);
                 self.do_load_level();
+                machinations.automap.init(self);
             }
             GameAction::NewGame => self.do_new_game(),
             GameAction::CompletedLevel => {
@@ -963,7 +1219,11 @@ impl Game {
                 machinations.hud_msgs.init(self);
             }
             GameAction::None => {}
-            GameAction::LoadGame => self.do_load_game(),
+            GameAction::LoadGame => {
+                machinations.hud_msgs.init(self);
+                self.do_load_game();
+                machinations.automap.init(self);
+            }
             GameAction::SaveGame => self.do_save_game(),
             GameAction::PlayDemo => self.do_play_demo(),
             GameAction::Victory => {
@@ -972,27 +1232,33 @@ impl Game {
                 machinations.hud_msgs.init(self);
             }
             GameAction::WorldDone => self.do_world_done(),
-            GameAction::Screenshot => todo!("M_ScreenShot(); gameaction = ga_nothing"),
+            GameAction::Screenshot => {
+                self.take_screenshot = true;
+                self.pending_action = GameAction::None;
+            }
         }
 
-        // TODO: get commands, check consistancy,
-        // and build new consistancy check
-        // buf = (gametic / ticdup) % BACKUPTICS;
+        // get commands, check consistancy, and build new consistancy check
+        let buf = self.game_tic as usize % BACKUPTICS;
 
         // Checks ticcmd consistency and turbo cheat
         for i in 0..MAXPLAYERS {
             if self.players_in_game[i] {
                 // sets the players cmd for this tic
-                self.players[i].cmd = self.netcmds[i][0];
-                // memcpy(cmd, &netcmds[i][buf], sizeof(ticcmd_t));
+                self.players[i].cmd = self.netcmds[i][buf];
                 if self.demo.playback {
                     let mut cmd = self.players[i].cmd;
                     self.read_demo_tic_cmd(&mut cmd);
                     self.players[i].cmd = cmd;
                 }
-                // if (demorecording)
-                //     TODO: G_WriteDemoTiccmd(cmd);
-                // TODO: Netgame stuff here
+                if self.demo.recording {
+                    let mut cmd = self.players[i].cmd;
+                    self.write_demo_tic_cmd(&mut cmd);
+                    self.players[i].cmd = cmd;
+                }
+                if self.options.netgame {
+                    self.check_consistancy(i);
+                }
             }
         }
 
@@ -1028,7 +1294,7 @@ impl Game {
                 // update statusbar information
                 machinations.statusbar.ticker(self);
                 // update the automap display info
-                // AM_Ticker();
+                machinations.automap.ticker(self);
                 // update the HUD statuses (things like timeout displayed messages)
                 machinations.hud_msgs.ticker(self);
             }
@@ -1085,5 +1351,34 @@ impl Game {
             update_specials(level, &mut self.pic_data);
             respawn_specials(level);
         }
+        self.check_deathmatch_limits();
+    }
+
+    /// End a deathmatch level once the time limit has passed or a player has
+    /// reached the frag limit
+    fn check_deathmatch_limits(&mut self) {
+        if self.options.deathmatch == 0 {
+            return;
+        }
+        let Some(level) = self.level.as_mut() else {
+            return;
+        };
+
+        if let Some(minutes) = self.time_limit
+            && minutes > 0
+            && level.level_time >= minutes as u32 * 60 * TICRATE as u32
+        {
+            info!("Time limit of {minutes} minutes reached");
+            level.game_action = Some(GameAction::CompletedLevel);
+            return;
+        }
+
+        if let Some(limit) = self.options.frag_limit
+            && let Some(player) = (0..MAXPLAYERS)
+                .find(|&i| self.players_in_game[i] && self.players[i].frag_count(i) >= limit)
+        {
+            info!("Player {} reached the frag limit of {limit}", player + 1);
+            level.game_action = Some(GameAction::CompletedLevel);
+        }
     }
 }
@@ -0,0 +1,577 @@
+//! Peer to peer lockstep networking over UDP, modelled on `d_net.c`.
+//!
+//! Every node sends its player's `TicCmd`s to every other node, and a tic is
+//! only run once the commands for it have arrived from all nodes. Each packet
+//! carries all the commands the receiver has not acknowledged yet, so lost
+//! packets are made up for by the next one. The commands are held in
+//! `Game.netcmds` which doubles as the resend buffer, so a node never builds
+//! more than `BACKUPTICS` ahead of the slowest node.
+//!
+//! Nodes are given in player order and every node must be given the same
+//! list. Player 1 is the key player, the game settings it was started with
+//! are used by all the other nodes.
+
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use gameplay::log::{error, info};
+use gameplay::tic_cmd::TicCmd;
+use gameplay::{GameAction, GameOptions, MAXPLAYERS, Skill};
+use math::p_random_index;
+
+use crate::{BACKUPTICS, Game};
+
+/// How long to wait for all the nodes to turn up
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(60);
+/// How often to repeat the setup packet while waiting for the other nodes
+const SETUP_RESEND: Duration = Duration::from_millis(100);
+/// How long to wait for the other nodes to get the last commands when quitting
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+const PKT_SETUP: u8 = 0;
+const PKT_TICS: u8 = 1;
+const PKT_QUIT: u8 = 2;
+
+/// Byte size of a `TicCmd` in a packet
+const CMD_SIZE: usize = 10;
+/// Byte size of the header of a tics packet
+const TICS_HEADER: usize = 11;
+const SETTINGS_SIZE: usize = 11;
+const MAX_PACKET: usize = TICS_HEADER + BACKUPTICS * CMD_SIZE;
+
+/// The game settings sent by the key player
+fn write_settings(options: &GameOptions) -> [u8; SETTINGS_SIZE] {
+    [
+        options.skill as u8,
+        options.episode as u8,
+        options.map as u8,
+        options.deathmatch,
+        options.respawn_parm as u8,
+        options.fast_parm as u8,
+        options.no_monsters as u8,
+        options.vanilla_physics as u8,
+        // Limits are sent as a byte each, 0 is no limit
+        options.time_limit.unwrap_or(0).clamp(0, 255) as u8,
+        options.frag_limit.unwrap_or(0).clamp(0, 255) as u8,
+        options.no_skull_limit as u8,
+    ]
+}
+
+fn read_settings(settings: &[u8], options: &mut GameOptions) {
+    options.skill = Skill::from(settings[0]);
+    options.episode = settings[1] as usize;
+    options.map = settings[2] as usize;
+    options.deathmatch = settings[3];
+    options.respawn_parm = settings[4] == 1;
+    options.fast_parm = settings[5] == 1;
+    options.no_monsters = settings[6] == 1;
+    options.vanilla_physics = settings[7] == 1;
+    options.time_limit = (settings[8] != 0).then_some(settings[8] as i32);
+    options.frag_limit = (settings[9] != 0).then_some(settings[9] as i32);
+    options.no_skull_limit = settings[10] == 1;
+}
+
+fn write_cmd(cmd: &TicCmd, buf: &mut Vec<u8>) {
+    buf.push(cmd.forwardmove as u8);
+    buf.push(cmd.sidemove as u8);
+    buf.extend_from_slice(&cmd.angleturn.to_le_bytes());
+    buf.extend_from_slice(&cmd.consistancy.to_le_bytes());
+    buf.push(cmd.chatchar);
+    buf.push(cmd.buttons);
+    buf.extend_from_slice(&cmd.lookdir.to_le_bytes());
+}
+
+fn read_cmd(buf: &[u8]) -> TicCmd {
+    TicCmd {
+        forwardmove: buf[0] as i8,
+        sidemove: buf[1] as i8,
+        angleturn: i16::from_le_bytes([buf[2], buf[3]]),
+        consistancy: i16::from_le_bytes([buf[4], buf[5]]),
+        chatchar: buf[6],
+        buttons: buf[7],
+        lookdir: i16::from_le_bytes([buf[8], buf[9]]),
+    }
+}
+
+/// Errors from `recv_from` that only mean nothing was read. Some platforms
+/// report a node that isn't listening yet as a refused or reset connection.
+fn is_retry(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        ErrorKind::WouldBlock | ErrorKind::ConnectionRefused | ErrorKind::ConnectionReset
+    )
+}
+
+fn read_u32(buf: &[u8]) -> u32 {
+    u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]])
+}
+
+pub struct NetGame {
+    socket: UdpSocket,
+    /// Address of each node, in player order
+    nodes: Vec<SocketAddr>,
+    consoleplayer: usize,
+    /// The settings all nodes play with, sent in reply to late setup packets
+    settings: [u8; SETTINGS_SIZE],
+    /// Nodes still in the game
+    in_game: [bool; MAXPLAYERS],
+    /// The next tic to be built by this node
+    maketic: u32,
+    /// The next tic expected from each node
+    nettics: [u32; MAXPLAYERS],
+    /// The next tic each node expects from this one. Anything older has
+    /// arrived and doesn't need sending again.
+    acked: [u32; MAXPLAYERS],
+}
+
+impl NetGame {
+    /// Bind to this player's address and wait for all the other nodes to
+    /// answer. `consoleplayer` is the index in to `nodes` for this node. The
+    /// nodes all leave with the key player's settings in `options`.
+    ///
+    /// Doom function name `D_ArbitrateNetStart`
+    pub fn connect(
+        consoleplayer: usize,
+        nodes: Vec<SocketAddr>,
+        options: &mut GameOptions,
+    ) -> io::Result<Self> {
+        if nodes.len() < 2 || nodes.len() > MAXPLAYERS {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("a netgame needs 2 to {MAXPLAYERS} nodes"),
+            ));
+        }
+        if consoleplayer >= nodes.len() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("player {} has no node", consoleplayer + 1),
+            ));
+        }
+
+        let socket = UdpSocket::bind(nodes[consoleplayer])?;
+        socket.set_read_timeout(Some(SETUP_RESEND))?;
+        let mut net = Self {
+            socket,
+            in_game: [false; MAXPLAYERS],
+            consoleplayer,
+            settings: write_settings(options),
+            maketic: 0,
+            nettics: [0; MAXPLAYERS],
+            acked: [0; MAXPLAYERS],
+            nodes,
+        };
+        net.in_game[..net.nodes.len()].fill(true);
+
+        let all = (1u8 << net.nodes.len()) - 1;
+        let me = 1u8 << consoleplayer;
+        // nodes this node has heard from
+        let mut seen = me;
+        // nodes that have heard from this node
+        let mut known = me;
+        let mut got_settings = consoleplayer == 0;
+
+        info!("Waiting for {} other players", net.nodes.len() - 1);
+        let start = Instant::now();
+        let mut last_send: Option<Instant> = None;
+        let mut buf = [0u8; MAX_PACKET];
+        while seen != all || known != all || !got_settings {
+            if start.elapsed() > CONNECT_TIMEOUT {
+                return Err(io::Error::new(
+                    ErrorKind::TimedOut,
+                    "not all players joined the netgame",
+                ));
+            }
+            if last_send.is_none_or(|t| t.elapsed() >= SETUP_RESEND) {
+                net.send_setup(seen, None);
+                last_send = Some(Instant::now());
+            }
+
+            let (len, from) = match net.socket.recv_from(&mut buf) {
+                Ok(recv) => recv,
+                Err(e) if is_retry(&e) || e.kind() == ErrorKind::TimedOut => continue,
+                Err(e) => return Err(e),
+            };
+            let Some(player) = net.packet_player(&buf[..len], from) else {
+                continue;
+            };
+            match buf[0] {
+                PKT_SETUP if len == 3 + SETTINGS_SIZE => {
+                    seen |= 1 << player;
+                    if buf[2] & me != 0 {
+                        known |= 1 << player;
+                    }
+                    if player == 0 {
+                        net.settings.copy_from_slice(&buf[3..3 + SETTINGS_SIZE]);
+                        got_settings = true;
+                    }
+                }
+                // The node has already started so must have heard from all
+                PKT_TICS => {
+                    seen |= 1 << player;
+                    known |= 1 << player;
+                }
+                _ => {}
+            }
+        }
+
+        read_settings(&net.settings, options);
+        net.socket.set_nonblocking(true)?;
+        info!("All {} players joined", net.nodes.len());
+        Ok(net)
+    }
+
+    pub fn consoleplayer(&self) -> usize {
+        self.consoleplayer
+    }
+
+    pub fn players_in_game(&self) -> [bool; MAXPLAYERS] {
+        self.in_game
+    }
+
+    /// Is there room to build another tic without overwriting a command that
+    /// hasn't been run, or that a node hasn't acknowledged yet?
+    pub fn can_build(&self, game_tic: u32) -> bool {
+        let oldest = (0..MAXPLAYERS)
+            .filter(|&i| self.in_game[i] && i != self.consoleplayer)
+            .map(|i| self.acked[i])
+            .fold(game_tic, u32::min);
+        self.maketic < oldest + BACKUPTICS as u32
+    }
+
+    /// Add this node's command for the next tic
+    pub fn build_tic(&mut self, cmd: TicCmd, netcmds: &mut [[TicCmd; BACKUPTICS]; MAXPLAYERS]) {
+        netcmds[self.consoleplayer][self.maketic as usize % BACKUPTICS] = cmd;
+        self.maketic += 1;
+    }
+
+    /// Have the commands for `game_tic` arrived from every node?
+    pub fn ready(&self, game_tic: u32) -> bool {
+        (0..MAXPLAYERS)
+            .filter(|&i| self.in_game[i])
+            .all(|i| self.tics_from(i) > game_tic)
+    }
+
+    fn tics_from(&self, player: usize) -> u32 {
+        if player == self.consoleplayer {
+            self.maketic
+        } else {
+            self.nettics[player]
+        }
+    }
+
+    /// Send the commands the other nodes are missing, and store any that
+    /// have arrived. Returns the players that left the game.
+    ///
+    /// Doom function name `NetUpdate`
+    pub fn update(
+        &mut self,
+        game_tic: u32,
+        netcmds: &mut [[TicCmd; BACKUPTICS]; MAXPLAYERS],
+    ) -> Vec<usize> {
+        let mut left = Vec::new();
+        let mut buf = [0u8; MAX_PACKET];
+        loop {
+            let (len, from) = match self.socket.recv_from(&mut buf) {
+                Ok(recv) => recv,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if is_retry(&e) => continue,
+                Err(e) => {
+                    error!("Network receive failed: {e}");
+                    break;
+                }
+            };
+            let Some(player) = self.packet_player(&buf[..len], from) else {
+                continue;
+            };
+            if !self.in_game[player] {
+                continue;
+            }
+            match buf[0] {
+                PKT_SETUP => self.send_setup((1 << self.nodes.len()) - 1, Some(player)),
+                PKT_TICS if len >= TICS_HEADER => {
+                    self.read_tics(player, game_tic, &buf[..len], netcmds)
+                }
+                PKT_QUIT => {
+                    self.in_game[player] = false;
+                    left.push(player);
+                }
+                _ => {}
+            }
+        }
+
+        for player in 0..self.nodes.len() {
+            if self.in_game[player] && player != self.consoleplayer {
+                self.send_tics(player, netcmds);
+            }
+        }
+        left
+    }
+
+    /// Keep exchanging commands until every node has all of this node's, or
+    /// until `FLUSH_TIMEOUT`. Used before quitting so the other nodes can
+    /// still run the tics this node has run.
+    pub fn flush(&mut self, game_tic: u32, netcmds: &mut [[TicCmd; BACKUPTICS]; MAXPLAYERS]) {
+        let start = Instant::now();
+        while start.elapsed() < FLUSH_TIMEOUT {
+            self.update(game_tic, netcmds);
+            let acked = (0..self.nodes.len())
+                .filter(|&i| self.in_game[i] && i != self.consoleplayer)
+                .all(|i| self.acked[i] >= self.maketic);
+            if acked {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// Returns the player that sent the packet if it is valid and came from
+    /// that player's node
+    fn packet_player(&self, packet: &[u8], from: SocketAddr) -> Option<usize> {
+        let player = *packet.get(1)? as usize;
+        (player < self.nodes.len() && player != self.consoleplayer && self.nodes[player] == from)
+            .then_some(player)
+    }
+
+    fn read_tics(
+        &mut self,
+        player: usize,
+        game_tic: u32,
+        packet: &[u8],
+        netcmds: &mut [[TicCmd; BACKUPTICS]; MAXPLAYERS],
+    ) {
+        // A node can't have more tics than have been made
+        self.acked[player] = self.acked[player].max(read_u32(&packet[2..]).min(self.maketic));
+        let start = read_u32(&packet[6..]);
+        let count = packet[10] as usize;
+        let cmds = packet[TICS_HEADER..].chunks_exact(CMD_SIZE).take(count);
+        for (tic, cmd) in (start..).zip(cmds) {
+            // Older tics are duplicates. A tic can't be stored until the one
+            // in its slot has been run.
+            if tic == self.nettics[player] && tic < game_tic + BACKUPTICS as u32 {
+                netcmds[player][tic as usize % BACKUPTICS] = read_cmd(cmd);
+                self.nettics[player] += 1;
+            }
+        }
+    }
+
+    fn send_tics(&self, player: usize, netcmds: &[[TicCmd; BACKUPTICS]; MAXPLAYERS]) {
+        let start = self.acked[player].max(self.maketic.saturating_sub(BACKUPTICS as u32));
+        let mut packet = Vec::with_capacity(MAX_PACKET);
+        packet.push(PKT_TICS);
+        packet.push(self.consoleplayer as u8);
+        packet.extend_from_slice(&self.nettics[player].to_le_bytes());
+        packet.extend_from_slice(&start.to_le_bytes());
+        packet.push(self.maketic.saturating_sub(start) as u8);
+        for tic in start..self.maketic {
+            write_cmd(
+                &netcmds[self.consoleplayer][tic as usize % BACKUPTICS],
+                &mut packet,
+            );
+        }
+        self.send(player, &packet);
+    }
+
+    /// Send the setup packet to `player`, or to all nodes if `None`
+    fn send_setup(&self, seen: u8, player: Option<usize>) {
+        let mut packet = vec![PKT_SETUP, self.consoleplayer as u8, seen];
+        packet.extend_from_slice(&self.settings);
+        for node in 0..self.nodes.len() {
+            if node != self.consoleplayer && player.is_none_or(|p| p == node) {
+                self.send(node, &packet);
+            }
+        }
+    }
+
+    fn send(&self, player: usize, packet: &[u8]) {
+        if let Err(e) = self.socket.send_to(packet, self.nodes[player])
+            && !is_retry(&e)
+        {
+            error!("Network send to player {} failed: {e}", player + 1);
+        }
+    }
+}
+
+impl Drop for NetGame {
+    /// Tell the other nodes this one is leaving. Sent a few times as there is
+    /// no reply.
+    ///
+    /// Doom function name `D_QuitNetGame`
+    fn drop(&mut self) {
+        let packet = [PKT_QUIT, self.consoleplayer as u8];
+        for _ in 0..4 {
+            for player in 0..self.nodes.len() {
+                if self.in_game[player] && player != self.consoleplayer {
+                    self.send(player, &packet);
+                }
+            }
+        }
+    }
+}
+
+impl Game {
+    /// Start a netgame with the players from `net`. The settings must already
+    /// have been taken from the key player by `NetGame::connect`.
+    pub fn start_netgame(&mut self, net: &NetGame) {
+        self.consoleplayer = net.consoleplayer();
+        self.players_in_game = net.players_in_game();
+        self.options.netgame = true;
+        self.usergame = true;
+        self.init_new();
+        self.pending_action = GameAction::None;
+    }
+
+    /// Queue `cmd` for this node's player, if there is room for it, then send
+    /// and receive the commands of all nodes.
+    pub fn net_update(&mut self, net: &mut NetGame, cmd: TicCmd) {
+        if net.can_build(self.game_tic) {
+            let mut cmd = cmd;
+            cmd.consistancy =
+                self.consistancy[self.consoleplayer][net.maketic as usize % BACKUPTICS];
+            net.build_tic(cmd, &mut self.netcmds);
+        }
+        for player in net.update(self.game_tic, &mut self.netcmds) {
+            info!("Player {} left the game", player + 1);
+            self.players_in_game[player] = false;
+            self.players[player].remove_mobj();
+        }
+    }
+
+    /// Check the command for this tic came from a node that is in the same
+    /// state, then store the state to check against later tics.
+    ///
+    /// Part of `G_Ticker`
+    pub(crate) fn check_consistancy(&mut self, player: usize) {
+        let buf = self.game_tic as usize % BACKUPTICS;
+        let cmd = &self.players[player].cmd;
+        if self.game_tic as usize > BACKUPTICS && self.consistancy[player][buf] != cmd.consistancy {
+            error!(
+                "Consistency failure for player {} ({} should be {})",
+                player + 1,
+                cmd.consistancy,
+                self.consistancy[player][buf]
+            );
+            self.running = false;
+        }
+        self.consistancy[player][buf] = match self.players[player].mobj() {
+            Some(mobj) => mobj.xy.x.0 as i16,
+            None => p_random_index() as i16,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    const NODES: [&str; 2] = ["127.0.0.1:45710", "127.0.0.1:45711"];
+
+    /// Run two nodes for `tics`, each sending its player number as the
+    /// forward move. Returns the commands each node ran.
+    fn run_node(player: usize, tics: u32) -> (GameOptions, Vec<[TicCmd; 2]>) {
+        let nodes = NODES.iter().map(|n| n.parse().unwrap()).collect();
+        let mut options = GameOptions {
+            map: player + 3,
+            ..Default::default()
+        };
+        let mut net = NetGame::connect(player, nodes, &mut options).unwrap();
+        let mut netcmds = [[TicCmd::new(); BACKUPTICS]; MAXPLAYERS];
+        let mut ran = Vec::new();
+        let mut game_tic = 0;
+        let start = Instant::now();
+        while game_tic < tics {
+            assert!(start.elapsed() < CONNECT_TIMEOUT, "netgame stalled");
+            if net.can_build(game_tic) && net.maketic < tics {
+                let cmd = TicCmd {
+                    forwardmove: player as i8 + 1,
+                    angleturn: net.maketic as i16,
+                    ..TicCmd::new()
+                };
+                net.build_tic(cmd, &mut netcmds);
+            }
+            net.update(game_tic, &mut netcmds);
+            while game_tic < tics && net.ready(game_tic) {
+                let buf = game_tic as usize % BACKUPTICS;
+                ran.push([netcmds[0][buf], netcmds[1][buf]]);
+                game_tic += 1;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        net.flush(game_tic, &mut netcmds);
+        (options, ran)
+    }
+
+    #[test]
+    fn lockstep_localhost() {
+        let tics = 100;
+        let other = thread::spawn(move || run_node(1, tics));
+        let (options0, ran0) = run_node(0, tics);
+        let (options1, ran1) = other.join().unwrap();
+
+        // The key player's settings are used by all
+        assert_eq!(options0.map, 3);
+        assert_eq!(options1.map, 3);
+
+        assert_eq!(ran0.len(), tics as usize);
+        for (tic, (a, b)) in ran0.iter().zip(ran1.iter()).enumerate() {
+            for player in 0..2 {
+                assert_eq!(a[player].forwardmove, player as i8 + 1);
+                assert_eq!(a[player].angleturn, tic as i16);
+                assert_eq!(a[player].forwardmove, b[player].forwardmove);
+                assert_eq!(a[player].angleturn, b[player].angleturn);
+            }
+        }
+    }
+
+    #[test]
+    fn packets_only_from_player_node() {
+        let nodes: Vec<SocketAddr> = NODES.iter().map(|n| n.parse().unwrap()).collect();
+        let mut net = NetGame {
+            socket: UdpSocket::bind("127.0.0.1:0").unwrap(),
+            in_game: [true, true, false, false],
+            consoleplayer: 0,
+            settings: [0; SETTINGS_SIZE],
+            maketic: 5,
+            nettics: [0; MAXPLAYERS],
+            acked: [0; MAXPLAYERS],
+            nodes: nodes.clone(),
+        };
+        let packet = [PKT_QUIT, 1];
+        assert_eq!(net.packet_player(&packet, nodes[1]), Some(1));
+        assert_eq!(net.packet_player(&packet, nodes[0]), None);
+        assert_eq!(
+            net.packet_player(&packet, "127.0.0.1:45799".parse().unwrap()),
+            None
+        );
+
+        // Acknowledging tics that haven't been made is ignored
+        let mut packet = vec![PKT_TICS, 1];
+        packet.extend_from_slice(&100u32.to_le_bytes());
+        packet.extend_from_slice(&0u32.to_le_bytes());
+        packet.push(0);
+        let mut netcmds = [[TicCmd::new(); BACKUPTICS]; MAXPLAYERS];
+        net.read_tics(1, 0, &packet, &mut netcmds);
+        assert_eq!(net.acked[1], 5);
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let options = GameOptions {
+            skill: Skill::Hard,
+            episode: 2,
+            map: 5,
+            deathmatch: 2,
+            time_limit: Some(20),
+            frag_limit: None,
+            ..Default::default()
+        };
+        let mut read = GameOptions::default();
+        read_settings(&write_settings(&options), &mut read);
+        assert_eq!(read.skill, Skill::Hard);
+        assert_eq!((read.episode, read.map), (2, 5));
+        assert_eq!(read.deathmatch, 2);
+        assert_eq!(read.time_limit, Some(20));
+        assert_eq!(read.frag_limit, None);
+    }
+}
@@ -0,0 +1,238 @@
+//! Writing and reading of savegame files. The level, thinkers and players are
+//! archived by the gameplay crate, this module adds the game header and the
+//! world info around that.
+
+use std::fs;
+use std::path::PathBuf;
+
+use gameplay::english::GGSAVED;
+use gameplay::log::{error, info, warn};
+use gameplay::{
+    GameAction, GameMode, LevelArchive, MAXPLAYERS, MapData, SAVE_END_MARKER, SAVE_VERSION,
+    SaveError, SaveReader, SaveWriter, Skill,
+};
+use gamestate_traits::savegame_file;
+use math::{p_random_index, p_set_random_index};
+
+use crate::Game;
+
+/// Length of the description as shown in the load/save menus
+pub const SAVESTRINGSIZE: usize = 24;
+const SAVE_MAGIC: &str = "ROOM4DOOM SAVE";
+const SAVE_MAGIC_SIZE: usize = 16;
+
+impl Game {
+    /// Full path to the file for a savegame slot
+    pub fn savegame_path(&self, slot: usize) -> PathBuf {
+        self.options.save_dir.join(savegame_file(slot))
+    }
+
+    /// Read only the description from the savegame in this slot. Doom
+    /// function name `M_ReadSaveStrings`
+    pub(crate) fn read_savegame_description(&self, slot: usize) -> Option<String> {
+        let data = fs::read(self.savegame_path(slot)).ok()?;
+        let mut input = SaveReader::new(&data);
+        let description = input.read_str(SAVESTRINGSIZE).ok()?;
+        (input.read_str(SAVE_MAGIC_SIZE).ok()? == SAVE_MAGIC).then_some(description)
+    }
+
+    /// Doom function name `G_DoSaveGame`
+    pub(crate) fn do_save_game(&mut self) {
+        self.pending_action = GameAction::None;
+        let Some(level) = self.level.as_mut() else {
+            warn!("Can't save, no level is loaded");
+            return;
+        };
+
+        let mut out = SaveWriter::new();
+        out.write_str(&self.save_description, SAVESTRINGSIZE);
+        out.write_str(SAVE_MAGIC, SAVE_MAGIC_SIZE);
+        out.write_u32(SAVE_VERSION);
+
+        out.write_u8(self.game_type.mode as u8);
+        out.write_i32(self.game_skill as i32);
+        out.write_u8(self.options.episode as u8);
+        out.write_u8(self.options.map as u8);
+        for in_game in self.players_in_game.iter() {
+            out.write_bool(*in_game);
+        }
+        out.write_u8(self.consoleplayer as u8);
+        out.write_u8(self.options.deathmatch);
+        out.write_bool(self.options.respawn_parm);
+        out.write_bool(self.options.fast_parm);
+        out.write_bool(self.options.no_monsters);
+        out.write_u8(p_random_index() as u8);
+
+        let world = &self.world_info;
+        out.write_bool(world.didsecret);
+        out.write_u32(world.last as u32);
+        out.write_u32(world.next as u32);
+        out.write_i32(world.maxfrags);
+        out.write_i32(world.partime);
+        for plyr in world.plyr.iter() {
+            out.write_i32(plyr.score);
+        }
+
+        level.archive(&mut out);
+        out.write_u8(SAVE_END_MARKER);
+
+        let path = self.savegame_path(self.save_slot);
+        if let Some(dir) = path.parent()
+            && let Err(e) = fs::create_dir_all(dir)
+        {
+            error!("Couldn't create save directory {:?}: {}", dir, e);
+            return;
+        }
+        if let Err(e) = fs::write(&path, out.data()) {
+            error!("Couldn't write savegame {:?}: {}", path, e);
+            return;
+        }
+        info!("Saved game to {:?}", path);
+        self.save_description.clear();
+        self.players[self.consoleplayer].message = Some(GGSAVED);
+    }
+
+    /// Doom function name `G_DoLoadGame`
+    pub(crate) fn do_load_game(&mut self) {
+        self.pending_action = GameAction::None;
+
+        let mut path = PathBuf::from(&self.save_name);
+        if path.is_relative() && !path.exists() {
+            path = self.options.save_dir.join(path);
+        }
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(e) => {
+                error!("Couldn't read savegame {:?}: {}", path, e);
+                return;
+            }
+        };
+
+        if let Err(e) = self.load_game_data(&data) {
+            error!("Couldn't load savegame {:?}: {}", path, e);
+        } else {
+            info!("Loaded game from {:?}", path);
+        }
+    }
+
+    /// The episode and map must be in range for the game mode, as
+    /// `prepare_new` would otherwise clamp them, and the map must be in the
+    /// WAD so that `load_level` can't fail part way.
+    fn save_map_is_valid(&self, episode: usize, map: usize) -> bool {
+        let pwad = !self.options.pwad.is_empty();
+        let in_range = episode >= 1
+            && map >= 1
+            && match self.game_type.mode {
+                GameMode::Commercial => true,
+                GameMode::Shareware => episode == 1 && map <= 5,
+                GameMode::Retail => (episode <= 4 || pwad) && map <= 9,
+                _ => (episode <= 3 || pwad) && map <= 9,
+            };
+        in_range && self.wad_data.lump_exists(&self.map_name(episode, map))
+    }
+
+    fn load_game_data(&mut self, data: &[u8]) -> Result<(), SaveError> {
+        let mut input = SaveReader::new(data);
+        let _description = input.read_str(SAVESTRINGSIZE)?;
+        if input.read_str(SAVE_MAGIC_SIZE)? != SAVE_MAGIC {
+            return Err(SaveError::Invalid("not a savegame".into()));
+        }
+        let version = input.read_u32()?;
+        if version != SAVE_VERSION {
+            return Err(SaveError::Version(version));
+        }
+
+        // Nothing is changed until the whole file has been read and checked
+        if input.read_u8()? != self.game_type.mode as u8 {
+            return Err(SaveError::Invalid(
+                "savegame is for another game mode".into(),
+            ));
+        }
+        let skill = input.read_i32()?;
+        if !(Skill::NoItems as i32..=Skill::Nightmare as i32).contains(&skill) {
+            return Err(SaveError::Invalid(format!("skill {}", skill)));
+        }
+        let episode = input.read_u8()? as usize;
+        let map = input.read_u8()? as usize;
+        if !self.save_map_is_valid(episode, map) {
+            return Err(SaveError::Invalid(format!(
+                "episode {} map {} is not in this game",
+                episode, map
+            )));
+        }
+        let mut players_in_game = [false; MAXPLAYERS];
+        for in_game in players_in_game.iter_mut() {
+            *in_game = input.read_bool()?;
+        }
+        let consoleplayer = input.read_u8()? as usize;
+        if consoleplayer >= MAXPLAYERS || !players_in_game[consoleplayer] {
+            return Err(SaveError::Invalid(format!(
+                "console player {}",
+                consoleplayer
+            )));
+        }
+        let deathmatch = input.read_u8()?;
+        let respawn_parm = input.read_bool()?;
+        let fast_parm = input.read_bool()?;
+        let no_monsters = input.read_bool()?;
+        let random_index = input.read_u8()? as usize;
+
+        let didsecret = input.read_bool()?;
+        let last = input.read_u32()? as usize;
+        let next = input.read_u32()? as usize;
+        let maxfrags = input.read_i32()?;
+        let partime = input.read_i32()?;
+        let mut scores = [0; MAXPLAYERS];
+        for score in scores.iter_mut() {
+            *score = input.read_i32()?;
+        }
+
+        let archive = LevelArchive::read(&mut input, &players_in_game)?;
+        if input.read_u8()? != SAVE_END_MARKER {
+            return Err(SaveError::Invalid("bad end marker".into()));
+        }
+        // Only the map itself can tell if the archive is for this version of it
+        let mut map_data = MapData::default();
+        map_data.load(&self.map_name(episode, map), &self.pic_data, &self.wad_data);
+        archive.check_map(&map_data)?;
+
+        // The whole file is good, so now the game can be changed
+        self.options.skill = Skill::from(skill);
+        self.options.episode = episode;
+        self.options.map = map;
+        self.options.deathmatch = deathmatch;
+        self.options.respawn_parm = respawn_parm;
+        self.options.fast_parm = fast_parm;
+        self.options.no_monsters = no_monsters;
+        self.players_in_game = players_in_game;
+        self.consoleplayer = consoleplayer;
+
+        self.prepare_new();
+        self.load_level(false);
+
+        let world = &mut self.world_info;
+        world.didsecret = didsecret;
+        world.last = last;
+        world.next = next;
+        world.maxfrags = maxfrags;
+        world.partime = partime;
+        for (plyr, score) in world.plyr.iter_mut().zip(scores) {
+            plyr.score = score;
+        }
+
+        let restored = match self.level.as_mut() {
+            Some(level) => level.unarchive(archive),
+            None => Err(SaveError::Invalid("level failed to load".into())),
+        };
+        if let Err(e) = restored {
+            // Too late to keep the old game, so drop back to the title
+            self.level = None;
+            self.start_title();
+            return Err(e);
+        }
+
+        // prepare_new cleared the random index
+        p_set_random_index(random_index);
+        Ok(())
+    }
+}
@@ -7,17 +7,18 @@ use gamestate_traits::SubsystemTrait;
 
 /// Blob of various tickers required during gameplay, this exists mostly to pass
 /// things around as some functions can end up with quite a few args
-pub struct GameSubsystem<I, S, H, F>
+pub struct GameSubsystem<I, S, H, F, A>
 where
     I: SubsystemTrait,
     S: SubsystemTrait,
     H: SubsystemTrait,
     F: SubsystemTrait,
+    A: SubsystemTrait,
 {
     /// Shows the players current status, updated every tick
     pub statusbar: S,
-    // update the automap display info
-    // AM_Ticker();
+    /// The automap, drawn in place of the player view when active
+    pub automap: A,
     // update the HUD statuses (things like timeout displayed messages)
     pub hud_msgs: H,
     /// Screen wipe and intermission - WI_Ticker calls world_done()
@@ -1,4 +1,6 @@
-use gamestate_traits::{GameTraits, PixelBuffer, Scancode, SubsystemTrait, TICRATE};
+use gamestate_traits::{
+    Anchor, BufferSize, GameTraits, PixelBuffer, Scancode, ScreenScale, SubsystemTrait, TICRATE,
+};
 use hud_util::{HUD_STRING, HUDString, load_char_patches};
 use wad::WadData;
 use wad::types::WadPalette;
@@ -15,6 +17,11 @@ pub struct Messages {
     /// End of lines (wraps around)
     current: usize,
     count_down: i32,
+    /// Level name shown while the automap is active
+    title: HUDString,
+    show_title: bool,
+    /// Where the HUD scaled part being drawn is kept
+    anchor: Anchor,
 }
 
 impl Messages {
@@ -31,6 +38,9 @@ impl Messages {
             start: 0,
             current: 0,
             count_down: COUNT_DOWN,
+            title: HUD_STRING,
+            show_title: false,
+            anchor: Anchor::TopLeft,
         }
     }
 
@@ -88,6 +98,12 @@ impl Messages {
             pos += 1;
         }
     }
+
+    /// Draw the level name bottom-left, above the statusbar
+    pub fn draw_title(&self, machination: &impl SubsystemTrait, buffer: &mut impl PixelBuffer) {
+        let y = 167 - self.title.line_height();
+        self.title.draw(0, y, machination, buffer);
+    }
 }
 
 impl SubsystemTrait for Messages {
@@ -95,6 +111,8 @@ impl SubsystemTrait for Messages {
         for l in self.lines.iter_mut() {
             l.clear();
         }
+        self.title.clear();
+        self.show_title = false;
     }
 
     fn responder(&mut self, _sc: Scancode, _game: &mut impl GameTraits) -> bool {
@@ -107,6 +125,18 @@ impl SubsystemTrait for Messages {
                 l.inc_current_char();
             }
         }
+        self.show_title = game.automap_active();
+        if self.show_title && self.title.line().is_empty() {
+            // Only the characters in the HUD font can be drawn
+            let title = game
+                .level_title()
+                .to_ascii_uppercase()
+                .chars()
+                .filter(|c| (' '..='_').contains(c))
+                .collect();
+            self.title.replace(title);
+            self.title.set_draw_all();
+        }
         if let Some(msg) = game.player_msg_take() {
             self.add_line(msg.to_ascii_uppercase());
         }
@@ -126,10 +156,19 @@ impl SubsystemTrait for Messages {
         &self.palette
     }
 
+    fn screen_scale(&self, size: &BufferSize) -> ScreenScale {
+        ScreenScale::hud(size, self.anchor)
+    }
+
     fn draw(&mut self, buffer: &mut impl PixelBuffer) {
         self.screen_width = buffer.size().width();
         self.screen_height = buffer.size().height();
+        self.anchor = Anchor::TopLeft;
         self.draw_wrapped(self, buffer);
+        if self.show_title {
+            self.anchor = Anchor::BottomLeft;
+            self.draw_title(self, buffer);
+        }
     }
 }
 
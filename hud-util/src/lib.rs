@@ -86,6 +86,14 @@ impl HUDString {
         &self.data
     }
 
+    /// Width in pixels of the string if it is drawn on one line
+    pub fn width(&self) -> i32 {
+        self.data
+            .chars()
+            .map(|c| get_patch_for_char(c).map_or(self.space_width, |p| p.width as i32))
+            .sum()
+    }
+
     pub fn replace(&mut self, string: String) {
         self.data = string;
     }
@@ -1,78 +1,591 @@
 use nanoserde::{DeRon, SerRon};
+use sdl2::controller::{Axis, Button};
 use sdl2::keyboard::Scancode;
 use sdl2::mouse::MouseButton;
 
+/// The most keys that can be bound to one action from the controls menu. The
+/// config file can bind any number.
+pub const MAX_KEYS_PER_ACTION: usize = 2;
+
+/// A controller button, or a trigger used as a button
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PadInput {
+    Button(Button),
+    /// `Axis::TriggerLeft` or `Axis::TriggerRight`
+    Trigger(Axis),
+}
+
+/// The names used in the config, which are the same as SDL's. These aren't
+/// looked up with SDL so that the config can be read before it is started.
+const PAD_NAMES: [(PadInput, &str); 17] = [
+    (PadInput::Button(Button::A), "a"),
+    (PadInput::Button(Button::B), "b"),
+    (PadInput::Button(Button::X), "x"),
+    (PadInput::Button(Button::Y), "y"),
+    (PadInput::Button(Button::Back), "back"),
+    (PadInput::Button(Button::Guide), "guide"),
+    (PadInput::Button(Button::Start), "start"),
+    (PadInput::Button(Button::LeftStick), "leftstick"),
+    (PadInput::Button(Button::RightStick), "rightstick"),
+    (PadInput::Button(Button::LeftShoulder), "leftshoulder"),
+    (PadInput::Button(Button::RightShoulder), "rightshoulder"),
+    (PadInput::Button(Button::DPadUp), "dpup"),
+    (PadInput::Button(Button::DPadDown), "dpdown"),
+    (PadInput::Button(Button::DPadLeft), "dpleft"),
+    (PadInput::Button(Button::DPadRight), "dpright"),
+    (PadInput::Trigger(Axis::TriggerLeft), "lefttrigger"),
+    (PadInput::Trigger(Axis::TriggerRight), "righttrigger"),
+];
+
+impl PadInput {
+    /// The name used in the config, `None` for buttons that can't be bound
+    pub fn name(&self) -> Option<&'static str> {
+        PAD_NAMES.iter().find(|(p, _)| p == self).map(|(_, n)| *n)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        PAD_NAMES.iter().find(|(_, n)| *n == name).map(|(p, _)| *p)
+    }
+}
+
+/// How the controller sticks and triggers respond
+#[derive(Debug, Clone, PartialEq, DeRon, SerRon)]
+pub struct ControllerConfig {
+    /// Stick movement less than this fraction of the full range is ignored
+    pub deadzone: f32,
+    /// The stick position past the deadzone is raised to this power, so 1.0
+    /// is linear and higher gives finer control near the centre
+    pub curve: f32,
+    /// Turning speed with the stick fully over, as a multiple of the fast
+    /// keyboard turn
+    pub turn_speed: f32,
+    /// Looking speed with the stick fully over, as a multiple of the look keys
+    pub look_speed: f32,
+    /// Push the right stick up to look down
+    pub invert_look: bool,
+    /// How far a trigger is pulled before it counts as pressed, 0.0-1.0
+    pub trigger_threshold: f32,
+}
+
+impl Default for ControllerConfig {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.15,
+            curve: 2.0,
+            turn_speed: 1.0,
+            look_speed: 2.0,
+            invert_look: false,
+            trigger_threshold: 0.3,
+        }
+    }
+}
+
+/// Everything that can be bound to keys, mouse buttons, or controller buttons
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DeRon, SerRon)]
+pub enum Action {
+    Forward,
+    Backward,
+    TurnLeft,
+    TurnRight,
+    StrafeLeft,
+    StrafeRight,
+    Fire,
+    Use,
+    /// Held to make turning strafe instead
+    Strafe,
+    /// Held to run
+    Speed,
+    /// Fist, or chainsaw if owned
+    Weapon1,
+    Weapon2,
+    /// Shotgun, or the super shotgun if owned
+    Weapon3,
+    Weapon4,
+    Weapon5,
+    Weapon6,
+    Weapon7,
+    Chainsaw,
+    SuperShotgun,
+    NextWeapon,
+    PrevWeapon,
+    LookUp,
+    LookDown,
+    CenterView,
+    /// Held to look up and down with the mouse
+    MouseLook,
+    Menu,
+    Automap,
+    Pause,
+    QuickSave,
+    QuickLoad,
+    Screenshot,
+    MapZoomIn,
+    MapZoomOut,
+    /// Show the whole map, or go back to the view from before
+    MapBigMap,
+    /// The pan keys only move the map while follow mode is off
+    MapPanLeft,
+    MapPanRight,
+    MapPanUp,
+    MapPanDown,
+    MapFollow,
+    MapGrid,
+    MapMark,
+    MapClearMarks,
+}
+
+impl Action {
+    /// Every action, in the order of the enum
+    pub const ALL: [Action; 42] = [
+        Action::Forward,
+        Action::Backward,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::Fire,
+        Action::Use,
+        Action::Strafe,
+        Action::Speed,
+        Action::Weapon1,
+        Action::Weapon2,
+        Action::Weapon3,
+        Action::Weapon4,
+        Action::Weapon5,
+        Action::Weapon6,
+        Action::Weapon7,
+        Action::Chainsaw,
+        Action::SuperShotgun,
+        Action::NextWeapon,
+        Action::PrevWeapon,
+        Action::LookUp,
+        Action::LookDown,
+        Action::CenterView,
+        Action::MouseLook,
+        Action::Menu,
+        Action::Automap,
+        Action::Pause,
+        Action::QuickSave,
+        Action::QuickLoad,
+        Action::Screenshot,
+        Action::MapZoomIn,
+        Action::MapZoomOut,
+        Action::MapBigMap,
+        Action::MapPanLeft,
+        Action::MapPanRight,
+        Action::MapPanUp,
+        Action::MapPanDown,
+        Action::MapFollow,
+        Action::MapGrid,
+        Action::MapMark,
+        Action::MapClearMarks,
+    ];
+
+    /// The name shown in the controls menu
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Action::Forward => "FORWARD",
+            Action::Backward => "BACKWARD",
+            Action::TurnLeft => "TURN LEFT",
+            Action::TurnRight => "TURN RIGHT",
+            Action::StrafeLeft => "STRAFE LEFT",
+            Action::StrafeRight => "STRAFE RIGHT",
+            Action::Fire => "FIRE",
+            Action::Use => "USE",
+            Action::Strafe => "STRAFE ON",
+            Action::Speed => "RUN",
+            Action::Weapon1 => "FIST",
+            Action::Weapon2 => "PISTOL",
+            Action::Weapon3 => "SHOTGUN",
+            Action::Weapon4 => "CHAINGUN",
+            Action::Weapon5 => "ROCKETS",
+            Action::Weapon6 => "PLASMA",
+            Action::Weapon7 => "BFG",
+            Action::Chainsaw => "CHAINSAW",
+            Action::SuperShotgun => "SUPER SHOTGUN",
+            Action::NextWeapon => "NEXT WEAPON",
+            Action::PrevWeapon => "PREV WEAPON",
+            Action::LookUp => "LOOK UP",
+            Action::LookDown => "LOOK DOWN",
+            Action::CenterView => "CENTER VIEW",
+            Action::MouseLook => "MOUSE LOOK",
+            Action::Menu => "MENU",
+            Action::Automap => "AUTOMAP",
+            Action::Pause => "PAUSE",
+            Action::QuickSave => "QUICKSAVE",
+            Action::QuickLoad => "QUICKLOAD",
+            Action::Screenshot => "SCREENSHOT",
+            Action::MapZoomIn => "ZOOM IN",
+            Action::MapZoomOut => "ZOOM OUT",
+            Action::MapBigMap => "WHOLE MAP",
+            Action::MapPanLeft => "PAN LEFT",
+            Action::MapPanRight => "PAN RIGHT",
+            Action::MapPanUp => "PAN UP",
+            Action::MapPanDown => "PAN DOWN",
+            Action::MapFollow => "FOLLOW",
+            Action::MapGrid => "GRID",
+            Action::MapMark => "MARK",
+            Action::MapClearMarks => "CLEAR MARKS",
+        }
+    }
+
+    fn default_keys(&self) -> Vec<Scancode> {
+        let key = match self {
+            Action::Forward => Scancode::W,
+            Action::Backward => Scancode::S,
+            Action::TurnLeft => Scancode::Left,
+            Action::TurnRight => Scancode::Right,
+            Action::StrafeLeft => Scancode::A,
+            Action::StrafeRight => Scancode::D,
+            Action::Fire => Scancode::RCtrl,
+            Action::Use => Scancode::Space,
+            Action::Strafe => Scancode::RAlt,
+            Action::Speed => Scancode::LShift,
+            Action::Weapon1 => Scancode::Num1,
+            Action::Weapon2 => Scancode::Num2,
+            Action::Weapon3 => Scancode::Num3,
+            Action::Weapon4 => Scancode::Num4,
+            Action::Weapon5 => Scancode::Num5,
+            Action::Weapon6 => Scancode::Num6,
+            Action::Weapon7 => Scancode::Num7,
+            Action::Chainsaw => Scancode::Num8,
+            Action::SuperShotgun => Scancode::Num9,
+            Action::NextWeapon => Scancode::RightBracket,
+            Action::PrevWeapon => Scancode::LeftBracket,
+            Action::LookUp => Scancode::PageUp,
+            Action::LookDown => Scancode::PageDown,
+            Action::CenterView => Scancode::End,
+            Action::MouseLook => return Vec::new(),
+            Action::Menu => Scancode::Escape,
+            Action::Automap => Scancode::Tab,
+            Action::Pause => Scancode::Pause,
+            Action::QuickSave => Scancode::F6,
+            Action::QuickLoad => Scancode::F9,
+            Action::Screenshot => Scancode::F12,
+            Action::MapZoomIn => return vec![Scancode::Equals, Scancode::KpPlus],
+            Action::MapZoomOut => return vec![Scancode::Minus, Scancode::KpMinus],
+            Action::MapBigMap => Scancode::Num0,
+            // Not the arrows, which turn
+            Action::MapPanLeft => Scancode::Kp4,
+            Action::MapPanRight => Scancode::Kp6,
+            Action::MapPanUp => Scancode::Kp8,
+            Action::MapPanDown => Scancode::Kp2,
+            Action::MapFollow => Scancode::F,
+            Action::MapGrid => Scancode::G,
+            Action::MapMark => Scancode::M,
+            Action::MapClearMarks => Scancode::C,
+        };
+        vec![key]
+    }
+
+    fn default_mouse(&self) -> Vec<MouseButton> {
+        match self {
+            Action::Fire => vec![MouseButton::Left],
+            Action::Strafe => vec![MouseButton::Middle],
+            Action::Forward => vec![MouseButton::Right],
+            _ => Vec::new(),
+        }
+    }
+
+    fn default_pad(&self) -> Vec<PadInput> {
+        let input = match self {
+            Action::Forward => PadInput::Button(Button::DPadUp),
+            Action::Backward => PadInput::Button(Button::DPadDown),
+            Action::TurnLeft => PadInput::Button(Button::DPadLeft),
+            Action::TurnRight => PadInput::Button(Button::DPadRight),
+            Action::Fire => PadInput::Trigger(Axis::TriggerRight),
+            Action::Use => PadInput::Button(Button::A),
+            Action::Speed => PadInput::Trigger(Axis::TriggerLeft),
+            Action::NextWeapon => PadInput::Button(Button::RightShoulder),
+            Action::PrevWeapon => PadInput::Button(Button::LeftShoulder),
+            Action::CenterView => PadInput::Button(Button::RightStick),
+            Action::Menu => PadInput::Button(Button::Start),
+            Action::Automap => PadInput::Button(Button::Back),
+            _ => return Vec::new(),
+        };
+        vec![input]
+    }
+}
+
+/// The keys, mouse buttons, and controller buttons bound to one action, as
+/// stored in the config
+#[derive(Debug, Clone, DeRon, SerRon)]
+pub struct ActionBinding {
+    pub action: Action,
+    #[nserde(default)]
+    pub keys: Vec<i32>,
+    #[nserde(default)]
+    pub mouse: Vec<u8>,
+    /// Missing from configs written before controllers were supported, which
+    /// keep the default controller bindings
+    pub pad: Option<Vec<String>>,
+}
+
+/// The bindings as stored in the user config. Any action missing from
+/// `bindings` keeps its default.
 #[derive(Debug, Clone, DeRon, SerRon)]
 pub struct InputConfig {
-    pub(crate) key_right: i32,
-    pub(crate) key_left: i32,
-    pub(crate) key_up: i32,
-    pub(crate) key_down: i32,
-    pub(crate) key_strafeleft: i32,
-    pub(crate) key_straferight: i32,
-    pub(crate) key_fire: i32,
-    pub(crate) key_use: i32,
-    pub(crate) key_strafe: i32,
-    pub(crate) key_speed: i32,
-    pub(crate) mousebfire: u8,
-    pub(crate) mousebstrafe: u8,
-    pub(crate) mousebforward: u8,
+    #[nserde(default)]
+    pub(crate) bindings: Vec<ActionBinding>,
+    #[nserde(default)]
+    pub(crate) controller: ControllerConfig,
+    // The single key or button bindings of older configs. These are read so
+    // existing configs keep their controls, and are never written.
+    key_right: Option<i32>,
+    key_left: Option<i32>,
+    key_up: Option<i32>,
+    key_down: Option<i32>,
+    key_strafeleft: Option<i32>,
+    key_straferight: Option<i32>,
+    key_fire: Option<i32>,
+    key_use: Option<i32>,
+    key_strafe: Option<i32>,
+    key_speed: Option<i32>,
+    mousebfire: Option<u8>,
+    mousebstrafe: Option<u8>,
+    mousebforward: Option<u8>,
 }
 
 impl Default for InputConfig {
     fn default() -> Self {
-        InputConfig {
-            key_right: Scancode::Right as i32,
-            key_left: Scancode::Left as i32,
-
-            key_up: Scancode::W as i32,
-            key_down: Scancode::S as i32,
-            key_strafeleft: Scancode::A as i32,
-            key_straferight: Scancode::D as i32,
-            key_fire: Scancode::RCtrl as i32,
-            key_use: Scancode::Space as i32,
-            key_strafe: Scancode::RAlt as i32,
-            key_speed: Scancode::LShift as i32,
-
-            mousebfire: MouseButton::Left as u8,
-            mousebstrafe: MouseButton::Middle as u8,
-            mousebforward: MouseButton::Right as u8,
-        }
+        (&InputConfigSdl::default()).into()
     }
 }
 
+/// The bindings of every action, indexed by `Action`
+#[derive(Debug, Clone, PartialEq)]
 pub struct InputConfigSdl {
-    pub(crate) key_right: Scancode,
-    pub(crate) key_left: Scancode,
-    pub(crate) key_up: Scancode,
-    pub(crate) key_down: Scancode,
-    pub(crate) key_strafeleft: Scancode,
-    pub(crate) key_straferight: Scancode,
-    pub(crate) key_fire: Scancode,
-    pub(crate) key_use: Scancode,
-    pub(crate) key_strafe: Scancode,
-    pub(crate) key_speed: Scancode,
-    pub(crate) mousebfire: MouseButton,
-    pub(crate) mousebstrafe: MouseButton,
-    pub(crate) mousebforward: MouseButton,
+    keys: Vec<Vec<Scancode>>,
+    mouse: Vec<Vec<MouseButton>>,
+    pad: Vec<Vec<PadInput>>,
+    controller: ControllerConfig,
+}
+
+impl Default for InputConfigSdl {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL.iter().map(Action::default_keys).collect(),
+            mouse: Action::ALL.iter().map(Action::default_mouse).collect(),
+            pad: Action::ALL.iter().map(Action::default_pad).collect(),
+            controller: ControllerConfig::default(),
+        }
+    }
+}
+
+impl InputConfigSdl {
+    pub fn keys(&self, action: Action) -> &[Scancode] {
+        &self.keys[action as usize]
+    }
+
+    pub fn mouse_buttons(&self, action: Action) -> &[MouseButton] {
+        &self.mouse[action as usize]
+    }
+
+    pub fn pad_inputs(&self, action: Action) -> &[PadInput] {
+        &self.pad[action as usize]
+    }
+
+    pub fn controller(&self) -> &ControllerConfig {
+        &self.controller
+    }
+
+    /// Is `sc` one of the keys bound to `action`
+    pub fn is_key(&self, action: Action, sc: Scancode) -> bool {
+        self.keys[action as usize].contains(&sc)
+    }
+
+    /// Bind `sc` to `action`, removing it from any other action. If the action
+    /// already has `MAX_KEYS_PER_ACTION` keys the oldest is dropped.
+    pub fn bind_key(&mut self, action: Action, sc: Scancode) {
+        if self.is_key(action, sc) {
+            return;
+        }
+        for keys in self.keys.iter_mut() {
+            keys.retain(|k| *k != sc);
+        }
+        let keys = &mut self.keys[action as usize];
+        if keys.len() >= MAX_KEYS_PER_ACTION {
+            keys.drain(..=keys.len() - MAX_KEYS_PER_ACTION);
+        }
+        keys.push(sc);
+    }
+
+    /// Remove every key, mouse button, and controller button bound to `action`
+    pub fn clear(&mut self, action: Action) {
+        self.keys[action as usize].clear();
+        self.mouse[action as usize].clear();
+        self.pad[action as usize].clear();
+    }
 }
 
 impl From<&InputConfig> for InputConfigSdl {
     fn from(i: &InputConfig) -> Self {
+        let mut cfg = Self {
+            controller: i.controller.clone(),
+            ..Self::default()
+        };
+
+        let legacy_keys = [
+            (Action::TurnRight, i.key_right),
+            (Action::TurnLeft, i.key_left),
+            (Action::Forward, i.key_up),
+            (Action::Backward, i.key_down),
+            (Action::StrafeLeft, i.key_strafeleft),
+            (Action::StrafeRight, i.key_straferight),
+            (Action::Fire, i.key_fire),
+            (Action::Use, i.key_use),
+            (Action::Strafe, i.key_strafe),
+            (Action::Speed, i.key_speed),
+        ];
+        for (action, key) in legacy_keys {
+            if let Some(sc) = key.and_then(Scancode::from_i32) {
+                cfg.keys[action as usize] = vec![sc];
+            }
+        }
+        let legacy_mouse = [
+            (Action::Fire, i.mousebfire),
+            (Action::Strafe, i.mousebstrafe),
+            (Action::Forward, i.mousebforward),
+        ];
+        for (action, button) in legacy_mouse {
+            if let Some(button) = button {
+                cfg.mouse[action as usize] = vec![MouseButton::from_ll(button)];
+            }
+        }
+
+        for binding in i.bindings.iter() {
+            let action = binding.action as usize;
+            cfg.keys[action] = binding
+                .keys
+                .iter()
+                .filter_map(|k| Scancode::from_i32(*k))
+                .collect();
+            cfg.mouse[action] = binding
+                .mouse
+                .iter()
+                .map(|b| MouseButton::from_ll(*b))
+                .filter(|b| *b != MouseButton::Unknown)
+                .collect();
+            if let Some(pad) = &binding.pad {
+                cfg.pad[action] = pad.iter().filter_map(|n| PadInput::from_name(n)).collect();
+            }
+        }
+        cfg
+    }
+}
+
+impl From<&InputConfigSdl> for InputConfig {
+    fn from(i: &InputConfigSdl) -> Self {
         Self {
-            key_right: Scancode::from_i32(i.key_right).unwrap(),
-            key_left: Scancode::from_i32(i.key_left).unwrap(),
-            key_up: Scancode::from_i32(i.key_up).unwrap(),
-            key_down: Scancode::from_i32(i.key_down).unwrap(),
-            key_strafeleft: Scancode::from_i32(i.key_strafeleft).unwrap(),
-            key_straferight: Scancode::from_i32(i.key_straferight).unwrap(),
-            key_fire: Scancode::from_i32(i.key_fire).unwrap(),
-            key_use: Scancode::from_i32(i.key_use).unwrap(),
-            key_strafe: Scancode::from_i32(i.key_strafe).unwrap(),
-            key_speed: Scancode::from_i32(i.key_speed).unwrap(),
-            mousebfire: MouseButton::from_ll(i.mousebfire),
-            mousebstrafe: MouseButton::from_ll(i.mousebstrafe),
-            mousebforward: MouseButton::from_ll(i.mousebforward),
+            bindings: Action::ALL
+                .iter()
+                .map(|action| ActionBinding {
+                    action: *action,
+                    keys: i.keys(*action).iter().map(|k| *k as i32).collect(),
+                    mouse: i.mouse_buttons(*action).iter().map(|b| *b as u8).collect(),
+                    pad: Some(
+                        i.pad_inputs(*action)
+                            .iter()
+                            .filter_map(|p| p.name().map(str::to_string))
+                            .collect(),
+                    ),
+                })
+                .collect(),
+            controller: i.controller.clone(),
+            key_right: None,
+            key_left: None,
+            key_up: None,
+            key_down: None,
+            key_strafeleft: None,
+            key_straferight: None,
+            key_fire: None,
+            key_use: None,
+            key_strafe: None,
+            key_speed: None,
+            mousebfire: None,
+            mousebstrafe: None,
+            mousebforward: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_in_enum_order() {
+        for (i, action) in Action::ALL.iter().enumerate() {
+            assert_eq!(*action as usize, i);
+        }
+    }
+
+    #[test]
+    fn default_keys_are_unique() {
+        let sdl = InputConfigSdl::default();
+        let mut seen = Vec::new();
+        for action in Action::ALL {
+            for key in sdl.keys(action) {
+                assert!(!seen.contains(key), "{key:?} is bound twice");
+                seen.push(*key);
+            }
         }
+        assert_eq!(sdl.keys(Action::MapPanLeft), &[Scancode::Kp4]);
+    }
+
+    #[test]
+    fn bindings_round_trip() {
+        let mut sdl = InputConfigSdl::default();
+        sdl.bind_key(Action::Fire, Scancode::F);
+        sdl.clear(Action::Automap);
+        let ron = InputConfig::from(&sdl).serialize_ron();
+        let read = InputConfigSdl::from(&InputConfig::deserialize_ron(&ron).unwrap());
+        assert_eq!(read, sdl);
+        assert_eq!(read.keys(Action::Fire), &[Scancode::RCtrl, Scancode::F]);
+        assert!(read.keys(Action::Automap).is_empty());
+    }
+
+    #[test]
+    fn old_config_is_migrated() {
+        let ron = "(key_right: 7, key_left: 4, key_up: 26, key_down: 22, key_strafeleft: 80,
+            key_straferight: 79, key_fire: 224, key_use: 8, key_strafe: 226,
+            key_speed: 225, mousebfire: 3, mousebstrafe: 2, mousebforward: 1)";
+        let cfg = InputConfigSdl::from(&InputConfig::deserialize_ron(ron).unwrap());
+        assert_eq!(cfg.keys(Action::TurnRight), &[Scancode::D]);
+        assert_eq!(cfg.keys(Action::StrafeLeft), &[Scancode::Left]);
+        assert_eq!(cfg.mouse_buttons(Action::Fire), &[MouseButton::Right]);
+        assert_eq!(cfg.mouse_buttons(Action::Forward), &[MouseButton::Left]);
+        // Actions the old config couldn't bind get the defaults
+        assert_eq!(cfg.keys(Action::Automap), &[Scancode::Tab]);
+        assert_eq!(
+            cfg.pad_inputs(Action::Fire),
+            &[PadInput::Trigger(Axis::TriggerRight)]
+        );
+    }
+
+    #[test]
+    fn pad_bindings_by_name() {
+        let ron = r#"(bindings: [(action: Use, keys: [44], pad: ["x", "dpup", "nope"])],
+            controller: (deadzone: 0.25, curve: 1.0, turn_speed: 1.0, look_speed: 1.0,
+            invert_look: true, trigger_threshold: 0.5))"#;
+        let cfg = InputConfigSdl::from(&InputConfig::deserialize_ron(ron).unwrap());
+        assert_eq!(
+            cfg.pad_inputs(Action::Use),
+            &[
+                PadInput::Button(Button::X),
+                PadInput::Button(Button::DPadUp)
+            ]
+        );
+        assert_eq!(cfg.controller().deadzone, 0.25);
+        assert!(cfg.controller().invert_look);
+    }
+
+    #[test]
+    fn binding_a_key_moves_it() {
+        let mut cfg = InputConfigSdl::default();
+        cfg.bind_key(Action::Use, Scancode::W);
+        cfg.bind_key(Action::Use, Scancode::E);
+        assert!(cfg.keys(Action::Forward).is_empty());
+        assert_eq!(cfg.keys(Action::Use), &[Scancode::W, Scancode::E]);
     }
 }
@@ -2,18 +2,76 @@
 //! inputs in the `Game` in a generalised way.
 //!
 //! Also does config options for controls.
+//!
+//! Game controllers are opened as they are plugged in. The sticks move, turn,
+//! and look, and their buttons and triggers are bound to actions the same as
+//! keys.
 
 pub mod config;
 
 use std::collections::hash_set::HashSet;
 
-use config::InputConfigSdl;
-use gameplay::WeaponType;
+use config::{Action, ControllerConfig, InputConfigSdl, PadInput};
+use gameplay::log::{info, warn};
 use gameplay::tic_cmd::*;
-use sdl2::EventPump;
+use gameplay::{Player, PowerType, WeaponType};
+use sdl2::controller::{Axis, Button, GameController};
 use sdl2::event::Event;
 use sdl2::keyboard::Scancode as Sc;
 use sdl2::mouse::MouseButton as Mb;
+use sdl2::{EventPump, GameControllerSubsystem};
+
+/// How far the look keys move the view each tic
+const LOOK_SPEED: i16 = 4;
+
+/// The weapon each weapon key sends. The player changes the fist to the
+/// chainsaw, and the shotgun to the super shotgun, if they are owned. The
+/// super shotgun key is handled apart as it can't be sent.
+const WEAPON_SLOTS: [(Action, WeaponType); 8] = [
+    (Action::Weapon1, WeaponType::Fist),
+    (Action::Weapon2, WeaponType::Pistol),
+    (Action::Weapon3, WeaponType::Shotgun),
+    (Action::Weapon4, WeaponType::Chaingun),
+    (Action::Weapon5, WeaponType::Missile),
+    (Action::Weapon6, WeaponType::Plasma),
+    (Action::Weapon7, WeaponType::BFG),
+    (Action::Chainsaw, WeaponType::Chainsaw),
+];
+
+/// Actions that are handled by the responders, which are only sent keys. A
+/// controller button bound to one sends the first key bound to it.
+const RESPONDER_ACTIONS: [Action; 17] = [
+    Action::Menu,
+    Action::Automap,
+    Action::Pause,
+    Action::QuickSave,
+    Action::QuickLoad,
+    Action::Screenshot,
+    Action::MapZoomIn,
+    Action::MapZoomOut,
+    Action::MapBigMap,
+    Action::MapPanLeft,
+    Action::MapPanRight,
+    Action::MapPanUp,
+    Action::MapPanDown,
+    Action::MapFollow,
+    Action::MapGrid,
+    Action::MapMark,
+    Action::MapClearMarks,
+];
+
+/// The order next and previous weapon cycle through
+const WEAPON_ORDER: [WeaponType; 9] = [
+    WeaponType::Fist,
+    WeaponType::Chainsaw,
+    WeaponType::Pistol,
+    WeaponType::Shotgun,
+    WeaponType::SuperShotgun,
+    WeaponType::Chaingun,
+    WeaponType::Missile,
+    WeaponType::Plasma,
+    WeaponType::BFG,
+];
 
 #[derive(Default, Clone)]
 pub struct InputEvents {
@@ -24,6 +82,15 @@ pub struct InputEvents {
     mouse_threshold: f32,
     mouse_acceleration: f32,
     turn_held: u32,
+    /// Next or previous weapon was held last tic
+    weapon_cycle_held: bool,
+    /// Controller buttons held, and the position of each axis
+    pad_buttons: HashSet<Button>,
+    pad_axes: [i16; 6],
+    /// Controller buttons and triggers pressed since `take_pad_keys`
+    pad_pressed: Vec<PadInput>,
+    /// The left and right triggers were pressed at the last `take_pad_keys`
+    pad_triggers_held: [bool; 2],
 }
 impl InputEvents {
     fn new(mouse_scale: (i32, i32)) -> Self {
@@ -89,13 +156,160 @@ impl InputEvents {
         }
     }
 
-    pub fn build_tic_cmd(&mut self, cfg: &InputConfigSdl) -> TicCmd {
+    /// Is a controller button held, or a trigger pulled past the threshold
+    pub fn is_pad_pressed(&self, input: PadInput, cfg: &ControllerConfig) -> bool {
+        match input {
+            PadInput::Button(button) => self.pad_buttons.contains(&button),
+            PadInput::Trigger(axis) => {
+                self.pad_axes[axis as usize] as f32 / i16::MAX as f32 >= cfg.trigger_threshold
+            }
+        }
+    }
+
+    /// The position of a stick axis from -1.0 to 1.0, after the deadzone and
+    /// response curve
+    fn pad_stick(&self, axis: Axis, cfg: &ControllerConfig) -> f32 {
+        let value = (self.pad_axes[axis as usize] as f32 / i16::MAX as f32).clamp(-1.0, 1.0);
+        let deadzone = cfg.deadzone.clamp(0.0, 0.99);
+        if value.abs() <= deadzone {
+            return 0.0;
+        }
+        let scaled = (value.abs() - deadzone) / (1.0 - deadzone);
+        scaled.powf(cfg.curve.max(0.1)).copysign(value)
+    }
+
+    /// Apply a controller event. Opening and closing controllers is done by
+    /// `Input`, this only releases everything when one is removed.
+    pub fn controller_event(&mut self, event: &Event) {
+        match event {
+            Event::ControllerAxisMotion { axis, value, .. } => {
+                self.pad_axes[*axis as usize] = *value;
+            }
+            Event::ControllerButtonDown { button, .. } => {
+                if self.pad_buttons.insert(*button) {
+                    self.pad_pressed.push(PadInput::Button(*button));
+                }
+            }
+            Event::ControllerButtonUp { button, .. } => {
+                self.pad_buttons.remove(button);
+            }
+            Event::ControllerDeviceRemoved { .. } => {
+                self.pad_buttons.clear();
+                self.pad_axes = [0; 6];
+                self.pad_pressed.clear();
+            }
+            _ => {}
+        }
+    }
+
+    /// The keys that controller buttons pressed since the last call stand in
+    /// for, so that the menus and other responders can be used. The d-pad, A,
+    /// and B move around the menus, and a button bound to one of
+    /// `RESPONDER_ACTIONS` sends the first key bound to that action.
+    pub fn take_pad_keys(&mut self, cfg: &InputConfigSdl) -> Vec<Sc> {
+        for (i, axis) in [Axis::TriggerLeft, Axis::TriggerRight]
+            .into_iter()
+            .enumerate()
+        {
+            let pressed = self.is_pad_pressed(PadInput::Trigger(axis), cfg.controller());
+            if pressed && !self.pad_triggers_held[i] {
+                self.pad_pressed.push(PadInput::Trigger(axis));
+            }
+            self.pad_triggers_held[i] = pressed;
+        }
+
+        let mut keys = Vec::new();
+        for input in self.pad_pressed.drain(..) {
+            match input {
+                PadInput::Button(Button::DPadUp) => keys.push(Sc::Up),
+                PadInput::Button(Button::DPadDown) => keys.push(Sc::Down),
+                PadInput::Button(Button::DPadLeft) => keys.push(Sc::Left),
+                PadInput::Button(Button::DPadRight) => keys.push(Sc::Right),
+                PadInput::Button(Button::A) => keys.push(Sc::Return),
+                PadInput::Button(Button::B) => keys.push(Sc::Backspace),
+                _ => {}
+            }
+            for action in RESPONDER_ACTIONS {
+                if !cfg.pad_inputs(action).contains(&input) {
+                    continue;
+                }
+                // Escape always opens the menu
+                let key = match cfg.keys(action).first() {
+                    Some(key) => Some(*key),
+                    None if action == Action::Menu => Some(Sc::Escape),
+                    None => None,
+                };
+                keys.extend(key);
+            }
+        }
+        keys
+    }
+
+    /// Is a key, mouse button, or controller button bound to `action` held
+    pub fn is_action(&self, cfg: &InputConfigSdl, action: Action) -> bool {
+        cfg.keys(action).iter().any(|k| self.is_kb_pressed(*k))
+            || cfg
+                .mouse_buttons(action)
+                .iter()
+                .any(|b| self.is_mb_pressed(*b))
+            || cfg
+                .pad_inputs(action)
+                .iter()
+                .any(|p| self.is_pad_pressed(*p, cfg.controller()))
+    }
+
+    /// Would the player change `weapon` to another when it is sent? Those
+    /// can't be selected so are skipped when cycling.
+    fn is_redirected(player: &Player, weapon: WeaponType) -> bool {
+        let status = &player.status;
+        match weapon {
+            WeaponType::Fist => {
+                status.weaponowned[WeaponType::Chainsaw as usize]
+                    && !(status.readyweapon == WeaponType::Chainsaw
+                        && status.powers[PowerType::Strength as usize] == 0)
+            }
+            WeaponType::Shotgun => {
+                status.weaponowned[WeaponType::SuperShotgun as usize]
+                    && status.readyweapon != WeaponType::SuperShotgun
+            }
+            _ => false,
+        }
+    }
+
+    /// The weapon after (or before if `!forward`) the one being changed to,
+    /// skipping those that aren't owned or that the player would change to
+    /// another. The super shotgun is sent as the shotgun, which the player
+    /// changes to the super shotgun.
+    fn cycle_weapon(player: &Player, forward: bool) -> Option<u8> {
+        let current = if player.pendingweapon == WeaponType::NoChange {
+            player.status.readyweapon
+        } else {
+            player.pendingweapon
+        };
+        let start = WEAPON_ORDER.iter().position(|w| *w == current)?;
+        let len = WEAPON_ORDER.len();
+        (1..len)
+            .map(|i| {
+                if forward {
+                    WEAPON_ORDER[(start + i) % len]
+                } else {
+                    WEAPON_ORDER[(start + len - i) % len]
+                }
+            })
+            .find(|w| player.status.weaponowned[*w as usize] && !Self::is_redirected(player, *w))
+            .map(|w| match w {
+                WeaponType::SuperShotgun => WeaponType::Shotgun as u8,
+                w => w as u8,
+            })
+    }
+
+    pub fn build_tic_cmd(&mut self, cfg: &InputConfigSdl, player: &Player) -> TicCmd {
         let mut cmd = TicCmd::default();
 
         // cmd->consistancy = consistancy[consoleplayer][maketic % BACKUPTICS];
 
-        let strafe = self.is_kb_pressed(cfg.key_strafe) || self.is_mb_pressed(cfg.mousebstrafe);
-        let speed = if self.is_kb_pressed(cfg.key_speed) {
+        let strafe = self.is_action(cfg, Action::Strafe);
+        let speed = if self.is_action(cfg, Action::Speed) {
             1
         } else {
             0
@@ -104,8 +318,8 @@ impl InputEvents {
         let mut side = 0;
         let mut forward = 0;
 
-        let turn_right = self.is_kb_pressed(cfg.key_right);
-        let turn_left = self.is_kb_pressed(cfg.key_left);
+        let turn_right = self.is_action(cfg, Action::TurnRight);
+        let turn_left = self.is_action(cfg, Action::TurnLeft);
 
         if turn_left || turn_right {
             self.turn_held += 1;
@@ -116,72 +330,87 @@ impl InputEvents {
         let turn_speed = if self.turn_held < 6 { 2 } else { speed };
 
         if strafe {
-            if self.is_kb_pressed(cfg.key_right) {
+            if turn_right {
                 side += SIDEMOVE[speed];
             }
-            if self.is_kb_pressed(cfg.key_left) {
+            if turn_left {
                 side -= SIDEMOVE[speed];
             }
         } else {
-            if self.is_kb_pressed(cfg.key_right) {
+            if turn_right {
                 cmd.angleturn -= ANGLETURN[turn_speed];
             }
-            if self.is_kb_pressed(cfg.key_left) {
+            if turn_left {
                 cmd.angleturn += ANGLETURN[turn_speed];
             }
         }
 
-        if self.is_kb_pressed(cfg.key_up) {
+        if self.is_action(cfg, Action::Forward) {
             forward += FORWARDMOVE[speed];
         }
 
-        if self.is_kb_pressed(cfg.key_down) {
+        if self.is_action(cfg, Action::Backward) {
             forward -= FORWARDMOVE[speed];
         }
 
-        if self.is_kb_pressed(cfg.key_straferight) {
+        if self.is_action(cfg, Action::StrafeRight) {
             side += SIDEMOVE[speed];
         }
 
-        if self.is_kb_pressed(cfg.key_strafeleft) {
+        if self.is_action(cfg, Action::StrafeLeft) {
             side -= SIDEMOVE[speed];
         }
 
-        if self.is_kb_pressed(cfg.key_fire) || self.is_mb_pressed(cfg.mousebfire) {
+        if self.is_action(cfg, Action::Fire) {
             cmd.buttons |= TIC_CMD_BUTTONS.bt_attack;
         }
 
-        if self.is_kb_pressed(cfg.key_use) {
+        if self.is_action(cfg, Action::Use) {
             cmd.buttons |= TIC_CMD_BUTTONS.bt_use;
         }
 
-        for i in 0..WeaponType::NumWeapons as u8 {
-            if let Some(key) = Sc::from_i32(30 + i as i32) {
-                if self.is_kb_pressed(key) {
-                    cmd.buttons |= TIC_CMD_BUTTONS.bt_change;
-                    if i == 8 {
-                        cmd.buttons |= 2 << TIC_CMD_BUTTONS.bt_weaponshift;
-                    } else {
-                        cmd.buttons |= i << TIC_CMD_BUTTONS.bt_weaponshift;
-                    }
-                }
+        for (action, weapon) in WEAPON_SLOTS {
+            if self.is_action(cfg, action) {
+                cmd.buttons |= TIC_CMD_BUTTONS.bt_change;
+                cmd.buttons |= (weapon as u8) << TIC_CMD_BUTTONS.bt_weaponshift;
             }
         }
+        // Sent as the shotgun, so only when the player will change it
+        if self.is_action(cfg, Action::SuperShotgun)
+            && Self::is_redirected(player, WeaponType::Shotgun)
+        {
+            cmd.buttons |= TIC_CMD_BUTTONS.bt_change;
+            cmd.buttons |= (WeaponType::Shotgun as u8) << TIC_CMD_BUTTONS.bt_weaponshift;
+        }
 
-        // Mouse
-        if self.is_mb_pressed(cfg.mousebforward) {
-            forward += FORWARDMOVE[speed];
+        // Only change once per press, the pending weapon isn't updated until
+        // the player thinks
+        let next = self.is_action(cfg, Action::NextWeapon);
+        let prev = self.is_action(cfg, Action::PrevWeapon);
+        if (next || prev)
+            && !self.weapon_cycle_held
+            && let Some(weapon) = Self::cycle_weapon(player, next)
+        {
+            cmd.buttons |= TIC_CMD_BUTTONS.bt_change;
+            cmd.buttons |= weapon << TIC_CMD_BUTTONS.bt_weaponshift;
+        }
+        self.weapon_cycle_held = next || prev;
+
+        if self.is_action(cfg, Action::LookUp) {
+            cmd.lookdir += LOOK_SPEED;
+        }
+        if self.is_action(cfg, Action::LookDown) {
+            cmd.lookdir -= LOOK_SPEED;
+        }
+        if self.is_action(cfg, Action::CenterView) {
+            cmd.lookdir = -player.lookdir;
         }
 
         let mousex = self.mouse_delta.0;
         let mousey = self.mouse_delta.1;
 
-        if true {
-            // TODO: invert settings - disabled until we have a setting for
-            //       mouse movement
-            // cmd.lookdir = -(mousey) as i16;
-        } else {
-            forward += -self.mouse_delta.1;
+        if self.is_action(cfg, Action::MouseLook) {
+            cmd.lookdir -= mousey as i16;
         }
 
         if strafe {
@@ -191,6 +420,19 @@ impl InputEvents {
         }
         self.reset_mouse_delta();
 
+        // Controller sticks, which are up when negative
+        let pad = cfg.controller();
+        forward -= (self.pad_stick(Axis::LeftY, pad) * FORWARDMOVE[speed] as f32) as i32;
+        side += (self.pad_stick(Axis::LeftX, pad) * SIDEMOVE[speed] as f32) as i32;
+        let turn = self.pad_stick(Axis::RightX, pad);
+        if strafe {
+            side += (turn * SIDEMOVE[speed] as f32) as i32;
+        } else {
+            cmd.angleturn -= (turn * ANGLETURN[1] as f32 * pad.turn_speed) as i16;
+        }
+        let look = self.pad_stick(Axis::RightY, pad) * LOOK_SPEED as f32 * pad.look_speed;
+        cmd.lookdir += if pad.invert_look { look } else { -look } as i16;
+
         forward = forward.clamp(-MAXPLMOVE, MAXPLMOVE);
         side = side.clamp(-MAXPLMOVE, MAXPLMOVE);
 
@@ -217,22 +459,48 @@ impl InputEvents {
 /// Fetch all input
 pub struct Input {
     pump: EventPump,
+    /// Opens controllers as they are plugged in. SDL sends no controller
+    /// events without it.
+    controller_subsystem: Option<GameControllerSubsystem>,
+    /// Controllers are closed when dropped
+    controllers: Vec<GameController>,
     pub events: InputEvents,
-    pub config: InputConfigSdl,
     quit: bool,
 }
 
 impl Input {
-    pub fn new(mut pump: EventPump, config: InputConfigSdl) -> Input {
+    pub fn new(
+        mut pump: EventPump,
+        controller_subsystem: Option<GameControllerSubsystem>,
+    ) -> Input {
         pump.pump_events();
         Input {
             pump,
+            controller_subsystem,
+            controllers: Vec::new(),
             events: InputEvents::new((5, 1)),
-            config,
             quit: false,
         }
     }
 
+    /// SDL sends `ControllerDeviceAdded` for controllers that are already
+    /// plugged in when it starts, so this is the only place they're opened
+    fn open_controller(&mut self, index: u32) {
+        let Some(subsystem) = &self.controller_subsystem else {
+            return;
+        };
+        match subsystem.open(index) {
+            Ok(controller) => {
+                let id = controller.instance_id();
+                if self.controllers.iter().all(|c| c.instance_id() != id) {
+                    info!("Opened controller {}", controller.name());
+                    self.controllers.push(controller);
+                }
+            }
+            Err(e) => warn!("Couldn't open controller {index}: {e}"),
+        }
+    }
+
     /// The way this is set up to work is that for each `game tick`, a fresh set
     /// of events is gathered and stored. This results in a constant stream
     /// of events as long as an input is active/pressed. The event is
@@ -289,6 +557,15 @@ impl Input {
                     self.events.apply_mouse_sensitivity((xrel, yrel));
                 }
 
+                Event::ControllerDeviceAdded { which, .. } => self.open_controller(which),
+                Event::ControllerDeviceRemoved { which, .. } => {
+                    self.controllers.retain(|c| c.instance_id() != which);
+                    self.events.controller_event(&event);
+                }
+                Event::ControllerAxisMotion { .. }
+                | Event::ControllerButtonDown { .. }
+                | Event::ControllerButtonUp { .. } => self.events.controller_event(&event),
+
                 Event::Quit { .. } => self.quit = true, // Early out if Quit
                 _ => events_callback(event),
             }
@@ -298,3 +575,126 @@ impl Input {
         self.quit
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn axis(axis: Axis, value: i16) -> Event {
+        Event::ControllerAxisMotion {
+            timestamp: 0,
+            which: 0,
+            axis,
+            value,
+        }
+    }
+
+    fn button(button: Button) -> Event {
+        Event::ControllerButtonDown {
+            timestamp: 0,
+            which: 0,
+            button,
+        }
+    }
+
+    #[test]
+    fn stick_moves_and_turns() {
+        let cfg = InputConfigSdl::default();
+        let player = Player::default();
+        let mut events = InputEvents::default();
+        events.controller_event(&axis(Axis::LeftY, i16::MIN));
+        events.controller_event(&axis(Axis::LeftX, i16::MAX));
+        events.controller_event(&axis(Axis::RightX, i16::MAX));
+        let cmd = events.build_tic_cmd(&cfg, &player);
+        assert_eq!(cmd.forwardmove as i32, FORWARDMOVE[0]);
+        assert_eq!(cmd.sidemove as i32, SIDEMOVE[0]);
+        assert_eq!(cmd.angleturn, -ANGLETURN[1]);
+    }
+
+    #[test]
+    fn stick_deadzone_and_curve() {
+        let cfg = InputConfigSdl::default();
+        let player = Player::default();
+        let mut events = InputEvents::default();
+        // Inside the default deadzone of 15%
+        events.controller_event(&axis(Axis::LeftY, -4000));
+        assert_eq!(events.build_tic_cmd(&cfg, &player).forwardmove, 0);
+        // Half way past the deadzone is a quarter speed on the default curve
+        events.controller_event(&axis(Axis::LeftY, -(0.575 * i16::MAX as f32) as i16));
+        let forward = events.build_tic_cmd(&cfg, &player).forwardmove as i32;
+        assert_eq!(forward, FORWARDMOVE[0] / 4);
+    }
+
+    #[test]
+    fn trigger_fires_and_removal_releases() {
+        let cfg = InputConfigSdl::default();
+        let player = Player::default();
+        let mut events = InputEvents::default();
+        events.controller_event(&axis(Axis::TriggerRight, 5000));
+        let cmd = events.build_tic_cmd(&cfg, &player);
+        assert_eq!(cmd.buttons & TIC_CMD_BUTTONS.bt_attack, 0);
+        events.controller_event(&axis(Axis::TriggerRight, i16::MAX));
+        events.controller_event(&button(Button::A));
+        let cmd = events.build_tic_cmd(&cfg, &player);
+        assert_ne!(cmd.buttons & TIC_CMD_BUTTONS.bt_attack, 0);
+        assert_ne!(cmd.buttons & TIC_CMD_BUTTONS.bt_use, 0);
+
+        events.controller_event(&Event::ControllerDeviceRemoved {
+            timestamp: 0,
+            which: 0,
+        });
+        let cmd = events.build_tic_cmd(&cfg, &player);
+        assert_eq!(cmd.buttons, 0);
+    }
+
+    #[test]
+    fn cycle_skips_redirected_weapons() {
+        let mut player = Player::default();
+        for w in [
+            WeaponType::Fist,
+            WeaponType::Chainsaw,
+            WeaponType::Pistol,
+            WeaponType::Shotgun,
+            WeaponType::SuperShotgun,
+        ] {
+            player.status.weaponowned[w as usize] = true;
+        }
+        player.pendingweapon = WeaponType::NoChange;
+        let shotgun = Some(WeaponType::Shotgun as u8);
+
+        // From the pistol the shotgun would become the super shotgun
+        player.status.readyweapon = WeaponType::Pistol;
+        assert_eq!(InputEvents::cycle_weapon(&player, true), shotgun);
+        assert!(InputEvents::is_redirected(&player, WeaponType::Shotgun));
+        // The fist would become the chainsaw
+        assert_eq!(
+            InputEvents::cycle_weapon(&player, false),
+            Some(WeaponType::Chainsaw as u8)
+        );
+
+        // With the super shotgun out the shotgun can be selected
+        player.status.readyweapon = WeaponType::SuperShotgun;
+        assert_eq!(InputEvents::cycle_weapon(&player, false), shotgun);
+        assert!(!InputEvents::is_redirected(&player, WeaponType::Shotgun));
+        assert_eq!(
+            InputEvents::cycle_weapon(&player, true),
+            Some(WeaponType::Chainsaw as u8)
+        );
+    }
+
+    #[test]
+    fn buttons_send_responder_keys() {
+        let cfg = InputConfigSdl::default();
+        let mut events = InputEvents::default();
+        events.controller_event(&button(Button::Start));
+        events.controller_event(&button(Button::DPadDown));
+        events.controller_event(&button(Button::Back));
+        assert_eq!(
+            events.take_pad_keys(&cfg),
+            vec![Sc::Escape, Sc::Down, Sc::Tab]
+        );
+        // Held buttons aren't sent again
+        events.controller_event(&button(Button::Start));
+        assert!(events.take_pad_keys(&cfg).is_empty());
+    }
+}
@@ -8,7 +8,7 @@ pub(crate) const SHOW_NEXT_LOC_DELAY: i32 = 4;
 
 pub(crate) struct Patches {
     pub nums: [WadPatch; 10],
-    // pub minus: WadPatch,
+    pub minus: WadPatch,
     pub percent: WadPatch,
     pub kills: WadPatch,
     pub sp_secret: WadPatch,
@@ -20,13 +20,15 @@ pub(crate) struct Patches {
     pub finish: WadPatch,
     // secret: WadPatch,
     // frags: WadPatch,
-    // killers: WadPatch,
-    // victims: WadPatch,
-    // total: WadPatch,
+    pub killers: WadPatch,
+    pub victims: WadPatch,
+    pub total: WadPatch,
     // sucks: WadPatch,
-    // star: WadPatch,
-    // bstar: WadPatch,
-    // players: [WadPatch; MAXPLAYERS],
+    /// Marks the console player's row and column of the frags table
+    pub star: WadPatch,
+    pub bstar: WadPatch,
+    /// Player faces, coloured in each player's colour
+    pub players: [WadPatch; MAXPLAYERS],
     // bplayers: [WadPatch; MAXPLAYERS],
 }
 
@@ -56,6 +58,7 @@ impl Patches {
 
         Self {
             nums: get_num_sprites("WINUM", 0, wad),
+            minus: WadPatch::from_lump(wad.get_lump("WIMINUS").unwrap()),
             percent: WadPatch::from_lump(wad.get_lump("WIPCNT").unwrap()),
             kills: WadPatch::from_lump(wad.get_lump("WIOSTK").unwrap()),
             sp_secret: WadPatch::from_lump(wad.get_lump("WISCRT2").unwrap()),
@@ -68,12 +71,12 @@ impl Patches {
             // secret: WadPatch::from_lump(wad.get_lump("WIOSTS").unwrap()),
             // frags: WadPatch::from_lump(wad.get_lump("WIFRGS").unwrap()),
             // sucks: WadPatch::from_lump(wad.get_lump("WISUCKS").unwrap()),
-            // killers: WadPatch::from_lump(wad.get_lump("WIKILRS").unwrap()),
-            // victims: WadPatch::from_lump(wad.get_lump("WIVCTMS").unwrap()),
-            // total: WadPatch::from_lump(wad.get_lump("WIMSTT").unwrap()),
-            // star: WadPatch::from_lump(wad.get_lump("STFST01").unwrap()),
-            // bstar: WadPatch::from_lump(wad.get_lump("STFDEAD0").unwrap()),
-            // players: unsafe { players.map(|n| n.assume_init()) },
+            killers: WadPatch::from_lump(wad.get_lump("WIKILRS").unwrap()),
+            victims: WadPatch::from_lump(wad.get_lump("WIVCTMS").unwrap()),
+            total: WadPatch::from_lump(wad.get_lump("WIMSTT").unwrap()),
+            star: WadPatch::from_lump(wad.get_lump("STFST01").unwrap()),
+            bstar: WadPatch::from_lump(wad.get_lump("STFDEAD0").unwrap()),
+            players: unsafe { players.map(|n| n.assume_init()) },
             // bplayers: unsafe { bplayers.map(|n| n.assume_init()) },
         }
     }
@@ -0,0 +1,89 @@
+use crate::Intermission;
+use gamestate_traits::{PixelBuffer, SubsystemTrait};
+
+const DM_MATRIXX: i32 = 42;
+const DM_MATRIXY: i32 = 68;
+const DM_SPACINGX: i32 = 40;
+const DM_TOTALSX: i32 = 269;
+const DM_KILLERSX: i32 = 10;
+const DM_KILLERSY: i32 = 100;
+const DM_VICTIMSX: i32 = 5;
+const DM_VICTIMSY: i32 = 50;
+const WI_SPACINGY: i32 = 33;
+
+impl Intermission {
+    /// Draw a two digit frag count with the right edge at `x`
+    fn draw_frags(&self, frags: i32, mut x: i32, y: i32, pixels: &mut impl PixelBuffer) {
+        let width = self.patches.nums[0].width as i32;
+        let mut n = frags.unsigned_abs();
+        for _ in 0..2 {
+            x -= width;
+            self.draw_patch_pixels(&self.patches.nums[(n % 10) as usize], x, y, pixels);
+            n /= 10;
+        }
+        if frags < 0 {
+            self.draw_patch_pixels(&self.patches.minus, x - 8, y, pixels);
+        }
+    }
+
+    /// Draw the table of who fragged who. Each row is the frags a player got,
+    /// each column is how often a player was the victim.
+    ///
+    /// Doom function name `WI_drawDeathmatchStats`
+    pub(super) fn draw_dm_stats_pixels(&mut self, buffer: &mut impl PixelBuffer) {
+        // Background
+        self.draw_patch_pixels(self.get_bg(), 0, 0, buffer);
+        self.draw_animated_bg_pixels(buffer);
+        self.draw_level_finish_pixels(buffer);
+
+        let patches = &self.patches;
+        let total_x = DM_TOTALSX - patches.total.width as i32 / 2;
+        self.draw_patch_pixels(
+            &patches.total,
+            total_x,
+            DM_MATRIXY - WI_SPACINGY + 10,
+            buffer,
+        );
+        self.draw_patch_pixels(&patches.killers, DM_KILLERSX, DM_KILLERSY, buffer);
+        self.draw_patch_pixels(&patches.victims, DM_VICTIMSX, DM_VICTIMSY, buffer);
+
+        // Player faces along the top and down the side
+        let mut x = DM_MATRIXX + DM_SPACINGX;
+        let mut y = DM_MATRIXY;
+        for (i, info) in self.level_info.plyr.iter().enumerate() {
+            if info.inn {
+                let face = &patches.players[i];
+                let half = face.width as i32 / 2;
+                self.draw_patch_pixels(face, x - half, DM_MATRIXY - WI_SPACINGY, buffer);
+                self.draw_patch_pixels(face, DM_MATRIXX - half, y, buffer);
+                if i == self.level_info.pnum {
+                    self.draw_patch_pixels(
+                        &patches.bstar,
+                        x - half,
+                        DM_MATRIXY - WI_SPACINGY,
+                        buffer,
+                    );
+                    self.draw_patch_pixels(&patches.star, DM_MATRIXX - half, y, buffer);
+                }
+            }
+            x += DM_SPACINGX;
+            y += WI_SPACINGY;
+        }
+
+        let width = patches.nums[0].width as i32;
+        let mut y = DM_MATRIXY + 10;
+        for (i, info) in self.level_info.plyr.iter().enumerate() {
+            if info.inn {
+                let mut x = DM_MATRIXX + DM_SPACINGX;
+                for (frags, victim) in info.frags.iter().zip(self.level_info.plyr.iter()) {
+                    if victim.inn {
+                        self.draw_frags(*frags, x + width, y, buffer);
+                    }
+                    x += DM_SPACINGX;
+                }
+                self.draw_frags(info.frag_count(i), DM_TOTALSX + width, y, buffer);
+            }
+            y += WI_SPACINGY;
+        }
+    }
+}
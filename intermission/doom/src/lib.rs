@@ -1,17 +1,20 @@
 //! Display the end-of-level statistics for the player and the next level's name
 
 use crate::defs::{
-    AnimType, Animation, MAP_POINTS, Patches, SHOW_NEXT_LOC_DELAY, State, animations
+    AnimType, Animation, MAP_POINTS, Patches, SHOW_NEXT_LOC_DELAY, State, animations,
 };
 use gameplay::{TICRATE, m_random};
 use gamestate_traits::{
-    GameMode, GameTraits, MusTrack, PixelBuffer, Scancode, SubsystemTrait, WorldEndPlayerInfo, WorldInfo
+    GameMode, GameTraits, MusTrack, PixelBuffer, Scancode, SubsystemTrait, WorldEndPlayerInfo,
+    WorldInfo,
 };
+use hud_util::HUDString;
 use log::warn;
 use wad::WadData;
 use wad::types::{WadPalette, WadPatch};
 
 mod defs;
+mod dm_stat_state;
 mod loc_state;
 mod no_state;
 mod stat_state;
@@ -19,6 +22,7 @@ mod stat_state;
 const EP4_BG: &str = "INTERPIC";
 const COMMERCIAL_BG: &str = "INTERPIC";
 const TITLE_Y: i32 = 2;
+const SCREEN_WIDTH: i32 = 320;
 
 pub struct Intermission {
     palette: WadPalette,
@@ -41,6 +45,15 @@ pub struct Intermission {
     state: State,
     /// General patches not specific to retail/commercial/registered
     patches: Patches,
+    /// UMAPINFO level name patches for the finished and entering screens
+    this_pic: Option<WadPatch>,
+    next_pic: Option<WadPatch>,
+    /// UMAPINFO level names, used if there is no level name patch
+    this_name: HUDString,
+    next_name: HUDString,
+    /// UMAPINFO backgrounds, these replace the animated episode maps
+    exit_pic: Option<WadPatch>,
+    enter_pic: Option<WadPatch>,
 }
 
 impl Intermission {
@@ -146,31 +159,93 @@ impl Intermission {
             count: SHOW_NEXT_LOC_DELAY * TICRATE,
             state: State::None,
             patches: Patches::new(wad),
+            this_pic: None,
+            next_pic: None,
+            this_name: HUDString::new(wad),
+            next_name: HUDString::new(wad),
+            exit_pic: None,
+            enter_pic: None,
         }
     }
 
     pub(crate) fn get_bg(&self) -> &WadPatch {
-        self.bg_patches
-            .get(self.current_bg)
-            .unwrap_or(&self.bg_patches[self.bg_patches.len() - 1])
+        let custom = if self.state == State::StatCount {
+            self.exit_pic.as_ref()
+        } else {
+            self.enter_pic.as_ref()
+        };
+        custom.unwrap_or_else(|| {
+            self.bg_patches
+                .get(self.current_bg)
+                .unwrap_or(&self.bg_patches[self.bg_patches.len() - 1])
+        })
     }
 
-    pub(crate) fn get_this_level_name(&self) -> &WadPatch {
-        let ep = if self.level_info.episode >= self.level_names.len() {
-            self.level_names.len() - 1
+    /// A UMAPINFO background is in use so the episode map isn't drawn
+    pub(crate) fn custom_bg(&self) -> bool {
+        if self.state == State::StatCount {
+            self.exit_pic.is_some()
         } else {
-            self.level_info.episode
-        };
-        &self.level_names[ep][self.level_info.last - 1]
+            self.enter_pic.is_some()
+        }
+    }
+
+    pub(crate) fn get_this_level_name(&self) -> Option<&WadPatch> {
+        if self.this_pic.is_some() {
+            return self.this_pic.as_ref();
+        }
+        let ep = self.level_info.episode.min(self.level_names.len() - 1);
+        self.level_names[ep].get(self.level_info.last.wrapping_sub(1))
+    }
+
+    pub(crate) fn get_enter_level_name(&self) -> Option<&WadPatch> {
+        if self.next_pic.is_some() {
+            return self.next_pic.as_ref();
+        }
+        let ep = self.level_info.next_episode.min(self.level_names.len() - 1);
+        self.level_names[ep].get(self.level_info.next)
     }
 
-    pub(crate) fn get_enter_level_name(&self) -> &WadPatch {
-        let ep = if self.level_info.episode >= self.level_names.len() {
-            self.level_names.len() - 1
+    /// Draw the level name patch, or the UMAPINFO name if there isn't one
+    pub(crate) fn draw_level_name(
+        &self,
+        patch: Option<&WadPatch>,
+        name: &HUDString,
+        y: i32,
+        pixels: &mut impl PixelBuffer,
+    ) {
+        if let Some(patch) = patch {
+            self.draw_patch_pixels(patch, (SCREEN_WIDTH - patch.width as i32) / 2, y, pixels);
         } else {
-            self.level_info.episode
+            name.draw((SCREEN_WIDTH - name.width()) / 2, y, self, pixels);
+        }
+    }
+
+    /// Load the UMAPINFO names and pictures for the levels being left and
+    /// entered
+    fn init_map_info(&mut self, wad: &WadData) {
+        let patch = |name: Option<&String>| {
+            name.and_then(|name| wad.get_lump(name))
+                .map(WadPatch::from_lump)
         };
-        &self.level_names[ep][self.level_info.next]
+        let last = self.level_info.last_info.as_ref();
+        let next = self.level_info.next_info.as_ref();
+
+        self.this_pic = patch(last.and_then(|info| info.level_pic.as_ref()));
+        self.next_pic = patch(next.and_then(|info| info.level_pic.as_ref()));
+        self.exit_pic = patch(last.and_then(|info| info.exit_pic.as_ref()));
+        // The exit pic is used for both screens if the next level has no enter pic
+        self.enter_pic =
+            patch(next.and_then(|info| info.enter_pic.as_ref())).or_else(|| self.exit_pic.clone());
+
+        for (hud, info) in [(&mut self.this_name, last), (&mut self.next_name, next)] {
+            let name = info
+                .and_then(|info| info.level_name.as_ref())
+                .map(|name| hud_chars(name))
+                .unwrap_or_default();
+            hud.replace(name);
+            hud.set_draw_all();
+        }
     }
 
     fn init_animated_bg(&mut self) {
@@ -238,8 +313,8 @@ impl Intermission {
         }
     }
 
-    fn draw_animated_bg_pixels(&self, scale: i32, pixels: &mut impl PixelBuffer) {
-        if self.mode == GameMode::Commercial || self.level_info.episode > 2 {
+    fn draw_animated_bg_pixels(&self, pixels: &mut impl PixelBuffer) {
+        if self.mode == GameMode::Commercial || self.level_info.episode > 2 || self.custom_bg() {
             return;
         }
 
@@ -247,8 +322,8 @@ impl Intermission {
             if anim.counter >= 0 {
                 self.draw_patch_pixels(
                     &anim.patches[anim.counter as usize],
-                    anim.location.0 * scale,
-                    anim.location.1 * scale,
+                    anim.location.0,
+                    anim.location.1,
                     pixels,
                 );
             }
@@ -275,6 +350,14 @@ impl Intermission {
     // }
 }
 
+/// Only the characters in the HUD font can be drawn
+fn hud_chars(name: &str) -> String {
+    name.to_ascii_uppercase()
+        .chars()
+        .filter(|c| (' '..='_').contains(c))
+        .collect()
+}
+
 impl SubsystemTrait for Intermission {
     fn init(&mut self, game: &impl GameTraits) {
         self.bg_count = 0;
@@ -286,8 +369,9 @@ impl SubsystemTrait for Intermission {
         self.player_info = game.player_end_info().clone();
         self.level_info = game.level_end_info().clone();
         self.current_bg = self.level_info.episode;
+        self.init_map_info(game.get_wad_data());
 
-        // TODO: deathmatch stuff
+        // The deathmatch frags table replaces the stats but counts down the same
         self.init_stats();
     }
 
@@ -346,18 +430,19 @@ impl SubsystemTrait for Intermission {
     }
 
     fn draw(&mut self, buffer: &mut impl PixelBuffer) {
-        let scale = buffer.size().height() / 200;
-
         // TODO: stats and next are two different screens.
         match self.state {
+            State::StatCount if self.level_info.deathmatch => {
+                self.draw_dm_stats_pixels(buffer);
+            }
             State::StatCount => {
-                self.draw_stats_pixels(scale, buffer);
+                self.draw_stats_pixels(buffer);
             }
             State::NextLoc => {
-                self.draw_next_loc_pixels(scale, buffer);
+                self.draw_next_loc_pixels(buffer);
             }
             State::None => {
-                self.draw_no_state(scale, buffer);
+                self.draw_no_state(buffer);
             }
         }
     }
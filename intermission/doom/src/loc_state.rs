@@ -1,4 +1,4 @@
-use crate::{Intermission, MAP_POINTS, SHOW_NEXT_LOC_DELAY, State, TICRATE, TITLE_Y};
+use crate::{Intermission, MAP_POINTS, SCREEN_WIDTH, SHOW_NEXT_LOC_DELAY, State, TICRATE, TITLE_Y};
 use gamestate_traits::{GameMode, PixelBuffer, SubsystemTrait};
 use wad::types::WadPatch;
 
@@ -21,43 +21,40 @@ impl Intermission {
         }
     }
 
-    pub(super) fn draw_on_lnode(
-        &self,
-        lv: usize,
-        patch: &WadPatch,
-        scale: i32,
-        pixels: &mut impl PixelBuffer,
-    ) {
+    pub(super) fn draw_on_lnode(&self, lv: usize, patch: &WadPatch, pixels: &mut impl PixelBuffer) {
         let ep = self.level_info.episode;
         let point = MAP_POINTS[ep][lv];
 
         let x = point.0 - patch.left_offset as i32;
         let y = point.1 - patch.top_offset as i32;
 
-        self.draw_patch_pixels(patch, x * scale, y * scale, pixels);
+        self.draw_patch_pixels(patch, x, y, pixels);
     }
 
-    pub(super) fn draw_enter_level_pixels(&self, scale: i32, buffer: &mut impl PixelBuffer) {
-        let half = buffer.size().width() / 2;
-        let mut y = TITLE_Y * scale;
+    pub(super) fn draw_enter_level_pixels(&self, buffer: &mut impl PixelBuffer) {
+        let mut y = TITLE_Y;
         self.draw_patch_pixels(
             &self.patches.enter,
-            half - self.patches.enter.width as i32 * scale / 2,
+            (SCREEN_WIDTH - self.patches.enter.width as i32) / 2,
             y,
             buffer,
         );
-        y += (5 * self.patches.enter.height as i32 * scale) / 4;
-        let patch = self.get_enter_level_name();
-        self.draw_patch_pixels(patch, half - patch.width as i32 * scale / 2, y, buffer);
+        y += (5 * self.patches.enter.height as i32) / 4;
+        self.draw_level_name(self.get_enter_level_name(), &self.next_name, y, buffer);
     }
 
-    pub(super) fn draw_next_loc_pixels(&self, scale: i32, buffer: &mut impl PixelBuffer) {
+    pub(super) fn draw_next_loc_pixels(&self, buffer: &mut impl PixelBuffer) {
         // Background
         self.draw_patch_pixels(self.get_bg(), 0, 0, buffer);
-        self.draw_animated_bg_pixels(scale, buffer);
+        self.draw_animated_bg_pixels(buffer);
 
-        // Location stuff only for episodes 1-3
-        if self.mode != GameMode::Commercial && self.level_info.episode <= 2 {
+        // Location stuff only for episodes 1-3, and only within an episode
+        if self.mode != GameMode::Commercial
+            && self.level_info.episode <= 2
+            && self.level_info.next_episode == self.level_info.episode
+            && self.level_info.next < 9
+            && !self.custom_bg()
+        {
             let last = if self.level_info.last == 8 {
                 self.level_info.next - 1
             } else {
@@ -65,21 +62,23 @@ impl Intermission {
             };
 
             for i in 0..last {
-                self.draw_on_lnode(i, &self.yah_patches[2], scale, buffer);
+                self.draw_on_lnode(i, &self.yah_patches[2], buffer);
             }
 
             if self.level_info.didsecret {
-                self.draw_on_lnode(8, &self.yah_patches[2], scale, buffer);
+                self.draw_on_lnode(8, &self.yah_patches[2], buffer);
             }
 
             if self.pointer_on {
                 let next_level = self.level_info.next;
-                self.draw_on_lnode(next_level, &self.yah_patches[self.yah_idx], scale, buffer);
+                self.draw_on_lnode(next_level, &self.yah_patches[self.yah_idx], buffer);
             }
         }
 
-        if self.mode != GameMode::Commercial || self.level_info.next != 30 {
-            self.draw_enter_level_pixels(scale, buffer);
+        if (self.mode != GameMode::Commercial || self.level_info.next != 30)
+            && !self.level_info.end_game
+        {
+            self.draw_enter_level_pixels(buffer);
         }
     }
 }
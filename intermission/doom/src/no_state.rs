@@ -3,9 +3,9 @@ use gamestate_traits::{GameTraits, PixelBuffer};
 use log::info;
 
 impl Intermission {
-    pub(super) fn draw_no_state(&mut self, scale: i32, pixels: &mut impl PixelBuffer) {
+    pub(super) fn draw_no_state(&mut self, pixels: &mut impl PixelBuffer) {
         self.pointer_on = true;
-        self.draw_next_loc_pixels(scale, pixels);
+        self.draw_next_loc_pixels(pixels);
     }
 
     pub(super) fn init_no_state(&mut self) {
@@ -1,4 +1,4 @@
-use crate::{Intermission, SHOW_NEXT_LOC_DELAY, State, TICRATE, TITLE_Y};
+use crate::{Intermission, SCREEN_WIDTH, SHOW_NEXT_LOC_DELAY, State, TICRATE, TITLE_Y};
 use gamestate_traits::util::draw_num_pixels;
 use gamestate_traits::{GameMode, PixelBuffer, SubsystemTrait};
 
@@ -23,7 +23,7 @@ impl Intermission {
 
         // self.count -= 1;
         if self.count <= 0 {
-            if self.mode == GameMode::Commercial {
+            if self.mode == GameMode::Commercial || self.level_info.end_game {
                 self.init_no_state();
             } else {
                 self.init_next_loc();
@@ -31,18 +31,16 @@ impl Intermission {
         }
     }
 
-    pub(super) fn draw_level_finish_pixels(&self, scale: i32, pixels: &mut impl PixelBuffer) {
-        let half = pixels.size().width() / 2;
-        let mut y = TITLE_Y * scale;
+    pub(super) fn draw_level_finish_pixels(&self, pixels: &mut impl PixelBuffer) {
+        let mut y = TITLE_Y;
         self.draw_patch_pixels(
             &self.patches.finish,
-            half - self.patches.enter.width as i32 * scale / 2,
+            (SCREEN_WIDTH - self.patches.finish.width as i32) / 2,
             y,
             pixels,
         );
-        y += (5 * self.patches.finish.height as i32) / 4 * scale;
-        let patch = self.get_this_level_name();
-        self.draw_patch_pixels(patch, half - patch.width as i32 * scale / 2, y, pixels);
+        y += (5 * self.patches.finish.height as i32) / 4;
+        self.draw_level_name(self.get_this_level_name(), &self.this_name, y, pixels);
     }
 
     fn draw_percent(&self, p: u32, x: i32, y: i32, pixels: &mut impl PixelBuffer) {
@@ -50,13 +48,13 @@ impl Intermission {
         draw_num_pixels(p, x, y, 0, &self.patches.nums, self, pixels);
     }
 
-    fn draw_time(&self, t: u32, mut x: i32, y: i32, scale: i32, buffer: &mut impl PixelBuffer) {
+    fn draw_time(&self, t: u32, mut x: i32, y: i32, buffer: &mut impl PixelBuffer) {
         let mut div = 1;
         if t <= 61 * 59 {
             loop {
                 let n = (t / div) % 60;
                 x = draw_num_pixels(n, x, y, 1, &self.patches.nums, self, buffer)
-                    - self.patches.colon.width as i32 * scale;
+                    - self.patches.colon.width as i32;
                 div *= 60;
 
                 if div == 60 || t / div != 0 {
@@ -70,19 +68,19 @@ impl Intermission {
         }
     }
 
-    pub(super) fn draw_stats_pixels(&mut self, scale: i32, buffer: &mut impl PixelBuffer) {
-        let width = buffer.size().width();
-        let stats_x = SP_STATSX * scale;
-        let stats_y = SP_STATSY * scale;
-        let time_x = SP_TIMEX * scale;
-        let time_y = SP_TIMEY * scale;
+    pub(super) fn draw_stats_pixels(&mut self, buffer: &mut impl PixelBuffer) {
+        let width = SCREEN_WIDTH;
+        let stats_x = SP_STATSX;
+        let stats_y = SP_STATSY;
+        let time_x = SP_TIMEX;
+        let time_y = SP_TIMEY;
 
         // Background
         self.draw_patch_pixels(self.get_bg(), 0, 0, buffer);
-        self.draw_animated_bg_pixels(scale, buffer);
-        self.draw_level_finish_pixels(scale, buffer);
+        self.draw_animated_bg_pixels(buffer);
+        self.draw_level_finish_pixels(buffer);
 
-        let mut lh = (3 * self.patches.nums[0].height / 2) as i32;
+        let lh = (3 * self.patches.nums[0].height / 2) as i32;
         self.draw_patch_pixels(&self.patches.kills, stats_x, stats_y, buffer);
         self.draw_percent(
             self.player_info.total_kills as u32,
@@ -91,7 +89,6 @@ impl Intermission {
             buffer,
         );
 
-        lh += lh;
         self.draw_patch_pixels(&self.patches.items, stats_x, stats_y + lh, buffer);
         self.draw_percent(
             self.player_info.items_collected as u32,
@@ -100,12 +97,11 @@ impl Intermission {
             buffer,
         );
 
-        lh += lh;
-        self.draw_patch_pixels(&self.patches.sp_secret, stats_x, stats_y + lh, buffer);
+        self.draw_patch_pixels(&self.patches.sp_secret, stats_x, stats_y + 2 * lh, buffer);
         self.draw_percent(
             self.player_info.secrets_found as u32,
             width - stats_x,
-            stats_y + lh,
+            stats_y + 2 * lh,
             buffer,
         );
 
@@ -114,7 +110,6 @@ impl Intermission {
             self.player_info.level_time / TICRATE as u32,
             width / 2 - time_x,
             time_y,
-            scale,
             buffer,
         );
 
@@ -124,7 +119,6 @@ impl Intermission {
                 self.level_info.partime as u32,
                 width - time_x,
                 time_y,
-                scale,
                 buffer,
             );
         }
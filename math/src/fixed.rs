@@ -14,6 +14,8 @@ pub const fn fixedt(x: i32) -> fixed_t {
     fixed_t::new(x)
 }
 
+/// The 16.16 fixed point number used for all gameplay maths. Add, subtract,
+/// and negate wrap on overflow as they do in C, demos depend on this.
 #[allow(non_camel_case_types)]
 #[derive(Clone, Copy, Debug)]
 pub struct fixed_t(pub i32);
@@ -57,7 +59,7 @@ impl fixed_t {
     }
 
     pub const fn abs(self) -> Self {
-        fixed_t(self.0.abs())
+        fixed_t(self.0.wrapping_abs())
     }
 
     pub const fn is_sign_negative(self) -> bool {
@@ -73,7 +75,7 @@ impl Neg for fixed_t {
     type Output = fixed_t;
 
     fn neg(self) -> Self::Output {
-        fixedt(self.0.neg())
+        fixedt(self.0.wrapping_neg())
     }
 }
 
@@ -90,7 +92,7 @@ impl Add<fixed_t> for fixed_t {
 
     #[inline]
     fn add(self, rhs: Self) -> Self::Output {
-        fixed_t::new(self.0 + rhs.0)
+        fixed_t::new(self.0.wrapping_add(rhs.0))
     }
 }
 
@@ -99,7 +101,7 @@ impl Sub for fixed_t {
 
     #[inline]
     fn sub(self, rhs: Self) -> Self::Output {
-        fixed_t::new(self.0 - rhs.0)
+        fixed_t::new(self.0.wrapping_sub(rhs.0))
     }
 }
 
@@ -111,7 +113,7 @@ impl SubAssign for fixed_t {
 
 impl AddAssign for fixed_t {
     fn add_assign(&mut self, rhs: Self) {
-        self.0 -= self.0 + rhs.0;
+        self.0 = self.0.wrapping_add(rhs.0);
     }
 }
 
@@ -201,4 +203,21 @@ mod test {
         let result = fixed_t::from_int(2) * FT_ONE;
         assert_eq!(result, fixed_t::from_int(2))
     }
+
+    #[test]
+    fn test_add_assign() {
+        let mut result = fixed_t::from_int(2);
+        result += FT_ONE;
+        assert_eq!(result, fixed_t::from_int(3));
+        result -= fixed_t::from_int(5);
+        assert_eq!(result, fixed_t::from_int(-2));
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let max = fixed_t::new(i32::MAX);
+        assert_eq!(max + fixed_t::new(1), fixed_t::new(i32::MIN));
+        assert_eq!(fixed_t::new(i32::MIN) - fixed_t::new(1), max);
+        assert_eq!(fixed_t::new(i32::MIN).abs(), fixed_t::new(i32::MIN));
+    }
 }
@@ -91,6 +91,20 @@ pub const fn m_clear_random() {
     }
 }
 
+/// The current position in the gameplay random table. Savegames store this so
+/// a loaded game continues with the same random sequence.
+#[inline]
+pub const fn p_random_index() -> usize {
+    unsafe { PRNDINDEX }
+}
+
+#[inline]
+pub const fn p_set_random_index(index: usize) {
+    unsafe {
+        PRNDINDEX = index & 0xFF;
+    }
+}
+
 #[inline]
 pub const fn p_subrandom() -> i32 {
     let r = p_random();
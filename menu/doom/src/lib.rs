@@ -4,19 +4,126 @@
 //! could make this fully generic with a little work, or use it as the basis for
 //! a different menu.
 
-use gamestate_traits::{GameMode, GameTraits, PixelBuffer, Scancode, Skill, SubsystemTrait};
+use gamestate_traits::english::{
+    DETAILHI, DETAILLO, DOSY, EMPTYSTRING, ENDGAME, LOADNET, NETEND, QLOADNET, QLPROMPT, QSAVESPOT,
+    QSPROMPT, QUITMSG, SAVEDEAD,
+};
+use gamestate_traits::sdl2::mouse::MouseButton;
+use gamestate_traits::{
+    Action, GameMode, GameTraits, InputConfigSdl, PixelBuffer, RenderApiType, Scancode, Settings,
+    Skill, SubsystemTrait, deh_string, savegame_file,
+};
+use hud_util::HUDString;
 use sound_traits::SfxName;
 use std::collections::HashMap;
 use wad::WadData;
 use wad::types::{WadPalette, WadPatch};
 
 const SAVESTRINGSIZE: i32 = 24;
+/// Number of slots in the load and save menus
+const SAVE_SLOTS: usize = 6;
 const SKULLXOFF: i32 = -32;
 const LINEHEIGHT: i32 = 16;
 const SKULLS: [&str; 2] = ["M_SKULL1", "M_SKULL2"];
+/// Patches drawn by the menus that aren't a title or item
+const EXTRA_PATCHES: [&str; 11] = [
+    "M_THERML", "M_THERMM", "M_THERMR", "M_THERMO", "M_GDHIGH", "M_GDLOW", "M_MSGOFF", "M_MSGON",
+    "M_LSLEFT", "M_LSCNTR", "M_LSRGHT",
+];
+
+/// Window sizes that can be picked in the video menu
+const RESOLUTIONS: [(u32, u32); 9] = [
+    (640, 480),
+    (800, 600),
+    (1024, 768),
+    (1280, 720),
+    (1280, 960),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+    (3840, 2160),
+];
+
+/// The pages of the controls menu, each a title and the actions on it
+const CONTROL_PAGES: [(&str, &[Action]); 6] = [
+    (
+        "MOVEMENT",
+        &[
+            Action::Forward,
+            Action::Backward,
+            Action::TurnLeft,
+            Action::TurnRight,
+            Action::StrafeLeft,
+            Action::StrafeRight,
+            Action::Fire,
+            Action::Use,
+            Action::Strafe,
+            Action::Speed,
+        ],
+    ),
+    (
+        "WEAPONS",
+        &[
+            Action::Weapon1,
+            Action::Weapon2,
+            Action::Weapon3,
+            Action::Weapon4,
+            Action::Weapon5,
+            Action::Weapon6,
+            Action::Weapon7,
+            Action::Chainsaw,
+            Action::SuperShotgun,
+        ],
+    ),
+    (
+        "SWITCH AND LOOK",
+        &[
+            Action::NextWeapon,
+            Action::PrevWeapon,
+            Action::LookUp,
+            Action::LookDown,
+            Action::CenterView,
+            Action::MouseLook,
+        ],
+    ),
+    (
+        "GAME",
+        &[
+            Action::Menu,
+            Action::Automap,
+            Action::Pause,
+            Action::QuickSave,
+            Action::QuickLoad,
+            Action::Screenshot,
+        ],
+    ),
+    (
+        "AUTOMAP",
+        &[
+            Action::MapZoomIn,
+            Action::MapZoomOut,
+            Action::MapBigMap,
+            Action::MapPanLeft,
+            Action::MapPanRight,
+            Action::MapPanUp,
+            Action::MapPanDown,
+        ],
+    ),
+    (
+        "AUTOMAP MARKS",
+        &[
+            Action::MapFollow,
+            Action::MapGrid,
+            Action::MapMark,
+            Action::MapClearMarks,
+        ],
+    ),
+];
 
 #[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
 enum Status {
+    /// A blank row, such as the one a slider is drawn in. The cursor skips it
+    Empty, // -1
     NoCursor, // 0
     Ok,
     ArrowsOk,
@@ -27,7 +134,11 @@ struct MenuItem {
     status: Status,
     /// The name of the patch in the wad to draw for this item
     patch: String,
-    /// A function pointer to the 'logic' that drives this menu item
+    /// Text drawn instead of a patch, for items that Doom has no patch for
+    text: &'static str,
+    /// A function pointer to the 'logic' that drives this menu item. It is
+    /// passed the index of the item, or for `Status::ArrowsOk` items 0 for left
+    /// and 1 for right
     logic: fn(&mut MenuDoom, usize, &mut dyn GameTraits),
     /// The `char` which activates this item (as a capital letter)
     hotkey: char,
@@ -43,6 +154,22 @@ impl MenuItem {
         Self {
             status,
             patch: patch.to_string(),
+            text: "",
+            logic,
+            hotkey,
+        }
+    }
+
+    fn text(
+        status: Status,
+        text: &'static str,
+        logic: fn(&mut MenuDoom, usize, &mut dyn GameTraits),
+        hotkey: char,
+    ) -> Self {
+        Self {
+            status,
+            patch: String::new(),
+            text,
             logic,
             hotkey,
         }
@@ -125,10 +252,109 @@ enum MenuIndex {
     Skill,
     ReadThis1,
     ReadThis2,
+    Options,
+    Sound,
+    Load,
+    Save,
+    Video,
+    Controls,
 }
 
 fn place_holder(_: &mut MenuDoom, _: usize, _: &mut dyn GameTraits) {}
 
+/// A message drawn over everything, such as a confirmation prompt. Doom
+/// function name `M_StartMessage`
+struct Prompt {
+    text: String,
+    /// Called with the answer if this is a yes or no question, otherwise any
+    /// key dismisses the prompt
+    response: Option<fn(&mut MenuDoom, bool, &mut dyn GameTraits)>,
+}
+
+/// Upper-case the text as the HUD font has no lower case, and replace any
+/// characters the font doesn't have
+fn menu_text(text: &str) -> String {
+    text.to_ascii_uppercase()
+        .chars()
+        .map(|c| match c {
+            '!'..='_' | '\n' => c,
+            _ => ' ',
+        })
+        .collect()
+}
+
+/// The character a key types in to a savegame description
+fn scancode_char(sc: Scancode) -> Option<char> {
+    if sc == Scancode::Space {
+        return Some(' ');
+    }
+    let mut chars = sc.name().chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if ('!'..='_').contains(&c) => Some(c),
+        _ => None,
+    }
+}
+
+/// Change the game settings, and keep the copy used for drawing in sync
+fn change_settings(
+    menu: &mut MenuDoom,
+    game: &mut dyn GameTraits,
+    change: impl FnOnce(&mut Settings),
+) {
+    let mut settings = *game.settings();
+    change(&mut settings);
+    game.set_settings(settings);
+    menu.settings = settings;
+}
+
+/// Change the key bindings, and keep the copy used for drawing in sync
+fn change_bindings(
+    menu: &mut MenuDoom,
+    game: &mut dyn GameTraits,
+    change: impl FnOnce(&mut InputConfigSdl),
+) {
+    let mut bindings = game.key_bindings().clone();
+    change(&mut bindings);
+    game.set_key_bindings(bindings.clone());
+    menu.bindings = bindings;
+}
+
+/// The rows of a page of the controls menu
+fn control_items(page: usize) -> Vec<MenuItem> {
+    CONTROL_PAGES[page]
+        .1
+        .iter()
+        .map(|action| MenuItem::text(Status::Ok, action.name(), sel_control, 0 as char))
+        .collect()
+}
+
+/// The keys, mouse buttons, and controller buttons bound to an action, as
+/// shown in the controls menu
+fn binding_names(bindings: &InputConfigSdl, action: Action) -> Vec<String> {
+    let keys = bindings.keys(action).iter().map(|k| k.name().to_string());
+    let buttons = bindings.mouse_buttons(action).iter().map(|b| match b {
+        MouseButton::Left => "MOUSE LEFT".to_string(),
+        MouseButton::Middle => "MOUSE MIDDLE".to_string(),
+        MouseButton::Right => "MOUSE RIGHT".to_string(),
+        b => format!("MOUSE {}", *b as u8),
+    });
+    let pad = bindings
+        .pad_inputs(action)
+        .iter()
+        .filter_map(|p| p.name())
+        .map(|name| format!("PAD {name}"));
+    keys.chain(buttons).chain(pad).collect()
+}
+
+/// Volumes are 0-128 but the sliders have 16 steps
+const fn volume_to_slider(volume: i32) -> i32 {
+    (volume * 15 + 64) / 128
+}
+
+const fn slider_to_volume(slider: i32) -> i32 {
+    slider * 128 / 15
+}
+
 type Patches = HashMap<String, WadPatch>;
 
 pub struct MenuDoom {
@@ -142,6 +368,24 @@ pub struct MenuDoom {
     save_old: String,
     /// Which char of the buffer to edit
     save_char_idx: usize,
+    /// The description of each savegame slot
+    save_strings: [String; SAVE_SLOTS],
+    /// The message or question shown, if any
+    prompt: Option<Prompt>,
+    /// Used to draw the savegame descriptions and prompts
+    text: HUDString,
+    /// A copy of the game settings for drawing the options
+    settings: Settings,
+    /// A copy of the key bindings for drawing the controls menu
+    bindings: InputConfigSdl,
+    /// The page of `CONTROL_PAGES` shown in the controls menu
+    controls_page: usize,
+    /// The action that the next key pressed is bound to
+    binding: Option<Action>,
+    /// The slot used by quicksave and quickload, once one has been picked
+    quick_save_slot: Option<usize>,
+    /// The next save from the save menu picks the quicksave slot
+    pick_quick_save: bool,
     //
     /// Main menu def
     menus: Vec<MenuSet>,
@@ -167,9 +411,9 @@ impl MenuDoom {
                                                     * LINEHEIGHT */
                 vec![
                     MenuItem::new(Status::Ok, "M_NGAME", sel_new_game, 'N'),
-                    MenuItem::new(Status::Ok, "M_OPTION", place_holder, 'O'),
-                    MenuItem::new(Status::Ok, "M_LOADG", place_holder, 'L'),
-                    MenuItem::new(Status::Ok, "M_SAVEG", place_holder, 'S'),
+                    MenuItem::new(Status::Ok, "M_OPTION", sel_options, 'O'),
+                    MenuItem::new(Status::Ok, "M_LOADG", sel_load_game, 'L'),
+                    MenuItem::new(Status::Ok, "M_SAVEG", sel_save_game, 'S'),
                     MenuItem::new(Status::Ok, "M_RDTHIS", sel_readthis, 'R'),
                     MenuItem::new(Status::Ok, "M_QUITG", sel_quit_game, 'Q'),
                 ],
@@ -258,6 +502,95 @@ impl MenuDoom {
                     }
                 },
             ),
+            MenuSet::new(
+                MenuIndex::Options,
+                MenuIndex::TopLevel,
+                vec![Title::new("M_OPTTTL", 108, 15)],
+                60,
+                37,
+                vec![
+                    MenuItem::new(Status::Ok, "M_ENDGAM", sel_end_game, 'E'),
+                    MenuItem::new(Status::Ok, "M_MESSG", sel_messages, 'M'),
+                    MenuItem::new(Status::Ok, "M_DETAIL", sel_detail, 'G'),
+                    MenuItem::new(Status::ArrowsOk, "M_SCRNSZ", sel_screen_size, 'S'),
+                    MenuItem::new(Status::Empty, "", place_holder, 0 as char),
+                    MenuItem::new(Status::ArrowsOk, "M_MSENS", sel_mouse_sens, 'M'),
+                    MenuItem::new(Status::Empty, "", place_holder, 0 as char),
+                    MenuItem::new(Status::Ok, "M_SVOL", sel_sound, 'S'),
+                    MenuItem::text(Status::Ok, "VIDEO MODE", sel_video, 'V'),
+                    MenuItem::text(Status::Ok, "CONTROLS", sel_controls, 'C'),
+                ],
+            ),
+            MenuSet::new(
+                MenuIndex::Sound,
+                MenuIndex::Options,
+                vec![Title::new("M_SVOL", 60, 38)],
+                80,
+                64,
+                vec![
+                    MenuItem::new(Status::ArrowsOk, "M_SFXVOL", sel_sfx_vol, 'S'),
+                    MenuItem::new(Status::Empty, "", place_holder, 0 as char),
+                    MenuItem::new(Status::ArrowsOk, "M_MUSVOL", sel_mus_vol, 'M'),
+                    MenuItem::new(Status::Empty, "", place_holder, 0 as char),
+                ],
+            ),
+            MenuSet::new(
+                MenuIndex::Load,
+                MenuIndex::TopLevel,
+                vec![Title::new("M_LOADG", 72, 28)],
+                80,
+                54,
+                (1..=SAVE_SLOTS as u32)
+                    .map(|i| {
+                        MenuItem::new(
+                            Status::NoCursor,
+                            "",
+                            sel_load_slot,
+                            char::from_digit(i, 10).unwrap(),
+                        )
+                    })
+                    .collect(),
+            ),
+            MenuSet::new(
+                MenuIndex::Save,
+                MenuIndex::TopLevel,
+                vec![Title::new("M_SAVEG", 72, 28)],
+                80,
+                54,
+                (1..=SAVE_SLOTS as u32)
+                    .map(|i| {
+                        MenuItem::new(
+                            Status::Ok,
+                            "",
+                            sel_save_slot,
+                            char::from_digit(i, 10).unwrap(),
+                        )
+                    })
+                    .collect(),
+            ),
+            MenuSet::new(
+                MenuIndex::Video,
+                MenuIndex::Options,
+                vec![],
+                60,
+                64,
+                vec![
+                    MenuItem::text(Status::ArrowsOk, "RESOLUTION", sel_resolution, 'R'),
+                    MenuItem::text(Status::Ok, "FULLSCREEN", sel_fullscreen, 'F'),
+                    MenuItem::text(Status::Ok, "DETAIL", sel_detail, 'D'),
+                    MenuItem::text(Status::ArrowsOk, "RENDERER", sel_renderer, 'E'),
+                    MenuItem::text(Status::ArrowsOk, "HUD SCALE", sel_hud_scale, 'H'),
+                    MenuItem::text(Status::Ok, "ASPECT 4:3", sel_aspect, 'A'),
+                ],
+            ),
+            MenuSet::new(
+                MenuIndex::Controls,
+                MenuIndex::Options,
+                vec![],
+                40,
+                26,
+                control_items(0),
+            ),
         ];
 
         let mut patches = HashMap::new();
@@ -274,7 +607,7 @@ impl MenuDoom {
             }
         }
 
-        for patch in SKULLS {
+        for patch in SKULLS.iter().chain(EXTRA_PATCHES.iter()) {
             if let Some(lump) = wad.get_lump(patch) {
                 patches.insert(patch.to_string(), WadPatch::from_lump(lump));
             }
@@ -289,6 +622,15 @@ impl MenuDoom {
             save_slot: 0,
             save_old: String::new(),
             save_char_idx: 0,
+            save_strings: Default::default(),
+            prompt: None,
+            text: HUDString::new(wad),
+            settings: Settings::default(),
+            bindings: InputConfigSdl::default(),
+            controls_page: 0,
+            binding: None,
+            quick_save_slot: None,
+            pick_quick_save: false,
             //
             menus,
             current_menu: MenuIndex::TopLevel,
@@ -316,10 +658,48 @@ impl MenuDoom {
     fn exit_menu(&mut self, game: &mut dyn GameTraits) {
         self.active = false;
         self.in_help = false;
+        self.save_enter = false;
         self.current_menu = MenuIndex::TopLevel;
         game.start_sound(SfxName::Swtchx);
     }
 
+    /// Show a message or question over everything. The menu is kept active so
+    /// the game is paused until it is answered
+    fn start_prompt(
+        &mut self,
+        text: String,
+        response: Option<fn(&mut MenuDoom, bool, &mut dyn GameTraits)>,
+    ) {
+        self.prompt = Some(Prompt { text, response });
+        self.active = true;
+    }
+
+    /// Doom function name `M_ReadSaveStrings`
+    fn read_save_strings(&mut self, game: &mut dyn GameTraits) {
+        for slot in 0..SAVE_SLOTS {
+            let description = game.savegame_description(slot);
+            self.menus[MenuIndex::Load as usize].items[slot].status = if description.is_some() {
+                Status::Ok
+            } else {
+                Status::NoCursor
+            };
+            self.save_strings[slot] =
+                description.unwrap_or_else(|| deh_string(EMPTYSTRING).to_string());
+        }
+    }
+
+    fn text_width(&mut self, text: &str) -> i32 {
+        self.text.replace(menu_text(text));
+        self.text.width()
+    }
+
+    /// Doom function name `M_WriteText`
+    fn write_text(&mut self, x: i32, y: i32, text: &str, pixels: &mut impl PixelBuffer) {
+        self.text.replace(menu_text(text));
+        self.text.set_draw_all();
+        self.text.draw(x, y, self, pixels);
+    }
+
     fn get_current_menu(&mut self) -> &mut MenuSet {
         let mut idx = 0;
         for (i, m) in self.menus.iter().enumerate() {
@@ -336,7 +716,191 @@ impl MenuDoom {
             .unwrap_or_else(|| panic!("{name} not in cache"))
     }
 
+    /// Doom function name `M_DrawThermo`
+    fn draw_thermo(&self, x: i32, y: i32, width: i32, dot: i32, pixels: &mut impl PixelBuffer) {
+        let mut xx = x;
+        self.draw_patch_pixels(self.get_patch("M_THERML"), xx, y, pixels);
+        xx += 8;
+        for _ in 0..width {
+            self.draw_patch_pixels(self.get_patch("M_THERMM"), xx, y, pixels);
+            xx += 8;
+        }
+        self.draw_patch_pixels(self.get_patch("M_THERMR"), xx, y, pixels);
+        self.draw_patch_pixels(self.get_patch("M_THERMO"), x + 8 + dot * 8, y, pixels);
+    }
+
+    /// Doom function name `M_DrawSaveLoadBorder`
+    fn draw_save_load_border(&self, x: i32, y: i32, pixels: &mut impl PixelBuffer) {
+        self.draw_patch_pixels(self.get_patch("M_LSLEFT"), x - 8, y + 7, pixels);
+        let mut x = x;
+        for _ in 0..SAVESTRINGSIZE {
+            self.draw_patch_pixels(self.get_patch("M_LSCNTR"), x, y + 7, pixels);
+            x += 8;
+        }
+        self.draw_patch_pixels(self.get_patch("M_LSRGHT"), x, y + 7, pixels);
+    }
+
+    /// Doom function names `M_DrawLoad` and `M_DrawSave`
+    fn draw_save_load(&mut self, pixels: &mut impl PixelBuffer) {
+        let active = &self.menus[self.current_menu as usize];
+        let (x, y) = (active.x, active.y);
+        for slot in 0..SAVE_SLOTS {
+            let y = y + LINEHEIGHT * slot as i32;
+            self.draw_save_load_border(x, y, pixels);
+            let text = self.save_strings[slot].clone();
+            self.write_text(x, y, &text, pixels);
+        }
+
+        if self.current_menu == MenuIndex::Save && self.save_enter {
+            let text = self.save_strings[self.save_slot].clone();
+            let width = self.text_width(&text);
+            self.write_text(
+                x + width,
+                y + LINEHEIGHT * self.save_slot as i32,
+                "_",
+                pixels,
+            );
+        }
+    }
+
+    /// Doom function name `M_DrawOptions`
+    fn draw_options(&self, pixels: &mut impl PixelBuffer) {
+        let active = &self.menus[MenuIndex::Options as usize];
+        let (x, y) = (active.x, active.y);
+        let detail = if self.settings.hi_res {
+            "M_GDHIGH"
+        } else {
+            "M_GDLOW"
+        };
+        self.draw_patch_pixels(self.get_patch(detail), x + 175, y + LINEHEIGHT * 2, pixels);
+        let messages = if self.settings.show_messages {
+            "M_MSGON"
+        } else {
+            "M_MSGOFF"
+        };
+        self.draw_patch_pixels(self.get_patch(messages), x + 120, y + LINEHEIGHT, pixels);
+        self.draw_thermo(
+            x,
+            y + LINEHEIGHT * 4,
+            2,
+            self.settings.screen_size - 10,
+            pixels,
+        );
+        self.draw_thermo(
+            x,
+            y + LINEHEIGHT * 6,
+            10,
+            self.settings.mouse_sensitivity,
+            pixels,
+        );
+    }
+
+    /// Doom function name `M_DrawSound`
+    fn draw_sound(&self, pixels: &mut impl PixelBuffer) {
+        let active = &self.menus[MenuIndex::Sound as usize];
+        let (x, y) = (active.x, active.y);
+        let sfx = volume_to_slider(self.settings.sfx_volume);
+        self.draw_thermo(x, y + LINEHEIGHT, 16, sfx, pixels);
+        let music = volume_to_slider(self.settings.mus_volume);
+        self.draw_thermo(x, y + LINEHEIGHT * 3, 16, music, pixels);
+    }
+
+    /// The current value of each item is drawn beside it
+    fn draw_video(&mut self, pixels: &mut impl PixelBuffer) {
+        let active = &self.menus[MenuIndex::Video as usize];
+        let (x, y) = (active.x + 120, active.y + 4);
+        let width = self.text_width("VIDEO MODE");
+        self.write_text(160 - width / 2, 38, "VIDEO MODE", pixels);
+
+        let settings = self.settings;
+        let resolution = format!("{}X{}", settings.width, settings.height);
+        self.write_text(x, y, &resolution, pixels);
+        let fullscreen = if settings.fullscreen { "ON" } else { "OFF" };
+        self.write_text(x, y + LINEHEIGHT, fullscreen, pixels);
+        let detail = if settings.hi_res { "HIGH" } else { "LOW" };
+        self.write_text(x, y + LINEHEIGHT * 2, detail, pixels);
+        let renderer = match settings.renderer {
+            RenderApiType::Software => "SOFTWARE",
+            RenderApiType::SoftOpenGL => "SOFTWARE OPENGL",
+            RenderApiType::OpenGL => "OPENGL",
+            RenderApiType::Vulkan => "VULKAN",
+        };
+        self.write_text(x, y + LINEHEIGHT * 3, renderer, pixels);
+        let hud_scale = format!("{}%", settings.hud_scale);
+        self.write_text(x, y + LINEHEIGHT * 4, &hud_scale, pixels);
+        let aspect = if settings.aspect_correct { "ON" } else { "OFF" };
+        self.write_text(x, y + LINEHEIGHT * 5, aspect, pixels);
+    }
+
+    /// The keys bound to each action are drawn beside it, with the page and
+    /// how to change the bindings above and below
+    fn draw_controls(&mut self, pixels: &mut impl PixelBuffer) {
+        let active = &self.menus[MenuIndex::Controls as usize];
+        let (x, y) = (150, active.y + 4);
+        let (name, actions) = CONTROL_PAGES[self.controls_page];
+        let title = format!(
+            "CONTROLS: {name} {}/{}",
+            self.controls_page + 1,
+            CONTROL_PAGES.len()
+        );
+        let width = self.text_width(&title);
+        self.write_text(160 - width / 2, 8, &title, pixels);
+
+        for (i, action) in actions.iter().enumerate() {
+            let mut text = String::new();
+            if self.binding == Some(*action) {
+                text.push_str("PRESS A KEY");
+            } else {
+                // As many as fit on the screen
+                for name in binding_names(&self.bindings, *action) {
+                    let next = if text.is_empty() {
+                        name
+                    } else {
+                        format!("{text}, {name}")
+                    };
+                    if !text.is_empty() && x + self.text_width(&next) > 316 {
+                        break;
+                    }
+                    text = next;
+                }
+                if text.is_empty() {
+                    text.push_str("NONE");
+                }
+            }
+            self.write_text(x, y + LINEHEIGHT * i as i32, &text, pixels);
+        }
+
+        let hint = "ENTER TO BIND, DEL TO CLEAR, ARROWS FOR PAGE";
+        let width = self.text_width(hint);
+        self.write_text(160 - width / 2, 190, hint, pixels);
+    }
+
+    /// Show a page of the controls menu, keeping the cursor on it
+    fn set_controls_page(&mut self, page: usize) {
+        self.controls_page = page;
+        let menu = &mut self.menus[MenuIndex::Controls as usize];
+        menu.items = control_items(page);
+        menu.last_on = menu.last_on.min(menu.items.len() - 1);
+    }
+
+    /// Each line of the prompt is centered on the screen
+    fn draw_prompt(&mut self, text: &str, pixels: &mut impl PixelBuffer) {
+        let lines: Vec<String> = text.split('\n').map(menu_text).collect();
+        let mut y = 100 - self.text.line_height() * lines.len() as i32 / 2;
+        for line in lines {
+            let x = 160 - self.text_width(&line) / 2;
+            self.write_text(x, y, &line, pixels);
+            y += self.text.line_height();
+        }
+    }
+
     fn draw_pixels(&mut self, pixels: &mut impl PixelBuffer) {
+        if let Some(prompt) = self.prompt.as_ref() {
+            let text = prompt.text.clone();
+            self.draw_prompt(&text, pixels);
+            return;
+        }
+
         if self.active || self.in_help {
             let active = &self.menus[self.current_menu as usize];
             // Titles
@@ -346,10 +910,30 @@ impl MenuDoom {
             // sub-items
             let x = active.x; // * f;
             let mut y = active.y; // * f;
+            let mut texts = Vec::new();
             for item in active.items.iter() {
-                self.draw_patch_pixels(self.get_patch(&item.patch), x, y, pixels);
+                if !item.patch.is_empty() {
+                    self.draw_patch_pixels(self.get_patch(&item.patch), x, y, pixels);
+                } else if !item.text.is_empty() {
+                    texts.push((y, item.text));
+                }
                 y += LINEHEIGHT; // * f;
             }
+            // The HUD font is half the height of the menu patches
+            for (y, text) in texts {
+                self.write_text(x, y + 4, text, pixels);
+            }
+
+            match self.current_menu {
+                MenuIndex::Options => self.draw_options(pixels),
+                MenuIndex::Sound => self.draw_sound(pixels),
+                MenuIndex::Load | MenuIndex::Save => self.draw_save_load(pixels),
+                MenuIndex::Video => self.draw_video(pixels),
+                MenuIndex::Controls => self.draw_controls(pixels),
+                _ => {}
+            }
+
+            let active = &self.menus[self.current_menu as usize];
 
             // SKULL
             //let y = active.y * f - 5 + active.last_on as i32 * LINEHEIGHT * f;
@@ -379,8 +963,262 @@ fn sel_readthis2(menu: &mut MenuDoom, _: usize, _: &mut dyn GameTraits) {
     menu.current_menu = MenuIndex::TopLevel;
 }
 
-fn sel_quit_game(_menu: &mut MenuDoom, _: usize, game: &mut dyn GameTraits) {
-    game.quit_game();
+fn sel_options(menu: &mut MenuDoom, _: usize, _: &mut dyn GameTraits) {
+    menu.current_menu = MenuIndex::Options;
+}
+
+/// Doom function name `M_LoadGame`
+fn sel_load_game(menu: &mut MenuDoom, _: usize, game: &mut dyn GameTraits) {
+    if game.is_netgame() {
+        menu.start_prompt(deh_string(LOADNET).to_string(), None);
+        return;
+    }
+    menu.read_save_strings(game);
+    menu.current_menu = MenuIndex::Load;
+}
+
+/// Doom function name `M_LoadSelect`
+fn sel_load_slot(menu: &mut MenuDoom, choice: usize, game: &mut dyn GameTraits) {
+    game.load_game(savegame_file(choice));
+    menu.exit_menu(game);
+}
+
+/// Doom function name `M_SaveGame`
+fn sel_save_game(menu: &mut MenuDoom, _: usize, game: &mut dyn GameTraits) {
+    if !game.is_user_game() {
+        menu.start_prompt(deh_string(SAVEDEAD).to_string(), None);
+        return;
+    }
+    if game.level().is_none() {
+        return;
+    }
+    menu.read_save_strings(game);
+    menu.current_menu = MenuIndex::Save;
+}
+
+/// Start entering a description for the save. Doom function name
+/// `M_SaveSelect`
+fn sel_save_slot(menu: &mut MenuDoom, choice: usize, _: &mut dyn GameTraits) {
+    menu.save_enter = true;
+    menu.save_slot = choice;
+    menu.save_old = menu.save_strings[choice].clone();
+    if menu.save_strings[choice] == deh_string(EMPTYSTRING) {
+        menu.save_strings[choice].clear();
+    }
+    menu.save_char_idx = menu.save_strings[choice].len();
+}
+
+/// Save to the quicksave slot, or pick the slot from the save menu if there
+/// isn't one yet.
+///
+/// Doom function name `M_QuickSave`
+fn quick_save(menu: &mut MenuDoom, game: &mut dyn GameTraits) {
+    if !game.is_user_game() {
+        game.start_sound(SfxName::Oof);
+        return;
+    }
+    if game.level().is_none() {
+        return;
+    }
+    menu.read_save_strings(game);
+    let Some(slot) = menu.quick_save_slot else {
+        menu.active = true;
+        menu.current_menu = MenuIndex::Save;
+        menu.pick_quick_save = true;
+        return;
+    };
+    let text = deh_string(QSPROMPT).replace("%s", &menu.save_strings[slot]);
+    menu.start_prompt(text, Some(quick_save_response));
+}
+
+fn quick_save_response(menu: &mut MenuDoom, yes: bool, game: &mut dyn GameTraits) {
+    if yes && let Some(slot) = menu.quick_save_slot {
+        game.save_game(menu.save_strings[slot].clone(), slot);
+    }
+}
+
+/// Doom function name `M_QuickLoad`
+fn quick_load(menu: &mut MenuDoom, game: &mut dyn GameTraits) {
+    if game.is_netgame() {
+        menu.start_prompt(deh_string(QLOADNET).to_string(), None);
+        return;
+    }
+    let Some(slot) = menu.quick_save_slot else {
+        menu.start_prompt(deh_string(QSAVESPOT).to_string(), None);
+        return;
+    };
+    menu.read_save_strings(game);
+    let text = deh_string(QLPROMPT).replace("%s", &menu.save_strings[slot]);
+    menu.start_prompt(text, Some(quick_load_response));
+}
+
+fn quick_load_response(menu: &mut MenuDoom, yes: bool, game: &mut dyn GameTraits) {
+    if yes && let Some(slot) = menu.quick_save_slot {
+        game.load_game(savegame_file(slot));
+    }
+}
+
+/// Doom function name `M_EndGame`
+fn sel_end_game(menu: &mut MenuDoom, _: usize, game: &mut dyn GameTraits) {
+    if !game.is_user_game() {
+        game.start_sound(SfxName::Oof);
+        return;
+    }
+    if game.is_netgame() {
+        menu.start_prompt(deh_string(NETEND).to_string(), None);
+        return;
+    }
+    menu.start_prompt(deh_string(ENDGAME).to_string(), Some(end_game_response));
+}
+
+fn end_game_response(menu: &mut MenuDoom, yes: bool, game: &mut dyn GameTraits) {
+    if yes {
+        menu.exit_menu(game);
+        game.end_game();
+    }
+}
+
+/// Doom function name `M_ChangeMessages`
+fn sel_messages(menu: &mut MenuDoom, _: usize, game: &mut dyn GameTraits) {
+    change_settings(menu, game, |s| s.show_messages = !s.show_messages);
+}
+
+/// Doom function name `M_ChangeDetail`
+fn sel_detail(menu: &mut MenuDoom, _: usize, game: &mut dyn GameTraits) {
+    change_settings(menu, game, |s| s.hi_res = !s.hi_res);
+    game.player_msg_set(if menu.settings.hi_res {
+        DETAILHI
+    } else {
+        DETAILLO
+    });
+}
+
+/// Only the sizes with and without the statusbar are used as the player view
+/// is always full screen.
+///
+/// Doom function name `M_SizeDisplay`
+fn sel_screen_size(menu: &mut MenuDoom, choice: usize, game: &mut dyn GameTraits) {
+    change_settings(menu, game, |s| {
+        s.screen_size = if choice == 0 {
+            (s.screen_size - 1).max(10)
+        } else {
+            (s.screen_size + 1).min(11)
+        };
+    });
+}
+
+/// Doom function name `M_ChangeSensitivity`
+fn sel_mouse_sens(menu: &mut MenuDoom, choice: usize, game: &mut dyn GameTraits) {
+    change_settings(menu, game, |s| {
+        s.mouse_sensitivity = if choice == 0 {
+            (s.mouse_sensitivity - 1).max(0)
+        } else {
+            (s.mouse_sensitivity + 1).min(9)
+        };
+    });
+}
+
+fn sel_sound(menu: &mut MenuDoom, _: usize, _: &mut dyn GameTraits) {
+    menu.current_menu = MenuIndex::Sound;
+}
+
+fn sel_video(menu: &mut MenuDoom, _: usize, _: &mut dyn GameTraits) {
+    menu.current_menu = MenuIndex::Video;
+}
+
+fn sel_controls(menu: &mut MenuDoom, _: usize, game: &mut dyn GameTraits) {
+    menu.bindings = game.key_bindings().clone();
+    menu.current_menu = MenuIndex::Controls;
+}
+
+/// Bind the next key pressed to the action on this row
+fn sel_control(menu: &mut MenuDoom, choice: usize, _: &mut dyn GameTraits) {
+    menu.binding = Some(CONTROL_PAGES[menu.controls_page].1[choice]);
+}
+
+/// Step to the next smaller or larger window size
+fn sel_resolution(menu: &mut MenuDoom, choice: usize, game: &mut dyn GameTraits) {
+    change_settings(menu, game, |s| {
+        let area = s.width * s.height;
+        let next = if choice == 0 {
+            RESOLUTIONS.iter().rev().find(|(w, h)| w * h < area)
+        } else {
+            RESOLUTIONS.iter().find(|(w, h)| w * h > area)
+        };
+        if let Some(&(width, height)) = next {
+            s.width = width;
+            s.height = height;
+        }
+    });
+}
+
+fn sel_fullscreen(menu: &mut MenuDoom, _: usize, game: &mut dyn GameTraits) {
+    change_settings(menu, game, |s| s.fullscreen = !s.fullscreen);
+}
+
+/// Cycle through the renderers that are available
+fn sel_renderer(menu: &mut MenuDoom, choice: usize, game: &mut dyn GameTraits) {
+    change_settings(menu, game, |s| {
+        let available = RenderApiType::AVAILABLE;
+        let current = available
+            .iter()
+            .position(|r| *r == s.renderer)
+            .unwrap_or_default();
+        let next = if choice == 0 {
+            (current + available.len() - 1) % available.len()
+        } else {
+            (current + 1) % available.len()
+        };
+        s.renderer = available[next];
+    });
+}
+
+/// Shrink or grow the statusbar and messages in steps of 10%
+fn sel_hud_scale(menu: &mut MenuDoom, choice: usize, game: &mut dyn GameTraits) {
+    change_settings(menu, game, |s| {
+        let step = if choice == 0 { -10 } else { 10 };
+        s.hud_scale = (s.hud_scale + step).clamp(50, 100);
+    });
+}
+
+fn sel_aspect(menu: &mut MenuDoom, _: usize, game: &mut dyn GameTraits) {
+    change_settings(menu, game, |s| s.aspect_correct = !s.aspect_correct);
+}
+
+/// Move a volume slider one step left or right
+fn step_volume(volume: i32, choice: usize) -> i32 {
+    let slider = volume_to_slider(volume);
+    if choice == 0 {
+        slider_to_volume((slider - 1).max(0))
+    } else {
+        slider_to_volume((slider + 1).min(15))
+    }
+}
+
+/// Doom function name `M_SfxVol`
+fn sel_sfx_vol(menu: &mut MenuDoom, choice: usize, game: &mut dyn GameTraits) {
+    change_settings(menu, game, |s| {
+        s.sfx_volume = step_volume(s.sfx_volume, choice)
+    });
+}
+
+/// Doom function name `M_MusicVol`
+fn sel_mus_vol(menu: &mut MenuDoom, choice: usize, game: &mut dyn GameTraits) {
+    change_settings(menu, game, |s| {
+        s.mus_volume = step_volume(s.mus_volume, choice)
+    });
+}
+
+/// Doom function name `M_QuitDOOM`
+fn sel_quit_game(menu: &mut MenuDoom, _: usize, _: &mut dyn GameTraits) {
+    let text = format!("{}\n\n{}", deh_string(QUITMSG), deh_string(DOSY));
+    menu.start_prompt(text, Some(quit_response));
+}
+
+fn quit_response(_: &mut MenuDoom, yes: bool, game: &mut dyn GameTraits) {
+    if yes {
+        game.quit_game();
+    }
 }
 
 // TODO: kind of bad, should make a better method to set episode even if not
@@ -403,16 +1241,109 @@ fn sel_skill(menu: &mut MenuDoom, choice: usize, game: &mut dyn GameTraits) {
 }
 
 impl SubsystemTrait for MenuDoom {
-    fn init(&mut self, _game: &impl GameTraits) {
+    fn init(&mut self, game: &impl GameTraits) {
         for menu in self.menus.iter_mut() {
             if menu.this == MenuIndex::Skill {
                 menu.last_on = 2;
             }
         }
+        self.settings = *game.settings();
+        self.bindings = game.key_bindings().clone();
     }
 
     fn responder(&mut self, mut sc: Scancode, game: &mut impl GameTraits) -> bool {
+        // Rebinding an action in the controls menu
+        if let Some(action) = self.binding.take() {
+            if sc != Scancode::Escape {
+                change_bindings(self, game, |b| b.bind_key(action, sc));
+            }
+            game.start_sound(SfxName::Pistol);
+            return true;
+        }
+
+        if game.key_bindings().is_key(Action::Screenshot, sc) {
+            game.screenshot();
+            return true;
+        }
+
+        // Entering a savegame description
+        if self.save_enter {
+            let slot = self.save_slot;
+            match sc {
+                Scancode::Backspace => {
+                    if self.save_char_idx > 0 {
+                        self.save_char_idx -= 1;
+                        self.save_strings[slot].pop();
+                    }
+                }
+                Scancode::Escape => {
+                    self.save_enter = false;
+                    self.save_strings[slot] = self.save_old.clone();
+                }
+                Scancode::Return => {
+                    self.save_enter = false;
+                    if !self.save_strings[slot].is_empty() {
+                        game.save_game(self.save_strings[slot].clone(), slot);
+                        if self.pick_quick_save {
+                            self.pick_quick_save = false;
+                            self.quick_save_slot = Some(slot);
+                        }
+                        self.exit_menu(game);
+                    }
+                }
+                _ => {
+                    if let Some(c) = scancode_char(sc)
+                        && (self.save_char_idx as i32) < SAVESTRINGSIZE - 1
+                        && self.text_width(&self.save_strings[slot].clone())
+                            < (SAVESTRINGSIZE - 2) * 8
+                    {
+                        self.save_strings[slot].push(c);
+                        self.save_char_idx += 1;
+                    }
+                }
+            }
+            return true;
+        }
+
+        // Take the key if a prompt is up, a question needs a y or n
+        if let Some(prompt) = self.prompt.as_ref() {
+            let response = prompt.response;
+            let yes = match sc {
+                Scancode::Y => true,
+                Scancode::N | Scancode::Space | Scancode::Escape => false,
+                _ if response.is_some() => return true,
+                _ => false,
+            };
+            self.prompt = None;
+            if let Some(response) = response {
+                (response)(self, yes, game);
+            }
+            self.active = false;
+            game.start_sound(SfxName::Swtchx);
+            return true;
+        }
+
         if !self.active {
+            if game.key_bindings().is_key(Action::QuickSave, sc) {
+                game.start_sound(SfxName::Swtchn);
+                quick_save(self, game);
+                return true;
+            }
+            if game.key_bindings().is_key(Action::QuickLoad, sc) {
+                game.start_sound(SfxName::Swtchn);
+                quick_load(self, game);
+                return true;
+            }
+            if game.key_bindings().is_key(Action::Pause, sc) {
+                game.toggle_pause_game();
+                return true;
+            }
+            // Escape always opens the menu so it can't be lost by rebinding
+            if game.key_bindings().is_key(Action::Menu, sc) || sc == Scancode::Escape {
+                self.enter_menu(game);
+                return true;
+            }
+
             // F-keys
             match sc {
                 Scancode::F1 => {
@@ -427,29 +1358,89 @@ impl SubsystemTrait for MenuDoom {
                     }
                     return true;
                 }
+                Scancode::Minus => {
+                    if !game.automap_active() {
+                        sel_screen_size(self, 0, game);
+                        game.start_sound(SfxName::Stnmov);
+                        return true;
+                    }
+                }
+                Scancode::Equals => {
+                    if !game.automap_active() {
+                        sel_screen_size(self, 1, game);
+                        game.start_sound(SfxName::Stnmov);
+                        return true;
+                    }
+                }
                 Scancode::F2 => {
                     // SAVE
+                    self.enter_menu(game);
+                    sel_save_game(self, 0, game);
+                    return true;
                 }
                 Scancode::F3 => {
                     // LOAD
+                    self.enter_menu(game);
+                    sel_load_game(self, 0, game);
+                    return true;
                 }
-                Scancode::F6 => {
-                    // QUICKSAVE
+                Scancode::F4 => {
+                    // SOUND VOLUME
+                    self.enter_menu(game);
+                    self.current_menu = MenuIndex::Sound;
+                    return true;
                 }
-                Scancode::F9 => {
-                    // QUICKLOAD
+                Scancode::F5 => {
+                    // DETAIL
+                    sel_detail(self, 0, game);
+                    game.start_sound(SfxName::Swtchn);
+                    return true;
                 }
-                Scancode::Pause => {
-                    game.toggle_pause_game();
+                Scancode::F7 => {
+                    // END GAME
+                    game.start_sound(SfxName::Swtchn);
+                    sel_end_game(self, 0, game);
                     return true;
                 }
-                Scancode::Escape => {
-                    self.enter_menu(game);
+                Scancode::F8 => {
+                    // MESSAGES
+                    sel_messages(self, 0, game);
+                    game.start_sound(SfxName::Swtchn);
+                    return true;
+                }
+                Scancode::F10 => {
+                    // QUIT
+                    game.start_sound(SfxName::Swtchn);
+                    sel_quit_game(self, 0, game);
                     return true;
                 }
                 _ => {}
             }
         } else {
+            if self.current_menu == MenuIndex::Controls {
+                match sc {
+                    Scancode::Left | Scancode::Right => {
+                        let pages = CONTROL_PAGES.len();
+                        let page = if sc == Scancode::Left {
+                            (self.controls_page + pages - 1) % pages
+                        } else {
+                            (self.controls_page + 1) % pages
+                        };
+                        self.set_controls_page(page);
+                        game.start_sound(SfxName::Stnmov);
+                        return true;
+                    }
+                    Scancode::Delete => {
+                        let last_on = self.menus[MenuIndex::Controls as usize].last_on;
+                        let action = CONTROL_PAGES[self.controls_page].1[last_on];
+                        change_bindings(self, game, |b| b.clear(action));
+                        game.start_sound(SfxName::Stnmov);
+                        return true;
+                    }
+                    _ => {}
+                }
+            }
+
             let hot_key = sc.to_string();
             if hot_key.len() == 1 {
                 let hk = hot_key.chars().next().unwrap();
@@ -468,23 +1459,42 @@ impl SubsystemTrait for MenuDoom {
                 }
                 Scancode::Down => {
                     let active = self.get_current_menu(); //&mut self.menus[self.current_menu as usize];
-                    active.last_on += 1;
-                    if active.last_on > active.items.len() - 1 {
-                        active.last_on = 0;
+                    loop {
+                        active.last_on += 1;
+                        if active.last_on > active.items.len() - 1 {
+                            active.last_on = 0;
+                        }
+                        if active.on().status != Status::Empty {
+                            break;
+                        }
                     }
                     game.start_sound(SfxName::Pstop);
                     return true;
                 }
                 Scancode::Up => {
                     let active = self.get_current_menu();
-                    if active.last_on == 0 {
-                        active.last_on = active.items.len() - 1;
-                    } else {
-                        active.last_on -= 1;
+                    loop {
+                        if active.last_on == 0 {
+                            active.last_on = active.items.len() - 1;
+                        } else {
+                            active.last_on -= 1;
+                        }
+                        if active.on().status != Status::Empty {
+                            break;
+                        }
                     }
                     game.start_sound(SfxName::Pstop);
                     return true;
                 }
+                Scancode::Left | Scancode::Right => {
+                    let item = self.get_current_menu().on();
+                    let logic = item.logic;
+                    if item.status == Status::ArrowsOk {
+                        game.start_sound(SfxName::Stnmov);
+                        (logic)(self, (sc == Scancode::Right) as usize, game);
+                    }
+                    return true;
+                }
 
                 Scancode::Return => {
                     let mut idx = 0;
@@ -498,9 +1508,16 @@ impl SubsystemTrait for MenuDoom {
                     let status = self.menus[idx].items[last_on].status;
                     let logic = self.menus[idx].items[last_on].logic;
 
-                    if status != Status::NoCursor {
-                        (logic)(self, last_on, game);
-                        game.start_sound(SfxName::Pistol);
+                    match status {
+                        Status::ArrowsOk => {
+                            (logic)(self, 1, game);
+                            game.start_sound(SfxName::Stnmov);
+                        }
+                        Status::Ok => {
+                            (logic)(self, last_on, game);
+                            game.start_sound(SfxName::Pistol);
+                        }
+                        Status::Empty | Status::NoCursor => {}
                     }
                     return true;
                 }
@@ -519,7 +1536,8 @@ impl SubsystemTrait for MenuDoom {
         false
     }
 
-    fn ticker(&mut self, _: &mut impl GameTraits) -> bool {
+    fn ticker(&mut self, game: &mut impl GameTraits) -> bool {
+        self.settings = *game.settings();
         self.skull_anim_counter -= 1;
         if self.skull_anim_counter <= 0 {
             self.which_skull ^= 1;
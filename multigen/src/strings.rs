@@ -43,11 +43,11 @@ pub struct MapObjInfo {
 
 pub const MOBJ_INFO_ARRAY_STR: &str = r#"
 const NUM_CATEGORIES: usize = MapObjKind::Count as usize;
-pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = ["#;
+pub static mut MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = ["#;
 
 pub const SPRITE_NAME_ARRAY_STR: &str = r#"
 const NUM_SPRNAMES: usize = SpriteNum::Count as usize;
-pub const SPRNAMES: [&str; NUM_SPRNAMES] = [
+pub static mut SPRNAMES: [&str; NUM_SPRNAMES] = [
 "#;
 
 pub const ARRAY_END_STR: &str = r#"
@@ -67,6 +67,14 @@ impl Default for SpriteNum {
     fn default() -> Self {
         SpriteNum::TROO
     }
+}
+impl From<u16> for SpriteNum {
+    fn from(w: u16) -> Self {
+        if w >= SpriteNum::Count as u16 {
+            panic!("{} is not a variant of SpriteNum", w);
+        }
+        unsafe { std::mem::transmute(w) }
+    }
 }"#;
 
 pub const STATE_ENUM_HEADER: &str = r#"
@@ -112,7 +120,7 @@ pub struct State {
 }
 
 const NUM_STATES: usize = StateNum::Count as usize;
-pub const STATES: [State; NUM_STATES] = [
+pub static mut STATES: [State; NUM_STATES] = [
     // State::new(sprite, frame, tics, action, next_state, misc1, misc2)"#;
 
 pub const MKIND_ENUM_HEADER: &str = r#"
@@ -8,6 +8,7 @@ pub mod wipe;
 use gameplay::{Level, PicData, Player};
 use golem::{ColorFormat, Context, GolemError, Texture, TextureFilter};
 use render_soft::SoftwareRenderer;
+pub use render_trait::RenderApiType;
 use render_trait::{BufferSize, PixelBuffer, PlayViewRenderer, RenderTrait};
 use sdl2::rect::Rect;
 use sdl2::render::{Canvas, TextureCreator};
@@ -20,21 +21,6 @@ use wipe::Wipe;
 /// channels should match pixel format
 const SOFT_PIXEL_CHANNELS: usize = 4;
 
-#[derive(Debug, Default, PartialEq, PartialOrd, Clone, Copy)]
-pub enum RenderApiType {
-    /// Purely software. Typically used with blitting a framebuffer maintained
-    /// in memory directly to screen using SDL2
-    #[default]
-    Software,
-    /// Software framebuffer blitted to screen using OpenGL (and can use
-    /// shaders)
-    SoftOpenGL,
-    /// OpenGL
-    OpenGL,
-    /// Vulkan
-    Vulkan,
-}
-
 struct Buffer {
     size: BufferSize,
     /// Total length is width * height * CHANNELS, where CHANNELS is RGB bytes
@@ -3,6 +3,26 @@ use gameplay::{Level, PicData, Player};
 /// channels should match pixel format
 pub const SOFT_PIXEL_CHANNELS: usize = 4;
 
+#[derive(Debug, Default, PartialEq, PartialOrd, Clone, Copy)]
+pub enum RenderApiType {
+    /// Purely software. Typically used with blitting a framebuffer maintained
+    /// in memory directly to screen using SDL2
+    #[default]
+    Software,
+    /// Software framebuffer blitted to screen using OpenGL (and can use
+    /// shaders)
+    SoftOpenGL,
+    /// OpenGL
+    OpenGL,
+    /// Vulkan
+    Vulkan,
+}
+
+impl RenderApiType {
+    /// The renderers that can be selected. OpenGL and Vulkan aren't done yet
+    pub const AVAILABLE: [RenderApiType; 2] = [RenderApiType::Software, RenderApiType::SoftOpenGL];
+}
+
 #[derive(Clone, Copy)]
 pub struct BufferSize {
     hi_res: bool,
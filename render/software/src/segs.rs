@@ -79,6 +79,9 @@ pub(crate) struct SegRender {
     /// Stores the column number of the texture required for this opening
     pub(super) openings: Vec<f32>,
     lastopening: f32,
+    /// Palette index of each pixel drawn by the wall and flat columns, so
+    /// translucent columns can be blended with `TRANMAP`
+    pub(super) colour_indexes: Vec<u8>,
     /// Light level for the wall
     wall_lights: usize,
     pub yslopes: Vec<Vec<f32>>,
@@ -143,6 +146,7 @@ impl SegRender {
             wall_lights: 0,
             openings: vec![f32::MAX; screen_width * screen_height],
             lastopening: 0.0,
+            colour_indexes: vec![0; screen_width * screen_height],
             yslopes: (0..=screen_height + 1)
                 .map(|y| unsafe {
                     (0..LOOKDIRS)
@@ -829,6 +833,8 @@ impl SegRender {
         let mut frac = dc_texturemid + (y_start - self.centery) * self.dc_iscale;
 
         let mut pos = pixels.get_buf_index(self.rw_startx as u32 as usize, y_start as u32 as usize);
+        let width = pixels.size().width_usize();
+        let mut index = y_start as u32 as usize * width + self.rw_startx as u32 as usize;
 
         let colourmap = if !sky {
             pic_data.vert_light_colourmap(self.wall_lights, self.rw_scale)
@@ -848,18 +854,22 @@ impl SegRender {
             }
             #[cfg(not(feature = "safety_check"))]
             unsafe {
-                let c = pal.get_unchecked(*colourmap.get_unchecked(tc));
+                let px = *colourmap.get_unchecked(tc);
+                let c = pal.get_unchecked(px);
                 pixels
                     .buf_mut()
                     .get_unchecked_mut(pos..pos + SOFT_PIXEL_CHANNELS)
                     .copy_from_slice(c);
+                *self.colour_indexes.get_unchecked_mut(index) = px as u8;
             }
             #[cfg(feature = "safety_check")]
             {
                 pixels.set_pixel(dc_x, i as u32 as usize, &pal[colourmap[tc]].0);
+                self.colour_indexes[index] = colourmap[tc] as u8;
             }
             frac += self.dc_iscale;
             pos += pixels.pitch();
+            index += width;
         }
     }
 
@@ -885,6 +895,8 @@ impl SegRender {
         let pal = pic_data.palette();
         let tex_len = texture.data.len() - 1; // always square
         let mut pos = pixels.get_buf_index(self.rw_startx as u32 as usize, y_start);
+        let width = pixels.size().width_usize();
+        let mut index = y_start * width + self.rw_startx as u32 as usize;
 
         for y_slope in self.yslopes[self.look_yslope][y_start..=y_end].iter() {
             let diminished_light = plane_height * y_slope;
@@ -902,18 +914,22 @@ impl SegRender {
             #[cfg(not(feature = "safety_check"))]
             unsafe {
                 let tc = *texture.data.get_unchecked(x_step).get_unchecked(y_step);
-                let c = pal.get_unchecked(*colourmap.get_unchecked(tc));
+                let px = *colourmap.get_unchecked(tc);
+                let c = pal.get_unchecked(px);
                 pixels
                     .buf_mut()
                     .get_unchecked_mut(pos..pos + SOFT_PIXEL_CHANNELS)
                     .copy_from_slice(c);
+                *self.colour_indexes.get_unchecked_mut(index) = px as u8;
             }
             #[cfg(feature = "safety_check")]
             {
                 let px = colourmap[texture.data[x_step][y_pos]];
                 pixels.set_pixel(dc_x, y_pos, &pal[px].0);
+                self.colour_indexes[index] = px as u8;
             }
             pos += pixels.pitch();
+            index += width;
         }
     }
 
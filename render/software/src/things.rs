@@ -244,7 +244,7 @@ impl SoftwareRenderer {
 
     // R_DrawVisSprite
     fn draw_vissprite(
-        &self,
+        &mut self,
         vis: &VisSprite,
         clip_bottom: &[f32],
         clip_top: &[f32],
@@ -257,11 +257,15 @@ impl SoftwareRenderer {
         let dc_iscale = vis.x_iscale.abs();
         let dc_texmid = vis.texture_mid;
         let mut frac = vis.start_frac;
-        let colourmap = if vis.mobj_flags & MapObjFlag::Shadow as u32 != 0 {
+        let shadow = vis.mobj_flags & MapObjFlag::Shadow as u32 != 0;
+        let colourmap = if shadow {
             pic_data.colourmap(33)
         } else {
             pic_data.vert_light_colourmap(vis.light_level, vis.scale)
         };
+        let translucent = !shadow
+            && pic_data.translucent_missiles()
+            && vis.mobj_flags & MapObjFlag::Translucent as u32 != 0;
 
         let xfrac = vis.x_iscale * self.y_scale; // proportional to x1..x2
         for x in vis.x1.ceil() as u32 as usize..=vis.x2.floor() as u32 as usize {
@@ -286,7 +290,8 @@ impl SoftwareRenderer {
                 draw_masked_column(
                     texture_column,
                     colourmap,
-                    vis.mobj_flags & MapObjFlag::Shadow as u32 != 0,
+                    shadow,
+                    translucent,
                     dc_iscale,
                     self.seg_renderer.centery,
                     x,
@@ -294,6 +299,7 @@ impl SoftwareRenderer {
                     top,
                     bottom,
                     pic_data,
+                    &mut self.seg_renderer.colour_indexes,
                     rend.draw_buffer(),
                 );
             }
@@ -602,6 +608,7 @@ impl SoftwareRenderer {
                         texture_column,
                         pic_data.vert_light_colourmap(wall_lights, spryscale),
                         false,
+                        seg.linedef.translucent,
                         1.0 / spryscale,
                         self.seg_renderer.centery,
                         x,
@@ -609,6 +616,7 @@ impl SoftwareRenderer {
                         top,
                         bottom,
                         pic_data,
+                        &mut self.seg_renderer.colour_indexes,
                         rend.draw_buffer(),
                     );
 
@@ -625,6 +633,7 @@ fn draw_masked_column(
     texture_column: &[usize],
     colourmap: &[usize],
     fuzz: bool,
+    translucent: bool,
     fracstep: f32,
     centery: f32,
     dc_x: usize,
@@ -632,12 +641,14 @@ fn draw_masked_column(
     yl: f32,
     mut yh: f32,
     pic_data: &PicData,
+    colour_indexes: &mut [u8],
     pixels: &mut impl PixelBuffer,
 ) {
     if yh >= pixels.size().height_f32() {
         yh = pixels.size().height_f32() - 1.0;
     }
     let pal = pic_data.palette();
+    let width = pixels.size().width_usize();
     let mut frac = dc_texturemid + (yl - centery) * fracstep;
     for y in yl as u32 as usize..=yh as u32 as usize {
         let select = frac as u32 as usize;
@@ -649,8 +660,13 @@ fn draw_masked_column(
             frac += fracstep;
             continue;
         }
-        let c = pal[colourmap[texture_column[select]]];
-        pixels.set_pixel(dc_x, y, &c);
+        let index = y * width + dc_x;
+        let mut px = colourmap[texture_column[select]];
+        if translucent {
+            px = pic_data.translucent_index(px, colour_indexes[index] as usize);
+        }
+        colour_indexes[index] = px as u8;
+        pixels.set_pixel(dc_x, y, &pal[px]);
         frac += fracstep;
     }
 }
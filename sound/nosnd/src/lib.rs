@@ -65,6 +65,8 @@ impl SoundServer<SfxName, usize, SndError> for Snd {
 
     fn change_music(&mut self, _: usize, _: bool) {}
 
+    fn change_music_lump(&mut self, _: String, _: Vec<u8>, _: bool) {}
+
     fn stop_music(&mut self) {}
 
     fn set_mus_volume(&mut self, _: i32) {}
@@ -9,7 +9,9 @@ use math::{VecF2, fixed_t};
 use sdl2::AudioSubsystem;
 use sdl2::audio::{AudioCVT, AudioFormat};
 use sdl2::mixer::{AUDIO_S16LSB, Chunk, DEFAULT_CHANNELS, InitFlag, Music, Sdl2MixerContext};
-use sound_traits::{InitResult, MUS_DATA, SfxName, SoundAction, SoundServer, SoundServerTic};
+use sound_traits::{
+    InitResult, MUS_DATA, SFX_OVERRIDES, SfxName, SoundAction, SoundServer, SoundServerTic,
+};
 use wad::WadData;
 
 use crate::info::SFX_INFO_BASE;
@@ -125,6 +127,9 @@ pub struct Snd<'a> {
     tx: SndServerTx,
     chunks: Vec<SfxInfo>,
     music: Option<Music<'a>>,
+    /// Songs played by lump name and converted to MIDI if needed. Kept for as
+    /// long as the `MUS_DATA` songs are
+    lump_music: Vec<(String, &'static [u8])>,
     listener: SoundObject<SfxName>,
     sources: [SoundObject<SfxName>; MIXER_CHANNELS as usize],
     sfx_vol: i32,
@@ -150,15 +155,19 @@ impl<'a> Snd<'a> {
 
         let chunks: Vec<SfxInfo> = SFX_INFO_BASE
             .iter()
-            .map(|s| {
-                let name = format!("DS{}", s.name.to_ascii_uppercase());
+            .enumerate()
+            .map(|(i, s)| {
+                let patch = unsafe { SFX_OVERRIDES[i] };
+                let sfx_name = patch.name.unwrap_or(s.name);
+                let priority = patch.priority.unwrap_or(s.priority);
+                let name = format!("DS{}", sfx_name.to_ascii_uppercase());
                 if let Some(lump) = wad.get_lump(&name) {
                     let chunk = lump_sfx_to_chunk(lump.data.clone(), AudioFormat::S16LSB, 44_100)
                         .unwrap_or_else(|_| panic!("{name} failed to parse"));
-                    SfxInfo::new(s.name.to_string(), s.priority, Some(chunk))
+                    SfxInfo::new(sfx_name.to_string(), priority, Some(chunk))
                 } else {
                     debug!("{name} is missing");
-                    SfxInfo::new(s.name.to_string(), s.priority, None)
+                    SfxInfo::new(sfx_name.to_string(), priority, None)
                 }
             })
             .collect();
@@ -195,6 +204,7 @@ impl<'a> Snd<'a> {
             tx,
             chunks,
             music: None,
+            lump_music: Vec::new(),
             listener: SoundObject::default(),
             sources: [SoundObject::default(); MIXER_CHANNELS as usize],
             sfx_vol: 64,
@@ -376,6 +386,37 @@ impl<'a> SoundServer<SfxName, usize, sdl2::Error> for Snd<'a> {
         self.start_music(music, looping)
     }
 
+    fn change_music_lump(&mut self, name: String, data: Vec<u8>, looping: bool) {
+        Music::halt();
+        self.music.take();
+
+        let song = if let Some((_, song)) = self.lump_music.iter().find(|(n, _)| *n == name) {
+            *song
+        } else {
+            // MIDI and anything else SDL mixer can read is played as is
+            let song = if data.starts_with(&MUS_ID) {
+                read_mus_to_midi(&data)
+            } else {
+                Some(data)
+            };
+            let Some(song) = song else {
+                log::error!("MUS: {name} could not be converted");
+                return;
+            };
+            let song: &'static [u8] = Box::leak(song.into_boxed_slice());
+            self.lump_music.push((name.clone(), song));
+            song
+        };
+
+        if let Ok(music) =
+            Music::from_static_bytes(song).map_err(|e| log::error!("MUS: {name}, error: {e}"))
+        {
+            music.play(if looping { -1 } else { 0 }).unwrap();
+            self.music = Some(music);
+            Music::set_volume(self.mus_vol);
+        }
+    }
+
     fn stop_music(&mut self) {
         Music::halt();
     }
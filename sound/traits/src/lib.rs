@@ -48,6 +48,9 @@ pub enum SoundAction<S: Debug + Copy, M: Debug> {
     PauseMusic,
     ResumeMusic,
     ChangeMusic(M, bool),
+    /// Music that isn't one of the `M` tracks, e.g, a song named by UMAPINFO.
+    /// Lump name, the MUS or MIDI data, and looping/not
+    ChangeMusicLump(String, Vec<u8>, bool),
     StopMusic,
     Shutdown,
 }
@@ -89,6 +92,9 @@ where
 
     fn change_music(&mut self, music: M, looping: bool);
 
+    /// Play a song from lump data. The name can be used to cache the song
+    fn change_music_lump(&mut self, name: String, data: Vec<u8>, looping: bool);
+
     fn stop_music(&mut self);
 
     fn set_mus_volume(&mut self, volume: i32);
@@ -130,6 +136,9 @@ where
                 SoundAction::PauseMusic => self.pause_music(),
                 SoundAction::ResumeMusic => self.resume_music(),
                 SoundAction::ChangeMusic(music, looping) => self.change_music(music, looping),
+                SoundAction::ChangeMusicLump(name, data, looping) => {
+                    self.change_music_lump(name, data, looping)
+                }
                 SoundAction::StopMusic => self.stop_music(),
                 SoundAction::SfxVolume(v) => self.set_sfx_volume(v),
                 SoundAction::MusicVolume(v) => self.set_mus_volume(v),
@@ -219,6 +228,8 @@ mod tests {
 
         fn change_music(&mut self, _music: Music, _looping: bool) {}
 
+        fn change_music_lump(&mut self, _name: String, _data: Vec<u8>, _looping: bool) {}
+
         fn stop_music(&mut self) {}
 
         fn set_mus_volume(&mut self, _volume: i32) {}
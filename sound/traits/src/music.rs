@@ -12,6 +12,16 @@ impl MusData {
         }
     }
 
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Change the lump used, e.g, from a dehacked patch. Must be done before
+    /// the sound server loads the music
+    pub fn set_name(&mut self, name: &'static str) {
+        self.name = name;
+    }
+
     pub fn lump_name(&self) -> String {
         format!("D_{}", self.name.to_uppercase())
     }
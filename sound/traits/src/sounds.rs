@@ -113,6 +113,23 @@ pub enum SfxName {
     NumSfx,
 }
 
+/// Replacement lump name (without the `DS` prefix) and priority for an sfx
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SfxOverride {
+    pub name: Option<&'static str>,
+    pub priority: Option<i32>,
+}
+
+const NO_OVERRIDE: SfxOverride = SfxOverride {
+    name: None,
+    priority: None,
+};
+
+/// Changes to the sfx table from dehacked patches, indexed by `SfxName`. This is
+/// unsafe and must be set before the sound server is initialised.
+pub static mut SFX_OVERRIDES: [SfxOverride; SfxName::NumSfx as usize] =
+    [NO_OVERRIDE; SfxName::NumSfx as usize];
+
 impl Default for SfxName {
     fn default() -> Self {
         Self::None
@@ -9,8 +9,8 @@ use gamestate_traits::util::{
     get_small_percent_sprites, get_st_key_sprites,
 };
 use gamestate_traits::{
-    AmmoType, GameMode, GameTraits, PixelBuffer, PlayerStatus, Scancode, SubsystemTrait,
-    WEAPON_INFO, WeaponType,
+    AmmoType, Anchor, BufferSize, GameMode, GameTraits, PixelBuffer, PlayerStatus, Scancode,
+    ScreenScale, SubsystemTrait, WEAPON_INFO, WeaponType,
 };
 use std::collections::HashMap;
 use wad::WadData;
@@ -37,6 +37,8 @@ pub struct ClassicStatusbar {
     /// Keys: blue yellow red. Skulls: blue yellow red
     keys: [WadPatch; 6],
     status: PlayerStatus,
+    /// Shown in place of the weapons in deathmatch
+    frags: Option<i32>,
     faces: DoomguyFace,
 }
 
@@ -101,6 +103,8 @@ impl ClassicStatusbar {
 
         let lump = wad.get_lump("STFB1").unwrap();
         patches.insert("STFB1", WadPatch::from_lump(lump));
+        let lump = wad.get_lump("STTMINUS").unwrap();
+        patches.insert("STTMINUS", WadPatch::from_lump(lump));
 
         Self {
             screen_width: 0,
@@ -121,6 +125,7 @@ impl ClassicStatusbar {
             yell_nums: get_num_sprites("STYSNUM", 0, wad),
             keys: get_st_key_sprites(wad),
             status: PlayerStatus::default(),
+            frags: None,
             faces: DoomguyFace::new(wad),
         }
     }
@@ -188,7 +193,7 @@ impl ClassicStatusbar {
             return;
         }
 
-        let ammo = WEAPON_INFO[self.status.readyweapon as usize].ammo;
+        let ammo = unsafe { WEAPON_INFO[self.status.readyweapon as usize].ammo };
         if ammo == AmmoType::NoAmmo {
             return;
         }
@@ -284,6 +289,20 @@ impl ClassicStatusbar {
         }
     }
 
+    fn draw_frags_pixels(&self, frags: i32, pixels: &mut impl PixelBuffer) {
+        let width = self.big_nums[0].width as i32;
+        let y = self.status_top + 3;
+        // Right aligned to the same spot as the original
+        let count = frags.unsigned_abs();
+        let digits = count.to_string().len() as i32;
+        let x = self.status_left + 138 - width * digits;
+        draw_num_pixels(count, x, y, 0, &self.big_nums, self, pixels);
+        if frags < 0 {
+            let minus = self.get_patch("STTMINUS");
+            self.draw_patch_pixels(minus, x - minus.width as i32, y, pixels);
+        }
+    }
+
     fn draw_face_pixels(&self, mut big: bool, upper: bool, pixels: &mut impl PixelBuffer) {
         if upper {
             big = true;
@@ -320,6 +339,7 @@ impl SubsystemTrait for ClassicStatusbar {
 
     fn ticker(&mut self, game: &mut impl GameTraits) -> bool {
         self.status = game.player_status();
+        self.frags = game.player_frags();
         self.faces.tick(&self.status);
         false
     }
@@ -328,6 +348,10 @@ impl SubsystemTrait for ClassicStatusbar {
         &self.palette
     }
 
+    fn screen_scale(&self, size: &BufferSize) -> ScreenScale {
+        ScreenScale::hud(size, Anchor::Bottom)
+    }
+
     fn draw(&mut self, buffer: &mut impl PixelBuffer) {
         self.screen_width = 320;
         self.screen_height = 200;
@@ -342,7 +366,11 @@ impl SubsystemTrait for ClassicStatusbar {
         self.draw_health_pixels(buffer);
         self.draw_armour_pixels(buffer);
         self.draw_ammo_big_pixels(buffer);
-        self.draw_weapons_pixels(buffer);
+        if let Some(frags) = self.frags {
+            self.draw_frags_pixels(frags, buffer);
+        } else {
+            self.draw_weapons_pixels(buffer);
+        }
         self.draw_keys_pixels(buffer);
     }
 }
@@ -6,8 +6,8 @@
 use crate::faces::DoomguyFace;
 use gamestate_traits::util::{draw_num_pixels, get_num_sprites, get_st_key_sprites};
 use gamestate_traits::{
-    AmmoType, GameMode, GameTraits, PixelBuffer, PlayerStatus, Scancode, SubsystemTrait,
-    WEAPON_INFO, WeaponType,
+    AmmoType, Anchor, BufferSize, GameMode, GameTraits, PixelBuffer, PlayerStatus, Scancode,
+    ScreenScale, SubsystemTrait, WEAPON_INFO, WeaponType,
 };
 use std::collections::HashMap;
 use wad::WadData;
@@ -127,7 +127,7 @@ impl CustomStatusbar {
             return;
         }
 
-        let ammo = WEAPON_INFO[self.status.readyweapon as usize].ammo;
+        let ammo = unsafe { WEAPON_INFO[self.status.readyweapon as usize].ammo };
         if ammo == AmmoType::NoAmmo {
             return;
         }
@@ -276,6 +276,10 @@ impl SubsystemTrait for CustomStatusbar {
         &self.palette
     }
 
+    fn screen_scale(&self, size: &BufferSize) -> ScreenScale {
+        ScreenScale::hud(size, Anchor::Bottom)
+    }
+
     fn draw(&mut self, buffer: &mut impl PixelBuffer) {
         self.screen_width = 320;
         self.screen_height = 200;
@@ -1,4 +1,7 @@
-use log::warn;
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+use log::{error, warn};
 
 use crate::types::{WadNode, WadSegment, WadSubSector, WadVertex};
 use crate::{Lump, MapLump, WadData};
@@ -24,6 +27,16 @@ impl ExtendedNodeType {
         )
     }
 
+    /// The uncompressed type a compressed type inflates to
+    pub fn uncompressed(&self) -> Self {
+        match self {
+            ExtendedNodeType::ZNOD => ExtendedNodeType::XNOD,
+            ExtendedNodeType::ZGLN => ExtendedNodeType::XGLN,
+            ExtendedNodeType::ZGL2 => ExtendedNodeType::XGL2,
+            t => *t,
+        }
+    }
+
     pub fn is_gl(&self) -> bool {
         matches!(
             self,
@@ -88,15 +101,24 @@ impl NodeLumpType {
 /// | 4-byte chunk | u32     | Subsector N: Seg count for this subsector                     |
 /// | 4-bytes      | u32     | Segs count                                                    |
 /// | 11-byte chunk| Segment | Seg N: New layout: `u32`:Vertex 1, `u32`Vertex 2, `u16`:Line, `u8`:Side |
+/// | GL: 11-byte  | Segment | Seg N: `u32`:Vertex 1, `u32`:Partner seg, `u16`:Line, `u8`:Side |
+/// | GL2: 13-byte | Segment | Seg N: `u32`:Vertex 1, `u32`:Partner seg, `u32`:Line, `u8`:Side |
 /// | 4-byte chunk | u32     | Node count                                                    |
 /// | 32-byte chunk| Node    | Node N: Same as vanilla except child ref are u32              |
 ///
 /// Note: a 16:16 fixed point number is stored in 4 bytes.
 ///
+/// Note: the compressed `Z` types are the same layout as the `X` types, but
+/// everything after the 4 byte signature is zlib compressed.
+///
+/// Note: GL segs don't store the second vertex, it is the first vertex of the
+/// next seg in the subsector. GL nodes also contain "minisegs" which have no
+/// linedef, these are dropped so the subsectors match the vanilla layout.
+///
 /// Note: the OG Doom segs and subsectors lumps are empty if an extended format
 /// is used. From the OG format you will require: `WadSector`, `WadLinedef`,
 /// `WadSidedef`, and `WadThing`.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct WadExtendedMap {
     pub node_type: ExtendedNodeType,
     pub num_org_vertices: usize,
@@ -113,36 +135,79 @@ pub struct WadExtendedMap {
 }
 
 impl WadExtendedMap {
+    /// Parse the extended nodes for the map. Returns `None` if the map uses the
+    /// original Doom nodes, or if the nodes could not be decompressed.
     pub fn parse(wad_data: &WadData, map_name: &str) -> Option<Self> {
         let lump = wad_data.find_lump_for_map_or_panic(map_name, MapLump::Nodes);
+        Self::from_lump(lump)
+    }
+
+    /// Parse the `ZNODES` of a UDMF map. Returns `None` if the map has no
+    /// `ZNODES`, there is no node builder to make them.
+    pub fn parse_udmf(wad_data: &WadData, map_name: &str) -> Option<Self> {
+        wad_data
+            .find_lump_for_map(map_name, MapLump::Nodes)
+            .and_then(Self::from_lump)
+    }
+
+    /// ZDBSP can write GL nodes to the `SSECTORS` lump and leave `NODES` empty.
+    /// Returns `None` if `SSECTORS` doesn't hold GL nodes.
+    pub fn parse_gl_subsectors(wad_data: &WadData, map_name: &str) -> Option<Self> {
+        let lump = wad_data.find_lump_for_map_or_panic(map_name, MapLump::SSectors);
+        // Vanilla subsector data can start with an 'X' or 'Z' by chance
+        if lump.data.len() < 4 || !matches!(&lump.data[..4], b"XGLN" | b"ZGLN" | b"XGL2" | b"ZGL2")
+        {
+            return None;
+        }
+        Self::from_lump(lump)
+    }
 
-        let bytes = [
-            lump.read_i16(0) as u8,
-            lump.read_i16(1) as u8,
-            lump.read_i16(2) as u8,
-            lump.read_i16(3) as u8,
-        ];
+    /// Parse an extended `NODES` lump, see `parse()`
+    pub fn from_lump(lump: &Lump) -> Option<Self> {
+        if lump.data.len() < 4 {
+            return None;
+        }
+        let bytes = [lump.data[0], lump.data[1], lump.data[2], lump.data[3]];
         let node_type = NodeLumpType::from_bytes(&bytes);
 
         if let NodeLumpType::Extended(t) = node_type {
-            if t.is_uncompressed() {
-                return Some(Self::parse_uncompressed(lump, t));
+            let map = if t.is_uncompressed() {
+                Self::parse_uncompressed(lump, t)
             } else {
-                todo!("Compress zdoom nodes not supported yet")
+                Self::parse_uncompressed(&Self::inflate(lump)?, t)
+            };
+            if map.is_none() {
+                error!("Extended nodes in {} are truncated or corrupt", lump.name);
             }
+            return map;
         }
         None
     }
 
-    fn parse_uncompressed(lump: &Lump, etype: ExtendedNodeType) -> Self {
+    /// Decompress a `Z` type lump. The returned lump keeps the original
+    /// signature so it can be parsed as the uncompressed type.
+    fn inflate(lump: &Lump) -> Option<Lump> {
+        let mut data = lump.data[..4].to_vec();
+        let mut decoder = ZlibDecoder::new(&lump.data[4..]);
+        if let Err(e) = decoder.read_to_end(&mut data) {
+            error!("Could not inflate compressed nodes in {}: {}", lump.name, e);
+            return None;
+        }
+        Some(Lump {
+            name: lump.name.clone(),
+            data,
+        })
+    }
+
+    /// Returns `None` if a table runs past the end of the lump, or the
+    /// subsectors don't use every seg
+    fn parse_uncompressed(lump: &Lump, etype: ExtendedNodeType) -> Option<Self> {
         let mut ofs = 4;
-        let num_org_vertices = lump.read_u32(ofs) as usize;
-        ofs += 4;
-        let num_new_vertices = lump.read_u32(ofs) as usize;
-        ofs += 4;
+        let num_org_vertices = read_count(lump, &mut ofs)?;
+        let num_new_vertices = read_count(lump, &mut ofs)?;
 
         let mut vertexes = Vec::with_capacity(num_new_vertices);
-        let end = ofs + num_new_vertices * 8;
+        let end = table_end(lump, ofs, num_new_vertices, 8)?;
         // The vertices are in fixed-point format and will require conversion later
         // Each vert is x,y, where x and y are 4 bytes each
         while ofs < end {
@@ -151,13 +216,11 @@ impl WadExtendedMap {
             vertexes.push(WadVertex::new(fixed_t::new(v1), fixed_t::new(v2)));
             ofs += 8;
         }
-        debug_assert_eq!(vertexes.len(), num_new_vertices);
 
-        let num_subs = lump.read_u32(ofs) as usize;
-        ofs += 4;
+        let num_subs = read_count(lump, &mut ofs)?;
         let mut subsectors = Vec::with_capacity(num_subs);
-        let end = ofs + num_subs * 4;
-        let mut start_seg = 0;
+        let end = table_end(lump, ofs, num_subs, 4)?;
+        let mut start_seg = 0u32;
         // subsectors are an index
         while ofs < end {
             let seg_count = lump.read_u32(ofs);
@@ -166,29 +229,35 @@ impl WadExtendedMap {
                 seg_count,
                 start_seg,
             });
-            start_seg += seg_count;
+            start_seg = start_seg.checked_add(seg_count)?;
         }
-        debug_assert_eq!(subsectors.len(), num_subs);
 
-        let num_segs = lump.read_u32(ofs) as usize;
-        ofs += 4;
-        let mut segments = Vec::with_capacity(num_segs);
-        let end = ofs + num_segs * 11;
-        while ofs < end {
-            segments.push(WadSegment::new_z(
-                lump.read_u32(ofs),
-                lump.read_u32(ofs + 4),
-                lump.read_u16(ofs + 8),
-                lump.data[ofs + 10] as u16,
-            ));
-            ofs += 11;
+        let num_segs = read_count(lump, &mut ofs)?;
+        if start_seg as usize != num_segs {
+            return None;
+        }
+        let mut segments;
+        if etype.is_gl() {
+            let gl_segs;
+            (segments, gl_segs, ofs) = Self::parse_gl_segs(lump, ofs, num_segs, etype)?;
+            Self::remove_minisegs(&mut segments, &gl_segs, &mut subsectors);
+        } else {
+            segments = Vec::with_capacity(num_segs);
+            let end = table_end(lump, ofs, num_segs, 11)?;
+            while ofs < end {
+                segments.push(WadSegment::new_z(
+                    lump.read_u32(ofs),
+                    lump.read_u32(ofs + 4),
+                    lump.read_u16(ofs + 8),
+                    lump.data[ofs + 10] as u16,
+                ));
+                ofs += 11;
+            }
         }
-        debug_assert_eq!(segments.len(), num_segs);
 
-        let num_nodes = lump.read_u32(ofs) as usize;
-        ofs += 4;
+        let num_nodes = read_count(lump, &mut ofs)?;
         let mut nodes = Vec::with_capacity(num_nodes);
-        let end = ofs + num_nodes * 32;
+        let end = table_end(lump, ofs, num_nodes, 32)?;
         while ofs < end {
             nodes.push(WadNode::new(
                 lump.read_i16(ofs),     // X
@@ -214,28 +283,154 @@ impl WadExtendedMap {
             ));
             ofs += 32
         }
-        debug_assert_eq!(nodes.len(), num_nodes);
 
-        Self {
-            node_type: etype,
+        Some(Self {
+            node_type: etype.uncompressed(),
             num_org_vertices,
             num_new_vertices,
             vertexes,
             subsectors,
             segments,
             nodes,
+        })
+    }
+
+    /// The end vertex of GL segs is filled in from the next seg as the segs
+    /// of a subsector form a closed loop. The partner seg and miniseg flag of
+    /// each are returned alongside.
+    fn parse_gl_segs(
+        lump: &Lump,
+        mut ofs: usize,
+        num_segs: usize,
+        etype: ExtendedNodeType,
+    ) -> Option<(Vec<WadSegment>, Vec<GlSeg>, usize)> {
+        let is_gl2 = matches!(etype, ExtendedNodeType::XGL2 | ExtendedNodeType::ZGL2);
+        let seg_size = if is_gl2 { 13 } else { 11 };
+        let mut segments = Vec::with_capacity(num_segs);
+        let mut gl_segs = Vec::with_capacity(num_segs);
+        let end = table_end(lump, ofs, num_segs, seg_size)?;
+        while ofs < end {
+            let (linedef, side, miniseg) = if is_gl2 {
+                let line = lump.read_u32(ofs + 8);
+                (line as u16, lump.data[ofs + 12] as u16, line == u32::MAX)
+            } else {
+                let line = lump.read_u16(ofs + 8);
+                (line, lump.data[ofs + 10] as u16, line == u16::MAX)
+            };
+            segments.push(WadSegment::new_z(lump.read_u32(ofs), 0, linedef, side));
+            gl_segs.push(GlSeg {
+                partner: lump.read_u32(ofs + 4),
+                miniseg,
+            });
+            ofs += seg_size;
+        }
+        Some((segments, gl_segs, ofs))
+    }
+
+    /// Set the end vertex of GL segs, then drop the minisegs and fix up the
+    /// subsector seg ranges to match.
+    ///
+    /// A subsector of only minisegs is left with no segs, its `start_seg` then
+    /// points at the first seg of a neighbour across one of its minisegs so
+    /// the sector can still be found. Minisegs never cross a sector boundary.
+    fn remove_minisegs(
+        segments: &mut Vec<WadSegment>,
+        gl_segs: &[GlSeg],
+        subsectors: &mut [WadSubSector],
+    ) {
+        for sub in subsectors.iter() {
+            let start = sub.start_seg as usize;
+            let end = (start + sub.seg_count as usize).min(segments.len());
+            for i in start..end {
+                let next = if i + 1 < end { i + 1 } else { start };
+                segments[i].end_vertex = segments[next].start_vertex;
+            }
+        }
+
+        // The original seg range of each subsector, and the subsector of each seg
+        let mut ranges = Vec::with_capacity(subsectors.len());
+        let mut seg_subs = vec![usize::MAX; segments.len()];
+        let mut kept = Vec::with_capacity(segments.len());
+        for (n, sub) in subsectors.iter_mut().enumerate() {
+            let start = (sub.start_seg as usize).min(segments.len());
+            let end = (start + sub.seg_count as usize).min(segments.len());
+            ranges.push(start..end);
+            seg_subs[start..end].fill(n);
+            sub.start_seg = kept.len() as u32;
+            kept.extend(
+                segments[start..end]
+                    .iter()
+                    .zip(&gl_segs[start..end])
+                    .filter(|(_, gl)| !gl.miniseg)
+                    .map(|(seg, _)| seg.clone()),
+            );
+            sub.seg_count = kept.len() as u32 - sub.start_seg;
+        }
+        *segments = kept;
+
+        // Each pass can resolve the neighbours of those resolved before it
+        let mut has_sector: Vec<bool> = subsectors.iter().map(|s| s.seg_count != 0).collect();
+        let mut empty: Vec<usize> = (0..subsectors.len()).filter(|n| !has_sector[*n]).collect();
+        while !empty.is_empty() {
+            let count = empty.len();
+            empty.retain(|n| {
+                let neighbour = gl_segs[ranges[*n].clone()]
+                    .iter()
+                    .filter_map(|gl| seg_subs.get(gl.partner as usize))
+                    .find(|other| has_sector[**other]);
+                if let Some(other) = neighbour {
+                    subsectors[*n].start_seg = subsectors[*other].start_seg;
+                    has_sector[*n] = true;
+                    return false;
+                }
+                true
+            });
+            if empty.len() == count {
+                break;
+            }
+        }
+        for n in empty {
+            warn!("GL subsector {n} has only minisegs and no neighbour, using the first sector");
+            subsectors[n].start_seg = 0;
         }
     }
 }
 
+/// The parts of a GL seg that aren't kept in `WadSegment`
+struct GlSeg {
+    /// The same seg in the subsector on the other side, `u32::MAX` if none
+    partner: u32,
+    /// Minisegs have no linedef and are only used to close a subsector
+    miniseg: bool,
+}
+
+/// The end of a table of `count` entries of `size` bytes starting at `ofs`,
+/// or `None` if it doesn't fit in the lump
+fn table_end(lump: &Lump, ofs: usize, count: usize, size: usize) -> Option<usize> {
+    let end = count.checked_mul(size)?.checked_add(ofs)?;
+    (end <= lump.data.len()).then_some(end)
+}
+
+/// Read a `u32` count and move `ofs` past it
+fn read_count(lump: &Lump, ofs: &mut usize) -> Option<usize> {
+    table_end(lump, *ofs, 1, 4)?;
+    let count = lump.read_u32(*ofs) as usize;
+    *ofs += 4;
+    Some(count)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::io::Write;
+
+    use flate2::Compression;
+    use flate2::write::ZlibEncoder;
     use math::fixed_t;
 
-    use crate::WadData;
     use crate::types::{WadLineDef, WadNode, WadSector, WadSideDef, WadVertex};
+    use crate::{Lump, WadData};
 
-    use super::WadExtendedMap;
+    use super::{ExtendedNodeType, WadExtendedMap};
 
     #[ignore = "sunder.wad can't be included in git"]
     #[test]
@@ -336,6 +531,210 @@ mod tests {
         assert_eq!(node.children[1] ^ 0x80000000, 674);
     }
 
+    /// A tiny map: 4 original vertices plus 1 new, 2 subsectors, 3 segs
+    /// and a single node. Returned without the signature.
+    fn xnod_body() -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&4u32.to_le_bytes());
+        b.extend_from_slice(&1u32.to_le_bytes());
+        b.extend_from_slice(&(64i32 << 16).to_le_bytes());
+        b.extend_from_slice(&(32i32 << 16).to_le_bytes());
+
+        b.extend_from_slice(&2u32.to_le_bytes());
+        b.extend_from_slice(&2u32.to_le_bytes());
+        b.extend_from_slice(&1u32.to_le_bytes());
+
+        b.extend_from_slice(&3u32.to_le_bytes());
+        for (v1, v2, line, side) in [(0u32, 1u32, 0u16, 0u8), (1, 4, 1, 0), (4, 0, 2, 1)] {
+            b.extend_from_slice(&v1.to_le_bytes());
+            b.extend_from_slice(&v2.to_le_bytes());
+            b.extend_from_slice(&line.to_le_bytes());
+            b.push(side);
+        }
+        b.extend_from_slice(&1u32.to_le_bytes());
+        b.extend_from_slice(&node_bytes());
+        b
+    }
+
+    fn node_bytes() -> Vec<u8> {
+        let mut b = Vec::new();
+        for v in [0i16, 0, 64, 0, 64, 0, 0, 64, 0, -64, 0, 64] {
+            b.extend_from_slice(&v.to_le_bytes());
+        }
+        b.extend_from_slice(&0x8000_0000u32.to_le_bytes());
+        b.extend_from_slice(&0x8000_0001u32.to_le_bytes());
+        b
+    }
+
+    fn lump(sig: &[u8; 4], body: &[u8]) -> Lump {
+        let mut data = sig.to_vec();
+        data.extend_from_slice(body);
+        Lump {
+            name: "NODES".to_string(),
+            data,
+        }
+    }
+
+    fn compress(body: &[u8]) -> Vec<u8> {
+        let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
+        enc.write_all(body).unwrap();
+        enc.finish().unwrap()
+    }
+
+    #[test]
+    fn extended_nodes_xnod_fixture() {
+        let map = WadExtendedMap::from_lump(&lump(b"XNOD", &xnod_body())).unwrap();
+        assert_eq!(map.node_type, ExtendedNodeType::XNOD);
+        assert_eq!(map.num_org_vertices, 4);
+        assert_eq!(map.vertexes.len(), 1);
+        assert_eq!(map.vertexes[0].x, fixed_t::new(64 << 16));
+        assert_eq!(map.subsectors[1].start_seg, 2);
+        assert_eq!(map.subsectors[1].seg_count, 1);
+        assert_eq!(map.segments[1].end_vertex, 4);
+        assert_eq!(map.segments[2].side, 1);
+        assert_eq!(map.nodes[0].dx, 64);
+        assert_eq!(map.nodes[0].children, [0x8000_0000, 0x8000_0001]);
+    }
+
+    #[test]
+    fn extended_nodes_znod_round_trip() {
+        let body = xnod_body();
+        let plain = WadExtendedMap::from_lump(&lump(b"XNOD", &body)).unwrap();
+        let compressed = WadExtendedMap::from_lump(&lump(b"ZNOD", &compress(&body))).unwrap();
+        assert_eq!(plain, compressed);
+    }
+
+    #[test]
+    fn extended_nodes_znod_corrupt() {
+        let mut data = compress(&xnod_body());
+        data.truncate(data.len() / 2);
+        assert!(WadExtendedMap::from_lump(&lump(b"ZNOD", &data)).is_none());
+    }
+
+    /// One subsector of 3 GL segs, the middle is a miniseg. `line_size` is 2
+    /// for GLN and 4 for GL2.
+    fn xgl_body(line_size: usize) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&4u32.to_le_bytes());
+        b.extend_from_slice(&1u32.to_le_bytes());
+        b.extend_from_slice(&(64i32 << 16).to_le_bytes());
+        b.extend_from_slice(&(32i32 << 16).to_le_bytes());
+
+        b.extend_from_slice(&1u32.to_le_bytes());
+        b.extend_from_slice(&3u32.to_le_bytes());
+
+        b.extend_from_slice(&3u32.to_le_bytes());
+        for (v1, line, side) in [(0u32, 0u32, 0u8), (1, u32::MAX, 0), (4, 2, 1)] {
+            b.extend_from_slice(&v1.to_le_bytes());
+            b.extend_from_slice(&u32::MAX.to_le_bytes());
+            b.extend_from_slice(&line.to_le_bytes()[..line_size]);
+            b.push(side);
+        }
+        b.extend_from_slice(&1u32.to_le_bytes());
+        b.extend_from_slice(&node_bytes());
+        b
+    }
+
+    #[test]
+    fn extended_nodes_gl_minisegs_removed() {
+        for (sig, zsig, line_size) in [(b"XGLN", b"ZGLN", 2), (b"XGL2", b"ZGL2", 4)] {
+            let body = xgl_body(line_size);
+            let map = WadExtendedMap::from_lump(&lump(sig, &body)).unwrap();
+            assert!(map.node_type.is_gl());
+            assert_eq!(map.segments.len(), 2);
+            assert_eq!(map.subsectors[0].start_seg, 0);
+            assert_eq!(map.subsectors[0].seg_count, 2);
+            assert_eq!(
+                (map.segments[0].start_vertex, map.segments[0].end_vertex),
+                (0, 1)
+            );
+            // Last seg closes the loop back to the first vertex
+            assert_eq!(
+                (map.segments[1].start_vertex, map.segments[1].end_vertex),
+                (4, 0)
+            );
+            assert_eq!(map.segments[1].linedef, 2);
+            assert_eq!(map.segments[1].side, 1);
+            assert_eq!(map.nodes.len(), 1);
+
+            let compressed = WadExtendedMap::from_lump(&lump(zsig, &compress(&body))).unwrap();
+            assert_eq!(map, compressed);
+        }
+    }
+
+    /// Four subsectors, the last two are only minisegs. The third is next to
+    /// the second, and the fourth only next to the third.
+    fn xgl_miniseg_only_body() -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&4u32.to_le_bytes());
+        b.extend_from_slice(&1u32.to_le_bytes());
+        b.extend_from_slice(&(64i32 << 16).to_le_bytes());
+        b.extend_from_slice(&(32i32 << 16).to_le_bytes());
+
+        b.extend_from_slice(&4u32.to_le_bytes());
+        for count in [1u32, 3, 2, 1] {
+            b.extend_from_slice(&count.to_le_bytes());
+        }
+
+        b.extend_from_slice(&7u32.to_le_bytes());
+        let mini = u16::MAX;
+        for (v1, partner, line, side) in [
+            (2u32, u32::MAX, 1u16, 0u8),
+            (0, u32::MAX, 0, 0),
+            (1, 4, mini, 0),
+            (4, u32::MAX, 2, 1),
+            (4, 2, mini, 0),
+            (1, 6, mini, 0),
+            (2, 5, mini, 0),
+        ] {
+            b.extend_from_slice(&v1.to_le_bytes());
+            b.extend_from_slice(&partner.to_le_bytes());
+            b.extend_from_slice(&line.to_le_bytes());
+            b.push(side);
+        }
+        b.extend_from_slice(&1u32.to_le_bytes());
+        b.extend_from_slice(&node_bytes());
+        b
+    }
+
+    #[test]
+    fn extended_nodes_truncated() {
+        for (sig, body) in [(b"XNOD", xnod_body()), (b"XGLN", xgl_miniseg_only_body())] {
+            assert!(WadExtendedMap::from_lump(&lump(sig, &body)).is_some());
+            for len in 0..body.len() {
+                assert!(WadExtendedMap::from_lump(&lump(sig, &body[..len])).is_none());
+            }
+        }
+        // Subsectors using more segs than there are
+        let mut body = xnod_body();
+        body[20..24].copy_from_slice(&5u32.to_le_bytes());
+        assert!(WadExtendedMap::from_lump(&lump(b"XNOD", &body)).is_none());
+    }
+
+    #[test]
+    fn extended_nodes_gl2_line_65535_is_not_a_miniseg() {
+        let mut body = xgl_body(4);
+        // The first seg's line, after the vertices, subsectors and seg count
+        let line = 8 + 8 + 4 + 4 + 4 + 8;
+        body[line..line + 4].copy_from_slice(&65535u32.to_le_bytes());
+        let map = WadExtendedMap::from_lump(&lump(b"XGL2", &body)).unwrap();
+        assert_eq!(map.segments.len(), 2);
+        assert_eq!(map.segments[0].linedef, 65535);
+    }
+
+    #[test]
+    fn extended_nodes_gl_miniseg_only_subsector() {
+        let map = WadExtendedMap::from_lump(&lump(b"XGLN", &xgl_miniseg_only_body())).unwrap();
+        assert_eq!(map.segments.len(), 3);
+        assert_eq!(map.subsectors[1].start_seg, 1);
+        assert_eq!(map.subsectors[1].seg_count, 2);
+        for sub in &map.subsectors[2..] {
+            assert_eq!(sub.seg_count, 0);
+            // Points at a real seg of the neighbour to look up the sector from
+            assert_eq!(sub.start_seg, 1);
+        }
+    }
+
     #[test]
     fn extended_nodes_none() {
         let wad = WadData::new("../doom1.wad".into());
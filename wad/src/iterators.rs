@@ -505,6 +505,9 @@ impl WadData {
 
     pub fn node_lump_type(&self, map_name: &str) -> NodeLumpType {
         let info = self.find_lump_for_map_or_panic(map_name, MapLump::Nodes);
+        if info.data.len() < 4 {
+            return NodeLumpType::OGDoom;
+        }
         let bytes = [
             info.read_i16(0) as u8,
             info.read_i16(1) as u8,
@@ -44,3 +44,9 @@ pub mod types;
 
 /// ZDoom BSP support (and maybe others in future)
 pub mod extended;
+
+/// `UMAPINFO` map progression and details
+pub mod umapinfo;
+
+/// UDMF `TEXTMAP` maps
+pub mod udmf;
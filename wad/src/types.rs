@@ -221,7 +221,7 @@ impl WadThing {
 ///
 /// **NOTE**: the x and y types are increased to i32 for extended node support.
 /// The parsing for OG Doom should still be using i16 size (and convert it).
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct WadVertex {
     pub x: fixed_t,
     pub y: fixed_t,
@@ -317,7 +317,7 @@ impl WadLineDef {
 /// Each `Segment` record is 12 bytes
 ///
 /// **NOTE**: some internal types are changed for extended node support.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct WadSegment {
     /// The line starts from this point
     pub start_vertex: u32,
@@ -396,7 +396,7 @@ impl WadSegment {
 /// Each `SubSector` record is 4 bytes
 ///
 /// **NOTE**: internal types changed for zdoom extended node compatibility
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct WadSubSector {
     /// How many `Segment`s line this `SubSector`
     pub seg_count: u32,
@@ -650,7 +650,7 @@ impl WadNode {
 /// | 0x02-0x03  | i16       | Y origin (bottom, fixedpoint)              |
 /// | 0x04-0x05  | i16       | Num of columns                             |
 /// | 0x06-0x07  | i16       | Num of rows                                |
-/// | 0x08-0xN   | i16       | Block offsets, where N = columns*rows      |
+/// | 0x08-0xN   | u16       | Block offsets, where N = columns*rows      |
 /// | 0xN2-0xN3  | i16       | 0x0000, start of block of lines            |
 /// | 0xN4-0xN   | i16       | Lindedef, and all consectutive lines after |
 /// | 0xN+1-0xN+2| i16       | 0xFFFF, end of this block                  |
@@ -670,6 +670,9 @@ pub struct WadBlockMap {
     /// offset in to this array. The number at that location is then the
     /// index number in to the linedefs array.
     pub line_indexes: Vec<i16>,
+    /// Where each block's list starts in `line_indexes`. Each list is copied
+    /// out from the block's own offset so shared lists are repeated.
+    pub block_starts: Vec<usize>,
 }
 
 impl WadBlockMap {
@@ -679,6 +682,7 @@ impl WadBlockMap {
         width: i16,
         height: i16,
         lines: Vec<i16>,
+        block_starts: Vec<usize>,
     ) -> WadBlockMap {
         WadBlockMap {
             x_origin,
@@ -686,6 +690,7 @@ impl WadBlockMap {
             columns: width,
             rows: height,
             line_indexes: lines,
+            block_starts,
         }
     }
 }
@@ -693,9 +698,154 @@ impl WadBlockMap {
 #[derive(Debug, Clone)]
 pub struct WadRejectTable(Vec<u8>);
 
+/// Read a name stored in a fixed size, nul terminated, field
+fn read_name(data: &[u8]) -> String {
+    let end = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).to_ascii_uppercase()
+}
+
+/// One cycle of animated flats or textures from the BOOM `ANIMATED` lump
+///
+/// The data in the WAD lump is structured as follows:
+///
+/// | Field Size | Data Type | Content                          |
+/// |------------|-----------|----------------------------------|
+/// |  0x00      |    u8     | 0 = flat, 1 = texture, 255 = end |
+/// |  0x01-0x09 |  [u8; 9]  | Last name in the cycle           |
+/// |  0x0A-0x12 |  [u8; 9]  | First name in the cycle          |
+/// |  0x13-0x16 |    i32    | Tics per frame                   |
+///
+/// Each record is 23 bytes
+#[derive(Debug, Clone)]
+pub struct WadAnimation {
+    pub is_texture: bool,
+    pub end_name: String,
+    pub start_name: String,
+    pub speed: usize,
+}
+
+impl WadAnimation {
+    const SIZE: usize = 23;
+    const TERMINATOR: u8 = 0xff;
+
+    pub fn new(is_texture: bool, end_name: &str, start_name: &str, speed: usize) -> Self {
+        Self {
+            is_texture,
+            end_name: end_name.to_owned(),
+            start_name: start_name.to_owned(),
+            speed,
+        }
+    }
+
+    /// Parse the records of an `ANIMATED` lump up to the terminator
+    pub fn read_lump(data: &[u8]) -> Vec<Self> {
+        data.chunks_exact(Self::SIZE)
+            .take_while(|r| r[0] != Self::TERMINATOR)
+            .map(|r| Self {
+                is_texture: r[0] & 1 != 0,
+                end_name: read_name(&r[1..10]),
+                start_name: read_name(&r[10..19]),
+                speed: i32::from_le_bytes([r[19], r[20], r[21], r[22]]).max(1) as usize,
+            })
+            .collect()
+    }
+}
+
+/// A pair of switch textures from the BOOM `SWITCHES` lump
+///
+/// The data in the WAD lump is structured as follows:
+///
+/// | Field Size | Data Type | Content                                 |
+/// |------------|-----------|-----------------------------------------|
+/// |  0x00-0x08 |  [u8; 9]  | Texture for the off switch              |
+/// |  0x09-0x11 |  [u8; 9]  | Texture for the on switch               |
+/// |  0x12-0x13 |    i16    | 1 = shareware, 2 = registered, 3 = Doom II, 0 = end |
+///
+/// Each record is 20 bytes
+#[derive(Debug, Clone)]
+pub struct WadSwitch {
+    pub name1: String,
+    pub name2: String,
+    pub episode: i16,
+}
+
+impl WadSwitch {
+    const SIZE: usize = 20;
+
+    pub fn new(name1: &str, name2: &str, episode: i16) -> Self {
+        Self {
+            name1: name1.to_owned(),
+            name2: name2.to_owned(),
+            episode,
+        }
+    }
+
+    /// Parse the records of a `SWITCHES` lump up to the terminator
+    pub fn read_lump(data: &[u8]) -> Vec<Self> {
+        data.chunks_exact(Self::SIZE)
+            .map(|r| Self {
+                name1: read_name(&r[0..9]),
+                name2: read_name(&r[9..18]),
+                episode: i16::from_le_bytes([r[18], r[19]]),
+            })
+            .take_while(|s| s.episode != 0)
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::WadData;
+    use crate::types::{WadAnimation, WadSwitch};
+
+    fn name9(name: &str) -> Vec<u8> {
+        let mut n = name.as_bytes().to_vec();
+        n.resize(9, 0);
+        n
+    }
+
+    #[test]
+    fn animated_lump() {
+        let mut data = vec![1];
+        data.extend(name9("BFALL4"));
+        data.extend(name9("BFALL1"));
+        data.extend(8i32.to_le_bytes());
+        data.push(0);
+        data.extend(name9("nukage3"));
+        data.extend(name9("nukage1"));
+        data.extend(4i32.to_le_bytes());
+        data.push(0xff);
+
+        let anims = WadAnimation::read_lump(&data);
+        assert_eq!(anims.len(), 2);
+        assert!(anims[0].is_texture);
+        assert_eq!(anims[0].end_name, "BFALL4");
+        assert_eq!(anims[0].start_name, "BFALL1");
+        assert_eq!(anims[0].speed, 8);
+        assert!(!anims[1].is_texture);
+        assert_eq!(anims[1].start_name, "NUKAGE1");
+        assert_eq!(anims[1].speed, 4);
+    }
+
+    #[test]
+    fn switches_lump() {
+        let mut data = Vec::new();
+        for (a, b, ep) in [
+            ("SW1BRCOM", "SW2BRCOM", 1i16),
+            ("SW1SKULL", "SW2SKULL", 3),
+            ("", "", 0),
+        ] {
+            data.extend(name9(a));
+            data.extend(name9(b));
+            data.extend(ep.to_le_bytes());
+        }
+
+        let switches = WadSwitch::read_lump(&data);
+        assert_eq!(switches.len(), 2);
+        assert_eq!(switches[0].name1, "SW1BRCOM");
+        assert_eq!(switches[1].name2, "SW2SKULL");
+        assert_eq!(switches[1].episode, 3);
+    }
 
     #[test]
     fn texture1_header_0() {
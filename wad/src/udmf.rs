@@ -0,0 +1,524 @@
+//! Parsing for the UDMF `TEXTMAP` lump. A UDMF map is the map marker followed
+//! by `TEXTMAP`, optionally `ZNODES`, `REJECT`, and `BLOCKMAP`, then an
+//! `ENDMAP` marker. The text is parsed in to the same types as the binary map
+//! lumps so the level loader can use either. The full spec is in
+//! `misc/udmf.md` of ZDoom, or <https://doomwiki.org/wiki/UDMF>.
+//!
+//! ```text,ignore
+//! namespace = "doom";
+//! vertex { x = 64.0; y = -128.5; }
+//! linedef { v1 = 0; v2 = 1; sidefront = 0; blocking = true; }
+//! ```
+//!
+//! Only the fields that exist in the Doom namespace are used. Maps in other
+//! namespaces, such as Heretic or Strife, are refused as their specials and
+//! things are not Doom's.
+
+use std::collections::HashMap;
+
+use log::warn;
+use math::fixed_t;
+
+use crate::types::{WadLineDef, WadSector, WadSideDef, WadThing, WadVertex};
+
+/// Thing flags in the binary format, made from the UDMF booleans
+const THING_FLAGS: [(&str, i16); 5] = [
+    ("skill1", 1),
+    ("skill2", 1),
+    ("skill3", 2),
+    ("skill4", 4),
+    ("skill5", 4),
+];
+
+/// Linedef flags in the binary format, made from the UDMF booleans
+const LINE_FLAGS: [(&str, u16); 10] = [
+    ("blocking", 0x0001),
+    ("blockmonsters", 0x0002),
+    ("twosided", 0x0004),
+    ("dontpegtop", 0x0008),
+    ("dontpegbottom", 0x0010),
+    ("secret", 0x0020),
+    ("blocksound", 0x0040),
+    ("dontdraw", 0x0080),
+    ("mapped", 0x0100),
+    ("passuse", 0x0200),
+];
+
+/// A map parsed from `TEXTMAP`
+#[derive(Debug, Default, Clone)]
+pub struct UdmfMap {
+    pub namespace: String,
+    pub things: Vec<WadThing>,
+    /// Full precision, UDMF vertices are floating point
+    pub vertexes: Vec<WadVertex>,
+    pub linedefs: Vec<WadLineDef>,
+    pub sidedefs: Vec<WadSideDef>,
+    pub sectors: Vec<WadSector>,
+}
+
+impl UdmfMap {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let tokens = tokenise(text)?;
+        let mut map = UdmfMap::default();
+        let mut iter = tokens.into_iter().peekable();
+
+        while let Some((token, line)) = iter.next() {
+            let Token::Ident(name) = token else {
+                return Err(format!("line {line}: expected a block or assignment"));
+            };
+            let name = name.to_ascii_lowercase();
+            match iter.next() {
+                Some((Token::Symbol('='), _)) => {
+                    let value = assignment(&mut iter, line)?;
+                    if name == "namespace" {
+                        let Value::Str(namespace) = value else {
+                            return Err(format!("line {line}: namespace must be a string"));
+                        };
+                        map.namespace = namespace.to_ascii_lowercase();
+                    }
+                }
+                Some((Token::Symbol('{'), _)) => {
+                    let mut fields = HashMap::new();
+                    loop {
+                        match iter.next() {
+                            Some((Token::Symbol('}'), _)) => break,
+                            Some((Token::Ident(key), line)) => {
+                                match iter.next() {
+                                    Some((Token::Symbol('='), _)) => {}
+                                    _ => {
+                                        return Err(format!("line {line}: expected = after {key}"));
+                                    }
+                                }
+                                fields
+                                    .insert(key.to_ascii_lowercase(), assignment(&mut iter, line)?);
+                            }
+                            _ => return Err(format!("line {line}: unterminated {name} block")),
+                        }
+                    }
+                    map.add_block(&name, &Fields(fields))
+                        .map_err(|e| format!("line {line}: {name}: {e}"))?;
+                }
+                _ => return Err(format!("line {line}: expected = or {{ after {name}")),
+            }
+        }
+
+        if !matches!(map.namespace.as_str(), "doom" | "zdoom") {
+            return Err(format!("namespace {} is not supported", map.namespace));
+        }
+        map.validate()?;
+        Ok(map)
+    }
+
+    fn add_block(&mut self, name: &str, fields: &Fields) -> Result<(), String> {
+        match name {
+            "thing" => {
+                let mut flags = THING_FLAGS
+                    .iter()
+                    .filter(|(key, _)| fields.flag(key))
+                    .fold(0, |acc, (_, flag)| acc | flag);
+                if fields.flag("ambush") {
+                    flags |= 8;
+                }
+                // The binary format flags are the inverse of these
+                if !fields.flag("single") {
+                    flags |= 16;
+                }
+                if !fields.flag("dm") {
+                    flags |= 32;
+                }
+                if !fields.flag("coop") {
+                    flags |= 64;
+                }
+                if fields.flag("friend") {
+                    flags |= 128;
+                }
+                self.things.push(WadThing::new(
+                    fields.required_float("x")?.round() as i16,
+                    fields.required_float("y")?.round() as i16,
+                    fields.int("angle", 0)? as i16,
+                    fields.required_int("type")? as i16,
+                    flags,
+                ));
+            }
+            "vertex" => {
+                self.vertexes.push(WadVertex::new(
+                    fixed_t::from_float(fields.required_float("x")? as f32),
+                    fixed_t::from_float(fields.required_float("y")? as f32),
+                ));
+            }
+            "linedef" => {
+                let flags = LINE_FLAGS
+                    .iter()
+                    .filter(|(key, _)| fields.flag(key))
+                    .fold(0, |acc, (_, flag)| acc | flag);
+                // Converted maps have the tag in both, the id may be unset
+                let mut tag = fields.int("id", -1)?;
+                if tag < 0 {
+                    tag = fields.int("arg0", 0)?;
+                }
+                let front = fields.required_int("sidefront")? as u16;
+                let back = fields.int("sideback", -1)?;
+                let back = if back < 0 { None } else { Some(back as u16) };
+                self.linedefs.push(WadLineDef::new(
+                    fields.required_int("v1")? as u16,
+                    fields.required_int("v2")? as u16,
+                    flags,
+                    fields.int("special", 0)? as i16,
+                    tag as i16,
+                    front,
+                    back,
+                    [front, back.unwrap_or(u16::MAX)],
+                ));
+            }
+            "sidedef" => {
+                self.sidedefs.push(WadSideDef {
+                    x_offset: fields.int("offsetx", 0)? as i16,
+                    y_offset: fields.int("offsety", 0)? as i16,
+                    upper_tex: fields.texture("texturetop")?,
+                    lower_tex: fields.texture("texturebottom")?,
+                    middle_tex: fields.texture("texturemiddle")?,
+                    sector: fields.required_int("sector")? as i16,
+                });
+            }
+            "sector" => {
+                self.sectors.push(WadSector {
+                    floor_height: fields.int("heightfloor", 0)? as i16,
+                    ceil_height: fields.int("heightceiling", 0)? as i16,
+                    floor_tex: fields.required_string("texturefloor")?.to_ascii_uppercase(),
+                    ceil_tex: fields
+                        .required_string("textureceiling")?
+                        .to_ascii_uppercase(),
+                    light_level: fields.int("lightlevel", 160)? as i16,
+                    kind: fields.int("special", 0)? as i16,
+                    tag: fields.int("id", 0)? as i16,
+                });
+            }
+            _ => warn!("UDMF: unknown block {name}"),
+        }
+        Ok(())
+    }
+
+    /// Check all the indexes point to something so the loader can't panic
+    fn validate(&self) -> Result<(), String> {
+        for (i, line) in self.linedefs.iter().enumerate() {
+            if line.start_vertex as usize >= self.vertexes.len()
+                || line.end_vertex as usize >= self.vertexes.len()
+            {
+                return Err(format!("linedef {i} has an invalid vertex"));
+            }
+            if line.front_sidedef as usize >= self.sidedefs.len()
+                || line
+                    .back_sidedef
+                    .is_some_and(|b| b as usize >= self.sidedefs.len())
+            {
+                return Err(format!("linedef {i} has an invalid sidedef"));
+            }
+        }
+        for (i, side) in self.sidedefs.iter().enumerate() {
+            if side.sector < 0 || side.sector as usize >= self.sectors.len() {
+                return Err(format!("sidedef {i} has an invalid sector"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Symbol(char),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+}
+
+/// The value and `;` of an assignment, after the `=`
+fn assignment(
+    iter: &mut impl Iterator<Item = (Token, usize)>,
+    line: usize,
+) -> Result<Value, String> {
+    let value = match iter.next() {
+        Some((Token::Int(i), _)) => Value::Int(i),
+        Some((Token::Float(f), _)) => Value::Float(f),
+        Some((Token::Str(s), _)) => Value::Str(s),
+        Some((Token::Ident(s), _)) if s.eq_ignore_ascii_case("true") => Value::Bool(true),
+        Some((Token::Ident(s), _)) if s.eq_ignore_ascii_case("false") => Value::Bool(false),
+        Some((Token::Ident(s), _)) => Value::Str(s),
+        _ => return Err(format!("line {line}: expected a value")),
+    };
+    match iter.next() {
+        Some((Token::Symbol(';'), _)) => Ok(value),
+        _ => Err(format!("line {line}: expected ; after the value")),
+    }
+}
+
+/// The fields of one block
+struct Fields(HashMap<String, Value>);
+
+impl Fields {
+    fn int(&self, key: &str, default: i64) -> Result<i64, String> {
+        match self.0.get(key) {
+            None => Ok(default),
+            Some(Value::Int(i)) => Ok(*i),
+            Some(Value::Float(f)) => Ok(*f as i64),
+            Some(v) => Err(format!("{key} must be a number, not {v:?}")),
+        }
+    }
+
+    fn required_int(&self, key: &str) -> Result<i64, String> {
+        if !self.0.contains_key(key) {
+            return Err(format!("{key} is required"));
+        }
+        self.int(key, 0)
+    }
+
+    fn required_float(&self, key: &str) -> Result<f64, String> {
+        match self.0.get(key) {
+            None => Err(format!("{key} is required")),
+            Some(Value::Int(i)) => Ok(*i as f64),
+            Some(Value::Float(f)) => Ok(*f),
+            Some(v) => Err(format!("{key} must be a number, not {v:?}")),
+        }
+    }
+
+    fn required_string(&self, key: &str) -> Result<&str, String> {
+        match self.0.get(key) {
+            None => Err(format!("{key} is required")),
+            Some(Value::Str(s)) => Ok(s),
+            Some(v) => Err(format!("{key} must be a string, not {v:?}")),
+        }
+    }
+
+    /// A texture name, empty if not set or `-`
+    fn texture(&self, key: &str) -> Result<String, String> {
+        match self.0.get(key) {
+            None => Ok(String::new()),
+            Some(Value::Str(s)) if s == "-" => Ok(String::new()),
+            Some(Value::Str(s)) => Ok(s.to_ascii_uppercase()),
+            Some(v) => Err(format!("{key} must be a string, not {v:?}")),
+        }
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        matches!(self.0.get(key), Some(Value::Bool(true)))
+    }
+}
+
+/// Split the lump in to tokens, each paired with the line it is on
+fn tokenise(text: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|c| *c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if last == '*' => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            last = c;
+                        }
+                        None => return Err(format!("line {line}: unterminated comment")),
+                    }
+                }
+            }
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            if let Some(c) = chars.next() {
+                                s.push(c);
+                            }
+                        }
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            s.push(c);
+                        }
+                        None => return Err(format!("line {line}: unterminated string")),
+                    }
+                }
+                tokens.push((Token::Str(s), line));
+            }
+            '{' | '}' | '=' | ';' => tokens.push((Token::Symbol(c), line)),
+            c if c == '-' || c == '+' || c == '.' || c.is_ascii_digit() => {
+                let mut s = c.to_string();
+                while let Some(c) =
+                    chars.next_if(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-'))
+                {
+                    s.push(c);
+                }
+                tokens.push((
+                    number(&s).ok_or(format!("line {line}: bad number {s}"))?,
+                    line,
+                ));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut s = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    s.push(c);
+                }
+                tokens.push((Token::Ident(s), line));
+            }
+            c => return Err(format!("line {line}: unexpected {c}")),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Integers may be decimal, hex, or octal. Floats may use an exponent.
+fn number(s: &str) -> Option<Token> {
+    let (negative, digits) = match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    };
+    let sign = if negative { -1 } else { 1 };
+    if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        return i64::from_str_radix(hex, 16)
+            .ok()
+            .map(|i| Token::Int(sign * i));
+    }
+    if digits.contains(['.', 'e', 'E']) {
+        return s.parse::<f64>().ok().map(Token::Float);
+    }
+    if digits.len() > 1 && digits.starts_with('0') {
+        return i64::from_str_radix(&digits[1..], 8)
+            .ok()
+            .map(|i| Token::Int(sign * i));
+    }
+    digits.parse::<i64>().ok().map(|i| Token::Int(sign * i))
+}
+
+#[cfg(test)]
+mod tests {
+    use math::fixed_t;
+
+    use super::UdmfMap;
+
+    const TEXTMAP: &str = r#"
+        // A single square room
+        namespace = "doom";
+
+        vertex { x = 0.0; y = 0.0; }
+        vertex { x = 128.5; y = 0.0; }
+        vertex { x = 128.5; y = 128; }
+        vertex { x = 0; y = 128; }
+
+        /* Four walls */
+        linedef { v1 = 0; v2 = 1; sidefront = 0; blocking = true; }
+        linedef { v1 = 1; v2 = 2; sidefront = 1; blocking = true; special = 11; }
+        linedef { v1 = 2; v2 = 3; sidefront = 2; blocking = true; dontpegbottom = true; }
+        linedef { v1 = 3; v2 = 0; sidefront = 3; blocking = true; id = 7; }
+
+        sidedef { sector = 0; texturemiddle = "startan2"; }
+        sidedef { sector = 0; texturemiddle = "SW1BRCOM"; offsetx = 8; }
+        sidedef { sector = 0; texturemiddle = "STARTAN2"; }
+        sidedef { sector = 0; texturemiddle = "STARTAN2"; texturetop = "-"; }
+
+        sector
+        {
+            texturefloor = "FLOOR4_8";
+            textureceiling = "CEIL3_5";
+            heightceiling = 128;
+            lightlevel = 0xc0;
+            special = 9;
+        }
+
+        thing { x = 64.0; y = 64.0; angle = 90; type = 1; skill1 = true; skill2 = true;
+                skill3 = true; skill4 = true; skill5 = true; single = true; }
+        thing { x = 32; y = 32; type = 3004; skill4 = true; ambush = true; dm = true;
+                coop = true; single = true; }
+    "#;
+
+    #[test]
+    fn parse_room() {
+        let map = UdmfMap::parse(TEXTMAP).unwrap();
+        assert_eq!(map.namespace, "doom");
+        assert_eq!(map.vertexes.len(), 4);
+        assert_eq!(map.vertexes[1].x, fixed_t::from_float(128.5));
+        assert_eq!(map.vertexes[2].y, fixed_t::from_int(128));
+
+        assert_eq!(map.linedefs.len(), 4);
+        assert_eq!(map.linedefs[0].flags, 1);
+        assert_eq!(map.linedefs[0].back_sidedef, None);
+        assert_eq!(map.linedefs[0].sides, [0, u16::MAX]);
+        assert_eq!(map.linedefs[1].special, 11);
+        assert_eq!(map.linedefs[2].flags, 0x11);
+        assert_eq!(map.linedefs[3].sector_tag, 7);
+
+        assert_eq!(map.sidedefs[0].middle_tex, "STARTAN2");
+        assert_eq!(map.sidedefs[0].upper_tex, "");
+        assert_eq!(map.sidedefs[1].x_offset, 8);
+
+        assert_eq!(map.sectors[0].ceil_height, 128);
+        assert_eq!(map.sectors[0].light_level, 0xc0);
+        assert_eq!(map.sectors[0].kind, 9);
+
+        assert_eq!(map.things[0].angle, 90);
+        // Not in deathmatch or coop
+        assert_eq!(map.things[0].flags, 7 | 32 | 64);
+        assert_eq!(map.things[1].kind, 3004);
+        assert_eq!(map.things[1].flags, 4 | 8);
+    }
+
+    #[test]
+    fn bad_index() {
+        let text = r#"
+            namespace = "doom";
+            vertex { x = 0; y = 0; }
+            linedef { v1 = 0; v2 = 1; sidefront = 0; }
+        "#;
+        assert!(UdmfMap::parse(text).is_err());
+    }
+
+    #[test]
+    fn other_namespace() {
+        let text = r#"namespace = "heretic"; vertex { x = 0; y = 0; }"#;
+        assert!(UdmfMap::parse(text).unwrap_err().contains("heretic"));
+        let text = r#"namespace = "ZDoom"; vertex { x = 0; y = 0; }"#;
+        assert!(UdmfMap::parse(text).is_ok());
+    }
+
+    #[test]
+    fn missing_field() {
+        let text = r#"namespace = "doom"; vertex { x = 0; }"#;
+        assert!(UdmfMap::parse(text).unwrap_err().contains("y is required"));
+    }
+
+    #[test]
+    fn numbers() {
+        let text = r#"
+            namespace = "doom";
+            sector { texturefloor = "F"; textureceiling = "C"; heightfloor = -0x10;
+                     heightceiling = 010; lightlevel = 1.6e2; }
+        "#;
+        let map = UdmfMap::parse(text).unwrap();
+        assert_eq!(map.sectors[0].floor_height, -16);
+        assert_eq!(map.sectors[0].ceil_height, 8);
+        assert_eq!(map.sectors[0].light_level, 160);
+    }
+}
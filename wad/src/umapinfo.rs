@@ -0,0 +1,397 @@
+//! Parsing for the `UMAPINFO` lump, used by PWADs to set the progression,
+//! names, music, and end-of-game behaviour of maps. The full spec is in
+//! `misc/umapinfo.md`.
+//!
+//! ```text,ignore
+//! MAP E1M7
+//! {
+//!     levelname = "The Hidden Cave"
+//!     skytexture = "SKY2"
+//!     intertext = "You have beaten the baddies",
+//!         "and now must continue the fight."
+//! }
+//! ```
+
+use log::warn;
+
+/// The info for a single map. Any field that is `None` uses the game default
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MapInfo {
+    /// Lump name of the map such as `E1M1` or `MAP01`, always uppercase
+    pub map_name: String,
+    pub level_name: Option<String>,
+    /// Prefix for the level name on the automap, `Some("")` if cleared
+    pub label: Option<String>,
+    pub author: Option<String>,
+    /// Patch used for the level name on the intermission screen
+    pub level_pic: Option<String>,
+    pub next: Option<String>,
+    pub next_secret: Option<String>,
+    pub sky_texture: Option<String>,
+    pub music: Option<String>,
+    /// Background for the level finished screen
+    pub exit_pic: Option<String>,
+    /// Background for the entering level screen
+    pub enter_pic: Option<String>,
+    /// Par time in seconds
+    pub par_time: Option<i32>,
+    /// `Some(false)` overrides the default end of game for maps like E1M8
+    pub end_game: Option<bool>,
+    pub end_pic: Option<String>,
+    pub end_bunny: bool,
+    pub end_cast: bool,
+    pub no_intermission: bool,
+    /// Lines are joined with `\n`, `Some("")` if cleared
+    pub inter_text: Option<String>,
+    /// Lines are joined with `\n`, `Some("")` if cleared
+    pub inter_text_secret: Option<String>,
+    pub inter_backdrop: Option<String>,
+    pub inter_music: Option<String>,
+}
+
+impl MapInfo {
+    /// The game ends after this map
+    pub fn ends_game(&self) -> bool {
+        self.end_game == Some(true) || self.end_pic.is_some() || self.end_bunny || self.end_cast
+    }
+}
+
+/// All the maps from one or more `UMAPINFO` lumps
+#[derive(Debug, Default, Clone)]
+pub struct UMapInfo {
+    maps: Vec<MapInfo>,
+}
+
+impl UMapInfo {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut tokens = tokenise(text)?.into_iter().peekable();
+        let mut maps: Vec<MapInfo> = Vec::new();
+
+        while let Some((token, line)) = tokens.next() {
+            if !matches!(&token, Token::Ident(s) if s.eq_ignore_ascii_case("MAP")) {
+                return Err(format!("line {line}: expected MAP, found {token:?}"));
+            }
+            let mut info = MapInfo {
+                map_name: match tokens.next() {
+                    Some((Token::Ident(s) | Token::Str(s), _)) => s.to_ascii_uppercase(),
+                    _ => return Err(format!("line {line}: expected a map name")),
+                },
+                ..MapInfo::default()
+            };
+            if !matches!(tokens.next(), Some((Token::Symbol('{'), _))) {
+                return Err(format!("line {line}: expected {{ after {}", info.map_name));
+            }
+
+            loop {
+                let (key, line) = match tokens.next() {
+                    Some((Token::Symbol('}'), _)) => break,
+                    Some((Token::Ident(key), line)) => (key.to_ascii_lowercase(), line),
+                    Some((token, line)) => {
+                        return Err(format!("line {line}: expected a key, found {token:?}"));
+                    }
+                    None => return Err(format!("{} is missing a closing }}", info.map_name)),
+                };
+                if !matches!(tokens.next(), Some((Token::Symbol('='), _))) {
+                    return Err(format!("line {line}: expected = after {key}"));
+                }
+
+                let mut values = Vec::new();
+                loop {
+                    match tokens.next() {
+                        Some((Token::Symbol(c), line)) => {
+                            return Err(format!("line {line}: unexpected {c} in {key}"));
+                        }
+                        Some((token, _)) => values.push(token),
+                        None => return Err(format!("line {line}: {key} has no value")),
+                    }
+                    if !matches!(tokens.peek(), Some((Token::Symbol(','), _))) {
+                        break;
+                    }
+                    tokens.next();
+                }
+
+                set_key(&mut info, &key, &values).map_err(|e| format!("line {line}: {e}"))?;
+            }
+
+            if let Some(existing) = maps.iter_mut().find(|m| m.map_name == info.map_name) {
+                *existing = info;
+            } else {
+                maps.push(info);
+            }
+        }
+
+        Ok(Self { maps })
+    }
+
+    /// Add the maps from `other`, replacing any with the same name. Used when
+    /// more than one wad has a `UMAPINFO` lump.
+    pub fn merge(&mut self, other: UMapInfo) {
+        for info in other.maps {
+            if let Some(existing) = self.maps.iter_mut().find(|m| m.map_name == info.map_name) {
+                *existing = info;
+            } else {
+                self.maps.push(info);
+            }
+        }
+    }
+
+    /// Find the info for a map, e.g, `E1M1` or `MAP01`
+    pub fn get(&self, map_name: &str) -> Option<&MapInfo> {
+        self.maps
+            .iter()
+            .find(|m| m.map_name.eq_ignore_ascii_case(map_name))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.maps.is_empty()
+    }
+}
+
+/// Split a map name in to the episode and map numbers. `MAPxx` names are
+/// always episode 1.
+pub fn map_numbers(map_name: &str) -> Option<(usize, usize)> {
+    let name = map_name.to_ascii_uppercase();
+    let (episode, map) = if let Some(map) = name.strip_prefix("MAP") {
+        (1, map.parse().ok()?)
+    } else {
+        let (episode, map) = name.strip_prefix('E')?.split_once('M')?;
+        (episode.parse().ok()?, map.parse().ok()?)
+    };
+    // Episodes and maps count from 1
+    (episode != 0 && map != 0).then_some((episode, map))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Number(i32),
+    Symbol(char),
+}
+
+/// Split the lump in to tokens, each paired with the line it is on
+fn tokenise(text: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|c| *c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if last == '*' => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            last = c;
+                        }
+                        None => return Err(format!("line {line}: unterminated comment")),
+                    }
+                }
+            }
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            if let Some(c) = chars.next() {
+                                s.push(c);
+                            }
+                        }
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            s.push(c);
+                        }
+                        None => return Err(format!("line {line}: unterminated string")),
+                    }
+                }
+                tokens.push((Token::Str(s), line));
+            }
+            '{' | '}' | '=' | ',' => tokens.push((Token::Symbol(c), line)),
+            c if c == '-' || c.is_ascii_digit() => {
+                let mut s = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '.') {
+                    s.push(c);
+                }
+                // Map names such as `2ndmap` aren't valid, but the number may be a float
+                let number = s
+                    .parse::<f32>()
+                    .map_err(|_| format!("line {line}: bad number {s}"))?;
+                tokens.push((Token::Number(number as i32), line));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut s = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    s.push(c);
+                }
+                tokens.push((Token::Ident(s), line));
+            }
+            c => return Err(format!("line {line}: unexpected {c}")),
+        }
+    }
+    Ok(tokens)
+}
+
+fn set_key(info: &mut MapInfo, key: &str, values: &[Token]) -> Result<(), String> {
+    match key {
+        "levelname" => info.level_name = Some(string(values)?),
+        "label" => info.label = Some(clearable(values)?.unwrap_or_default()),
+        "author" => info.author = Some(string(values)?),
+        "levelpic" => info.level_pic = Some(lump_name(values)?),
+        "next" => info.next = Some(lump_name(values)?),
+        "nextsecret" => info.next_secret = Some(lump_name(values)?),
+        "skytexture" => info.sky_texture = Some(lump_name(values)?),
+        "music" => info.music = Some(lump_name(values)?),
+        "exitpic" => info.exit_pic = Some(lump_name(values)?),
+        "enterpic" => info.enter_pic = Some(lump_name(values)?),
+        "partime" => match values {
+            [Token::Number(n)] => info.par_time = Some(*n),
+            _ => return Err("partime must be a number".to_string()),
+        },
+        "endgame" => info.end_game = Some(boolean(values)?),
+        "endpic" => info.end_pic = Some(lump_name(values)?),
+        "endbunny" => info.end_bunny = boolean(values)?,
+        "endcast" => info.end_cast = boolean(values)?,
+        "nointermission" => info.no_intermission = boolean(values)?,
+        "intertext" => info.inter_text = Some(clearable(values)?.unwrap_or_default()),
+        "intertextsecret" => {
+            info.inter_text_secret = Some(clearable(values)?.unwrap_or_default());
+        }
+        "interbackdrop" => info.inter_backdrop = Some(lump_name(values)?),
+        "intermusic" => info.inter_music = Some(lump_name(values)?),
+        "episode" | "bossaction" => warn!("UMAPINFO: {key} is not supported"),
+        _ => warn!("UMAPINFO: unknown key {key}"),
+    }
+    Ok(())
+}
+
+fn string(values: &[Token]) -> Result<String, String> {
+    match values {
+        [Token::Str(s)] => Ok(s.clone()),
+        _ => Err("expected a string".to_string()),
+    }
+}
+
+fn lump_name(values: &[Token]) -> Result<String, String> {
+    match values {
+        [Token::Str(s) | Token::Ident(s)] => Ok(s.to_ascii_uppercase()),
+        _ => Err("expected a lump name".to_string()),
+    }
+}
+
+fn boolean(values: &[Token]) -> Result<bool, String> {
+    match values {
+        [Token::Ident(s)] if s.eq_ignore_ascii_case("true") => Ok(true),
+        [Token::Ident(s)] if s.eq_ignore_ascii_case("false") => Ok(false),
+        _ => Err("expected true or false".to_string()),
+    }
+}
+
+/// One or more strings joined by lines, or `None` for `clear`
+fn clearable(values: &[Token]) -> Result<Option<String>, String> {
+    if let [Token::Ident(s)] = values
+        && s.eq_ignore_ascii_case("clear")
+    {
+        return Ok(None);
+    }
+    let mut lines = Vec::with_capacity(values.len());
+    for value in values {
+        match value {
+            Token::Str(s) => lines.push(s.as_str()),
+            _ => return Err("expected strings or clear".to_string()),
+        }
+    }
+    Ok(Some(lines.join("\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UMapInfo, map_numbers};
+
+    const LUMP: &str = r#"
+// The first map
+MAP E1M7
+{
+    levelname = "The Hidden Cave"
+    skytexture =  "sky2"
+    partime = 90
+    next = E2M1
+    /* multi-line
+       comment */
+    intertext = "You have beaten the shit",
+        "out of those big barons",
+        "and now must continue the fight."
+    bossaction = BaronOfHell, 23, 666
+}
+
+map MAP01 {
+    label = clear
+    levelname = "Entryway"
+    endgame = true
+    intertextsecret = clear
+}
+"#;
+
+    #[test]
+    fn parse_maps() {
+        let info = UMapInfo::parse(LUMP).unwrap();
+        let e1m7 = info.get("e1m7").unwrap();
+        assert_eq!(e1m7.level_name.as_deref(), Some("The Hidden Cave"));
+        assert_eq!(e1m7.sky_texture.as_deref(), Some("SKY2"));
+        assert_eq!(e1m7.par_time, Some(90));
+        assert_eq!(e1m7.next.as_deref(), Some("E2M1"));
+        assert_eq!(
+            e1m7.inter_text.as_deref(),
+            Some(
+                "You have beaten the shit\nout of those big barons\nand now must continue the \
+                 fight."
+            )
+        );
+        assert!(!e1m7.ends_game());
+
+        let map01 = info.get("MAP01").unwrap();
+        assert_eq!(map01.label.as_deref(), Some(""));
+        assert_eq!(map01.inter_text_secret.as_deref(), Some(""));
+        assert_eq!(map01.inter_text, None);
+        assert!(map01.ends_game());
+    }
+
+    #[test]
+    fn merge_replaces() {
+        let mut info = UMapInfo::parse(LUMP).unwrap();
+        info.merge(UMapInfo::parse("MAP MAP01 { levelname = \"Other\" }").unwrap());
+        let map01 = info.get("MAP01").unwrap();
+        assert_eq!(map01.level_name.as_deref(), Some("Other"));
+        assert_eq!(map01.end_game, None);
+        assert!(info.get("E1M7").is_some());
+    }
+
+    #[test]
+    fn bad_lump() {
+        assert!(UMapInfo::parse("MAP E1M1 { levelname = }").is_err());
+        assert!(UMapInfo::parse("MAP E1M1 { partime = \"ten\" }").is_err());
+        assert!(UMapInfo::parse("MAP E1M1 { levelname = \"a\"").is_err());
+        let err = UMapInfo::parse("MAP E1M1 {\n\n endgame = maybe }").unwrap_err();
+        assert!(err.starts_with("line 3"));
+    }
+
+    #[test]
+    fn numbers_from_name() {
+        assert_eq!(map_numbers("E2M4"), Some((2, 4)));
+        assert_eq!(map_numbers("map32"), Some((1, 32)));
+        assert_eq!(map_numbers("MAP100"), Some((1, 100)));
+        assert_eq!(map_numbers("START"), None);
+        assert_eq!(map_numbers("MAP00"), None);
+        assert_eq!(map_numbers("E0M1"), None);
+    }
+}
@@ -4,7 +4,10 @@ use std::io::prelude::*;
 use std::path::PathBuf;
 use std::{fmt, str};
 
-use crate::types::WadBlockMap;
+use crate::types::{WadAnimation, WadBlockMap, WadSwitch};
+use crate::udmf::UdmfMap;
+use crate::umapinfo::UMapInfo;
+use log::warn;
 
 /// Used as an index to find a specific lump, typically combined
 /// with an offset for example: find the index for lump named "E1M1"
@@ -273,6 +276,13 @@ impl WadData {
             .find(|lump| lump.name == name.to_ascii_uppercase())
     }
 
+    /// Every lump with this name, in the order the WADs were loaded. Used for
+    /// lumps where each PWAD adds to the last, such as `DEHACKED`
+    pub fn get_lumps<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a Lump> {
+        let name = name.to_ascii_uppercase();
+        self.lumps.iter().filter(move |lump| lump.name == name)
+    }
+
     /// Find a general lump by name
     pub(super) fn find_lump_or_panic(&self, name: &str) -> &Lump {
         for info in self.lumps.iter().rev() {
@@ -285,21 +295,54 @@ impl WadData {
 
     /// Find the map name and adds the desired lump offset
     pub(super) fn find_lump_for_map_or_panic(&self, map_name: &str, lump: MapLump) -> &Lump {
-        for (idx, info) in self.lumps.iter().enumerate().rev() {
-            if info.name == map_name.to_ascii_uppercase() {
-                return &self.lumps[idx + lump as usize];
-            }
-        }
-        panic!("Could not find lump {}", map_name);
+        self.find_lump_for_map(map_name, lump)
+            .unwrap_or_else(|| panic!("Could not find lump {}", map_name))
     }
 
+    /// UDMF maps have no fixed lump order, so the lump is found by name
+    /// between the marker and `ENDMAP`. The nodes of a UDMF map are `ZNODES`.
     pub(super) fn find_lump_for_map(&self, map_name: &str, lump: MapLump) -> Option<&Lump> {
-        for (idx, info) in self.lumps.iter().enumerate().rev() {
-            if info.name == map_name.to_ascii_uppercase() {
-                return Some(&self.lumps[idx + lump as usize]);
-            }
+        let idx = self.map_index(map_name)?;
+        if self.is_udmf_index(idx) {
+            let name = match lump {
+                MapLump::Nodes => "ZNODES".to_string(),
+                lump => lump.to_string(),
+            };
+            return self.lumps[idx + 1..]
+                .iter()
+                .take_while(|info| info.name != "ENDMAP")
+                .find(|info| info.name == name);
         }
-        None
+        self.lumps.get(idx + lump as usize)
+    }
+
+    fn map_index(&self, map_name: &str) -> Option<usize> {
+        let map_name = map_name.to_ascii_uppercase();
+        self.lumps.iter().rposition(|info| info.name == map_name)
+    }
+
+    fn is_udmf_index(&self, idx: usize) -> bool {
+        self.lumps
+            .get(idx + 1)
+            .is_some_and(|info| info.name == "TEXTMAP")
+    }
+
+    /// True if the map is in the UDMF text format
+    pub fn is_udmf_map(&self, map_name: &str) -> bool {
+        self.map_index(map_name)
+            .is_some_and(|idx| self.is_udmf_index(idx))
+    }
+
+    /// Parse the `TEXTMAP` of a UDMF map. Returns `None` if the map is not
+    /// UDMF.
+    pub fn read_udmf(&self, map_name: &str) -> Option<Result<UdmfMap, String>> {
+        let idx = self.map_index(map_name)?;
+        if !self.is_udmf_index(idx) {
+            return None;
+        }
+        Some(UdmfMap::parse(&String::from_utf8_lossy(
+            &self.lumps[idx + 1].data,
+        )))
     }
 
     pub fn lump_exists(&self, lump_name: &str) -> bool {
@@ -317,17 +360,24 @@ impl WadData {
                 return None;
             }
 
-            let w = info.read_i16(4) as usize;
-            let h = info.read_i16(6) as usize;
+            let w = info.read_u16(4) as usize;
+            let h = info.read_u16(6) as usize;
             let word_len = 2;
             let ofs = 8; //info.offset;
-            let len = ofs + w * h * word_len;
+            let len = (ofs + w * h * word_len).min(info.data.len());
             let mut line_groups = Vec::with_capacity(info.data.len() / word_len);
+            let mut block_starts = Vec::with_capacity(w * h);
             for i in (ofs..len).step_by(2) {
-                let mut start =
-                    i16::from_le_bytes([info.data[i], info.data[i + 1]]) as usize * word_len;
-                while start < info.data.len() {
-                    let line = i16::from_le_bytes([info.data[start], info.data[start + 1]]);
+                // Offsets are unsigned so large maps can use the full 64k words
+                let mut start = info.read_u16(i) as usize * word_len;
+                block_starts.push(line_groups.len());
+                loop {
+                    if start + 1 >= info.data.len() {
+                        // Unterminated list, end it here to keep blocks aligned
+                        line_groups.push(-1);
+                        break;
+                    }
+                    let line = info.read_i16(start);
                     line_groups.push(line);
                     if line == -1 {
                         break;
@@ -342,11 +392,45 @@ impl WadData {
                 info.read_i16(4),
                 info.read_i16(6),
                 line_groups,
+                block_starts,
             ));
         }
         None
     }
 
+    /// Parse and merge every `UMAPINFO` lump, later wads replace the maps of
+    /// earlier ones. A lump that fails to parse is skipped.
+    pub fn read_umapinfo(&self) -> UMapInfo {
+        let mut umapinfo = UMapInfo::default();
+        for lump in self.get_lumps("UMAPINFO") {
+            match UMapInfo::parse(&String::from_utf8_lossy(&lump.data)) {
+                Ok(info) => umapinfo.merge(info),
+                Err(e) => warn!("Could not parse UMAPINFO: {e}"),
+            }
+        }
+        umapinfo
+    }
+
+    /// The BOOM `TRANMAP` lump, a 256x256 table of the palette index to use
+    /// when blending a colour over another
+    pub fn read_tranmap(&self) -> Option<Vec<u8>> {
+        self.get_lump("TRANMAP")
+            .filter(|lump| lump.data.len() == 256 * 256)
+            .map(|lump| lump.data.clone())
+    }
+
+    /// The BOOM `ANIMATED` lump from the last wad to have one
+    pub fn read_animated(&self) -> Option<Vec<WadAnimation>> {
+        self.get_lump("ANIMATED")
+            .map(|lump| WadAnimation::read_lump(&lump.data))
+    }
+
+    /// The BOOM `SWITCHES` lump from the last wad to have one
+    pub fn read_switches(&self) -> Option<Vec<WadSwitch>> {
+        self.get_lump("SWITCHES")
+            .map(|lump| WadSwitch::read_lump(&lump.data))
+    }
+
     pub fn read_rejects(&self, map_name: &str) -> Option<Vec<u8>> {
         if let Some(info) = self.find_lump_for_map(map_name, MapLump::Reject) {
             if info.data.len() == 0 {
@@ -684,6 +768,11 @@ mod tests {
         ];
         assert_eq!(blockmap.line_indexes, blocks);
         assert_eq!(blockmap.line_indexes.len(), blocks.len());
+        assert_eq!(blockmap.block_starts.len(), 36 * 23);
+        assert_eq!(blockmap.block_starts[1], 2);
+        for start in blockmap.block_starts {
+            assert_eq!(blockmap.line_indexes[start], 0);
+        }
     }
 
     #[test]